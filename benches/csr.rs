@@ -0,0 +1,157 @@
+//! CSR 파일 벤치마크 (cargo bench --bench csr)
+//! 1. check_pending_interrupts의 CSR 접근 패턴: HashMap 기반 CSR 파일과 비교
+//! 2. 긴 게스트 루프 실행 속도 (MIPS): 같은 프로그램을 CSR 파일만 바꾼 Cpu 두 개로 실행해서 비교
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use riscv_emulator::cpu::Xlen;
+use riscv_emulator::csr::CsrFile;
+use riscv_emulator::{Bus, Cpu, Csr, asm, csr};
+
+const ITERATIONS: u64 = 20_000_000;
+
+// 배열 기반으로 바꾸기 전의 CSR 파일 (비교 기준)
+// 매 step 접근하는 CSR도 CsrFile 기본 구현대로 read/write를 거침
+struct HashMapCsr {
+    data: HashMap<u16, u64>,
+}
+
+impl HashMapCsr {
+    fn new() -> Self {
+        HashMapCsr {
+            data: HashMap::new(),
+        }
+    }
+}
+
+impl CsrFile for HashMapCsr {
+    fn read(&self, addr: u16) -> u64 {
+        if self.data.contains_key(&addr) {
+            self.data[&addr]
+        } else {
+            0
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u64) {
+        self.data.insert(addr, value);
+    }
+}
+
+// check_pending_interrupts와 같은 순서로 MIP를 세 번 갱신한 뒤 MSTATUS/MIE 확인
+fn interrupt_check(read: &mut impl FnMut(u16) -> u64, write: &mut impl FnMut(u16, u64)) -> bool {
+    for bit in [csr::MIP_MTIP, csr::MIP_MSIP, csr::MIP_MEIP] {
+        let mip = read(csr::MIP);
+        write(csr::MIP, black_box(mip & !bit));
+    }
+    if read(csr::MSTATUS) & csr::MSTATUS_MIE == 0 {
+        return false;
+    }
+    read(csr::MIP) & read(csr::MIE) != 0
+}
+
+fn bench(name: &str, mut f: impl FnMut() -> u64) -> Duration {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<28} {:>8.2} ms  ({:>7.1} M iter/s, result {})",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        ITERATIONS as f64 / elapsed.as_secs_f64() / 1e6,
+        result
+    );
+    elapsed
+}
+
+fn csr_access() {
+    let mut map = HashMapCsr::new();
+    map.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    let map = std::cell::RefCell::new(map);
+    let hashmap = bench("interrupt check (HashMap)", || {
+        let mut taken = 0;
+        for _ in 0..ITERATIONS {
+            let pending =
+                interrupt_check(&mut |addr| map.borrow().read(addr), &mut |addr, value| {
+                    map.borrow_mut().write(addr, value)
+                });
+            taken += pending as u64;
+        }
+        taken
+    });
+
+    let mut file = Csr::new();
+    file.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    let file = std::cell::RefCell::new(file);
+    let array = bench("interrupt check (Csr)", || {
+        let mut taken = 0;
+        for _ in 0..ITERATIONS {
+            let pending =
+                interrupt_check(&mut |addr| file.borrow().read(addr), &mut |addr, value| {
+                    file.borrow_mut().write(addr, value)
+                });
+            taken += pending as u64;
+        }
+        taken
+    });
+    println!(
+        "speedup: {:.2}x",
+        hashmap.as_secs_f64() / array.as_secs_f64()
+    );
+}
+
+// 프로그램 끝까지 실행하고 걸린 시간
+fn run_guest<C: CsrFile>(name: &str, mut cpu: Cpu<C>, program: &[u32]) -> Duration {
+    let end = 0x80000000 + program.len() as u64 * 4;
+    cpu.load_program(program);
+    cpu.write_reg(2, 0x80100000);
+    let start = Instant::now();
+    while cpu.pc < end {
+        cpu.step_block();
+    }
+    let elapsed = start.elapsed();
+    let instructions = cpu.csr.read(csr::MINSTRET);
+    println!(
+        "{:<28} {:>8.2} ms  ({:>7.1} MIPS, {} instructions)",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        instructions as f64 / elapsed.as_secs_f64() / 1e6,
+        instructions
+    );
+    elapsed
+}
+
+fn guest_loop() {
+    let program = asm::assemble(
+        "
+            li t0, 2000000
+            li a0, 0
+        loop:
+            add a0, a0, t0
+            xor a1, a0, t0
+            slli a2, a1, 3
+            sd a2, 0(sp)
+            ld a3, 0(sp)
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    let hashmap = run_guest(
+        "guest loop (HashMap)",
+        Cpu::with_csr_file(0, Xlen::Rv64, Bus::new(), HashMapCsr::new()),
+        &program,
+    );
+    let array = run_guest("guest loop (Csr)", Cpu::new(0), &program);
+    println!(
+        "speedup: {:.2}x",
+        hashmap.as_secs_f64() / array.as_secs_f64()
+    );
+}
+
+fn main() {
+    csr_access();
+    guest_loop();
+}
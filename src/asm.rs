@@ -0,0 +1,1258 @@
+//! 명령어 인코더와 간단한 텍스트 어셈블러 (테스트 프로그램 작성용)
+//! 인코더: asm::add(3, 1, 2) 처럼 어셈블리 피연산자 순서대로 u32 인코딩을 만듦
+//! 어셈블러: 라벨과 의사 명령어를 포함한 소스를 Cpu::load_program용 Vec<u32>로 변환
+//! 모든 출력은 32비트 명령어 (압축 명령어는 생성하지 않음)
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cpu::Xlen;
+use crate::disasm;
+
+const OP_IMM: u32 = 0x13;
+const OP_IMM_32: u32 = 0x1B;
+const OP: u32 = 0x33;
+const OP_32: u32 = 0x3B;
+const LOAD: u32 = 0x03;
+const STORE: u32 = 0x23;
+const BRANCH: u32 = 0x63;
+const JAL: u32 = 0x6F;
+const JALR: u32 = 0x67;
+const LUI: u32 = 0x37;
+const AUIPC: u32 = 0x17;
+const SYSTEM: u32 = 0x73;
+const MISC_MEM: u32 = 0x0F;
+const AMO: u32 = 0x2F;
+
+/// ABI 레지스터 번호
+pub mod reg {
+    pub const ZERO: usize = 0;
+    pub const RA: usize = 1;
+    pub const SP: usize = 2;
+    pub const GP: usize = 3;
+    pub const TP: usize = 4;
+    pub const T0: usize = 5;
+    pub const T1: usize = 6;
+    pub const T2: usize = 7;
+    pub const S0: usize = 8;
+    pub const S1: usize = 9;
+    pub const A0: usize = 10;
+    pub const A1: usize = 11;
+    pub const A2: usize = 12;
+    pub const A3: usize = 13;
+    pub const A4: usize = 14;
+    pub const A5: usize = 15;
+    pub const A6: usize = 16;
+    pub const A7: usize = 17;
+}
+
+// ========================================
+// 형식별 인코딩
+// ========================================
+
+pub fn r_type(opcode: u32, funct3: u32, funct7: u32, rd: usize, rs1: usize, rs2: usize) -> u32 {
+    (funct7 << 25)
+        | ((rs2 as u32 & 0x1F) << 20)
+        | ((rs1 as u32 & 0x1F) << 15)
+        | (funct3 << 12)
+        | ((rd as u32 & 0x1F) << 7)
+        | opcode
+}
+
+pub fn i_type(opcode: u32, funct3: u32, rd: usize, rs1: usize, imm: i32) -> u32 {
+    ((imm as u32 & 0xFFF) << 20)
+        | ((rs1 as u32 & 0x1F) << 15)
+        | (funct3 << 12)
+        | ((rd as u32 & 0x1F) << 7)
+        | opcode
+}
+
+pub fn s_type(opcode: u32, funct3: u32, rs1: usize, rs2: usize, imm: i32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 5) & 0x7F) << 25)
+        | ((rs2 as u32 & 0x1F) << 20)
+        | ((rs1 as u32 & 0x1F) << 15)
+        | (funct3 << 12)
+        | ((imm & 0x1F) << 7)
+        | opcode
+}
+
+pub fn b_type(funct3: u32, rs1: usize, rs2: usize, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 12) & 0x1) << 31)
+        | (((imm >> 5) & 0x3F) << 25)
+        | ((rs2 as u32 & 0x1F) << 20)
+        | ((rs1 as u32 & 0x1F) << 15)
+        | (funct3 << 12)
+        | (((imm >> 1) & 0xF) << 8)
+        | (((imm >> 11) & 0x1) << 7)
+        | BRANCH
+}
+
+pub fn u_type(opcode: u32, rd: usize, imm20: u32) -> u32 {
+    ((imm20 & 0xFFFFF) << 12) | ((rd as u32 & 0x1F) << 7) | opcode
+}
+
+pub fn j_type(rd: usize, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 20) & 0x1) << 31)
+        | (((imm >> 1) & 0x3FF) << 21)
+        | (((imm >> 11) & 0x1) << 20)
+        | (((imm >> 12) & 0xFF) << 12)
+        | ((rd as u32 & 0x1F) << 7)
+        | JAL
+}
+
+// ========================================
+// RV64I
+// ========================================
+
+pub fn lui(rd: usize, imm20: u32) -> u32 {
+    u_type(LUI, rd, imm20)
+}
+
+pub fn auipc(rd: usize, imm20: u32) -> u32 {
+    u_type(AUIPC, rd, imm20)
+}
+
+pub fn jal(rd: usize, offset: i32) -> u32 {
+    j_type(rd, offset)
+}
+
+pub fn jalr(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(JALR, 0x0, rd, rs1, offset)
+}
+
+pub fn beq(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x0, rs1, rs2, offset)
+}
+
+pub fn bne(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x1, rs1, rs2, offset)
+}
+
+pub fn blt(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x4, rs1, rs2, offset)
+}
+
+pub fn bge(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x5, rs1, rs2, offset)
+}
+
+pub fn bltu(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x6, rs1, rs2, offset)
+}
+
+pub fn bgeu(rs1: usize, rs2: usize, offset: i32) -> u32 {
+    b_type(0x7, rs1, rs2, offset)
+}
+
+pub fn lb(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x0, rd, rs1, offset)
+}
+
+pub fn lh(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x1, rd, rs1, offset)
+}
+
+pub fn lw(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x2, rd, rs1, offset)
+}
+
+pub fn ld(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x3, rd, rs1, offset)
+}
+
+pub fn lbu(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x4, rd, rs1, offset)
+}
+
+pub fn lhu(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x5, rd, rs1, offset)
+}
+
+pub fn lwu(rd: usize, offset: i32, rs1: usize) -> u32 {
+    i_type(LOAD, 0x6, rd, rs1, offset)
+}
+
+pub fn sb(rs2: usize, offset: i32, rs1: usize) -> u32 {
+    s_type(STORE, 0x0, rs1, rs2, offset)
+}
+
+pub fn sh(rs2: usize, offset: i32, rs1: usize) -> u32 {
+    s_type(STORE, 0x1, rs1, rs2, offset)
+}
+
+pub fn sw(rs2: usize, offset: i32, rs1: usize) -> u32 {
+    s_type(STORE, 0x2, rs1, rs2, offset)
+}
+
+pub fn sd(rs2: usize, offset: i32, rs1: usize) -> u32 {
+    s_type(STORE, 0x3, rs1, rs2, offset)
+}
+
+pub fn addi(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x0, rd, rs1, imm)
+}
+
+pub fn slti(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x2, rd, rs1, imm)
+}
+
+pub fn sltiu(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x3, rd, rs1, imm)
+}
+
+pub fn xori(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x4, rd, rs1, imm)
+}
+
+pub fn ori(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x6, rd, rs1, imm)
+}
+
+pub fn andi(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM, 0x7, rd, rs1, imm)
+}
+
+pub fn slli(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM, 0x1, rd, rs1, (shamt & 0x3F) as i32)
+}
+
+pub fn srli(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM, 0x5, rd, rs1, (shamt & 0x3F) as i32)
+}
+
+pub fn srai(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM, 0x5, rd, rs1, (0x400 | (shamt & 0x3F)) as i32)
+}
+
+pub fn addiw(rd: usize, rs1: usize, imm: i32) -> u32 {
+    i_type(OP_IMM_32, 0x0, rd, rs1, imm)
+}
+
+pub fn slliw(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM_32, 0x1, rd, rs1, (shamt & 0x1F) as i32)
+}
+
+pub fn srliw(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM_32, 0x5, rd, rs1, (shamt & 0x1F) as i32)
+}
+
+pub fn sraiw(rd: usize, rs1: usize, shamt: u32) -> u32 {
+    i_type(OP_IMM_32, 0x5, rd, rs1, (0x400 | (shamt & 0x1F)) as i32)
+}
+
+pub fn add(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x0, 0x00, rd, rs1, rs2)
+}
+
+pub fn sub(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x0, 0x20, rd, rs1, rs2)
+}
+
+pub fn sll(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x1, 0x00, rd, rs1, rs2)
+}
+
+pub fn slt(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x2, 0x00, rd, rs1, rs2)
+}
+
+pub fn sltu(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x3, 0x00, rd, rs1, rs2)
+}
+
+pub fn xor(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x4, 0x00, rd, rs1, rs2)
+}
+
+pub fn srl(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x5, 0x00, rd, rs1, rs2)
+}
+
+pub fn sra(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x5, 0x20, rd, rs1, rs2)
+}
+
+pub fn or(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x6, 0x00, rd, rs1, rs2)
+}
+
+pub fn and(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x7, 0x00, rd, rs1, rs2)
+}
+
+pub fn addw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x0, 0x00, rd, rs1, rs2)
+}
+
+pub fn subw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x0, 0x20, rd, rs1, rs2)
+}
+
+pub fn sllw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x1, 0x00, rd, rs1, rs2)
+}
+
+pub fn srlw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x5, 0x00, rd, rs1, rs2)
+}
+
+pub fn sraw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x5, 0x20, rd, rs1, rs2)
+}
+
+/// fence pred,succ (iorw 비트마스크)
+pub fn fence(pred: u32, succ: u32) -> u32 {
+    ((pred & 0xF) << 24) | ((succ & 0xF) << 20) | MISC_MEM
+}
+
+/// fence.tso (fm=1000, rw,rw)
+pub fn fence_tso() -> u32 {
+    (0b1000 << 28) | fence(0b0011, 0b0011)
+}
+
+pub fn fence_i() -> u32 {
+    i_type(MISC_MEM, 0x1, 0, 0, 0)
+}
+
+pub fn ecall() -> u32 {
+    SYSTEM
+}
+
+pub fn ebreak() -> u32 {
+    i_type(SYSTEM, 0x0, 0, 0, 1)
+}
+
+pub fn mret() -> u32 {
+    r_type(SYSTEM, 0x0, 0x18, 0, 0, 2)
+}
+
+pub fn sret() -> u32 {
+    r_type(SYSTEM, 0x0, 0x08, 0, 0, 2)
+}
+
+pub fn wfi() -> u32 {
+    r_type(SYSTEM, 0x0, 0x08, 0, 0, 5)
+}
+
+// ========================================
+// M
+// ========================================
+
+pub fn mul(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x0, 0x01, rd, rs1, rs2)
+}
+
+pub fn mulh(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x1, 0x01, rd, rs1, rs2)
+}
+
+pub fn mulhsu(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x2, 0x01, rd, rs1, rs2)
+}
+
+pub fn mulhu(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x3, 0x01, rd, rs1, rs2)
+}
+
+pub fn div(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x4, 0x01, rd, rs1, rs2)
+}
+
+pub fn divu(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x5, 0x01, rd, rs1, rs2)
+}
+
+pub fn rem(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x6, 0x01, rd, rs1, rs2)
+}
+
+pub fn remu(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP, 0x7, 0x01, rd, rs1, rs2)
+}
+
+pub fn mulw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x0, 0x01, rd, rs1, rs2)
+}
+
+pub fn divw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x4, 0x01, rd, rs1, rs2)
+}
+
+pub fn divuw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x5, 0x01, rd, rs1, rs2)
+}
+
+pub fn remw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x6, 0x01, rd, rs1, rs2)
+}
+
+pub fn remuw(rd: usize, rs1: usize, rs2: usize) -> u32 {
+    r_type(OP_32, 0x7, 0x01, rd, rs1, rs2)
+}
+
+// ========================================
+// Zicsr (csr 필드는 rs2/funct7 자리에 들어감)
+// ========================================
+
+fn csr_type(funct3: u32, rd: usize, rs1: usize, csr: u16) -> u32 {
+    i_type(SYSTEM, funct3, rd, rs1, csr as i32)
+}
+
+pub fn csrrw(rd: usize, csr: u16, rs1: usize) -> u32 {
+    csr_type(0x1, rd, rs1, csr)
+}
+
+pub fn csrrs(rd: usize, csr: u16, rs1: usize) -> u32 {
+    csr_type(0x2, rd, rs1, csr)
+}
+
+pub fn csrrc(rd: usize, csr: u16, rs1: usize) -> u32 {
+    csr_type(0x3, rd, rs1, csr)
+}
+
+pub fn csrrwi(rd: usize, csr: u16, uimm: u32) -> u32 {
+    csr_type(0x5, rd, uimm as usize, csr)
+}
+
+pub fn csrrsi(rd: usize, csr: u16, uimm: u32) -> u32 {
+    csr_type(0x6, rd, uimm as usize, csr)
+}
+
+pub fn csrrci(rd: usize, csr: u16, uimm: u32) -> u32 {
+    csr_type(0x7, rd, uimm as usize, csr)
+}
+
+// ========================================
+// A (funct5, 폭(funct3) 지정)
+// ========================================
+
+/// AMO 인코딩. funct3: 0x2=.w, 0x3=.d
+pub fn amo(funct5: u32, funct3: u32, aq: bool, rl: bool, rd: usize, rs1: usize, rs2: usize) -> u32 {
+    let funct7 = (funct5 << 2) | ((aq as u32) << 1) | rl as u32;
+    r_type(AMO, funct3, funct7, rd, rs1, rs2)
+}
+
+pub fn lr_w(rd: usize, rs1: usize) -> u32 {
+    amo(0x02, 0x2, false, false, rd, rs1, 0)
+}
+
+pub fn lr_d(rd: usize, rs1: usize) -> u32 {
+    amo(0x02, 0x3, false, false, rd, rs1, 0)
+}
+
+pub fn sc_w(rd: usize, rs2: usize, rs1: usize) -> u32 {
+    amo(0x03, 0x2, false, false, rd, rs1, rs2)
+}
+
+pub fn sc_d(rd: usize, rs2: usize, rs1: usize) -> u32 {
+    amo(0x03, 0x3, false, false, rd, rs1, rs2)
+}
+
+// ========================================
+// 텍스트 어셈블러
+// ========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsmError {
+    /// 1부터 시작하는 소스 줄 번호
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// RV64 기준으로 어셈블 (주소 0 = 첫 명령어)
+pub fn assemble(source: &str) -> Result<Vec<u32>, AsmError> {
+    assemble_xlen(source, Xlen::Rv64)
+}
+
+/// XLEN에 따라 li 확장과 즉시값 범위가 달라짐
+pub fn assemble_xlen(source: &str, xlen: Xlen) -> Result<Vec<u32>, AsmError> {
+    let mut lines = Vec::new();
+    let mut labels = HashMap::new();
+    let mut pc: i64 = 0;
+
+    // 1단계: 라벨 주소 수집 (명령어 크기는 피연산자만으로 결정됨)
+    for (index, raw) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let mut text = strip_comment(raw).trim();
+        while let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                break;
+            }
+            if labels.insert(label.to_string(), pc).is_some() {
+                return Err(error(line_no, format!("duplicate label '{}'", label)));
+            }
+            text = text[colon + 1..].trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let (mnemonic, operands) = split_instruction(text);
+        let count = instruction_count(&mnemonic, &operands, xlen)
+            .map_err(|message| error(line_no, message))?;
+        lines.push((line_no, pc, mnemonic, operands));
+        pc += count as i64 * 4;
+    }
+
+    // 2단계: 인코딩
+    let mut program = Vec::new();
+    for (line_no, pc, mnemonic, operands) in lines {
+        let ctx = Context {
+            pc,
+            labels: &labels,
+            xlen,
+        };
+        let words = ctx
+            .encode(&mnemonic, &operands)
+            .map_err(|message| error(line_no, message))?;
+        program.extend(words);
+    }
+    Ok(program)
+}
+
+fn error(line: usize, message: String) -> AsmError {
+    AsmError { line, message }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let end = [line.find('#'), line.find("//")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+fn split_instruction(text: &str) -> (String, Vec<String>) {
+    let (mnemonic, rest) = match text.find(char::is_whitespace) {
+        Some(pos) => (&text[..pos], text[pos..].trim()),
+        None => (text, ""),
+    };
+    let operands = if rest.is_empty() {
+        Vec::new()
+    } else {
+        rest.split(',').map(|op| op.trim().to_string()).collect()
+    };
+    (mnemonic.to_lowercase(), operands)
+}
+
+/// li가 만들어 내는 명령어 수 (1단계에서 라벨 주소 계산용)
+fn instruction_count(mnemonic: &str, operands: &[String], xlen: Xlen) -> Result<usize, String> {
+    if mnemonic != "li" {
+        return Ok(1);
+    }
+    let imm = operands
+        .get(1)
+        .ok_or("li needs 2 operands".to_string())
+        .and_then(|op| parse_imm(op))?;
+    Ok(li_sequence(0, li_value(imm, xlen)?, xlen).len())
+}
+
+/// li rd, imm: 12비트면 addi, 32비트면 lui + addi(w)
+/// RV64에서 더 큰 값은 하위 12비트를 뺀 상위 부분을 재귀로 만든 뒤 slli + addi
+fn li_sequence(rd: usize, imm: i64, xlen: Xlen) -> Vec<u32> {
+    if (-2048..2048).contains(&imm) {
+        return vec![addi(rd, 0, imm as i32)];
+    }
+    if let Ok(imm) = i32::try_from(imm) {
+        let hi = (imm as i64 + 0x800) >> 12;
+        let lo = imm - ((hi << 12) as i32);
+        let mut words = vec![lui(rd, hi as u32)];
+        if lo != 0 {
+            // RV64에서는 addiw로 32비트 부호 확장을 유지
+            words.push(match xlen {
+                Xlen::Rv32 => addi(rd, rd, lo),
+                Xlen::Rv64 => addiw(rd, rd, lo),
+            });
+        }
+        return words;
+    }
+    // imm = (hi << shift) + lo. hi의 하위 0비트를 shift에 합쳐 hi를 최대한 작게 만듦
+    let lo = (imm << 52) >> 52;
+    let hi = (imm as u64).wrapping_add(0x800) >> 12;
+    let shift = 12 + hi.trailing_zeros();
+    let hi = ((hi >> (shift - 12)) << shift) as i64 >> shift;
+    let mut words = li_sequence(rd, hi, xlen);
+    words.push(slli(rd, rd, shift));
+    if lo != 0 {
+        words.push(addi(rd, rd, lo as i32));
+    }
+    words
+}
+
+/// li 즉시값: RV32는 32비트 (부호 없는 값도 허용), RV64는 64비트 전체
+fn li_value(imm: i64, xlen: Xlen) -> Result<i64, String> {
+    match xlen {
+        Xlen::Rv32 if (i32::MIN as i64..=u32::MAX as i64).contains(&imm) => Ok(imm as i32 as i64),
+        Xlen::Rv32 => Err(format!("li immediate {} out of 32-bit range", imm)),
+        Xlen::Rv64 => Ok(imm),
+    }
+}
+
+fn parse_imm(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    // 16진수와 2진수는 64비트 패턴 (0xFFFFFFFFFFFFFFFF = -1)
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16).map(|value| value as i64)
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        u64::from_str_radix(bin, 2).map(|value| value as i64)
+    } else {
+        digits.parse::<i64>()
+    }
+    .map_err(|_| format!("invalid immediate '{}'", text))?;
+    Ok(if negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+pub(crate) fn parse_reg(text: &str) -> Result<usize, String> {
+    let text = text.trim();
+    if let Some(num) = text.strip_prefix('x')
+        && let Ok(index) = num.parse::<usize>()
+        && index < 32
+    {
+        return Ok(index);
+    }
+    if text == "fp" {
+        return Ok(reg::S0);
+    }
+    disasm::ABI_NAMES
+        .iter()
+        .position(|&name| name == text)
+        .ok_or(format!("invalid register '{}'", text))
+}
+
+/// CSR 이름 또는 번호
+fn parse_csr(text: &str) -> Result<u16, String> {
+    if let Ok(value) = parse_imm(text) {
+        return match value {
+            0..=0xFFF => Ok(value as u16),
+            _ => Err(format!("CSR number {:#x} out of range", value)),
+        };
+    }
+    (0..0x1000u16)
+        .find(|&addr| disasm::csr_name(addr) == Some(text.trim()))
+        .ok_or(format!("unknown CSR '{}'", text))
+}
+
+/// fence 집합 문자열 (iorw)
+fn parse_fence_set(text: &str) -> Result<u32, String> {
+    text.trim().chars().try_fold(0, |set, c| match c {
+        'i' => Ok(set | 8),
+        'o' => Ok(set | 4),
+        'r' => Ok(set | 2),
+        'w' => Ok(set | 1),
+        _ => Err(format!("invalid fence set '{}'", text)),
+    })
+}
+
+fn check_range(value: i64, min: i64, max: i64, what: &str) -> Result<i32, String> {
+    if (min..=max).contains(&value) {
+        Ok(value as i32)
+    } else {
+        Err(format!(
+            "{} {} out of range [{}, {}]",
+            what, value, min, max
+        ))
+    }
+}
+
+fn imm12(text: &str) -> Result<i32, String> {
+    check_range(parse_imm(text)?, -2048, 2047, "immediate")
+}
+
+// 텍스트 어셈블러가 피연산자 형식별로 고르는 인코더
+type RegRegImm = fn(usize, usize, i32) -> u32;
+type RegRegShamt = fn(usize, usize, u32) -> u32;
+
+struct Context<'a> {
+    pc: i64,
+    labels: &'a HashMap<String, i64>,
+    xlen: Xlen,
+}
+
+impl Context<'_> {
+    /// 라벨 또는 pc 상대 오프셋 숫자
+    fn offset(&self, text: &str, bits: u32) -> Result<i32, String> {
+        let offset = match self.labels.get(text.trim()) {
+            Some(&addr) => addr - self.pc,
+            None => parse_imm(text).map_err(|_| format!("undefined label '{}'", text.trim()))?,
+        };
+        if offset % 2 != 0 {
+            return Err(format!("misaligned target offset {}", offset));
+        }
+        let limit = 1i64 << (bits - 1);
+        check_range(offset, -limit, limit - 1, "branch offset")
+    }
+
+    fn shamt(&self, text: &str, word: bool) -> Result<u32, String> {
+        let max = if word || self.xlen == Xlen::Rv32 {
+            31
+        } else {
+            63
+        };
+        Ok(check_range(parse_imm(text)?, 0, max, "shift amount")? as u32)
+    }
+
+    /// "off(reg)" 또는 "(reg)"
+    fn mem_operand(&self, text: &str) -> Result<(i32, usize), String> {
+        let text = text.trim();
+        let open = text
+            .find('(')
+            .ok_or(format!("expected offset(reg), got '{}'", text))?;
+        let close = text
+            .rfind(')')
+            .ok_or(format!("expected offset(reg), got '{}'", text))?;
+        let offset = match text[..open].trim() {
+            "" => 0,
+            off => imm12(off)?,
+        };
+        Ok((offset, parse_reg(&text[open + 1..close])?))
+    }
+
+    fn encode(&self, mnemonic: &str, ops: &[String]) -> Result<Vec<u32>, String> {
+        let expect = |count: usize| -> Result<(), String> {
+            if ops.len() == count {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} expects {} operands, got {}",
+                    mnemonic,
+                    count,
+                    ops.len()
+                ))
+            }
+        };
+        let r = |i: usize| parse_reg(&ops[i]);
+
+        // rd, rs1, rs2
+        let r_ops: Option<fn(usize, usize, usize) -> u32> = match mnemonic {
+            "add" => Some(add),
+            "sub" => Some(sub),
+            "sll" => Some(sll),
+            "slt" => Some(slt),
+            "sltu" => Some(sltu),
+            "xor" => Some(xor),
+            "srl" => Some(srl),
+            "sra" => Some(sra),
+            "or" => Some(or),
+            "and" => Some(and),
+            "addw" => Some(addw),
+            "subw" => Some(subw),
+            "sllw" => Some(sllw),
+            "srlw" => Some(srlw),
+            "sraw" => Some(sraw),
+            "mul" => Some(mul),
+            "mulh" => Some(mulh),
+            "mulhsu" => Some(mulhsu),
+            "mulhu" => Some(mulhu),
+            "div" => Some(div),
+            "divu" => Some(divu),
+            "rem" => Some(rem),
+            "remu" => Some(remu),
+            "mulw" => Some(mulw),
+            "divw" => Some(divw),
+            "divuw" => Some(divuw),
+            "remw" => Some(remw),
+            "remuw" => Some(remuw),
+            _ => None,
+        };
+        if let Some(encode) = r_ops {
+            expect(3)?;
+            return Ok(vec![encode(r(0)?, r(1)?, r(2)?)]);
+        }
+
+        // rd, rs1, imm12
+        let i_ops: Option<RegRegImm> = match mnemonic {
+            "addi" => Some(addi),
+            "slti" => Some(slti),
+            "sltiu" => Some(sltiu),
+            "xori" => Some(xori),
+            "ori" => Some(ori),
+            "andi" => Some(andi),
+            "addiw" => Some(addiw),
+            _ => None,
+        };
+        if let Some(encode) = i_ops {
+            expect(3)?;
+            return Ok(vec![encode(r(0)?, r(1)?, imm12(&ops[2])?)]);
+        }
+
+        // rd, rs1, shamt
+        let shift_ops: Option<(RegRegShamt, bool)> = match mnemonic {
+            "slli" => Some((slli, false)),
+            "srli" => Some((srli, false)),
+            "srai" => Some((srai, false)),
+            "slliw" => Some((slliw, true)),
+            "srliw" => Some((srliw, true)),
+            "sraiw" => Some((sraiw, true)),
+            _ => None,
+        };
+        if let Some((encode, word)) = shift_ops {
+            expect(3)?;
+            return Ok(vec![encode(r(0)?, r(1)?, self.shamt(&ops[2], word)?)]);
+        }
+
+        // reg, off(rs1)
+        let mem_ops: Option<fn(usize, i32, usize) -> u32> = match mnemonic {
+            "lb" => Some(lb),
+            "lh" => Some(lh),
+            "lw" => Some(lw),
+            "ld" => Some(ld),
+            "lbu" => Some(lbu),
+            "lhu" => Some(lhu),
+            "lwu" => Some(lwu),
+            "sb" => Some(sb),
+            "sh" => Some(sh),
+            "sw" => Some(sw),
+            "sd" => Some(sd),
+            _ => None,
+        };
+        if let Some(encode) = mem_ops {
+            expect(2)?;
+            let (offset, rs1) = self.mem_operand(&ops[1])?;
+            return Ok(vec![encode(r(0)?, offset, rs1)]);
+        }
+
+        // rs1, rs2, target (bgt/ble 계열은 피연산자를 바꿔서)
+        let branch_ops: Option<(RegRegImm, bool)> = match mnemonic {
+            "beq" => Some((beq, false)),
+            "bne" => Some((bne, false)),
+            "blt" => Some((blt, false)),
+            "bge" => Some((bge, false)),
+            "bltu" => Some((bltu, false)),
+            "bgeu" => Some((bgeu, false)),
+            "bgt" => Some((blt, true)),
+            "ble" => Some((bge, true)),
+            "bgtu" => Some((bltu, true)),
+            "bleu" => Some((bgeu, true)),
+            _ => None,
+        };
+        if let Some((encode, swap)) = branch_ops {
+            expect(3)?;
+            let offset = self.offset(&ops[2], 13)?;
+            let (rs1, rs2) = if swap { (r(1)?, r(0)?) } else { (r(0)?, r(1)?) };
+            return Ok(vec![encode(rs1, rs2, offset)]);
+        }
+
+        // rs, target (x0과 비교)
+        let branch_zero_ops: Option<(RegRegImm, bool)> = match mnemonic {
+            "beqz" => Some((beq, false)),
+            "bnez" => Some((bne, false)),
+            "bltz" => Some((blt, false)),
+            "bgez" => Some((bge, false)),
+            "bgtz" => Some((blt, true)),
+            "blez" => Some((bge, true)),
+            _ => None,
+        };
+        if let Some((encode, swap)) = branch_zero_ops {
+            expect(2)?;
+            let offset = self.offset(&ops[1], 13)?;
+            let (rs1, rs2) = if swap { (0, r(0)?) } else { (r(0)?, 0) };
+            return Ok(vec![encode(rs1, rs2, offset)]);
+        }
+
+        let word = match mnemonic {
+            "lui" | "auipc" => {
+                expect(2)?;
+                let imm = check_range(parse_imm(&ops[1])?, -0x80000, 0xFFFFF, "immediate")?;
+                let encode = if mnemonic == "lui" { lui } else { auipc };
+                encode(r(0)?, imm as u32)
+            }
+            "jal" if ops.len() == 1 => jal(reg::RA, self.offset(&ops[0], 21)?),
+            "jal" => {
+                expect(2)?;
+                jal(r(0)?, self.offset(&ops[1], 21)?)
+            }
+            "j" | "call" | "tail" => {
+                expect(1)?;
+                let rd = if mnemonic == "call" { reg::RA } else { 0 };
+                jal(rd, self.offset(&ops[0], 21)?)
+            }
+            "jalr" if ops.len() == 1 => jalr(reg::RA, 0, r(0)?),
+            "jalr" => {
+                expect(2)?;
+                let (offset, rs1) = self.mem_operand(&ops[1])?;
+                jalr(r(0)?, offset, rs1)
+            }
+            "jr" => {
+                expect(1)?;
+                jalr(0, 0, r(0)?)
+            }
+            "ret" => {
+                expect(0)?;
+                jalr(0, 0, reg::RA)
+            }
+            "nop" => {
+                expect(0)?;
+                addi(0, 0, 0)
+            }
+            "li" => {
+                expect(2)?;
+                let imm = li_value(parse_imm(&ops[1])?, self.xlen)?;
+                return Ok(li_sequence(r(0)?, imm, self.xlen));
+            }
+            "mv" => {
+                expect(2)?;
+                addi(r(0)?, r(1)?, 0)
+            }
+            "not" => {
+                expect(2)?;
+                xori(r(0)?, r(1)?, -1)
+            }
+            "neg" => {
+                expect(2)?;
+                sub(r(0)?, 0, r(1)?)
+            }
+            "negw" => {
+                expect(2)?;
+                subw(r(0)?, 0, r(1)?)
+            }
+            "sext.w" => {
+                expect(2)?;
+                addiw(r(0)?, r(1)?, 0)
+            }
+            "seqz" => {
+                expect(2)?;
+                sltiu(r(0)?, r(1)?, 1)
+            }
+            "snez" => {
+                expect(2)?;
+                sltu(r(0)?, 0, r(1)?)
+            }
+            "sltz" => {
+                expect(2)?;
+                slt(r(0)?, r(1)?, 0)
+            }
+            "sgtz" => {
+                expect(2)?;
+                slt(r(0)?, 0, r(1)?)
+            }
+            "fence" if ops.is_empty() => fence(0xF, 0xF),
+            "fence" => {
+                expect(2)?;
+                fence(parse_fence_set(&ops[0])?, parse_fence_set(&ops[1])?)
+            }
+            "fence.i" => {
+                expect(0)?;
+                fence_i()
+            }
+            "fence.tso" => {
+                expect(0)?;
+                fence_tso()
+            }
+            "ecall" => ecall(),
+            "ebreak" => ebreak(),
+            "mret" => mret(),
+            "sret" => sret(),
+            "wfi" => wfi(),
+            "csrrw" | "csrrs" | "csrrc" => {
+                expect(3)?;
+                let encode = match mnemonic {
+                    "csrrw" => csrrw,
+                    "csrrs" => csrrs,
+                    _ => csrrc,
+                };
+                encode(r(0)?, parse_csr(&ops[1])?, r(2)?)
+            }
+            "csrrwi" | "csrrsi" | "csrrci" => {
+                expect(3)?;
+                let encode = match mnemonic {
+                    "csrrwi" => csrrwi,
+                    "csrrsi" => csrrsi,
+                    _ => csrrci,
+                };
+                let uimm = check_range(parse_imm(&ops[2])?, 0, 31, "CSR immediate")?;
+                encode(r(0)?, parse_csr(&ops[1])?, uimm as u32)
+            }
+            "csrr" => {
+                expect(2)?;
+                csrrs(r(0)?, parse_csr(&ops[1])?, 0)
+            }
+            "csrw" | "csrs" | "csrc" => {
+                expect(2)?;
+                let encode = match mnemonic {
+                    "csrw" => csrrw,
+                    "csrs" => csrrs,
+                    _ => csrrc,
+                };
+                encode(0, parse_csr(&ops[0])?, r(1)?)
+            }
+            "csrwi" | "csrsi" | "csrci" => {
+                expect(2)?;
+                let encode = match mnemonic {
+                    "csrwi" => csrrwi,
+                    "csrsi" => csrrsi,
+                    _ => csrrci,
+                };
+                let uimm = check_range(parse_imm(&ops[1])?, 0, 31, "CSR immediate")?;
+                encode(0, parse_csr(&ops[0])?, uimm as u32)
+            }
+            ".word" => {
+                expect(1)?;
+                check_range(
+                    parse_imm(&ops[0])?,
+                    i32::MIN as i64,
+                    u32::MAX as i64,
+                    "word",
+                )? as u32
+            }
+            _ if mnemonic.starts_with("lr.")
+                || mnemonic.starts_with("sc.")
+                || mnemonic.starts_with("amo") =>
+            {
+                self.encode_amo(mnemonic, ops)?
+            }
+            _ => return Err(format!("unknown instruction '{}'", mnemonic)),
+        };
+        Ok(vec![word])
+    }
+
+    /// lr.w rd,(rs1) / sc.w rd,rs2,(rs1) / amoadd.d.aqrl rd,rs2,(rs1)
+    fn encode_amo(&self, mnemonic: &str, ops: &[String]) -> Result<u32, String> {
+        let mut parts = mnemonic.split('.');
+        let name = parts.next().unwrap_or_default();
+        let funct3 = match parts.next() {
+            Some("w") => 0x2,
+            Some("d") => 0x3,
+            _ => return Err(format!("unknown instruction '{}'", mnemonic)),
+        };
+        let (aq, rl) = match parts.next() {
+            None => (false, false),
+            Some("aq") => (true, false),
+            Some("rl") => (false, true),
+            Some("aqrl") => (true, true),
+            Some(_) => return Err(format!("unknown instruction '{}'", mnemonic)),
+        };
+        let funct5 = match name {
+            "lr" => 0x02,
+            "sc" => 0x03,
+            "amoswap" => 0x01,
+            "amoadd" => 0x00,
+            "amoxor" => 0x04,
+            "amoand" => 0x0C,
+            "amoor" => 0x08,
+            "amomin" => 0x10,
+            "amomax" => 0x14,
+            "amominu" => 0x18,
+            "amomaxu" => 0x1C,
+            _ => return Err(format!("unknown instruction '{}'", mnemonic)),
+        };
+        let (rd, rs2, addr) = match (name, ops) {
+            ("lr", [rd, addr]) => (parse_reg(rd)?, 0, addr),
+            (_, [rd, rs2, addr]) if name != "lr" => (parse_reg(rd)?, parse_reg(rs2)?, addr),
+            _ => return Err(format!("{}: wrong number of operands", mnemonic)),
+        };
+        let (offset, rs1) = self.mem_operand(addr)?;
+        if offset != 0 {
+            return Err(format!("{} takes no offset", mnemonic));
+        }
+        Ok(amo(funct5, funct3, aq, rl, rd, rs1, rs2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reg::*;
+    use super::*;
+    use crate::cpu::Cpu;
+    use crate::csr;
+    use crate::disasm::disassemble;
+
+    #[test]
+    fn test_encoder_matches_known_encodings() {
+        assert_eq!(add(3, 1, 2), 0x002081B3);
+        assert_eq!(sub(A0, A0, A1), 0x40B50533);
+        assert_eq!(addi(A0, ZERO, 42), 0x02A00513);
+        assert_eq!(ld(A1, 8, A0), 0x00853583);
+        assert_eq!(sd(A1, -8, A0), 0xFEB53C23);
+        assert_eq!(lui(A0, 0x12345), 0x12345537);
+        assert_eq!(jalr(ZERO, 0, RA), 0x00008067);
+        assert_eq!(beq(A0, A1, 8), 0x00B50463);
+        assert_eq!(bne(A0, ZERO, -4), 0xFE051EE3);
+        assert_eq!(jal(ZERO, 8), 0x0080006F);
+        assert_eq!(srai(A0, A0, 63), 0x43F55513);
+        assert_eq!(csrrs(T0, csr::MSTATUS, ZERO), 0x300022F3);
+        assert_eq!(lr_w(A0, A0), 0x1005252F);
+        assert_eq!(fence(0xF, 0xF), 0x0FF0000F);
+        assert_eq!(fence_tso(), 0x8330000F);
+        assert_eq!(assemble("fence.tso").unwrap(), [fence_tso()]);
+        assert_eq!(ecall(), 0x00000073);
+        assert_eq!(ebreak(), 0x00100073);
+        assert_eq!(mret(), 0x30200073);
+    }
+
+    #[test]
+    fn test_assemble_with_labels() {
+        let program = assemble(
+            "
+            # 1부터 10까지 합
+                li a0, 0
+                li t0, 10
+            loop:
+                add a0, a0, t0
+                addi t0, t0, -1
+                bnez t0, loop   // 뒤로 분기
+                j done
+                nop
+            done: ret
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program,
+            vec![
+                addi(A0, ZERO, 0),
+                addi(T0, ZERO, 10),
+                add(A0, A0, T0),
+                addi(T0, T0, -1),
+                bne(T0, ZERO, -8),
+                jal(ZERO, 8),
+                addi(ZERO, ZERO, 0),
+                jalr(ZERO, 0, RA),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_li_expansion_keeps_labels_correct() {
+        let program = assemble(
+            "
+                li a0, 0x12345678
+                li a1, 0x1000
+                j end
+            end:
+            ",
+        )
+        .unwrap();
+        // lui+addiw, lui, jal
+        assert_eq!(program.len(), 4);
+        assert_eq!(program[0], lui(A0, 0x12345));
+        assert_eq!(program[1], addiw(A0, A0, 0x678));
+        assert_eq!(program[2], lui(A1, 0x1));
+        assert_eq!(program[3], jal(ZERO, 4));
+    }
+
+    #[test]
+    fn test_li_negative_low_part_rounds_up() {
+        // 0x12345FFF = (0x12346 << 12) - 1
+        let program = assemble("li a0, 0x12345FFF").unwrap();
+        assert_eq!(program, vec![lui(A0, 0x12346), addiw(A0, A0, -1)]);
+        let rv32 = assemble_xlen("li a0, 0xFFFFF000", Xlen::Rv32).unwrap();
+        assert_eq!(rv32, vec![lui(A0, 0xFFFFF)]);
+        assert!(assemble_xlen("li a0, 0x100000000", Xlen::Rv32).is_err());
+    }
+
+    #[test]
+    fn test_li_dram_address() {
+        // lui 0x80001은 RV64에서 음수로 부호 확장되므로 slli로 만듦
+        let program = assemble("li t0, 0x80001000").unwrap();
+        assert_eq!(
+            program,
+            vec![lui(T0, 0x80), addiw(T0, T0, 1), slli(T0, T0, 12)]
+        );
+        let rv32 = assemble_xlen("li t0, 0x80001000", Xlen::Rv32).unwrap();
+        assert_eq!(rv32, vec![lui(T0, 0x80001)]);
+    }
+
+    #[test]
+    fn test_li_64_bit_values_execute() {
+        for value in [
+            0x8000_0000,
+            0x8000_1000,
+            0xFFFF_FFFF,
+            0x1_0000_0000,
+            0x8000_0000_0000_0000,
+            0x7FFF_FFFF_FFFF_FFFF,
+            0xFFFF_FFFF_FFFF_FFFF,
+            0x1234_5678_9ABC_DEF0,
+            0x0000_0800_0000_0800,
+            0xFFFF_FFFF_7FFF_F800,
+            0x0123_4567_89AB_C800,
+        ] {
+            let program = assemble(&format!("li a0, {:#x}", value)).unwrap();
+            assert!(
+                program.len() <= 8,
+                "{:#x}: {} instructions",
+                value,
+                program.len()
+            );
+            let mut cpu = Cpu::new(0);
+            cpu.load_program(&program);
+            for _ in 0..program.len() {
+                cpu.step();
+            }
+            assert_eq!(cpu.read_reg(A0), value, "{:#x}", value);
+        }
+        assert_eq!(assemble("li a0, -1").unwrap(), vec![addi(A0, ZERO, -1)]);
+    }
+
+    #[test]
+    fn test_assemble_round_trips_through_disassembler() {
+        let source = [
+            "add ra,sp,gp",
+            "ld a1,8(a0)",
+            "sd a1,-8(a0)",
+            "slli a0,a0,3",
+            "sraiw a0,a1,31",
+            "mul a0,a0,a1",
+            "mv a0,a1",
+            "neg a0,a1",
+            "not a0,a1",
+            "seqz a0,a1",
+            "snez a0,a1",
+            "sext.w a0,a1",
+            "csrr t0,mstatus",
+            "csrw mtvec,t0",
+            "csrsi mstatus,8",
+            "csrrw a0,mscratch,a1",
+            "fence r,w",
+            "fence.i",
+            "amoswap.w.aqrl a0,a1,(a0)",
+            "amoadd.d a0,a1,(s0)",
+            "lr.d a0,(a0)",
+            "sc.w t0,a1,(a0)",
+            "jr a0",
+            "jalr a0",
+            "jalr t0,16(a0)",
+            "ret",
+            "ecall",
+            "mret",
+        ];
+        for line in source {
+            let program = assemble(line).unwrap();
+            assert_eq!(disassemble(program[0], 0), line, "{:#010x}", program[0]);
+        }
+    }
+
+    #[test]
+    fn test_assemble_errors_report_line() {
+        let err = assemble("nop\nadd a0, a1\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(assemble("addi a0, a0, 4096").is_err());
+        assert!(assemble("j missing").is_err());
+        assert!(assemble("frobnicate a0").is_err());
+        assert!(assemble("add a0, a1, x32").is_err());
+        assert!(assemble("a:\na:\nnop").is_err());
+    }
+}
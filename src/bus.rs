@@ -1,37 +1,232 @@
 use std::collections::HashMap;
+use std::sync::atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
+use crate::csr;
 use crate::devices;
 use crate::devices::stdioterminal::StdioTerminal;
+use crate::scheduler::{Event, Scheduler};
+use crate::write_buffer::{Forward, MemoryModel, WriteBufferEntry, WriteBuffers};
 
-pub struct WriteBufferEntry {
-    pub addr: u64,
-    pub value: u64,
-    pub size: u8,
+/// hart 하나가 보는 버스
+/// 메모리와 장치, 예약, 인터럽트 선은 같은 머신의 모든 Bus가 공유하고 (share()),
+/// 시간(이벤트 스케줄러)과 write buffer는 Bus마다 따로 가짐
+/// 한 스레드에서 실행하면 Bus 하나를 hart들이 번갈아 사용하고 (Machine::run),
+/// 병렬 실행에서는 hart 스레드마다 share()로 만든 Bus를 씀 (Machine::run_parallel)
+pub struct Bus {
+    memory: devices::Memory,
+    shared: Arc<Shared>,
+    // MemoryModel::Relaxed일 때 hart별 write buffer
+    write_buffers: Option<WriteBuffers>,
+    // 장치 이벤트 (mtimecmp 도달, UART 입력 확인)
+    scheduler: Scheduler,
+    // hold_clock() 이후 tick 수. Some이면 tick()이 시계를 진행하지 않고 세기만 함
+    held_ticks: Option<u64>,
+    // 마지막 AMO/LR/SC가 rl이었는지 (다음 aq 연산 전에 SeqCst 펜스가 필요)
+    release_pending: bool,
 }
 
-pub struct Bus {
+/// 번역된 코드(JIT)가 Bus를 거치지 않고 DRAM에 접근할 때 쓰는 호스트 주소
+pub struct HostMemory {
+    /// DRAM 시작
+    pub ram: *mut u8,
+    /// 그래뉼 store 순번 (devices::memory::GRANULE_SHIFT, GRANULES)
+    pub granules: *mut u64,
+    /// 디코딩 캐시에 올라간 코드 페이지 비트맵 (페이지당 1비트)
+    pub code_pages: *const u64,
+    /// store를 직접 해도 되는지. 병렬 실행에서는 쓰기마다 그래뉼 잠금이 필요하므로 false
+    pub stores: bool,
+}
+
+// 잠금이 필요한 장치 상태
+struct Devices {
     clint: devices::Clint,
-    memory: devices::Memory,
     uart: devices::Uart,
-    reservations: HashMap<u64, u64>,
-    write_buffers: HashMap<u64, Vec<WriteBufferEntry>>,
+    // mtime 시간 기준
+    timebase: devices::Timebase,
+    // CLINT mtime에 마지막으로 반영한 시각 (timebase.time 기준)
+    clint_synced: u64,
+}
+
+// LR이 남긴 예약
+struct Reservation {
+    addr: u64,
+    // DRAM이면 LR 때 그래뉼의 store 순번. 바뀌었으면 사이에 store가 있었으므로 예약은 무효
+    seq: Option<u64>,
+}
+
+// 모든 Bus가 공유하는 상태 (병렬 실행에서는 스레드 간 공유)
+struct Shared {
+    devices: Mutex<Devices>,
+    reservations: Mutex<HashMap<u64, Reservation>>,
+    // reservations 크기. 예약이 없으면 has_reservation이 잠금을 건너뜀
+    reservation_count: AtomicUsize,
+    // 디코딩 캐시에 올라간 DRAM 페이지 (비트맵)
+    code_pages: Vec<AtomicU64>,
+    // hart별로 코드 페이지에 쓰기가 일어나 무효화해야 하는 페이지 번호
+    invalidated_pages: Vec<Mutex<Vec<u64>>>,
+    // invalidated_pages가 비어 있지 않은지 (명령어마다 잠금 없이 확인)
+    has_invalidated: Vec<AtomicBool>,
+    // hart별 장치 인터럽트 선 (MIP 비트 형식). 장치 상태가 바뀔 때만 갱신
+    irq_lines: Vec<AtomicU64>,
+}
+
+// 디코딩 캐시 페이지 크기 (cpu::icache::PAGE_SHIFT와 같음)
+const CODE_PAGE_SHIFT: u64 = 12;
+
+/// 호스트 UART 입력을 확인하는 간격 (tick)
+pub const UART_POLL_INTERVAL: u64 = 1024;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // 다른 hart 스레드가 패닉해도 장치 상태는 일관되므로 계속 사용
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Shared {
+    fn new(harts: usize, uart: devices::Uart, code_pages: usize) -> Self {
+        Shared {
+            devices: Mutex::new(Devices {
+                clint: devices::Clint::with_harts(harts),
+                uart,
+                timebase: devices::Timebase::default(),
+                clint_synced: 0,
+            }),
+            reservations: Mutex::new(HashMap::new()),
+            reservation_count: AtomicUsize::new(0),
+            code_pages: (0..code_pages).map(|_| AtomicU64::new(0)).collect(),
+            invalidated_pages: (0..harts).map(|_| Mutex::new(Vec::new())).collect(),
+            has_invalidated: (0..harts).map(|_| AtomicBool::new(false)).collect(),
+            irq_lines: (0..harts).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn devices(&self) -> MutexGuard<'_, Devices> {
+        lock(&self.devices)
+    }
+
+    fn reservations(&self) -> MutexGuard<'_, HashMap<u64, Reservation>> {
+        lock(&self.reservations)
+    }
+
+    fn set_line(&self, hart: usize, bit: u64, level: bool) {
+        if let Some(lines) = self.irq_lines.get(hart) {
+            if level {
+                lines.fetch_or(bit, Ordering::Release);
+            } else {
+                lines.fetch_and(!bit, Ordering::Release);
+            }
+        }
+    }
+
+    /// CLINT 상태로 hart마다 MTIP/MSIP 선을 갱신
+    /// 아직 걸리지 않은 가장 가까운 타이머까지 남은 mtime 단위 시간을 반환
+    fn update_clint_lines(&self, devices: &Devices) -> Option<u64> {
+        let mut next_timer = None;
+        for hart in 0..self.irq_lines.len() {
+            let ticks_until_timer = devices.clint.ticks_until_timer(hart);
+            self.set_line(hart, csr::MIP_MTIP, ticks_until_timer == Some(0));
+            self.set_line(
+                hart,
+                csr::MIP_MSIP,
+                devices.clint.check_software_interrupt(hart),
+            );
+            if let Some(delta) = ticks_until_timer.filter(|&delta| delta > 0) {
+                next_timer = Some(next_timer.map_or(delta, |next: u64| next.min(delta)));
+            }
+        }
+        next_timer
+    }
+
+    // PLIC이 없으므로 UART 인터럽트는 hart 0의 MEIP로 연결
+    fn update_uart_line(&self, devices: &Devices) {
+        self.set_line(0, csr::MIP_MEIP, devices.uart.check_interrupt());
+    }
+
+    fn sync_reservation_count(&self, reservations: &HashMap<u64, Reservation>) {
+        self.reservation_count
+            .store(reservations.len(), Ordering::SeqCst);
+    }
+}
+
+impl Devices {
+    /// 밀린 시간을 CLINT mtime에 반영
+    fn sync_clint(&mut self, now: u64) {
+        let time = self.timebase.time(now);
+        self.clint.advance(time.saturating_sub(self.clint_synced));
+        self.clint_synced = self.clint_synced.max(time);
+    }
+
+    // 마지막 sync 이후 흐른 mtime 단위 시간
+    fn pending_time(&self, now: u64) -> u64 {
+        self.timebase.time(now).saturating_sub(self.clint_synced)
+    }
 }
 
 impl Bus {
     pub fn new() -> Self {
+        Self::with_harts(1)
+    }
+
+    /// harts개 hart가 공유하는 버스 (CLINT msip/mtimecmp와 인터럽트 선이 hart마다 하나씩)
+    pub fn with_harts(harts: usize) -> Self {
         let (terminal, _handle) = StdioTerminal::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::UartPoll, UART_POLL_INTERVAL);
+        let code_pages = (devices::DRAM_SIZE >> CODE_PAGE_SHIFT).div_ceil(64) as usize;
         Self {
-            clint: devices::Clint::new(),
             memory: devices::Memory::new(),
-            uart: devices::Uart::new(Box::new(terminal)),
-            reservations: HashMap::new(),
-            write_buffers: HashMap::new(),
+            shared: Arc::new(Shared::new(
+                harts,
+                devices::Uart::new(Box::new(terminal)),
+                code_pages,
+            )),
+            write_buffers: None,
+            scheduler,
+            held_ticks: None,
+            release_pending: false,
+        }
+    }
+
+    /// 메모리와 장치가 없는 빈 버스
+    /// Machine에서 실행 중이 아닌 hart가 공유 버스를 돌려준 동안 자리를 채움
+    pub fn detached() -> Self {
+        let uart = devices::Uart::new(Box::new(devices::terminal::NullTerminal));
+        Self {
+            memory: devices::Memory::empty(),
+            shared: Arc::new(Shared::new(0, uart, 0)),
+            write_buffers: None,
+            scheduler: Scheduler::new(),
+            held_ticks: None,
+            release_pending: false,
         }
     }
 
+    /// 같은 메모리와 장치를 공유하는 버스 (병렬 실행에서 hart 스레드마다 하나)
+    /// 시간은 이 버스의 현재 시각부터 따로 진행하므로 mtime은 호스트 시계 기준이어야 일관됨
+    /// write buffer 설정도 이어받음 (seed는 이 버스의 RNG에서 뽑음)
+    pub fn share(&mut self) -> Bus {
+        let mut scheduler = Scheduler::new();
+        scheduler.advance(self.scheduler.now());
+        scheduler.schedule(Event::UartPoll, UART_POLL_INTERVAL);
+        let mut bus = Bus {
+            memory: self.memory.share(),
+            shared: Arc::clone(&self.shared),
+            write_buffers: self.write_buffers.as_mut().map(WriteBuffers::fork),
+            scheduler,
+            held_ticks: None,
+            release_pending: false,
+        };
+        bus.refresh_clint();
+        bus
+    }
+
+    pub fn harts(&self) -> usize {
+        self.shared.irq_lines.len()
+    }
+
     pub fn read8(&mut self, addr: u64) -> u8 {
         if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart.read8((addr - devices::UART_BASE) as u8)
+            self.uart_read8(addr)
         } else if addr >= devices::DRAM_BASE {
             self.memory.read8(addr)
         } else {
@@ -41,7 +236,7 @@ impl Bus {
 
     pub fn read16(&mut self, addr: u64) -> u16 {
         if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart.read8((addr - devices::UART_BASE) as u8) as u16
+            self.uart_read8(addr) as u16
         } else if addr >= devices::DRAM_BASE {
             self.memory.read16(addr)
         } else {
@@ -51,9 +246,9 @@ impl Bus {
 
     pub fn read32(&mut self, addr: u64) -> u32 {
         if addr >= devices::CLINT_BASE && addr < devices::CLINT_BASE + devices::CLINT_SIZE {
-            self.clint.read32(addr - devices::CLINT_BASE)
+            self.clint_read(|clint| clint.read32(addr - devices::CLINT_BASE) as u64) as u32
         } else if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart.read8((addr - devices::UART_BASE) as u8) as u32
+            self.uart_read8(addr) as u32
         } else if addr >= devices::DRAM_BASE {
             self.memory.read32(addr)
         } else {
@@ -63,9 +258,9 @@ impl Bus {
 
     pub fn read64(&mut self, addr: u64) -> u64 {
         if addr >= devices::CLINT_BASE && addr < devices::CLINT_BASE + devices::CLINT_SIZE {
-            self.clint.read64(addr - devices::CLINT_BASE)
+            self.clint_read(|clint| clint.read64(addr - devices::CLINT_BASE))
         } else if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart.read8((addr - devices::UART_BASE) as u8) as u64
+            self.uart_read8(addr) as u64
         } else if addr >= devices::DRAM_BASE {
             self.memory.read64(addr)
         } else {
@@ -73,118 +268,642 @@ impl Bus {
         }
     }
     pub fn write8(&mut self, addr: u64, value: u8) {
-        self.invalidate_reservations(addr);
         if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart.write8((addr - devices::UART_BASE) as u8, value);
+            self.uart_write8(addr, value);
         } else if addr >= devices::DRAM_BASE {
             self.memory.write8(addr, value);
+            self.note_code_write(addr, 1);
         } else {
             panic!("Invalid address: {:#x}", addr);
         }
     }
 
     pub fn write16(&mut self, addr: u64, value: u16) {
-        self.invalidate_reservations(addr);
         if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart
-                .write8((addr - devices::UART_BASE) as u8, value as u8);
+            self.uart_write8(addr, value as u8);
         } else if addr >= devices::DRAM_BASE {
             self.memory.write16(addr, value);
+            self.note_code_write(addr, 2);
         } else {
             panic!("Invalid address: {:#x}", addr);
         }
     }
 
     pub fn write32(&mut self, addr: u64, value: u32) {
-        self.invalidate_reservations(addr);
         if addr >= devices::CLINT_BASE && addr < devices::CLINT_BASE + devices::CLINT_SIZE {
-            self.clint.write32(addr - devices::CLINT_BASE, value);
+            self.clint_write(|clint| clint.write32(addr - devices::CLINT_BASE, value));
         } else if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart
-                .write8((addr - devices::UART_BASE) as u8, value as u8);
+            self.uart_write8(addr, value as u8);
         } else if addr >= devices::DRAM_BASE {
             self.memory.write32(addr, value);
+            self.note_code_write(addr, 4);
         } else {
             panic!("Invalid address: {:#x}", addr);
         }
     }
 
     pub fn write64(&mut self, addr: u64, value: u64) {
-        self.invalidate_reservations(addr);
         if addr >= devices::CLINT_BASE && addr < devices::CLINT_BASE + devices::CLINT_SIZE {
-            self.clint.write64(addr - devices::CLINT_BASE, value);
+            self.clint_write(|clint| clint.write64(addr - devices::CLINT_BASE, value));
         } else if addr >= devices::UART_BASE && addr < devices::UART_BASE + devices::UART_SIZE {
-            self.uart
-                .write8((addr - devices::UART_BASE) as u8, value as u8);
+            self.uart_write8(addr, value as u8);
         } else if addr >= devices::DRAM_BASE {
             self.memory.write64(addr, value);
+            self.note_code_write(addr, 8);
         } else {
             panic!("Invalid address: {:#x}", addr);
         }
     }
 
+    pub fn read_sized(&mut self, addr: u64, size: u8) -> u64 {
+        match size {
+            1 => self.read8(addr) as u64,
+            2 => self.read16(addr) as u64,
+            4 => self.read32(addr) as u64,
+            8 => self.read64(addr),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write_sized(&mut self, addr: u64, size: u8, value: u64) {
+        match size {
+            1 => self.write8(addr, value as u8),
+            2 => self.write16(addr, value as u16),
+            4 => self.write32(addr, value as u32),
+            8 => self.write64(addr, value),
+            _ => unreachable!(),
+        }
+    }
+
+    // 정렬된 DRAM 위치면 호스트 atomic 연산에 쓸 오프셋
+    fn atomic_offset(addr: u64, size: u8) -> Option<usize> {
+        Self::ram_offset(addr).filter(|offset| offset.is_multiple_of(size as usize))
+    }
+
+    /// AMO: 읽기-수정-쓰기를 하나의 버스 연산으로 수행 (중간에 다른 hart가 끼어들 수 없음)
+    /// DRAM은 그래뉼 잠금 안에서 읽고 쓰므로 병렬 실행에서도 같은 그래뉼의 다른 쓰기와 원자적
+    pub fn atomic_rmw(&mut self, addr: u64, size: u8, op: impl FnOnce(u64) -> u64) -> u64 {
+        let Some(offset) = Self::atomic_offset(addr, size) else {
+            let old = self.read_sized(addr, size);
+            self.write_sized(addr, size, op(old));
+            return old;
+        };
+        let old = self
+            .memory
+            .update(offset, size as usize, |old| Some(op(old)));
+        self.note_code_write(addr, size as u64);
+        old
+    }
+
+    /// AMOCAS: 값이 expected와 같을 때만 쓰기, 항상 이전 값을 반환
+    pub fn compare_and_swap(&mut self, addr: u64, size: u8, expected: u64, new: u64) -> u64 {
+        let Some(offset) = Self::atomic_offset(addr, size) else {
+            let old = self.read_sized(addr, size);
+            if old == expected {
+                self.write_sized(addr, size, new);
+            }
+            return old;
+        };
+        let mask = u64::MAX >> (64 - 8 * size as u32);
+        let old = self.memory.update(offset, size as usize, |old| {
+            (old == expected & mask).then_some(new)
+        });
+        if old == expected & mask {
+            self.note_code_write(addr, size as u64);
+        }
+        old
+    }
+
+    /// AMOCAS.Q: 128비트 compare-and-swap (addr은 16바이트 정렬)
+    /// 블록이 그래뉼 하나라서 그래뉼 잠금으로 두 워드에 대한 다른 스레드의 모든 쓰기와 원자적
+    pub fn compare_and_swap128(&mut self, addr: u64, expected: u128, new: u128) -> u128 {
+        let Some(offset) = Self::atomic_offset(addr, 16) else {
+            let old = (self.read64(addr) as u128) | ((self.read64(addr + 8) as u128) << 64);
+            if old == expected {
+                self.write64(addr, new as u64);
+                self.write64(addr + 8, (new >> 64) as u64);
+            }
+            return old;
+        };
+        let old = self.memory.compare_exchange128(offset, expected, new);
+        if old == expected {
+            self.note_code_write(addr, 16);
+        }
+        old
+    }
+
     pub fn reserve(&mut self, hart_id: u64, addr: u64) {
-        self.reservations.insert(hart_id, addr);
+        let seq = Self::ram_offset(addr).map(|offset| self.memory.granule_seq(offset));
+        let mut reservations = self.shared.reservations();
+        reservations.insert(hart_id, Reservation { addr, seq });
+        self.shared.sync_reservation_count(&reservations);
+    }
+
+    /// LR: size바이트를 읽고 예약. DRAM이면 읽을 때의 그래뉼 store 순번을 기억
+    pub fn load_reserved(&mut self, hart_id: u64, addr: u64, size: u8) -> u64 {
+        let (value, seq) = match Self::atomic_offset(addr, size) {
+            Some(offset) => {
+                let (value, seq) = self.memory.load_linked(offset, size as usize);
+                (value, Some(seq))
+            }
+            None => (self.read_sized(addr, size), None),
+        };
+        let mut reservations = self.shared.reservations();
+        reservations.insert(hart_id, Reservation { addr, seq });
+        self.shared.sync_reservation_count(&reservations);
+        value
+    }
+
+    /// SC: 예약이 유효하면 쓰고 true. 결과와 관계없이 예약은 사라짐
+    /// DRAM은 그래뉼 잠금 안에서 store 순번이 LR 때와 같은지 확인하고 쓰므로,
+    /// 같은 값을 다시 쓴 store(ABA)를 포함해 LR 이후의 모든 store가 SC를 실패시킴
+    pub fn store_conditional(&mut self, hart_id: u64, addr: u64, size: u8, value: u64) -> bool {
+        let mut reservations = self.shared.reservations();
+        let reservation = reservations.remove(&hart_id);
+        self.shared.sync_reservation_count(&reservations);
+        drop(reservations);
+        let Some(reservation) = reservation.filter(|reservation| reservation.addr == addr) else {
+            return false;
+        };
+        // 성공하면 같은 그래뉼을 예약한 다른 hart의 예약은 순번이 바뀌어 무효
+        match (Self::atomic_offset(addr, size), reservation.seq) {
+            (Some(offset), Some(seq)) => {
+                let stored = self
+                    .memory
+                    .store_conditional(offset, size as usize, value, seq);
+                if stored {
+                    self.note_code_write(addr, size as u64);
+                }
+                stored
+            }
+            _ => {
+                // 순번이 없는 장치 주소는 같은 주소의 다른 예약을 직접 지움
+                let mut reservations = self.shared.reservations();
+                reservations.retain(|_hart, other| other.addr != addr);
+                self.shared.sync_reservation_count(&reservations);
+                drop(reservations);
+                self.write_sized(addr, size, value);
+                true
+            }
+        }
+    }
+
+    // LR 이후 예약 그래뉼에 store가 없었는지
+    fn reservation_valid(&self, reservation: &Reservation) -> bool {
+        match (Self::ram_offset(reservation.addr), reservation.seq) {
+            (Some(offset), Some(seq)) => self.memory.granule_seq(offset) == seq,
+            _ => true,
+        }
     }
 
     pub fn check_reservation(&self, hart_id: u64, addr: u64) -> bool {
-        match self.reservations.get(&hart_id) {
-            Some(val) => *val == addr,
+        match self.shared.reservations().get(&hart_id) {
+            Some(reservation) => reservation.addr == addr && self.reservation_valid(reservation),
             None => false,
         }
     }
 
+    /// hart가 주소와 관계없이 유효한 예약을 가지고 있는지 (Zawrs)
+    pub fn has_reservation(&self, hart_id: u64) -> bool {
+        self.shared.reservation_count.load(Ordering::SeqCst) != 0
+            && self
+                .shared
+                .reservations()
+                .get(&hart_id)
+                .is_some_and(|reservation| self.reservation_valid(reservation))
+    }
+
     pub fn clear_reservation(&mut self, hart_id: u64) {
-        self.reservations.remove(&hart_id);
+        let mut reservations = self.shared.reservations();
+        reservations.remove(&hart_id);
+        self.shared.sync_reservation_count(&reservations);
     }
 
-    pub fn invalidate_reservations(&mut self, addr: u64) {
-        self.reservations.retain(|_key, value| *value != addr);
+    /// AMO/LR/SC 전: rl이면 앞선 접근이 먼저 보이도록 Release 펜스 (aqrl은 SeqCst)
+    /// aq/rl은 RCsc라서 rl 연산 뒤의 aq 연산도 순서를 지켜야 하므로 그때도 SeqCst
+    /// 호스트 펜스는 병렬 실행에서만 필요 (한 스레드에서는 접근이 이미 순서대로)
+    pub fn atomic_fence_before(&mut self, aq: bool, rl: bool) {
+        if !self.memory.is_concurrent() {
+            return;
+        }
+        if aq && (rl || self.release_pending) {
+            atomic::fence(Ordering::SeqCst);
+            self.release_pending = false;
+        } else if rl {
+            atomic::fence(Ordering::Release);
+        }
     }
 
-    pub fn fence(&mut self, hart_id: u64, pred: u32, _succ: u32) {
+    /// AMO/LR/SC 뒤: aq면 이후 접근이 먼저 보이지 않도록 Acquire 펜스 (aqrl은 SeqCst)
+    pub fn atomic_fence_after(&mut self, aq: bool, rl: bool) {
+        if !self.memory.is_concurrent() {
+            return;
+        }
+        match (aq, rl) {
+            (true, true) => atomic::fence(Ordering::SeqCst),
+            (true, false) => atomic::fence(Ordering::Acquire),
+            _ => {}
+        }
+        self.release_pending = rl && !aq;
+    }
+
+    /// FENCE: 이전 store(pred W)와 이후 접근의 순서를 write buffer에 반영
+    /// load는 실행 시점에 수행하므로 write buffer에서는 pred R이 항상 지켜짐
+    /// 병렬 실행에서는 호스트 펜스도 실행 (W→R은 SeqCst, R→RW는 Acquire, W→W는 Release)
+    pub fn fence(&mut self, hart_id: u64, pred: u32, succ: u32) {
         const FENCE_W: u32 = 0b0001;
+        const FENCE_R: u32 = 0b0010;
+        // 장치 접근은 잠금 뒤에 있으므로 I는 R, O는 W처럼 취급
+        const READS: u32 = FENCE_R | 0b1000;
+        const WRITES: u32 = FENCE_W | 0b0100;
+
+        if self.memory.is_concurrent() && succ != 0 {
+            let acquire = pred & READS != 0;
+            let release = pred & WRITES != 0 && succ & WRITES != 0;
+            if pred & WRITES != 0 && succ & READS != 0 {
+                atomic::fence(Ordering::SeqCst);
+                self.release_pending = false;
+            } else if acquire && release {
+                atomic::fence(Ordering::AcqRel);
+            } else if acquire {
+                atomic::fence(Ordering::Acquire);
+            } else if release {
+                atomic::fence(Ordering::Release);
+            }
+        }
+        if pred & FENCE_W == 0 {
+            return;
+        }
+        if succ & FENCE_R != 0 {
+            self.flush_write_buffer(hart_id);
+        } else if let Some(buffers) = self.write_buffers.as_mut() {
+            buffers.barrier(hart_id);
+        }
+    }
+
+    /// 메모리 모델 변경. Sequential로 바꾸면 밀린 store를 모두 반영
+    pub fn set_memory_model(&mut self, model: MemoryModel) {
+        self.flush_write_buffers();
+        self.write_buffers = match model {
+            MemoryModel::Sequential => None,
+            MemoryModel::Relaxed { seed } => Some(WriteBuffers::new(seed)),
+        };
+    }
+
+    /// store를 write buffer에 두는지 (MemoryModel::Relaxed)
+    pub fn buffers_writes(&self) -> bool {
+        self.write_buffers.is_some()
+    }
+
+    // [addr, addr + size)가 모두 DRAM이면 write buffer 대상
+    fn bufferable(addr: u64, size: u64) -> bool {
+        Self::ram_offset(addr).is_some() && Self::ram_offset(addr + size - 1).is_some()
+    }
+
+    /// hart의 load. 자기 write buffer의 store를 먼저 봄
+    pub fn buffered_load(&mut self, hart_id: u64, addr: u64, size: u8) -> u64 {
+        let Some(buffers) = self.write_buffers.as_ref() else {
+            return self.read_sized(addr, size);
+        };
+        if !Self::bufferable(addr, size as u64) {
+            self.flush_write_buffer(hart_id);
+            return self.read_sized(addr, size);
+        }
+        match buffers.forward(hart_id, addr, size) {
+            Forward::Value(value) => value,
+            Forward::Memory => self.read_sized(addr, size),
+            Forward::Partial => {
+                self.drain_overlapping(hart_id, addr, size as u64);
+                self.read_sized(addr, size)
+            }
+        }
+    }
 
-        if pred & FENCE_W != 0 {
+    /// hart의 store. DRAM이면 write buffer에 넣고, MMIO면 밀린 store를 반영한 뒤 바로 씀
+    pub fn buffered_store(&mut self, hart_id: u64, addr: u64, size: u8, value: u64) {
+        let Some(buffers) = self.write_buffers.as_mut() else {
+            self.write_sized(addr, size, value);
+            return;
+        };
+        if !Self::bufferable(addr, size as u64) {
             self.flush_write_buffer(hart_id);
+            self.write_sized(addr, size, value);
+            return;
         }
+        if let Some(evicted) = buffers.push(hart_id, addr, size, value) {
+            self.apply_writes([evicted]);
+        }
+    }
+
+    /// [addr, addr + size)에 대한 hart의 밀린 store를 반영 (AMO, LR/SC, CBO.ZERO 전)
+    /// 겹치는 store보다 오래된 store도 함께 순서대로 반영
+    pub fn drain_overlapping(&mut self, hart_id: u64, addr: u64, size: u64) {
+        if let Some(buffers) = self.write_buffers.as_mut() {
+            let entries = buffers.take_through(hart_id, addr, size);
+            self.apply_writes(entries);
+        }
+    }
+
+    fn apply_writes(&mut self, entries: impl IntoIterator<Item = WriteBufferEntry>) {
+        for entry in entries {
+            self.write_sized(entry.addr, entry.size, entry.value);
+        }
+    }
+
+    /// 시작 이후 진행한 tick 수
+    pub fn now(&self) -> u64 {
+        self.scheduler.now()
     }
 
+    /// 시간을 1 tick 진행. 마감된 장치 이벤트가 있을 때만 장치를 처리
+    /// 시계를 붙잡은 동안에는 tick 수만 셈
     pub fn tick(&mut self) {
-        self.clint.tick();
+        match &mut self.held_ticks {
+            Some(held) => *held += 1,
+            None => {
+                if self.scheduler.advance(1) {
+                    self.run_events();
+                }
+            }
+        }
+        if self.write_buffers.is_some() {
+            self.drain_random();
+        }
+    }
+
+    // 약한 메모리 모드에서 tick마다 write buffer의 store 하나를 무작위로 반영
+    #[cold]
+    fn drain_random(&mut self) {
+        if let Some(entry) = self
+            .write_buffers
+            .as_mut()
+            .and_then(WriteBuffers::pick_drain)
+        {
+            self.apply_writes([entry]);
+        }
+    }
+
+    /// 시계를 붙잡음. 이후 tick()은 시각을 바꾸지 않음
+    /// Machine은 hart 차례 동안 시계를 붙잡고, 라운드가 끝나면 한 번에 진행함
+    pub fn hold_clock(&mut self) {
+        self.held_ticks = Some(0);
+    }
+
+    /// 시계를 놓고 붙잡은 동안의 tick 수를 반환
+    pub fn release_clock(&mut self) -> u64 {
+        self.held_ticks.take().unwrap_or(0)
+    }
+
+    /// 붙잡은 뒤 흐른 tick 수 (붙잡지 않았으면 0)
+    pub fn held_ticks(&self) -> u64 {
+        self.held_ticks.unwrap_or(0)
+    }
+
+    /// 시간을 ticks만큼 한 번에 진행
+    pub fn advance(&mut self, ticks: u64) {
+        if self.scheduler.advance(ticks) {
+            self.run_events();
+        }
+    }
+
+    /// 다음 장치 이벤트까지 남은 tick 수
+    pub fn ticks_until_next_event(&self) -> Option<u64> {
+        self.scheduler.ticks_until_next()
+    }
+
+    fn run_events(&mut self) {
+        while let Some(event) = self.scheduler.pop_due() {
+            match event {
+                Event::Timer => self.refresh_clint(),
+                Event::UartPoll => {
+                    self.receive_uart_input();
+                    // 다른 Bus(hart 스레드)가 바꾼 CLINT 상태도 주기적으로 반영
+                    self.refresh_clint();
+                    self.scheduler.schedule(Event::UartPoll, UART_POLL_INTERVAL);
+                }
+            }
+        }
+    }
+
+    pub fn timebase(&self) -> devices::Timebase {
+        self.shared.devices().timebase
+    }
+
+    /// mtime 시간 기준 변경. 지금까지 흐른 시간은 이전 기준으로 반영
+    pub fn set_timebase(&mut self, timebase: devices::Timebase) {
+        let now = self.scheduler.now();
+        let mut devices = self.shared.devices();
+        devices.sync_clint(now);
+        devices.timebase = timebase;
+        devices.clint_synced = timebase.time(now);
+        let next_timer = self.shared.update_clint_lines(&devices);
+        Self::schedule_timer(&mut self.scheduler, &devices, next_timer);
+    }
+
+    /// mtime을 따라잡고 선과 다음 타이머 이벤트를 갱신
+    fn refresh_clint(&mut self) {
+        self.clint_write(|_clint| {});
+    }
+
+    fn clint_read(&mut self, read: impl FnOnce(&devices::Clint) -> u64) -> u64 {
+        let now = self.scheduler.now();
+        let mut devices = self.shared.devices();
+        devices.sync_clint(now);
+        read(&devices.clint)
+    }
+
+    fn clint_write(&mut self, write: impl FnOnce(&mut devices::Clint)) {
+        let now = self.scheduler.now();
+        let mut devices = self.shared.devices();
+        devices.sync_clint(now);
+        write(&mut devices.clint);
+        let next_timer = self.shared.update_clint_lines(&devices);
+        Self::schedule_timer(&mut self.scheduler, &devices, next_timer);
+    }
+
+    // 가장 가까운 mtimecmp에 맞춰 이 Bus의 타이머 이벤트 예약
+    fn schedule_timer(scheduler: &mut Scheduler, devices: &Devices, next_timer: Option<u64>) {
+        match next_timer {
+            Some(delta) => {
+                let ticks = devices.timebase.ticks_until(scheduler.now(), delta);
+                scheduler.schedule(Event::Timer, ticks);
+            }
+            None => scheduler.cancel(Event::Timer),
+        }
+    }
+
+    fn uart_read8(&mut self, addr: u64) -> u8 {
+        let mut devices = self.shared.devices();
+        let value = devices.uart.read8((addr - devices::UART_BASE) as u8);
+        self.shared.update_uart_line(&devices);
+        value
+    }
+
+    fn uart_write8(&mut self, addr: u64, value: u8) {
+        let mut devices = self.shared.devices();
+        devices
+            .uart
+            .write8((addr - devices::UART_BASE) as u8, value);
+        self.shared.update_uart_line(&devices);
+    }
+
+    /// hart의 장치 인터럽트 선 (MIP의 MTIP/MSIP/MEIP 비트)
+    pub fn irq_lines(&self, hart_id: u64) -> u64 {
+        self.shared
+            .irq_lines
+            .get(hart_id as usize)
+            .map_or(0, |lines| lines.load(Ordering::Acquire))
+    }
+
+    pub fn mtime(&self) -> u64 {
+        let devices = self.shared.devices();
+        devices
+            .clint
+            .mtime()
+            .wrapping_add(devices.pending_time(self.scheduler.now()))
     }
 
-    pub fn check_timer_interrupt(&self) -> bool {
-        self.clint.check_timer_interrupt()
+    pub fn check_timer_interrupt(&self, hart_id: u64) -> bool {
+        let devices = self.shared.devices();
+        let pending = devices.pending_time(self.scheduler.now());
+        devices
+            .clint
+            .ticks_until_timer(hart_id as usize)
+            .is_some_and(|ticks| ticks <= pending)
     }
 
-    pub fn check_software_interrupt(&self) -> bool {
-        self.clint.check_software_interrupt()
+    pub fn check_software_interrupt(&self, hart_id: u64) -> bool {
+        self.shared
+            .devices()
+            .clint
+            .check_software_interrupt(hart_id as usize)
     }
 
     pub fn check_uart_interrupt(&self) -> bool {
-        self.uart.check_interrupt()
+        self.shared.devices().uart.check_interrupt()
     }
 
     pub fn push_uart_input(&mut self, data: u8) {
-        self.uart.push_input(data);
+        let mut devices = self.shared.devices();
+        devices.uart.push_input(data);
+        self.shared.update_uart_line(&devices);
+    }
+
+    /// UART에 연결된 터미널 교체 (기본은 표준 입출력)
+    pub fn set_terminal(&mut self, terminal: Box<dyn devices::terminal::Terminal>) {
+        self.shared.devices().uart.set_terminal(terminal);
     }
 
+    /// 호스트 터미널 입력 확인 (UartPoll 이벤트마다 호출)
     pub fn receive_uart_input(&mut self) {
-        self.uart.receive_input();
+        let mut devices = self.shared.devices();
+        devices.uart.receive_input();
+        self.shared.update_uart_line(&devices);
     }
 
-    pub fn flush_write_buffer(&mut self, hart_id: u64) {
-        if let Some(buffer) = self.write_buffers.remove(&hart_id) {
-            for entry in buffer {
-                match entry.size {
-                    1 => self.write8(entry.addr, entry.value as u8),
-                    2 => self.write16(entry.addr, entry.value as u16),
-                    4 => self.write32(entry.addr, entry.value as u32),
-                    8 => self.write64(entry.addr, entry.value),
-                    _ => unreachable!(),
-                }
+    /// 디코딩 캐시가 addr이 속한 페이지의 명령어를 보관하기 시작함
+    pub fn mark_code_page(&mut self, addr: u64) {
+        if let Some(page) = Self::dram_page(addr) {
+            self.shared.code_pages[page / 64].fetch_or(1 << (page % 64), Ordering::AcqRel);
+        }
+    }
+
+    /// 마지막 호출 이후 쓰기가 일어난 코드 페이지 번호 (addr >> 12)
+    pub fn take_invalidated_pages(&mut self, hart_id: u64) -> Vec<u64> {
+        let hart = hart_id as usize;
+        let Some(queue) = self.shared.invalidated_pages.get(hart) else {
+            return Vec::new();
+        };
+        let mut queue = lock(queue);
+        self.shared.has_invalidated[hart].store(false, Ordering::Release);
+        std::mem::take(&mut *queue)
+    }
+
+    /// 주소가 DRAM이면 DRAM 시작 기준 오프셋 (MMIO와 매핑되지 않은 주소는 None)
+    pub fn ram_offset(addr: u64) -> Option<usize> {
+        let offset = addr.checked_sub(devices::DRAM_BASE)?;
+        (offset < devices::DRAM_SIZE).then_some(offset as usize)
+    }
+
+    /// ram_offset으로 얻은 위치에서 읽기. 장치 분기를 거치지 않음
+    pub fn load_ram(&self, offset: usize, size: usize) -> u64 {
+        self.memory.load(offset, size)
+    }
+
+    /// ram_offset으로 얻은 위치에 쓰기. 장치 분기 없이 코드 페이지 추적만 수행
+    /// (예약은 그래뉼 store 순번으로 무효화됨)
+    pub fn store_ram(&mut self, offset: usize, size: usize, value: u64) {
+        let addr = devices::DRAM_BASE + offset as u64;
+        self.memory.store(offset, size, value);
+        self.note_code_write(addr, size as u64);
+    }
+
+    /// 번역된 코드가 DRAM에 직접 접근하기 위한 호스트 주소
+    pub fn host_memory(&self) -> HostMemory {
+        HostMemory {
+            ram: self.memory.host_ptr(),
+            granules: self.memory.granules_ptr(),
+            code_pages: self.shared.code_pages.as_ptr().cast::<u64>(),
+            stores: !self.memory.is_concurrent(),
+        }
+    }
+
+    /// hart가 아직 가져가지 않은 코드 페이지 쓰기가 있는지
+    pub fn has_invalidated_pages(&self, hart_id: u64) -> bool {
+        self.shared
+            .has_invalidated
+            .get(hart_id as usize)
+            .is_some_and(|pending| pending.load(Ordering::Acquire))
+    }
+
+    fn dram_page(addr: u64) -> Option<usize> {
+        let offset = addr.checked_sub(devices::DRAM_BASE)?;
+        (offset < devices::DRAM_SIZE).then_some((offset >> CODE_PAGE_SHIFT) as usize)
+    }
+
+    /// 코드 페이지에 대한 쓰기는 디코딩 캐시 무효화 대상으로 기록 (자기 수정 코드)
+    /// 메모리에 쓴 뒤에 불러야 함. 먼저 무효화하면 그 사이 다른 hart가 이전 바이트를 다시 디코딩해서
+    /// 캐시에 넣을 수 있음
+    fn note_code_write(&self, addr: u64, size: u64) {
+        for addr in [addr, addr + size - 1] {
+            let Some(page) = Self::dram_page(addr) else {
+                continue;
+            };
+            let Some(word) = self.shared.code_pages.get(page / 64) else {
+                continue;
+            };
+            let bit = 1 << (page % 64);
+            if word.load(Ordering::Acquire) & bit == 0
+                || word.fetch_and(!bit, Ordering::AcqRel) & bit == 0
+            {
+                continue;
             }
+            // 어느 hart의 디코딩 캐시에 있는지 모르므로 모든 hart에 알림
+            for (queue, pending) in self
+                .shared
+                .invalidated_pages
+                .iter()
+                .zip(&self.shared.has_invalidated)
+            {
+                lock(queue).push(addr >> CODE_PAGE_SHIFT);
+                pending.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// hart의 밀린 store를 모두 순서대로 반영
+    pub fn flush_write_buffer(&mut self, hart_id: u64) {
+        if let Some(buffers) = self.write_buffers.as_mut() {
+            let entries = buffers.take_all(hart_id);
+            self.apply_writes(entries);
+        }
+    }
+
+    /// 모든 hart의 밀린 store를 반영 (실행이 끝난 뒤 메모리를 볼 때)
+    pub fn flush_write_buffers(&mut self) {
+        if let Some(buffers) = self.write_buffers.as_mut() {
+            let entries = buffers.take_everything();
+            self.apply_writes(entries);
         }
     }
 }
@@ -244,7 +963,7 @@ mod tests {
         bus.write8(0x10000001, 0x01);
 
         // push_input으로 데이터 주입 → 인터럽트 발생
-        bus.uart.push_input(b'A');
+        bus.push_uart_input(b'A');
 
         assert!(bus.check_uart_interrupt());
     }
@@ -271,6 +990,93 @@ mod tests {
         assert_eq!(bus.read32(0x2000000), 1);
     }
 
+    // 이벤트 스케줄러 테스트
+    #[test]
+    fn test_timer_line_raised_by_scheduled_event() {
+        let mut bus = Bus::new();
+        bus.write64(0x2004000, 3); // mtimecmp
+        assert_eq!(bus.ticks_until_next_event(), Some(3));
+        bus.tick();
+        bus.tick();
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, 0);
+        bus.tick();
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, csr::MIP_MTIP);
+        assert_eq!(bus.mtime(), 3);
+        assert_eq!(bus.read64(0x200BFF8), 3);
+
+        // mtimecmp를 뒤로 옮기면 선이 내려가고 다시 예약
+        bus.write64(0x2004000, 10);
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, 0);
+        assert_eq!(bus.ticks_until_next_event(), Some(7));
+    }
+
+    #[test]
+    fn test_held_clock_counts_ticks() {
+        let mut bus = Bus::new();
+        bus.write64(0x2004000, 3); // mtimecmp
+        bus.hold_clock();
+        for _ in 0..5 {
+            bus.tick();
+        }
+        // 붙잡은 동안에는 시각과 장치 선이 그대로
+        assert_eq!(bus.now(), 0);
+        assert_eq!(bus.held_ticks(), 5);
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, 0);
+        assert_eq!(bus.release_clock(), 5);
+        bus.advance(3);
+        assert_eq!(bus.now(), 3);
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, csr::MIP_MTIP);
+    }
+
+    #[test]
+    fn test_mtime_write_reschedules_timer() {
+        let mut bus = Bus::new();
+        bus.write64(0x2004000, 100);
+        bus.write64(0x200BFF8, 99);
+        assert!(!bus.check_timer_interrupt(0));
+        bus.tick();
+        assert!(bus.check_timer_interrupt(0));
+        assert_eq!(bus.irq_lines(0) & csr::MIP_MTIP, csr::MIP_MTIP);
+    }
+
+    #[test]
+    fn test_software_and_uart_lines() {
+        let mut bus = Bus::new();
+        bus.write32(0x2000000, 1); // msip
+        assert_eq!(bus.irq_lines(0), csr::MIP_MSIP);
+        bus.write32(0x2000000, 0);
+        assert_eq!(bus.irq_lines(0), 0);
+
+        bus.write8(0x10000001, 0x01); // IER RX
+        bus.push_uart_input(b'A');
+        assert_eq!(bus.irq_lines(0), csr::MIP_MEIP);
+        assert_eq!(bus.read8(0x10000000), b'A');
+        assert_eq!(bus.irq_lines(0), 0);
+    }
+
+    #[test]
+    fn test_per_hart_interrupt_lines() {
+        let mut bus = Bus::with_harts(3);
+        bus.write64(0x2004000 + 8 * 2, 2); // hart 2 mtimecmp
+        bus.write64(0x2004000 + 8, 5); // hart 1 mtimecmp
+        bus.write32(0x2000000 + 4, 1); // hart 1 msip
+        assert_eq!(bus.irq_lines(1), csr::MIP_MSIP);
+        // 가장 가까운 mtimecmp 기준으로 이벤트 예약
+        assert_eq!(bus.ticks_until_next_event(), Some(2));
+        bus.tick();
+        bus.tick();
+        assert_eq!(bus.irq_lines(0), 0);
+        assert_eq!(bus.irq_lines(1), csr::MIP_MSIP);
+        assert_eq!(bus.irq_lines(2), csr::MIP_MTIP);
+        assert_eq!(bus.ticks_until_next_event(), Some(3));
+        for _ in 0..3 {
+            bus.tick();
+        }
+        assert_eq!(bus.irq_lines(1), csr::MIP_MSIP | csr::MIP_MTIP);
+        assert!(bus.check_timer_interrupt(1));
+        assert!(!bus.check_timer_interrupt(0));
+    }
+
     // 잘못된 주소 테스트
     #[test]
     #[should_panic(expected = "Invalid address")]
@@ -374,6 +1180,59 @@ mod tests {
         assert!(bus.check_reservation(hart_id, addr2));
     }
 
+    // Atomic 연산 테스트
+    #[test]
+    fn test_atomic_rmw_returns_old_value() {
+        let mut bus = Bus::new();
+        bus.write32(0x80001000, 10);
+        let old = bus.atomic_rmw(0x80001000, 4, |old| old + 5);
+        assert_eq!(old, 10);
+        assert_eq!(bus.read32(0x80001000), 15);
+    }
+
+    #[test]
+    fn test_atomic_rmw_byte_does_not_touch_neighbors() {
+        let mut bus = Bus::new();
+        bus.write32(0x80001000, 0x11223344);
+        bus.atomic_rmw(0x80001001, 1, |_| 0xFF);
+        assert_eq!(bus.read32(0x80001000), 0x1122FF44);
+    }
+
+    #[test]
+    fn test_atomic_rmw_invalidates_reservation() {
+        let mut bus = Bus::new();
+        bus.reserve(1, 0x80001000);
+        bus.atomic_rmw(0x80001000, 4, |old| old + 1);
+        assert!(!bus.check_reservation(1, 0x80001000));
+    }
+
+    #[test]
+    fn test_compare_and_swap() {
+        let mut bus = Bus::new();
+        bus.write64(0x80001000, 5);
+
+        // 불일치: 쓰지 않음
+        assert_eq!(bus.compare_and_swap(0x80001000, 8, 4, 9), 5);
+        assert_eq!(bus.read64(0x80001000), 5);
+
+        // 일치: 교체
+        assert_eq!(bus.compare_and_swap(0x80001000, 8, 5, 9), 5);
+        assert_eq!(bus.read64(0x80001000), 9);
+    }
+
+    #[test]
+    fn test_compare_and_swap128() {
+        let mut bus = Bus::new();
+        bus.write64(0x80001000, 0x1111);
+        bus.write64(0x80001008, 0x2222);
+        let expected = (0x2222u128 << 64) | 0x1111;
+        let new = (0xBBBBu128 << 64) | 0xAAAA;
+
+        assert_eq!(bus.compare_and_swap128(0x80001000, expected, new), expected);
+        assert_eq!(bus.read64(0x80001000), 0xAAAA);
+        assert_eq!(bus.read64(0x80001008), 0xBBBB);
+    }
+
     #[test]
     fn test_write8_invalidates_reservation() {
         let mut bus = Bus::new();
@@ -397,4 +1256,162 @@ mod tests {
         bus.write64(0x80001000, 0xFFFFFFFFFFFFFFFF);
         assert!(!bus.check_reservation(0, 0x80001000));
     }
+
+    #[test]
+    fn test_store_conditional_fails_after_same_value_store() {
+        let mut bus = Bus::with_harts(2);
+        bus.write32(0x80001000, 7);
+        assert_eq!(bus.load_reserved(0, 0x80001000, 4), 7);
+        // 다른 hart가 같은 값을 다시 써도 (ABA) SC는 실패
+        bus.write32(0x80001000, 7);
+        assert!(!bus.store_conditional(0, 0x80001000, 4, 8));
+        assert_eq!(bus.read32(0x80001000), 7);
+
+        assert_eq!(bus.load_reserved(0, 0x80001000, 4), 7);
+        assert!(bus.store_conditional(0, 0x80001000, 4, 8));
+        assert_eq!(bus.read32(0x80001000), 8);
+    }
+
+    #[test]
+    fn test_has_reservation_ignores_address() {
+        let mut bus = Bus::new();
+        assert!(!bus.has_reservation(0));
+        bus.reserve(0, 0x80001000);
+        assert!(bus.has_reservation(0));
+        assert!(!bus.has_reservation(1));
+        bus.write32(0x80001000, 0);
+        assert!(!bus.has_reservation(0));
+    }
+
+    // 디코딩 캐시 코드 페이지 테스트
+    #[test]
+    fn test_write_to_code_page_reports_invalidation() {
+        let mut bus = Bus::new();
+        bus.write32(0x80000000, 0x13);
+        assert!(bus.take_invalidated_pages(0).is_empty());
+
+        bus.mark_code_page(0x80000000);
+        bus.write32(0x80001000, 0x13); // 다른 페이지
+        assert!(bus.take_invalidated_pages(0).is_empty());
+        bus.write8(0x80000FFF, 0);
+        assert_eq!(bus.take_invalidated_pages(0), vec![0x80000]);
+
+        // 한 번 보고한 페이지는 다시 표시할 때까지 보고하지 않음
+        bus.write8(0x80000000, 0);
+        assert!(bus.take_invalidated_pages(0).is_empty());
+    }
+
+    #[test]
+    fn test_code_write_invalidates_every_hart() {
+        let mut bus = Bus::with_harts(2);
+        bus.mark_code_page(0x80000000);
+        bus.write32(0x80000010, 0x13);
+        assert!(bus.has_invalidated_pages(1));
+        assert_eq!(bus.take_invalidated_pages(0), vec![0x80000]);
+        assert_eq!(bus.take_invalidated_pages(1), vec![0x80000]);
+        assert!(!bus.has_invalidated_pages(1));
+    }
+
+    // RAM fast path 테스트
+    #[test]
+    fn test_ram_fast_path() {
+        let mut bus = Bus::new();
+        assert_eq!(Bus::ram_offset(devices::UART_BASE), None);
+        assert_eq!(
+            Bus::ram_offset(devices::DRAM_BASE + devices::DRAM_SIZE),
+            None
+        );
+        let offset = Bus::ram_offset(0x80001000).unwrap();
+        bus.reserve(0, 0x80001000);
+        bus.mark_code_page(0x80001000);
+        bus.store_ram(offset, 8, 0x1122_3344_5566_7788);
+        assert_eq!(bus.read64(0x80001000), 0x1122_3344_5566_7788);
+        assert_eq!(bus.load_ram(offset + 4, 4), 0x1122_3344);
+        // 느린 경로의 쓰기와 같은 부수 효과
+        assert!(!bus.has_reservation(0));
+        assert_eq!(bus.take_invalidated_pages(0), vec![0x80001]);
+    }
+
+    // write buffer (MemoryModel::Relaxed) 테스트
+    #[test]
+    fn test_sequential_store_is_visible() {
+        let mut bus = Bus::with_harts(2);
+        bus.buffered_store(0, 0x80001000, 8, 42);
+        assert_eq!(bus.read64(0x80001000), 42);
+        assert_eq!(bus.buffered_load(1, 0x80001000, 8), 42);
+    }
+
+    #[test]
+    fn test_relaxed_store_is_buffered_and_forwarded() {
+        let mut bus = Bus::with_harts(2);
+        bus.set_memory_model(MemoryModel::Relaxed { seed: 1 });
+        bus.buffered_store(0, 0x80001000, 8, 0x1122_3344_5566_7788);
+        // 자기 hart는 buffer의 값을, 다른 hart는 메모리를 봄
+        assert_eq!(bus.buffered_load(0, 0x80001004, 4), 0x1122_3344);
+        assert_eq!(bus.buffered_load(1, 0x80001000, 8), 0);
+        bus.flush_write_buffer(0);
+        assert_eq!(bus.buffered_load(1, 0x80001000, 8), 0x1122_3344_5566_7788);
+    }
+
+    #[test]
+    fn test_relaxed_partial_overlap_reads_memory() {
+        let mut bus = Bus::with_harts(1);
+        bus.set_memory_model(MemoryModel::Relaxed { seed: 1 });
+        bus.write64(0x80001000, 0xFFFF_FFFF_FFFF_FFFF);
+        bus.buffered_store(0, 0x80001000, 2, 0);
+        // 일부만 겹치면 store를 반영한 뒤 메모리에서 읽음
+        assert_eq!(bus.buffered_load(0, 0x80001000, 4), 0xFFFF_0000);
+        assert_eq!(bus.read16(0x80001000), 0);
+    }
+
+    #[test]
+    fn test_relaxed_fence() {
+        const W: u32 = 0b0001;
+        const R: u32 = 0b0010;
+        let mut bus = Bus::with_harts(1);
+        bus.set_memory_model(MemoryModel::Relaxed { seed: 3 });
+        bus.buffered_store(0, 0x80001000, 4, 1);
+        // pred에 W가 없으면 아무 일도 없음
+        bus.fence(0, R, R | W);
+        assert_eq!(bus.read32(0x80001000), 0);
+        bus.fence(0, W, R);
+        assert_eq!(bus.read32(0x80001000), 1);
+    }
+
+    #[test]
+    fn test_relaxed_fence_w_w_orders_drains() {
+        const W: u32 = 0b0001;
+        for seed in 0..20 {
+            let mut bus = Bus::with_harts(1);
+            bus.set_memory_model(MemoryModel::Relaxed { seed });
+            bus.buffered_store(0, 0x80001000, 4, 1);
+            bus.fence(0, W, W);
+            bus.buffered_store(0, 0x80002000, 4, 2);
+            while bus.read32(0x80002000) == 0 {
+                bus.tick();
+            }
+            assert_eq!(bus.read32(0x80001000), 1);
+        }
+    }
+
+    #[test]
+    fn test_relaxed_mmio_store_flushes() {
+        let mut bus = Bus::with_harts(2);
+        bus.set_memory_model(MemoryModel::Relaxed { seed: 1 });
+        bus.buffered_store(0, 0x80001000, 4, 7);
+        bus.buffered_store(0, devices::CLINT_BASE + 4, 4, 1);
+        // 장치 접근 전에 이전 store가 모두 보임
+        assert_eq!(bus.read32(0x80001000), 7);
+        assert!(bus.check_software_interrupt(1));
+    }
+
+    #[test]
+    fn test_sequential_model_flushes_buffers() {
+        let mut bus = Bus::with_harts(1);
+        bus.set_memory_model(MemoryModel::Relaxed { seed: 1 });
+        bus.buffered_store(0, 0x80001000, 4, 5);
+        bus.set_memory_model(MemoryModel::Sequential);
+        assert!(!bus.buffers_writes());
+        assert_eq!(bus.read32(0x80001000), 5);
+    }
 }
@@ -0,0 +1,288 @@
+//! 기본 블록 캐시
+//! 직선 코드를 분기/트랩 가능성이 있는 명령어까지 미리 디코딩해서 묶고,
+//! 블록 사이는 마지막으로 이어진 후속 블록 링크로 해시 조회 없이 연결
+
+use std::collections::HashMap;
+
+use crate::cpu::compressed::CompressedOp;
+use crate::cpu::icache::{DecodedInst, DecodedOp, PAGE_SHIFT};
+#[cfg(feature = "jit")]
+use crate::cpu::jit::NativeBlock;
+use crate::decoder::Instruction;
+
+/// 블록 하나의 최대 명령어 수 (인터럽트 지연 상한)
+pub const MAX_BLOCK_INSTS: usize = 64;
+
+// 블록마다 기억하는 후속 블록 링크 수 (분기 taken / not-taken)
+const SUCCESSOR_LINKS: usize = 2;
+
+pub struct Block {
+    pub start: u64,
+    /// 마지막 명령어 다음 주소
+    pub end: u64,
+    pub insts: Vec<DecodedInst>,
+    // (시작 pc, 블록 id). id가 재사용될 수 있으므로 따라갈 때 start를 다시 확인
+    successors: [Option<(u64, usize)>; SUCCESSOR_LINKS],
+    valid: bool,
+    /// 실행 횟수 (JIT 번역 시점 판단)
+    #[cfg(feature = "jit")]
+    pub runs: u32,
+    /// 번역된 앞부분 (블록과 함께 무효화)
+    #[cfg(feature = "jit")]
+    pub native: Option<NativeBlock>,
+}
+
+/// 블록을 끝내야 하는 명령어: 제어 흐름 변경, 권한/XLEN/인터럽트 상태 변경,
+/// 코드 캐시 플러시, illegal
+pub fn ends_block(inst: &DecodedInst) -> bool {
+    match inst.op {
+        DecodedOp::Base {
+            decoded: Ok(instruction),
+            ..
+        } => matches!(
+            instruction,
+            Instruction::Branch { .. }
+                | Instruction::Jal { .. }
+                | Instruction::Jalr { .. }
+                | Instruction::Ecall
+                | Instruction::Ebreak
+                | Instruction::Mret
+                | Instruction::Sret
+                | Instruction::Dret
+                | Instruction::Wfi
+                | Instruction::WrsNto
+                | Instruction::WrsSto
+                | Instruction::Csr { .. }
+                | Instruction::FenceI
+        ),
+        DecodedOp::Base {
+            decoded: Err(_), ..
+        } => true,
+        DecodedOp::Compressed(op) => matches!(
+            op,
+            None | Some(CompressedOp::Popret { .. })
+                | Some(CompressedOp::Popretz { .. })
+                | Some(CompressedOp::TableJump { .. })
+        ),
+    }
+}
+
+pub struct BlockCache {
+    blocks: Vec<Block>,
+    // 시작 pc → 블록 id
+    index: HashMap<u64, usize>,
+    // 페이지 번호 → 그 페이지에 걸친 블록 id
+    pages: HashMap<u64, Vec<usize>>,
+    // 무효화되어 재사용 가능한 id
+    free: Vec<usize>,
+    // 직전에 실행한 블록 (다음 조회 시 후속 링크를 먼저 확인)
+    last: Option<usize>,
+    pub chained: u64,
+    pub lookups: u64,
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockCache {
+    pub fn new() -> Self {
+        BlockCache {
+            blocks: Vec::new(),
+            index: HashMap::new(),
+            pages: HashMap::new(),
+            free: Vec::new(),
+            last: None,
+            chained: 0,
+            lookups: 0,
+        }
+    }
+
+    pub fn get(&self, id: usize) -> &Block {
+        &self.blocks[id]
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> &mut Block {
+        &mut self.blocks[id]
+    }
+
+    /// pc에서 시작하는 블록. 직전 블록의 후속 링크가 맞으면 해시 조회를 건너뜀
+    pub fn lookup(&mut self, pc: u64) -> Option<usize> {
+        if let Some(last) = self.last {
+            let linked = self.blocks[last]
+                .successors
+                .iter()
+                .flatten()
+                .find(|&&(start, _)| start == pc)
+                .map(|&(_, id)| id);
+            if let Some(id) = linked.filter(|&id| self.is_live(id, pc)) {
+                self.chained += 1;
+                return Some(id);
+            }
+        }
+        self.lookups += 1;
+        let id = self.index.get(&pc).copied()?;
+        // 다음 번에는 링크로 바로 찾도록 직전 블록에 연결
+        if let Some(last) = self.last {
+            self.link(last, pc, id);
+        }
+        Some(id)
+    }
+
+    fn is_live(&self, id: usize, pc: u64) -> bool {
+        let block = &self.blocks[id];
+        block.valid && block.start == pc
+    }
+
+    fn link(&mut self, from: usize, pc: u64, to: usize) {
+        let successors = &mut self.blocks[from].successors;
+        // 가장 오래된 링크를 밀어냄
+        successors.rotate_right(1);
+        successors[0] = Some((pc, to));
+    }
+
+    /// 방금 실행을 마친 블록 (다음 블록과 연결할 기준)
+    /// 블록 실행 중 FENCE.I 등으로 무효화되었으면 연결하지 않음
+    pub fn set_last(&mut self, id: Option<usize>) {
+        self.last = id.filter(|&id| self.blocks.get(id).is_some_and(|block| block.valid));
+    }
+
+    pub fn insert(&mut self, start: u64, insts: Vec<DecodedInst>) -> usize {
+        let end = insts.iter().fold(start, |addr, inst| addr + inst.len);
+        let block = Block {
+            start,
+            end,
+            insts,
+            successors: [None; SUCCESSOR_LINKS],
+            valid: true,
+            #[cfg(feature = "jit")]
+            runs: 0,
+            #[cfg(feature = "jit")]
+            native: None,
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.blocks[id] = block;
+                id
+            }
+            None => {
+                self.blocks.push(block);
+                self.blocks.len() - 1
+            }
+        };
+        self.index.insert(start, id);
+        // 마지막 명령어가 다음 페이지에 걸칠 수 있으므로 양쪽 페이지에 등록
+        let first_page = start >> PAGE_SHIFT;
+        let last_page = (end - 1) >> PAGE_SHIFT;
+        for page in first_page..=last_page {
+            self.pages.entry(page).or_default().push(id);
+        }
+        if let Some(last) = self.last {
+            self.link(last, start, id);
+        }
+        id
+    }
+
+    pub fn invalidate_page(&mut self, page: u64) {
+        let Some(ids) = self.pages.remove(&page) else {
+            return;
+        };
+        for id in ids {
+            let block = &mut self.blocks[id];
+            if !block.valid {
+                continue;
+            }
+            block.valid = false;
+            if self.index.get(&block.start) == Some(&id) {
+                self.index.remove(&block.start);
+            }
+            self.free.push(id);
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.blocks.clear();
+        self.index.clear();
+        self.pages.clear();
+        self.free.clear();
+        self.last = None;
+    }
+
+    /// 유효한 블록 수
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder;
+
+    fn inst(raw: u32) -> DecodedInst {
+        DecodedInst {
+            raw,
+            len: 4,
+            op: DecodedOp::Base {
+                inst: raw,
+                decoded: decoder::decode(raw),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ends_block() {
+        assert!(!ends_block(&inst(0x00000013))); // nop
+        assert!(ends_block(&inst(0x0080006F))); // j
+        assert!(ends_block(&inst(0x300022F3))); // csrr
+        assert!(ends_block(&inst(0x0000007F))); // illegal
+        assert!(!ends_block(&inst(0x00853583))); // ld
+    }
+
+    #[test]
+    fn test_insert_lookup_and_chain() {
+        let mut cache = BlockCache::new();
+        let a = cache.insert(0x80000000, vec![inst(0x13), inst(0x0080006F)]);
+        assert_eq!(cache.get(a).end, 0x80000008);
+        cache.set_last(Some(a));
+        let b = cache.insert(0x80000010, vec![inst(0x0080006F)]);
+        cache.set_last(Some(b));
+        assert_eq!(cache.lookup(0x80000000), Some(a));
+        assert_eq!(cache.lookups, 1);
+        // a → b 링크는 insert 시 생성됨
+        cache.set_last(Some(a));
+        assert_eq!(cache.lookup(0x80000010), Some(b));
+        assert_eq!(cache.chained, 1);
+    }
+
+    #[test]
+    fn test_invalidate_page_breaks_links() {
+        let mut cache = BlockCache::new();
+        let a = cache.insert(0x80000000, vec![inst(0x0080006F)]);
+        cache.set_last(Some(a));
+        cache.insert(0x80001000, vec![inst(0x0080006F)]);
+        cache.invalidate_page(0x80001000 >> PAGE_SHIFT);
+        assert_eq!(cache.len(), 1);
+        cache.set_last(Some(a));
+        assert_eq!(cache.lookup(0x80001000), None);
+        // 재사용된 id에 다른 시작 주소의 블록이 들어와도 링크로 잘못 찾지 않음
+        let c = cache.insert(0x80002000, vec![inst(0x0080006F)]);
+        cache.set_last(Some(a));
+        assert_eq!(cache.lookup(0x80001000), None);
+        assert_eq!(cache.lookup(0x80002000), Some(c));
+    }
+
+    #[test]
+    fn test_straddling_block_registered_on_both_pages() {
+        let mut cache = BlockCache::new();
+        cache.insert(0x80000FFE, vec![inst(0x13)]);
+        cache.invalidate_page(0x80001000 >> PAGE_SHIFT);
+        assert!(cache.is_empty());
+    }
+}
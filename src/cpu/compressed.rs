@@ -0,0 +1,557 @@
+//! 16비트 압축 명령어 (Zca, Zcd, Zcb, Zcmp, Zcmt, Zcmop)
+//! 대부분은 동일한 32비트 명령어로 확장하고, 확장할 수 없는 것만 별도 연산으로 디코딩
+//! Zcf(RV32 C.FLW 계열)는 지원하지 않음
+
+use crate::cpu::Xlen;
+use crate::cpu::extensions::Extensions;
+
+const OP_IMM: u32 = 0x13;
+const OP_IMM_32: u32 = 0x1B;
+const OP: u32 = 0x33;
+const OP_32: u32 = 0x3B;
+const LOAD: u32 = 0x03;
+const STORE: u32 = 0x23;
+const LOAD_FP: u32 = 0x07;
+const STORE_FP: u32 = 0x27;
+const BRANCH: u32 = 0x63;
+const JAL: u32 = 0x6F;
+const JALR: u32 = 0x67;
+const LUI: u32 = 0x37;
+
+const RA: u32 = 1;
+const SP: u32 = 2;
+
+// cm.jt/cm.jalt: index 32 미만은 cm.jt
+pub const JALT_MIN_INDEX: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompressedOp {
+    /// 동일한 동작의 32비트 명령어
+    Expanded(u32),
+    // Zcb 중 32비트 기본 ISA에 대응 명령어가 없는 것 (Zbb/Zba)
+    SextB(usize),
+    SextH(usize),
+    ZextH(usize),
+    ZextW(usize),
+    // Zcmp: rlist는 {ra, s0..} 레지스터 목록, stack_adj는 sp 조정 바이트 수
+    Push {
+        rlist: u32,
+        stack_adj: u64,
+    },
+    Pop {
+        rlist: u32,
+        stack_adj: u64,
+    },
+    Popret {
+        rlist: u32,
+        stack_adj: u64,
+    },
+    Popretz {
+        rlist: u32,
+        stack_adj: u64,
+    },
+    MvSa01 {
+        r1s: usize,
+        r2s: usize,
+    },
+    MvA01s {
+        r1s: usize,
+        r2s: usize,
+    },
+    // Zcmt
+    TableJump {
+        index: u32,
+    },
+}
+
+/// 16비트 명령어 디코딩. 예약되었거나 꺼진 확장의 인코딩은 None
+pub fn decode(inst: u16, xlen: Xlen, ext: &Extensions) -> Option<CompressedOp> {
+    if !ext.zca || inst == 0 {
+        return None;
+    }
+    let inst = inst as u32;
+    let funct3 = bits(inst, 15, 13);
+    let rv64 = xlen == Xlen::Rv64;
+    match (inst & 0x3, funct3) {
+        (0b00, _) => decode_q0(inst, funct3, rv64, ext),
+        (0b01, _) => decode_q1(inst, funct3, rv64, ext),
+        (0b10, _) => decode_q2(inst, funct3, rv64, ext),
+        _ => None,
+    }
+}
+
+fn decode_q0(inst: u32, funct3: u32, rv64: bool, ext: &Extensions) -> Option<CompressedOp> {
+    let rd = creg(bits(inst, 4, 2));
+    let rs1 = creg(bits(inst, 9, 7));
+    let word_off = (bits(inst, 12, 10) << 3) | (bit(inst, 6) << 2) | (bit(inst, 5) << 6);
+    let double_off = (bits(inst, 12, 10) << 3) | (bits(inst, 6, 5) << 6);
+    let expanded = match funct3 {
+        0b000 => {
+            // C.ADDI4SPN
+            let nzuimm = (bits(inst, 12, 11) << 4)
+                | (bits(inst, 10, 7) << 6)
+                | (bit(inst, 6) << 2)
+                | (bit(inst, 5) << 3);
+            if nzuimm == 0 {
+                return None;
+            }
+            i_type(nzuimm as i32, SP, 0x0, rd, OP_IMM)
+        }
+        0b001 if ext.zcd => i_type(double_off as i32, rs1, 0x3, rd, LOAD_FP),
+        0b010 => i_type(word_off as i32, rs1, 0x2, rd, LOAD),
+        0b011 if rv64 => i_type(double_off as i32, rs1, 0x3, rd, LOAD),
+        0b100 if ext.zcb => return decode_zcb_mem(inst, rd, rs1),
+        0b101 if ext.zcd => s_type(double_off as i32, rd, rs1, 0x3, STORE_FP),
+        0b110 => s_type(word_off as i32, rd, rs1, 0x2, STORE),
+        0b111 if rv64 => s_type(double_off as i32, rd, rs1, 0x3, STORE),
+        _ => return None,
+    };
+    Some(CompressedOp::Expanded(expanded))
+}
+
+/// Zcb: c.lbu, c.lhu, c.lh, c.sb, c.sh
+fn decode_zcb_mem(inst: u32, rd: u32, rs1: u32) -> Option<CompressedOp> {
+    let byte_off = (bit(inst, 5) << 1) | bit(inst, 6);
+    let half_off = bit(inst, 5) << 1;
+    let expanded = match (bits(inst, 12, 10), bit(inst, 6)) {
+        (0b000, _) => i_type(byte_off as i32, rs1, 0x4, rd, LOAD),
+        (0b001, 0) => i_type(half_off as i32, rs1, 0x5, rd, LOAD),
+        (0b001, 1) => i_type(half_off as i32, rs1, 0x1, rd, LOAD),
+        (0b010, _) => s_type(byte_off as i32, rd, rs1, 0x0, STORE),
+        (0b011, 0) => s_type(half_off as i32, rd, rs1, 0x1, STORE),
+        _ => return None,
+    };
+    Some(CompressedOp::Expanded(expanded))
+}
+
+fn decode_q1(inst: u32, funct3: u32, rv64: bool, ext: &Extensions) -> Option<CompressedOp> {
+    let rd = bits(inst, 11, 7);
+    let imm6 = sign_extend((bit(inst, 12) << 5) | bits(inst, 6, 2), 6);
+    let expanded = match funct3 {
+        // C.ADDI (rd=x0이면 C.NOP)
+        0b000 => i_type(imm6, rd, 0x0, rd, OP_IMM),
+        0b001 if rv64 => {
+            // C.ADDIW
+            if rd == 0 {
+                return None;
+            }
+            i_type(imm6, rd, 0x0, rd, OP_IMM_32)
+        }
+        // RV32 C.JAL
+        0b001 => j_type(cj_offset(inst), RA),
+        // C.LI
+        0b010 => i_type(imm6, 0, 0x0, rd, OP_IMM),
+        0b011 if rd == SP => {
+            // C.ADDI16SP
+            let nzimm = (bit(inst, 12) << 9)
+                | (bit(inst, 6) << 4)
+                | (bit(inst, 5) << 6)
+                | (bits(inst, 4, 3) << 7)
+                | (bit(inst, 2) << 5);
+            if nzimm == 0 {
+                return None;
+            }
+            i_type(sign_extend(nzimm, 10), SP, 0x0, SP, OP_IMM)
+        }
+        0b011 => {
+            let nzimm = (bit(inst, 12) << 17) | (bits(inst, 6, 2) << 12);
+            if nzimm == 0 {
+                // Zcmop: c.mop.n (rd = x1, x3, ..., x15)은 no-op
+                if ext.zcmop && rd % 2 == 1 && rd < 16 {
+                    i_type(0, 0, 0x0, 0, OP_IMM)
+                } else {
+                    return None;
+                }
+            } else {
+                // C.LUI
+                u_type(sign_extend(nzimm, 18), rd, LUI)
+            }
+        }
+        0b100 => return decode_q1_alu(inst, rv64, ext),
+        // C.J
+        0b101 => j_type(cj_offset(inst), 0),
+        // C.BEQZ / C.BNEZ
+        0b110 | 0b111 => {
+            let offset = (bit(inst, 12) << 8)
+                | (bits(inst, 11, 10) << 3)
+                | (bits(inst, 6, 5) << 6)
+                | (bits(inst, 4, 3) << 1)
+                | (bit(inst, 2) << 5);
+            let rs1 = creg(bits(inst, 9, 7));
+            b_type(sign_extend(offset, 9), 0, rs1, funct3 & 0x1)
+        }
+        _ => return None,
+    };
+    Some(CompressedOp::Expanded(expanded))
+}
+
+fn decode_q1_alu(inst: u32, rv64: bool, ext: &Extensions) -> Option<CompressedOp> {
+    let rd = creg(bits(inst, 9, 7));
+    let rs2 = creg(bits(inst, 4, 2));
+    let shamt = (bit(inst, 12) << 5) | bits(inst, 6, 2);
+    let expanded = match (bits(inst, 11, 10), bit(inst, 12), bits(inst, 6, 5)) {
+        // C.SRLI / C.SRAI (RV32의 shamt[5]=1은 32비트 쪽에서 거부)
+        (0b00, _, _) => i_type(shamt as i32, rd, 0x5, rd, OP_IMM),
+        (0b01, _, _) => i_type((0x400 | shamt) as i32, rd, 0x5, rd, OP_IMM),
+        // C.ANDI
+        (0b10, _, _) => {
+            let imm = sign_extend((bit(inst, 12) << 5) | bits(inst, 6, 2), 6);
+            i_type(imm, rd, 0x7, rd, OP_IMM)
+        }
+        (0b11, 0, 0b00) => r_type(0x20, rs2, rd, 0x0, rd, OP),
+        (0b11, 0, 0b01) => r_type(0x00, rs2, rd, 0x4, rd, OP),
+        (0b11, 0, 0b10) => r_type(0x00, rs2, rd, 0x6, rd, OP),
+        (0b11, 0, 0b11) => r_type(0x00, rs2, rd, 0x7, rd, OP),
+        // C.SUBW / C.ADDW
+        (0b11, 1, 0b00) if rv64 => r_type(0x20, rs2, rd, 0x0, rd, OP_32),
+        (0b11, 1, 0b01) if rv64 => r_type(0x00, rs2, rd, 0x0, rd, OP_32),
+        // Zcb: c.mul
+        (0b11, 1, 0b10) if ext.zcb => r_type(0x01, rs2, rd, 0x0, rd, OP),
+        (0b11, 1, 0b11) if ext.zcb => return decode_zcb_unary(inst, rd as usize, rv64),
+        _ => return None,
+    };
+    Some(CompressedOp::Expanded(expanded))
+}
+
+/// Zcb: c.zext.b, c.sext.b, c.zext.h, c.sext.h, c.zext.w, c.not
+fn decode_zcb_unary(inst: u32, rd: usize, rv64: bool) -> Option<CompressedOp> {
+    let reg = rd as u32;
+    let op = match bits(inst, 4, 2) {
+        0b000 => CompressedOp::Expanded(i_type(0xFF, reg, 0x7, reg, OP_IMM)),
+        0b001 => CompressedOp::SextB(rd),
+        0b010 => CompressedOp::ZextH(rd),
+        0b011 => CompressedOp::SextH(rd),
+        0b100 if rv64 => CompressedOp::ZextW(rd),
+        0b101 => CompressedOp::Expanded(i_type(-1, reg, 0x4, reg, OP_IMM)),
+        _ => return None,
+    };
+    Some(op)
+}
+
+fn decode_q2(inst: u32, funct3: u32, rv64: bool, ext: &Extensions) -> Option<CompressedOp> {
+    let rd = bits(inst, 11, 7);
+    let rs2 = bits(inst, 6, 2);
+    let word_sp_off = (bit(inst, 12) << 5) | (bits(inst, 6, 4) << 2) | (bits(inst, 3, 2) << 6);
+    let double_sp_off = (bit(inst, 12) << 5) | (bits(inst, 6, 5) << 3) | (bits(inst, 4, 2) << 6);
+    let expanded = match funct3 {
+        // C.SLLI
+        0b000 => {
+            let shamt = (bit(inst, 12) << 5) | bits(inst, 6, 2);
+            i_type(shamt as i32, rd, 0x1, rd, OP_IMM)
+        }
+        0b001 if ext.zcd => i_type(double_sp_off as i32, SP, 0x3, rd, LOAD_FP),
+        0b010 if rd != 0 => i_type(word_sp_off as i32, SP, 0x2, rd, LOAD),
+        0b011 if rv64 && rd != 0 => i_type(double_sp_off as i32, SP, 0x3, rd, LOAD),
+        0b100 => match (bit(inst, 12), rd, rs2) {
+            (0, 0, _) => return None,
+            // C.JR
+            (0, _, 0) => i_type(0, rd, 0x0, 0, JALR),
+            // C.MV
+            (0, _, _) => r_type(0x00, rs2, 0, 0x0, rd, OP),
+            // C.EBREAK
+            (1, 0, 0) => 0x00100073,
+            // C.JALR
+            (1, _, 0) => i_type(0, rd, 0x0, RA, JALR),
+            // C.ADD
+            _ => r_type(0x00, rs2, rd, 0x0, rd, OP),
+        },
+        0b101 if ext.zcd => {
+            let offset = (bits(inst, 12, 10) << 3) | (bits(inst, 9, 7) << 6);
+            s_type(offset as i32, rs2, SP, 0x3, STORE_FP)
+        }
+        0b101 => return decode_zcmp_zcmt(inst, rv64, ext),
+        0b110 => {
+            let offset = (bits(inst, 12, 9) << 2) | (bits(inst, 8, 7) << 6);
+            s_type(offset as i32, rs2, SP, 0x2, STORE)
+        }
+        0b111 if rv64 => {
+            let offset = (bits(inst, 12, 10) << 3) | (bits(inst, 9, 7) << 6);
+            s_type(offset as i32, rs2, SP, 0x3, STORE)
+        }
+        _ => return None,
+    };
+    Some(CompressedOp::Expanded(expanded))
+}
+
+/// Zcmp/Zcmt는 C.FSDSP 인코딩 공간을 재사용 (Zcd와 동시에 켤 수 없음)
+fn decode_zcmp_zcmt(inst: u32, rv64: bool, ext: &Extensions) -> Option<CompressedOp> {
+    let rlist = bits(inst, 7, 4);
+    let spimm = bits(inst, 3, 2) as u64;
+    let r1s = sreg(bits(inst, 9, 7));
+    let r2s = sreg(bits(inst, 4, 2));
+    match (bits(inst, 12, 10), bits(inst, 12, 8)) {
+        (_, 0b11000 | 0b11010 | 0b11100 | 0b11110) if ext.zcmp => {
+            // rlist 0~3은 예약
+            if rlist < 4 {
+                return None;
+            }
+            let stack_adj = stack_adj_base(rlist, rv64) + spimm * 16;
+            Some(match bits(inst, 12, 8) {
+                0b11000 => CompressedOp::Push { rlist, stack_adj },
+                0b11010 => CompressedOp::Pop { rlist, stack_adj },
+                0b11100 => CompressedOp::Popretz { rlist, stack_adj },
+                _ => CompressedOp::Popret { rlist, stack_adj },
+            })
+        }
+        (0b011, _) if ext.zcmp && r1s != r2s => match bits(inst, 6, 5) {
+            0b01 => Some(CompressedOp::MvSa01 { r1s, r2s }),
+            0b11 => Some(CompressedOp::MvA01s { r1s, r2s }),
+            _ => None,
+        },
+        (0b000, _) if ext.zcmt => Some(CompressedOp::TableJump {
+            index: bits(inst, 9, 2),
+        }),
+        _ => None,
+    }
+}
+
+/// rlist가 나타내는 레지스터 목록 {ra, s0, s1, ...}
+pub fn rlist_regs(rlist: u32) -> Vec<usize> {
+    const SREGS: [usize; 12] = [8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+    // rlist=15는 {ra, s0-s11} (s10만 빠지는 조합은 없음)
+    let count = if rlist == 15 { 12 } else { rlist as usize - 4 };
+    let mut regs = vec![RA as usize];
+    regs.extend_from_slice(&SREGS[..count]);
+    regs
+}
+
+/// 저장할 레지스터를 담는 16바이트 정렬 크기
+fn stack_adj_base(rlist: u32, rv64: bool) -> u64 {
+    let bytes = rlist_regs(rlist).len() as u64 * if rv64 { 8 } else { 4 };
+    bytes.div_ceil(16) * 16
+}
+
+fn bits(inst: u32, hi: u32, lo: u32) -> u32 {
+    (inst >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+fn bit(inst: u32, pos: u32) -> u32 {
+    (inst >> pos) & 1
+}
+
+fn sign_extend(value: u32, width: u32) -> i32 {
+    ((value << (32 - width)) as i32) >> (32 - width)
+}
+
+/// rd', rs1', rs2' (x8~x15)
+fn creg(field: u32) -> u32 {
+    field + 8
+}
+
+/// Zcmp r1s', r2s' (s0, s1, s2~s7)
+fn sreg(field: u32) -> usize {
+    match field {
+        0 => 8,
+        1 => 9,
+        _ => (field + 16) as usize,
+    }
+}
+
+fn cj_offset(inst: u32) -> i32 {
+    let offset = (bit(inst, 12) << 11)
+        | (bit(inst, 11) << 4)
+        | (bits(inst, 10, 9) << 8)
+        | (bit(inst, 8) << 10)
+        | (bit(inst, 7) << 6)
+        | (bit(inst, 6) << 7)
+        | (bits(inst, 5, 3) << 1)
+        | (bit(inst, 2) << 5);
+    sign_extend(offset, 12)
+}
+
+fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (((imm as u32) & 0xFFF) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn s_type(imm: i32, rs2: u32, rs1: u32, funct3: u32, opcode: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 5) & 0x7F) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | ((imm & 0x1F) << 7)
+        | opcode
+}
+
+fn b_type(imm: i32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 12) & 0x1) << 31)
+        | (((imm >> 5) & 0x3F) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | (((imm >> 1) & 0xF) << 8)
+        | (((imm >> 11) & 0x1) << 7)
+        | BRANCH
+}
+
+fn j_type(imm: i32, rd: u32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 20) & 0x1) << 31)
+        | (((imm >> 1) & 0x3FF) << 21)
+        | (((imm >> 11) & 0x1) << 20)
+        | (((imm >> 12) & 0xFF) << 12)
+        | (rd << 7)
+        | JAL
+}
+
+fn u_type(imm: i32, rd: u32, opcode: u32) -> u32 {
+    ((imm as u32) & 0xFFFFF000) | (rd << 7) | opcode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rv64(inst: u16) -> Option<CompressedOp> {
+        decode(inst, Xlen::Rv64, &Extensions::default())
+    }
+
+    fn zcmp(inst: u16, xlen: Xlen) -> Option<CompressedOp> {
+        let ext = Extensions {
+            zcd: false,
+            zcmp: true,
+            zcmt: true,
+            ..Extensions::default()
+        };
+        decode(inst, xlen, &ext)
+    }
+
+    #[test]
+    fn test_decode_base_expansions() {
+        // c.addi x10, 1 -> addi x10, x10, 1
+        assert_eq!(rv64(0x0505), Some(CompressedOp::Expanded(0x00150513)));
+        // c.li x10, -1 -> addi x10, x0, -1
+        assert_eq!(rv64(0x557D), Some(CompressedOp::Expanded(0xFFF00513)));
+        // c.mv x10, x11 -> add x10, x0, x11
+        assert_eq!(rv64(0x852E), Some(CompressedOp::Expanded(0x00B00533)));
+        // c.add x10, x11 -> add x10, x10, x11
+        assert_eq!(rv64(0x952E), Some(CompressedOp::Expanded(0x00B50533)));
+        // c.lw x10, 4(x11) -> lw x10, 4(x11)
+        assert_eq!(rv64(0x41C8), Some(CompressedOp::Expanded(0x0045A503)));
+        // c.sd x10, 8(x11) -> sd x10, 8(x11)
+        assert_eq!(rv64(0xE588), Some(CompressedOp::Expanded(0x00A5B423)));
+        // c.jr x1 -> jalr x0, 0(x1)
+        assert_eq!(rv64(0x8082), Some(CompressedOp::Expanded(0x00008067)));
+        // c.ebreak
+        assert_eq!(rv64(0x9002), Some(CompressedOp::Expanded(0x00100073)));
+    }
+
+    #[test]
+    fn test_decode_jumps_and_branches() {
+        // c.j -2 -> jal x0, -2
+        assert_eq!(rv64(0xBFFD), Some(CompressedOp::Expanded(0xFFFFF06F)));
+        // c.beqz x8, 8 -> beq x8, x0, 8
+        assert_eq!(rv64(0xC401), Some(CompressedOp::Expanded(0x00040463)));
+        // RV32: c.jal 4 -> jal x1, 4
+        let ext = Extensions::default();
+        assert_eq!(
+            decode(0x2011, Xlen::Rv32, &ext),
+            Some(CompressedOp::Expanded(0x004000EF))
+        );
+        // RV64의 같은 인코딩은 c.addiw x0 (예약)
+        assert_eq!(rv64(0x2011), None);
+    }
+
+    #[test]
+    fn test_decode_reserved() {
+        assert_eq!(rv64(0x0000), None);
+        // c.addi4spn nzuimm=0
+        assert_eq!(rv64(0x0004), None);
+        // c.lui x10, 0 (rd 짝수)
+        assert_eq!(rv64(0x6501), None);
+        // c.jr x0
+        assert_eq!(rv64(0x8002), None);
+    }
+
+    #[test]
+    fn test_decode_c_mop() {
+        // c.mop.1 = c.lui x1, 0
+        assert_eq!(rv64(0x6081), Some(CompressedOp::Expanded(0x00000013)));
+        let ext = Extensions {
+            zcmop: false,
+            ..Extensions::default()
+        };
+        assert_eq!(decode(0x6081, Xlen::Rv64, &ext), None);
+    }
+
+    #[test]
+    fn test_decode_zcb() {
+        // c.lbu x10, 1(x11) -> lbu x10, 1(x11)
+        assert_eq!(rv64(0x81C8), Some(CompressedOp::Expanded(0x0015C503)));
+        // c.zext.b x10 -> andi x10, x10, 0xFF
+        assert_eq!(rv64(0x9D61), Some(CompressedOp::Expanded(0x0FF57513)));
+        // c.not x10 -> xori x10, x10, -1
+        assert_eq!(rv64(0x9D75), Some(CompressedOp::Expanded(0xFFF54513)));
+        assert_eq!(rv64(0x9D65), Some(CompressedOp::SextB(10)));
+        assert_eq!(rv64(0x9D71), Some(CompressedOp::ZextW(10)));
+        // c.zext.w는 RV64 전용
+        let ext = Extensions::default();
+        assert_eq!(decode(0x9D71, Xlen::Rv32, &ext), None);
+    }
+
+    #[test]
+    fn test_decode_fsdsp_vs_zcmp() {
+        // cm.push {ra, s0-s1}, -32 (RV64) / c.fsdsp f24, 48(sp)
+        let inst = 0xB862;
+        assert_eq!(rv64(inst), Some(CompressedOp::Expanded(0x03813827)));
+        assert_eq!(
+            zcmp(inst, Xlen::Rv64),
+            Some(CompressedOp::Push {
+                rlist: 6,
+                stack_adj: 32
+            })
+        );
+        assert_eq!(
+            zcmp(inst, Xlen::Rv32),
+            Some(CompressedOp::Push {
+                rlist: 6,
+                stack_adj: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_zcmp_reserved_rlist() {
+        // rlist=3
+        assert_eq!(zcmp(0xB832, Xlen::Rv64), None);
+    }
+
+    #[test]
+    fn test_decode_zcmt() {
+        // cm.jt 1
+        assert_eq!(
+            zcmp(0xA006, Xlen::Rv64),
+            Some(CompressedOp::TableJump { index: 1 })
+        );
+        // cm.jalt 32
+        assert_eq!(
+            zcmp(0xA082, Xlen::Rv64),
+            Some(CompressedOp::TableJump { index: 32 })
+        );
+    }
+
+    #[test]
+    fn test_decode_mvsa01() {
+        // cm.mvsa01 s0, s1
+        assert_eq!(
+            zcmp(0xAC26, Xlen::Rv64),
+            Some(CompressedOp::MvSa01 { r1s: 8, r2s: 9 })
+        );
+        // r1s == r2s는 예약
+        assert_eq!(zcmp(0xAC22, Xlen::Rv64), None);
+    }
+
+    #[test]
+    fn test_rlist_regs() {
+        assert_eq!(rlist_regs(4), vec![1]);
+        assert_eq!(rlist_regs(6), vec![1, 8, 9]);
+        assert_eq!(rlist_regs(15).len(), 13);
+        assert_eq!(stack_adj_base(15, true), 112);
+        assert_eq!(stack_adj_base(15, false), 64);
+    }
+}
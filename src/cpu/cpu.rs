@@ -1,21 +1,94 @@
 use core::panic;
 
+use crate::cpu::block::{self, BlockCache, MAX_BLOCK_INSTS};
+use crate::cpu::compressed::{self, CompressedOp};
+use crate::cpu::crypto::{self, CryptoOp};
+use crate::cpu::entropy::EntropySource;
+use crate::cpu::extensions::{Extension, ExtensionError, Extensions};
+use crate::cpu::icache::{DecodeCache, DecodedInst, DecodedOp, PAGE_SHIFT, PAGE_SIZE};
+#[cfg(feature = "jit")]
+use crate::cpu::jit::{Jit, NativeContext};
+use crate::cpu::softfloat::{self, BFLOAT16, DOUBLE, Format, HALF, RoundingMode, SINGLE};
+use crate::cpu::timing::{Retired, Timing};
+use crate::cpu::tlb::Tlb;
+use crate::cpu::trigger::{self, TriggerAccess, TriggerHit};
+use crate::csr::CsrFile;
+use crate::decoder::{
+    AluOp, AmoOp, BranchOp, CboOp, CsrOp, DecodeError, FpFmt, FpOp, FusedOp, Instruction, LoadOp,
+    Width,
+};
+use crate::disasm::Disassembler;
 use crate::{bus, csr, debug_log, decoder, devices, elf};
 
+// RV64 전용 인코딩 판별용 (디코딩은 decoder::decode)
 const OP_IMM: u32 = 0x13;
 const OP_IMM_32: u32 = 0x1B;
-const OP: u32 = 0x33;
 const OP_32: u32 = 0x3B;
 const LOAD: u32 = 0x03;
 const STORE: u32 = 0x23;
-const BRANCH: u32 = 0x63;
-const JAL: u32 = 0x6F;
-const JALR: u32 = 0x67;
-const LUI: u32 = 0x37;
-const AUIPC: u32 = 0x17;
-const SYSTEM: u32 = 0x73;
-const MISC_MEM: u32 = 0x0F;
 const AMO: u32 = 0x2F;
+const OP_FP: u32 = 0x53;
+
+// rm 필드 값 7: frm 사용
+const RM_DYNAMIC: u32 = 0x7;
+
+// AMO funct5
+const AMO_CAS: u32 = 0x05;
+
+pub const DEFAULT_CACHE_BLOCK_SIZE: u64 = 64;
+
+// Bus가 관리하는 장치 인터럽트 선 (MIP의 나머지 비트는 소프트웨어가 씀)
+const DEVICE_LINES: u64 = csr::MIP_MTIP | csr::MIP_MSIP | csr::MIP_MEIP;
+
+// Zawrs: 대기 명령어가 스스로 끝나는 시간 (step 단위)
+pub const WRS_STO_TIMEOUT: u64 = 64;
+pub const WRS_NTO_TIMEOUT: u64 = 1024;
+
+/// RV32에서는 예약된 RV64 전용 인코딩
+pub(crate) fn rv64_only(inst: u32) -> bool {
+    let funct3 = decoder::funct3(inst);
+    match decoder::opcode(inst) {
+        OP_IMM_32 | OP_32 => true,
+        // LD, LWU / SD
+        LOAD => funct3 == 0x3 || funct3 == 0x6,
+        STORE => funct3 == 0x3,
+        // shamt[5]=1인 SLLI/SRLI/SRAI
+        OP_IMM => (funct3 == 0x1 || funct3 == 0x5) && inst & (1 << 25) != 0,
+        // .D AMO (AMOCAS.D는 RV32에서 레지스터 쌍 사용), AMOCAS.Q
+        AMO => funct3 == 0x4 || (funct3 == 0x3 && decoder::funct5(inst) != AMO_CAS),
+        OP_FP => match decoder::funct7(inst) {
+            // fcvt.l/lu.*, fcvt.*.l/lu
+            0x60..=0x63 | 0x68..=0x6B => decoder::rs2(inst) >= 2,
+            // fmv.x.d / fmv.d.x
+            0x71 | 0x79 => funct3 == 0x0,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// 확장 설정에 해당하는 misa F/D 비트
+fn misa_fp_bits(extensions: &Extensions) -> u64 {
+    let f = if extensions.f { csr::MISA_F } else { 0 };
+    let d = if extensions.d { csr::MISA_D } else { 0 };
+    f | d
+}
+
+/// misa.MXL / mstatus.SXL·UXL 인코딩
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Xlen {
+    Rv32 = 1,
+    Rv64 = 2,
+}
+
+impl Xlen {
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::Rv32 => 32,
+            Xlen::Rv64 => 64,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PrivilegeMode {
@@ -24,52 +97,266 @@ pub enum PrivilegeMode {
     Machine = 3,
 }
 
-pub struct Cpu {
+/// hart 하나. C는 CSR 파일 (기본은 배열 기반 Csr)
+pub struct Cpu<C = csr::Csr> {
     pub regs: [u64; 32],
-    pub csr: csr::Csr,
+    // f 레지스터 (좁은 형식은 NaN-boxing)
+    pub fregs: [u64; 32],
+    pub csr: C,
     pub pc: u64,
     pub mode: PrivilegeMode,
     pub bus: bus::Bus,
     pub halted: bool,
     pub hart_id: u64,
+    pub triggers: trigger::TriggerModule,
+    pub debug_mode: bool,
+    // Debug Mode에서 실행한 명령어가 예외를 일으켰는지 여부
+    pub debug_exception: bool,
+    single_step: bool,
+    // CBO 명령어가 다루는 캐시 블록 크기 (2의 거듭제곱)
+    pub cache_block_size: u64,
+    pub extensions: Extensions,
+    // Zkr seed CSR 엔트로피 소스
+    pub entropy: EntropySource,
+    // Zawrs: WRS 명령어로 대기한 step 수 (0이면 대기 중 아님)
+    pub wrs_stall: u64,
+    // WFI를 실행하고 인터럽트를 기다리는 중
+    pub wfi: bool,
+    // M 모드의 XLEN (리셋 후 고정)
+    mxl: Xlen,
+    // 실행 중인 명령어 길이 (압축 명령어는 2)
+    inst_len: u64,
+    // 실행하는 명령어마다 pc와 디스어셈블 결과를 출력
+    pub trace: bool,
+    // 디스어셈블/트레이스에서 분기 대상 이름으로 사용 (ELF .symtab)
+    pub symbols: elf::SymbolTable,
+    // 물리 주소 기준 디코딩 캐시 (Bus가 코드 페이지 쓰기를 알려주면 무효화)
+    pub decode_cache: DecodeCache,
+    // run()이 사용하는 기본 블록 캐시 (디코딩 캐시와 같은 시점에 무효화)
+    pub blocks: BlockCache,
+    // 게스트 페이지 → DRAM 오프셋 또는 MMIO (RAM 로드/스토어/fetch가 장치 분기를 건너뜀)
+    pub tlb: Tlb,
+    // 자주 실행되는 블록의 네이티브 코드 (코드 영역은 블록 캐시와 같이 비움)
+    #[cfg(feature = "jit")]
+    pub jit: Jit,
+    // 명령어별 사이클을 정하는 타이밍 모델 (None이면 명령어 하나가 1 사이클)
+    pub timing: Option<Timing>,
 }
 
 impl Cpu {
     pub fn new(hart_id: u64) -> Self {
-        let mut csr = csr::Csr::new();
-        // misa: RV64I + S + U 지원
-        // 비트 63-62: MXL=2 (64비트)
+        Self::with_xlen(hart_id, Xlen::Rv64)
+    }
+
+    pub fn with_xlen(hart_id: u64, mxl: Xlen) -> Self {
+        Self::with_bus(hart_id, mxl, bus::Bus::new())
+    }
+
+    /// 주어진 버스에 연결된 hart (Machine은 공유 버스 대신 빈 버스를 넘김)
+    pub fn with_bus(hart_id: u64, mxl: Xlen, bus: bus::Bus) -> Self {
+        Self::with_csr_file(hart_id, mxl, bus, csr::Csr::new())
+    }
+}
+
+impl<C: CsrFile> Cpu<C> {
+    /// 주어진 CSR 파일을 쓰는 hart (벤치마크에서 다른 CSR 파일 구현과 비교할 때 사용)
+    pub fn with_csr_file(hart_id: u64, mxl: Xlen, bus: bus::Bus, mut csr: C) -> Self {
+        let extensions = Extensions::default();
+        // misa: RV64I(RV32I) + S + U 지원
+        // 최상위 2비트: MXL (1=32비트, 2=64비트)
+        // 비트 3, 5: D, F (확장 설정을 따름)
         // 비트 8: I (기본 정수)
         // 비트 18: S (Supervisor)
         // 비트 20: U (User)
-        csr.write(csr::MISA, 0x8000000000140100);
+        let fp = misa_fp_bits(&extensions);
+        match mxl {
+            Xlen::Rv32 => csr.write(csr::MISA, 0x40140100 | fp),
+            Xlen::Rv64 => {
+                csr.write(csr::MISA, 0x8000000000140100 | fp);
+                // S/U 모드도 기본은 64비트
+                let xl = Xlen::Rv64 as u64;
+                csr.write(csr::MSTATUS, (xl << 34) | (xl << 32));
+            }
+        }
 
-        // mhartid: single core = 0
         csr.write(csr::MHARTID, hart_id);
 
+        // dcsr: Debug Mode에서는 카운터와 mtime이 멈춤 (읽기 전용 1)
+        csr.write(
+            csr::DCSR,
+            csr::DCSR_XDEBUGVER | csr::DCSR_STOPCOUNT | csr::DCSR_STOPTIME | 3,
+        );
+
         Self {
             regs: [0; 32],
+            fregs: [0; 32],
             csr: csr,
             pc: devices::memory::DRAM_BASE,
             mode: PrivilegeMode::Machine,
-            bus: bus::Bus::new(),
+            bus,
             halted: false,
             hart_id: hart_id,
+            triggers: trigger::TriggerModule::new(),
+            debug_mode: false,
+            debug_exception: false,
+            single_step: false,
+            cache_block_size: DEFAULT_CACHE_BLOCK_SIZE,
+            extensions,
+            entropy: EntropySource::default(),
+            wrs_stall: 0,
+            wfi: false,
+            mxl,
+            inst_len: 4,
+            trace: false,
+            symbols: elf::SymbolTable::default(),
+            decode_cache: DecodeCache::new(),
+            blocks: BlockCache::new(),
+            tlb: Tlb::new(),
+            #[cfg(feature = "jit")]
+            jit: Jit::new(),
+            timing: None,
         }
     }
 
+    /// 확장 조합을 검증한 뒤 적용
+    pub fn set_extensions(&mut self, extensions: Extensions) -> Result<(), ExtensionError> {
+        extensions.validate()?;
+        self.extensions = extensions;
+        let misa = self.csr.read(csr::MISA) & !(csr::MISA_F | csr::MISA_D);
+        self.csr.write(csr::MISA, misa | misa_fp_bits(&extensions));
+        Ok(())
+    }
+
     pub fn read_reg(&self, index: usize) -> u64 {
         self.regs[index]
     }
 
     pub fn write_reg(&mut self, index: usize, value: u64) {
         if index != 0 {
-            self.regs[index] = value;
+            // RV32에서는 하위 32비트를 부호 확장해서 보관
+            self.regs[index] = match self.xlen() {
+                Xlen::Rv32 => value as i32 as i64 as u64,
+                Xlen::Rv64 => value,
+            };
+        }
+    }
+
+    pub fn mxl(&self) -> Xlen {
+        self.mxl
+    }
+
+    /// 현재 권한 모드의 유효 XLEN (mstatus.SXL/UXL 반영)
+    pub fn xlen(&self) -> Xlen {
+        let (field, shift) = match self.mode {
+            _ if self.mxl == Xlen::Rv32 => return Xlen::Rv32,
+            PrivilegeMode::Machine => return self.mxl,
+            PrivilegeMode::Supervisor => (csr::MSTATUS_SXL, 34),
+            PrivilegeMode::User => (csr::MSTATUS_UXL, 32),
+        };
+        if (self.csr.read(csr::MSTATUS) & field) >> shift == Xlen::Rv32 as u64 {
+            Xlen::Rv32
+        } else {
+            Xlen::Rv64
+        }
+    }
+
+    /// mstatus.MBE/SBE/UBE: 현재 모드의 데이터 접근이 빅엔디언인지
+    /// 명령어 fetch는 항상 리틀엔디언
+    fn big_endian(&self) -> bool {
+        let bit = match self.mode {
+            PrivilegeMode::Machine => csr::MSTATUS_MBE,
+            PrivilegeMode::Supervisor => csr::MSTATUS_SBE,
+            PrivilegeMode::User => csr::MSTATUS_UBE,
+        };
+        self.csr.read(csr::MSTATUS) & bit != 0
+    }
+
+    /// 메모리 바이트 순서 <-> 레지스터 값 변환
+    fn data_order(&self, value: u64, size: u64) -> u64 {
+        if self.big_endian() {
+            swap_bytes(value, size)
+        } else {
+            value
+        }
+    }
+
+    fn read_data(&mut self, addr: u64, size: u64) -> u64 {
+        if self.bus.buffers_writes() {
+            let raw = self.bus.buffered_load(self.hart_id, addr, size as u8);
+            return self.data_order(raw, size);
+        }
+        let raw = match self.tlb.translate(addr, size) {
+            Some(offset) => self.bus.load_ram(offset, size as usize),
+            None => self.bus.read_sized(addr, size as u8),
+        };
+        self.data_order(raw, size)
+    }
+
+    fn write_data(&mut self, addr: u64, size: u64, value: u64) {
+        let raw = self.data_order(value, size);
+        if self.bus.buffers_writes() {
+            self.bus.buffered_store(self.hart_id, addr, size as u8, raw);
+            return;
+        }
+        match self.tlb.translate(addr, size) {
+            Some(offset) => self.bus.store_ram(offset, size as usize, raw),
+            None => self.bus.write_sized(addr, size as u8, raw),
         }
     }
 
+    /// 현재 XLEN 폭으로 zero-extend (주소, PC, 부호 없는 연산)
+    fn truncate_xlen(&self, value: u64) -> u64 {
+        match self.xlen() {
+            Xlen::Rv32 => value & 0xFFFF_FFFF,
+            Xlen::Rv64 => value,
+        }
+    }
+
+    /// 명령어 하나를 가져옴. 하위 2비트가 11이 아니면 16비트 압축 명령어
     pub fn fetch(&mut self) -> u32 {
-        self.bus.read32(self.pc)
+        self.fetch_at(self.pc)
+    }
+
+    fn fetch_at(&mut self, addr: u64) -> u32 {
+        let low = self.fetch16(addr);
+        if low & 0x3 != 0x3 {
+            return low;
+        }
+        (self.fetch16(addr + 2) << 16) | low
+    }
+
+    fn fetch16(&mut self, addr: u64) -> u32 {
+        match self.tlb.translate(addr, 2) {
+            Some(offset) => self.bus.load_ram(offset, 2) as u32,
+            None => self.bus.read16(addr) as u32,
+        }
+    }
+
+    /// 현재 XLEN, 확장, 심볼 기준으로 명령어 하나를 디스어셈블
+    pub fn disassemble(&self, inst: u32, pc: u64) -> String {
+        Disassembler::new(self.xlen())
+            .with_extensions(self.extensions)
+            .with_symbols(&self.symbols)
+            .disassemble(inst, pc)
+    }
+
+    /// 트레이스 한 줄: "core   0: 0x0000000080000000 (0x00000513) li a0,0"
+    fn trace_line(&self, inst: u32) -> String {
+        let pc = match self.xlen() {
+            Xlen::Rv32 => format!("{:#010x}", self.pc),
+            Xlen::Rv64 => format!("{:#018x}", self.pc),
+        };
+        let raw = match self.inst_len {
+            2 => format!("{:#06x}", inst),
+            _ => format!("{:#010x}", inst),
+        };
+        format!(
+            "core {:>3}: {} ({}) {}",
+            self.hart_id,
+            pc,
+            raw,
+            self.disassemble(inst, self.pc)
+        )
     }
 
     pub fn load_program(&mut self, program: &[u32]) {
@@ -91,10 +378,32 @@ impl Cpu {
         self.pc = entry;
     }
 
+    /// DTB를 DRAM 끝에 두고 부팅 규약대로 a0 = hartid, a1 = DTB 주소 설정
+    pub fn load_device_tree(&mut self, dtb: &[u8]) -> u64 {
+        let dram_end = devices::memory::DRAM_BASE + devices::memory::DRAM_SIZE;
+        let addr = (dram_end - dtb.len() as u64) & !7;
+        for (i, byte) in dtb.iter().enumerate() {
+            self.bus.write8(addr + i as u64, *byte);
+        }
+        self.write_reg(10, self.hart_id);
+        self.write_reg(11, addr);
+        addr
+    }
+
     pub fn trap(&mut self, cause: u64, tval: u64) {
+        if self.debug_mode {
+            // Debug Mode의 예외는 CSR을 바꾸지 않고 디버거에 보고만 함
+            debug_log!("Exception in Debug Mode: cause={:#x}", cause);
+            self.debug_exception = true;
+            return;
+        }
         let is_interrupt = (cause & csr::INTERRUPT_BIT) > 0;
+        let mcause = match self.mxl {
+            Xlen::Rv32 if is_interrupt => (cause & !csr::INTERRUPT_BIT) | csr::INTERRUPT_BIT32,
+            _ => cause,
+        };
         self.csr.write(csr::MEPC, self.pc);
-        self.csr.write(csr::MCAUSE, cause);
+        self.csr.write(csr::MCAUSE, mcause);
         self.csr.write(csr::MTVAL, tval);
 
         let mut mstatus = self.csr.read(csr::MSTATUS);
@@ -128,65 +437,687 @@ impl Cpu {
     }
 
     pub fn run(&mut self) {
-        while !self.halted {
+        while !self.halted && !self.debug_mode {
+            self.step_block();
+        }
+    }
+
+    /// 기본 블록 하나를 실행. 인터럽트는 블록 시작에서만 검사하고,
+    /// 예외는 블록 중간이라도 해당 명령어에서 trap한 뒤 블록을 빠져나감
+    /// 명령어마다 추가 작업이 필요한 상태(single step, 트레이스, 트리거, WRS/WFI 대기,
+    /// DRAM 밖 실행)에서는 step()과 같음
+    pub fn step_block(&mut self) {
+        if self.debug_mode {
+            return;
+        }
+        let pc = self.truncate_xlen(self.pc);
+        if self.single_step
+            || self.trace
+            || self.wrs_stall != 0
+            || self.wfi
+            || self.triggers.any_active()
+            || !Self::in_dram(pc)
+        {
             self.step();
+            return;
+        }
+        self.bus.tick();
+        self.count_cycle();
+        self.pc = pc;
+        if self.check_pending_interrupts() {
+            return;
+        }
+
+        self.sync_code_caches();
+        let id = match self.blocks.lookup(self.pc) {
+            Some(id) => id,
+            None => self.build_block(self.pc),
+        };
+        #[cfg(feature = "jit")]
+        let first = self.run_native(id);
+        #[cfg(not(feature = "jit"))]
+        let first = 0;
+        let count = self.blocks.get(id).insts.len();
+        for index in first..count {
+            if index > 0 {
+                self.bus.tick();
+                self.count_cycle();
+            }
+            let decoded = self.blocks.get(id).insts[index];
+            let pc = self.pc;
+            let mcycle = self.csr.mcycle();
+            self.inst_len = decoded.len;
+            self.execute_decoded_op(decoded.raw, decoded.op);
+            if self.wrs_stall == 0 {
+                self.count_instret(1);
+                self.count_timing(pc, &decoded, mcycle);
+            }
+            // trap이나 코드 쓰기가 일어나면 블록의 나머지는 실행하지 않음
+            if self.pc != pc + decoded.len
+                || self.halted
+                || self.debug_mode
+                || self.wrs_stall != 0
+                || self.bus.has_invalidated_pages(self.hart_id)
+            {
+                break;
+            }
+        }
+        self.blocks.set_last(Some(id));
+    }
+
+    /// 블록의 번역된 부분을 실행하고 실행한 명령어 수를 반환
+    /// 실행 횟수가 임계값에 도달한 블록은 이때 번역
+    #[cfg(feature = "jit")]
+    fn run_native(&mut self, id: usize) -> usize {
+        // 번역기는 RV64 시맨틱만 생성 (XLEN이 바뀌면 블록 캐시가 비워짐)
+        // 타이밍 모델은 명령어마다 호출해야 하므로 인터프리터로 실행
+        if self.xlen() != Xlen::Rv64 || self.timing.is_some() {
+            return 0;
+        }
+        let block = self.blocks.get_mut(id);
+        block.runs = block.runs.saturating_add(1);
+        if block.runs == self.jit.threshold {
+            block.native = self.jit.compile(block.start, &block.insts);
+        }
+        let Some(native) = block.native else {
+            return 0;
+        };
+        // write buffer와 빅엔디언 데이터는 Bus를 거쳐야 하므로 로드/스토어를 인터프리터에 넘김
+        let memory = self.bus.host_memory();
+        let direct = !self.bus.buffers_writes() && !self.big_endian();
+        let mut context = NativeContext {
+            pc: self.pc,
+            ram: memory.ram,
+            load_limit: if direct { devices::DRAM_SIZE } else { 0 },
+            store_limit: if direct && memory.stores {
+                devices::DRAM_SIZE
+            } else {
+                0
+            },
+            granules: memory.granules,
+            code_pages: memory.code_pages,
+        };
+        let executed = native.call(&mut self.regs, &mut context);
+        self.jit.native_runs += 1;
+        // 블록 시작에서 첫 명령어의 tick은 이미 처리함
+        for _ in 1..executed {
+            self.bus.tick();
+            self.count_cycle();
+        }
+        self.count_instret(executed as u64);
+        self.pc = context.pc;
+        executed
+    }
+
+    fn count_cycle(&mut self) {
+        self.csr.set_mcycle(self.csr.mcycle().wrapping_add(1));
+    }
+
+    fn count_instret(&mut self, count: u64) {
+        self.csr
+            .set_minstret(self.csr.minstret().wrapping_add(count));
+    }
+
+    // 타이밍 모델의 사이클 중 count_cycle()이 센 1 사이클을 뺀 나머지를 mcycle에 더함
+    // 명령어가 mcycle을 직접 썼으면(실행 전 값 mcycle과 다름) 쓴 값을 유지
+    fn count_timing(&mut self, pc: u64, decoded: &DecodedInst, mcycle: u64) {
+        let Some(timing) = self.timing.as_mut() else {
+            return;
+        };
+        let cycles = timing.retire(&Retired::new(pc, self.pc, decoded));
+        if self.csr.mcycle() == mcycle {
+            self.csr.set_mcycle(mcycle.wrapping_add(cycles - 1));
+        }
+    }
+
+    fn in_dram(addr: u64) -> bool {
+        (devices::DRAM_BASE..devices::DRAM_BASE + devices::DRAM_SIZE).contains(&addr)
+    }
+
+    /// start부터 블록을 끝내는 명령어, 페이지 끝, DRAM 끝 또는 MAX_BLOCK_INSTS까지 디코딩
+    fn build_block(&mut self, start: u64) -> usize {
+        let dram_end = devices::DRAM_BASE + devices::DRAM_SIZE;
+        let mut insts = Vec::new();
+        let mut addr = start;
+        loop {
+            let decoded = self.fetch_decoded_at(addr);
+            insts.push(decoded);
+            addr += decoded.len;
+            if block::ends_block(&decoded)
+                || insts.len() == MAX_BLOCK_INSTS
+                || addr.is_multiple_of(PAGE_SIZE)
+                || addr + 4 > dram_end
+            {
+                break;
+            }
+        }
+        self.blocks.insert(start, insts)
+    }
+
+    /// Bus가 알려준 코드 페이지 쓰기와 XLEN/확장 변경을 디코딩 캐시와 블록 캐시에 반영
+    fn sync_code_caches(&mut self) {
+        #[cfg(feature = "jit")]
+        if self.jit.is_full() {
+            self.flush_blocks();
+        }
+        for page in self.bus.take_invalidated_pages(self.hart_id) {
+            self.decode_cache.invalidate_page(page);
+            self.blocks.invalidate_page(page);
+            self.tlb.flush_page(page);
         }
+        if self.decode_cache.configure(self.xlen(), self.extensions) {
+            self.flush_blocks();
+        }
+    }
+
+    /// 블록 캐시와 블록이 가리키는 네이티브 코드를 함께 비움
+    fn flush_blocks(&mut self) {
+        self.blocks.flush();
+        #[cfg(feature = "jit")]
+        self.jit.flush();
     }
 
     pub fn step(&mut self) {
-        self.bus.receive_uart_input();
+        // Debug Mode에서는 hart가 정지 (stoptime=1이므로 mtime도 멈춤)
+        if self.debug_mode {
+            return;
+        }
+        // WFI 대기 중에는 시간만 흐름 (명령어를 실행하지 않으므로 mcycle도 멈춤)
+        if self.wfi {
+            if self.wfi_waiting(self.bus.irq_lines(self.hart_id)) {
+                self.bus.tick();
+                return;
+            }
+            self.wfi = false;
+        }
         self.bus.tick();
-        if self.check_pending_interrupts() {
+        self.count_cycle();
+        // 이전 명령어가 XLEN 밖으로 pc를 옮겼으면 wrap
+        self.pc = self.truncate_xlen(self.pc);
+
+        // stepie=0이면 single step 중에는 인터럽트 비활성화
+        let stepping = self.single_step;
+        let interrupts_enabled = !stepping || self.csr.read(csr::DCSR) & csr::DCSR_STEPIE != 0;
+        if interrupts_enabled && self.check_pending_interrupts() {
+            self.finish_single_step(stepping);
+            return;
+        }
+        if self.check_pending_triggers() {
+            self.finish_single_step(stepping);
+            return;
+        }
+
+        let decoded = self.fetch_decoded();
+        let inst = decoded.raw;
+        let mode = self.mode;
+        self.inst_len = decoded.len;
+        if self.check_triggers(
+            TriggerAccess::Execute,
+            self.pc,
+            self.inst_len,
+            Some(inst as u64),
+        ) {
+            self.finish_single_step(stepping);
             return;
         }
+        if self.trace {
+            println!("{}", self.trace_line(inst));
+        }
+        let pc = self.pc;
+        let mcycle = self.csr.mcycle();
+        self.execute_decoded_op(inst, decoded.op);
+        // WRS로 대기 중인 step은 명령어 retire가 아님
+        if self.wrs_stall == 0 {
+            self.triggers.retire(mode);
+            self.count_instret(1);
+            self.count_timing(pc, &decoded, mcycle);
+        }
+        self.finish_single_step(stepping);
+    }
+
+    /// single step 후 다음 명령어(또는 트랩 핸들러) 위치에서 Debug Mode 재진입
+    fn finish_single_step(&mut self, stepping: bool) {
+        if stepping && !self.debug_mode {
+            self.single_step = false;
+            self.enter_debug_mode(csr::DEBUG_CAUSE_STEP);
+        }
+    }
+
+    pub fn enter_debug_mode(&mut self, cause: u64) {
+        debug_log!("Enter Debug Mode: cause={}, pc={:#x}", cause, self.pc);
+        let mut dcsr = self.csr.read(csr::DCSR);
+        dcsr &= !(csr::DCSR_CAUSE | csr::DCSR_PRV);
+        dcsr |= cause << 6;
+        dcsr |= self.mode as u64;
+        self.csr.write(csr::DCSR, dcsr);
+        self.csr.write(csr::DPC, self.pc);
+        self.mode = PrivilegeMode::Machine;
+        self.wfi = false;
+        self.debug_mode = true;
+        self.single_step = false;
+    }
+
+    /// 외부 디버거의 halt 요청
+    pub fn halt_request(&mut self) {
+        if !self.debug_mode {
+            self.enter_debug_mode(csr::DEBUG_CAUSE_HALTREQ);
+        }
+    }
+
+    /// 외부 디버거의 resume 요청 (dret과 동일)
+    pub fn resume(&mut self) {
+        if self.debug_mode {
+            self.execute_dret();
+        }
+    }
+
+    /// Debug Mode에서 명령어 하나를 실행 (program buffer)
+    /// 예외가 발생하면 false, dret이면 Debug Mode를 빠져나감
+    pub fn execute_debug(&mut self, inst: u32) -> bool {
+        if !self.debug_mode {
+            return false;
+        }
+        self.debug_exception = false;
+        let pc = self.pc;
+        self.inst_len = 4;
+        self.execute(inst);
+        if self.debug_mode {
+            self.pc = pc;
+        }
+        !self.debug_exception
+    }
+
+    fn execute_dret(&mut self) {
+        let dcsr = self.csr.read(csr::DCSR);
+        self.pc = self.csr.read(csr::DPC);
+        self.mode = match dcsr & csr::DCSR_PRV {
+            0 => PrivilegeMode::User,
+            1 => PrivilegeMode::Supervisor,
+            _ => PrivilegeMode::Machine,
+        };
+        self.debug_mode = false;
+        self.single_step = dcsr & csr::DCSR_STEP != 0;
+        debug_log!("DRET pc={:#x}, step={}", self.pc, self.single_step);
+    }
+
+    /// 32비트 명령어 하나를 디코딩해서 실행 (디코딩 캐시를 거치지 않음)
+    fn execute(&mut self, inst: u32) {
+        let decoded = self.decode_base(inst);
+        self.execute_decoded_op(inst, DecodedOp::Base { inst, decoded });
+    }
+
+    /// 현재 XLEN과 확장 기준으로 명령어 인코딩 하나를 디코딩
+    fn decode_inst(&self, raw: u32) -> DecodedInst {
+        if raw & 0x3 == 0x3 {
+            let decoded = self.decode_base(raw);
+            return DecodedInst {
+                raw,
+                len: 4,
+                op: DecodedOp::Base { inst: raw, decoded },
+            };
+        }
+        let op = match compressed::decode(raw as u16, self.xlen(), &self.extensions) {
+            Some(CompressedOp::Expanded(inst)) => DecodedOp::Base {
+                inst,
+                decoded: self.decode_base(inst),
+            },
+            op => DecodedOp::Compressed(op),
+        };
+        DecodedInst { raw, len: 2, op }
+    }
 
-        let inst = self.fetch();
-        let op = decoder::opcode(inst);
+    /// RV32에서는 RV64 전용 인코딩도 illegal
+    fn decode_base(&self, inst: u32) -> Result<Instruction, DecodeError> {
+        if self.xlen() == Xlen::Rv32 && rv64_only(inst) {
+            return Err(DecodeError::Illegal(inst));
+        }
+        decoder::decode(inst)
+    }
 
+    fn execute_decoded_op(&mut self, raw: u32, op: DecodedOp) {
         match op {
-            OP_IMM => self.execute_op_imm(inst),
-            OP_IMM_32 => self.execute_op_imm_32(inst),
-            OP => self.execute_op(inst),
-            OP_32 => self.execute_op_32(inst),
-            LOAD => self.execute_load(inst),
-            STORE => self.execute_store(inst),
-            BRANCH => {
-                if self.execute_branch(inst) {
-                    return; // 분기 성공 시 PC 증가 안함
-                }
+            DecodedOp::Base {
+                inst,
+                decoded: Ok(instruction),
+            } => self.execute_decoded(inst, instruction),
+            DecodedOp::Base {
+                inst,
+                decoded: Err(err),
+            } => {
+                debug_log!("{}", err);
+                self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            }
+            DecodedOp::Compressed(Some(op)) => self.execute_compressed(raw as u16, op),
+            DecodedOp::Compressed(None) => {
+                debug_log!("Illegal compressed instruction: {:#x}", raw);
+                self.trap(csr::ILLEGAL_INSTRUCTION, raw as u64);
             }
-            JAL => {
-                self.execute_jal(inst);
-                return; // PC 직접 설정
+        }
+    }
+
+    /// pc의 명령어를 디코딩 캐시에서 찾고, 없으면 읽어서 디코딩한 뒤 캐시
+    fn fetch_decoded(&mut self) -> DecodedInst {
+        self.sync_code_caches();
+        self.fetch_decoded_at(self.pc)
+    }
+
+    fn fetch_decoded_at(&mut self, addr: u64) -> DecodedInst {
+        if let Some(decoded) = self.decode_cache.get(addr) {
+            return decoded;
+        }
+        let raw = self.fetch_at(addr);
+        let decoded = self.decode_inst(raw);
+        // MMIO에서 실행하는 명령어는 캐시하지 않음
+        if addr >= devices::DRAM_BASE {
+            self.bus.mark_code_page(addr);
+            self.bus.mark_code_page(addr + decoded.len - 1);
+            self.decode_cache.insert(addr, decoded);
+        }
+        decoded
+    }
+
+    /// 디코딩된 명령어 실행 (inst는 mtval에 기록할 원본 인코딩)
+    fn execute_decoded(&mut self, inst: u32, instruction: Instruction) {
+        // 각 핸들러는 trap, 분기, trigger 등으로 PC를 직접 설정했으면 true
+        let pc_set = match instruction {
+            _ if !self.base_enabled(instruction) => {
+                debug_log!("Extension disabled: {:?}", instruction);
+                self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+                true
+            }
+            Instruction::Lui { rd, imm } => {
+                self.execute_lui(rd, imm);
+                false
+            }
+            Instruction::Auipc { rd, imm } => {
+                self.execute_auipc(rd, imm);
+                false
+            }
+            Instruction::Jal { rd, offset } => {
+                self.execute_jal(rd, offset);
+                true
+            }
+            Instruction::Jalr { rd, rs1, offset } => {
+                self.execute_jalr(rd, rs1, offset);
+                true
+            }
+            Instruction::Branch {
+                op,
+                rs1,
+                rs2,
+                offset,
+            } => self.execute_branch(op, rs1, rs2, offset),
+            Instruction::Load {
+                op,
+                rd,
+                rs1,
+                offset,
+            } => self.execute_load(op, rd, rs1, offset),
+            Instruction::Store {
+                width,
+                rs1,
+                rs2,
+                offset,
+            } => self.execute_store(width, rs1, rs2, offset),
+            Instruction::OpImm { op, rd, rs1, imm } => {
+                self.execute_op_imm(op, rd, rs1, imm);
+                false
+            }
+            Instruction::OpImm32 { op, rd, rs1, imm } => {
+                self.execute_op_imm_32(op, rd, rs1, imm);
+                false
             }
-            JALR => {
-                self.execute_jalr(inst);
-                return; // PC 직접 설정
+            Instruction::Op { op, rd, rs1, rs2 } => {
+                self.execute_op(op, rd, rs1, rs2);
+                false
+            }
+            Instruction::Op32 { op, rd, rs1, rs2 } => {
+                self.execute_op_32(op, rd, rs1, rs2);
+                false
+            }
+            Instruction::Crypto { op, rd, rs1, rs2 } => self.execute_crypto(inst, op, rd, rs1, rs2),
+            Instruction::Fence { pred, succ } => {
+                debug_log!("FENCE pred={}, succ={}", pred, succ);
+                self.bus.fence(self.hart_id, pred, succ);
+                false
+            }
+            Instruction::FenceI => {
+                debug_log!("FENCE.I");
+                // 자기 store가 이후 명령어 fetch에 보여야 함
+                self.bus.flush_write_buffer(self.hart_id);
+                self.decode_cache.flush();
+                self.flush_blocks();
+                false
             }
-            LUI => self.execute_lui(inst),
-            AUIPC => self.execute_auipc(inst),
-            SYSTEM => {
-                if self.execute_system(inst) {
-                    return; // trap 시 PC 증가 안함
+            Instruction::Pause => {
+                // Zihintpause: 실행 속도만 늦추는 힌트
+                debug_log!("PAUSE");
+                false
+            }
+            Instruction::Cbo { op, rs1 } => self.execute_cbo(inst, op, rs1),
+            Instruction::Ecall => {
+                self.execute_ecall();
+                true
+            }
+            Instruction::Ebreak => {
+                self.execute_ebreak();
+                true
+            }
+            Instruction::Mret => {
+                self.execute_mret();
+                true
+            }
+            Instruction::Sret => {
+                self.execute_sret();
+                true
+            }
+            Instruction::Dret => {
+                debug_log!("DRET");
+                if self.debug_mode {
+                    self.execute_dret();
+                } else {
+                    self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
                 }
+                true
+            }
+            Instruction::Wfi => {
+                debug_log!("WFI");
+                self.execute_wfi(inst)
+            }
+            Instruction::SfenceVma { rs1, .. } => self.execute_sfence_vma(inst, rs1),
+            Instruction::WrsNto => {
+                debug_log!("WRS.NTO");
+                self.execute_wrs(inst, false)
+            }
+            Instruction::WrsSto => {
+                debug_log!("WRS.STO");
+                self.execute_wrs(inst, true)
+            }
+            Instruction::Csr { op, rd, rs1, csr } => self.execute_csr(inst, op, rd, rs1, csr),
+            Instruction::MopR { n, rd, .. } => {
+                // Zimop: 다른 확장이 재정의하기 전까지 rd에 0을 씀
+                debug_log!("MOP.R.{} rd={}", n, rd);
+                self.write_reg(rd, 0);
+                false
             }
-            MISC_MEM => self.execute_misc_mem(inst),
-            AMO => self.execute_amo(inst),
-            _ => panic!("Not Supported Opcode: {:#x}", op),
+            Instruction::MopRr { n, rd, .. } => {
+                debug_log!("MOP.RR.{} rd={}", n, rd);
+                self.write_reg(rd, 0);
+                false
+            }
+            Instruction::Amo {
+                op,
+                width,
+                rd,
+                rs1,
+                rs2,
+                ..
+            } => self.execute_amo(inst, op, width, rd, rs1, rs2),
+            Instruction::FpLoad {
+                width,
+                rd,
+                rs1,
+                offset,
+            } => self.execute_load_fp(inst, width, rd, rs1, offset),
+            Instruction::FpStore {
+                width,
+                rs1,
+                rs2,
+                offset,
+            } => self.execute_store_fp(inst, width, rs1, rs2, offset),
+            Instruction::FpOp {
+                op,
+                fmt,
+                rd,
+                rs1,
+                rs2,
+                rm,
+            } => self.execute_op_fp(inst, op, fmt, rd, [rs1, rs2], rm),
+            Instruction::FpFused {
+                op,
+                fmt,
+                rd,
+                rs1,
+                rs2,
+                rs3,
+                rm,
+            } => self.execute_fp_fused(inst, op, fmt, rd, [rs1, rs2, rs3], rm),
+        };
+        if !pc_set {
+            self.pc += self.inst_len;
+        }
+    }
+
+    /// 확장 플래그로 끌 수 있는 기본 인코딩 (Zicond, Zimop, Zawrs)
+    fn base_enabled(&self, instruction: Instruction) -> bool {
+        match instruction {
+            Instruction::Op {
+                op: AluOp::CzeroEqz | AluOp::CzeroNez,
+                ..
+            } => self.extensions.zicond,
+            Instruction::MopR { .. } | Instruction::MopRr { .. } => self.extensions.zimop,
+            Instruction::WrsNto | Instruction::WrsSto => self.extensions.zawrs,
+            _ => true,
+        }
+    }
+
+    /// 16비트 명령어 실행
+    /// 32비트로 확장 가능한 것은 execute()로 넘기고, 나머지(Zcb/Zcmp/Zcmt)는 직접 처리
+    fn execute_compressed(&mut self, inst: u16, op: CompressedOp) {
+        debug_log!("COMPRESSED {:#x} -> {:?}", inst, op);
+        let pc_set = match op {
+            CompressedOp::Expanded(expanded) => {
+                self.execute(expanded);
+                return;
+            }
+            CompressedOp::SextB(rd) => {
+                self.write_reg(rd, self.read_reg(rd) as i8 as i64 as u64);
+                false
+            }
+            CompressedOp::SextH(rd) => {
+                self.write_reg(rd, self.read_reg(rd) as i16 as i64 as u64);
+                false
+            }
+            CompressedOp::ZextH(rd) => {
+                self.write_reg(rd, self.read_reg(rd) & 0xFFFF);
+                false
+            }
+            CompressedOp::ZextW(rd) => {
+                self.write_reg(rd, self.read_reg(rd) & 0xFFFF_FFFF);
+                false
+            }
+            CompressedOp::Push { rlist, stack_adj } => {
+                self.execute_cm_push(rlist, stack_adj);
+                false
+            }
+            CompressedOp::Pop { rlist, stack_adj } => {
+                self.execute_cm_pop(rlist, stack_adj);
+                false
+            }
+            CompressedOp::Popretz { rlist, stack_adj } => {
+                self.execute_cm_pop(rlist, stack_adj);
+                self.write_reg(10, 0);
+                self.pc = self.read_reg(1) & !1;
+                true
+            }
+            CompressedOp::Popret { rlist, stack_adj } => {
+                self.execute_cm_pop(rlist, stack_adj);
+                self.pc = self.read_reg(1) & !1;
+                true
+            }
+            CompressedOp::MvSa01 { r1s, r2s } => {
+                let (a0, a1) = (self.read_reg(10), self.read_reg(11));
+                self.write_reg(r1s, a0);
+                self.write_reg(r2s, a1);
+                false
+            }
+            CompressedOp::MvA01s { r1s, r2s } => {
+                let (s1, s2) = (self.read_reg(r1s), self.read_reg(r2s));
+                self.write_reg(10, s1);
+                self.write_reg(11, s2);
+                false
+            }
+            CompressedOp::TableJump { index } => self.execute_cm_jt(inst, index),
+        };
+        if !pc_set {
+            self.pc += 2;
+        }
+    }
+
+    /// cm.push: {ra, s0-sN}을 sp 아래에 저장 (번호가 큰 레지스터가 높은 주소)
+    fn execute_cm_push(&mut self, rlist: u32, stack_adj: u64) {
+        let bytes = self.xlen().bits() as u64 / 8;
+        let sp = self.read_reg(2);
+        let mut addr = sp;
+        for &reg in compressed::rlist_regs(rlist).iter().rev() {
+            addr = self.truncate_xlen(addr.wrapping_sub(bytes));
+            let value = self.read_reg(reg);
+            self.write_data(addr, bytes, value);
+        }
+        self.write_reg(2, sp.wrapping_sub(stack_adj));
+    }
+
+    /// cm.pop 계열: push와 같은 배치에서 복원한 뒤 sp를 되돌림
+    fn execute_cm_pop(&mut self, rlist: u32, stack_adj: u64) {
+        let bytes = self.xlen().bits() as u64 / 8;
+        let sp = self.read_reg(2).wrapping_add(stack_adj);
+        let mut addr = sp;
+        for &reg in compressed::rlist_regs(rlist).iter().rev() {
+            addr = self.truncate_xlen(addr.wrapping_sub(bytes));
+            let value = self.read_data(addr, bytes);
+            self.write_reg(reg, value);
         }
-        self.pc += 4;
+        self.write_reg(2, sp);
     }
 
-    fn execute_op_imm(&mut self, inst: u32) {
+    /// cm.jt / cm.jalt: jvt 테이블에서 점프 주소를 읽음
+    /// 테이블은 명령어 fetch처럼 접근하므로 항상 리틀 엔디언
+    fn execute_cm_jt(&mut self, inst: u16, index: u32) -> bool {
+        let jvt = self.csr.read(csr::JVT);
+        if jvt & csr::JVT_MODE != 0 {
+            debug_log!("Unsupported jvt mode: {:#x}", jvt & csr::JVT_MODE);
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+        let entry = (jvt & !csr::JVT_MODE) + index as u64 * (self.xlen().bits() as u64 / 8);
+        let target = match self.xlen() {
+            Xlen::Rv32 => self.bus.read32(entry) as u64,
+            Xlen::Rv64 => self.bus.read64(entry),
+        };
+        if index >= compressed::JALT_MIN_INDEX {
+            self.write_reg(1, self.pc + 2);
+        }
+        debug_log!("CM.JT index={}, target={:#x}", index, target);
+        self.pc = target & !1;
+        true
+    }
+
+    fn execute_op_imm(&mut self, op: AluOp, rd: usize, rs1: usize, imm: i32) {
         debug_log!("OP_IMM");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let imm = decoder::imm_i(inst);
 
-        match funct3 {
-            0x0 => {
+        match op {
+            AluOp::Add => {
                 debug_log!(
                     "ADDI rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -196,7 +1127,7 @@ impl Cpu {
                 );
                 self.write_reg(rd, rs1_val.wrapping_add(imm as u64));
             }
-            0x1 => {
+            AluOp::Sll => {
                 let shamt = (imm as u64) & 0x3F;
                 debug_log!(
                     "SLLI rd={}, rs1={}, rs1_val={}, shamt={}",
@@ -207,7 +1138,7 @@ impl Cpu {
                 );
                 self.write_reg(rd, rs1_val << shamt);
             }
-            0x2 => {
+            AluOp::Slt => {
                 debug_log!(
                     "SLTI rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -222,7 +1153,7 @@ impl Cpu {
                 };
                 self.write_reg(rd, result);
             }
-            0x3 => {
+            AluOp::Sltu => {
                 debug_log!(
                     "SLTIU rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -233,7 +1164,7 @@ impl Cpu {
                 let result = if rs1_val < (imm as u64) { 1 } else { 0 };
                 self.write_reg(rd, result);
             }
-            0x4 => {
+            AluOp::Xor => {
                 debug_log!(
                     "XORI rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -243,34 +1174,30 @@ impl Cpu {
                 );
                 self.write_reg(rd, rs1_val ^ (imm as u64));
             }
-            0x5 => {
-                let funct6 = ((imm as u64) >> 6) & 0x3F;
+            AluOp::Srl => {
                 let shamt = (imm as u64) & 0x3F;
-                match funct6 {
-                    0x00 => {
-                        debug_log!(
-                            "SRLI rd={}, rs1={}, rs1_val={}, shamt={}",
-                            rd,
-                            rs1,
-                            rs1_val,
-                            shamt
-                        );
-                        self.write_reg(rd, rs1_val >> shamt);
-                    }
-                    0x10 => {
-                        debug_log!(
-                            "SRAI rd={}, rs1={}, rs1_val={}, shamt={}",
-                            rd,
-                            rs1,
-                            rs1_val,
-                            shamt
-                        );
-                        self.write_reg(rd, ((rs1_val as i64) >> shamt) as u64);
-                    }
-                    _ => panic!("Not Implemented OP_IMM funct6: {:#x}", funct6),
-                }
+                debug_log!(
+                    "SRLI rd={}, rs1={}, rs1_val={}, shamt={}",
+                    rd,
+                    rs1,
+                    rs1_val,
+                    shamt
+                );
+                self.write_reg(rd, self.truncate_xlen(rs1_val) >> shamt);
             }
-            0x6 => {
+            AluOp::Sra => {
+                let shamt = (imm as u64) & 0x3F;
+                debug_log!(
+                    "SRAI rd={}, rs1={}, rs1_val={}, shamt={}",
+                    rd,
+                    rs1,
+                    rs1_val,
+                    shamt
+                );
+                self.write_reg(rd, ((rs1_val as i64) >> shamt) as u64);
+            }
+            AluOp::Or => {
+                // rd=x0인 ORI는 Zicbop prefetch.i/r/w 힌트 (no-op)
                 debug_log!(
                     "ORI rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -280,7 +1207,7 @@ impl Cpu {
                 );
                 self.write_reg(rd, rs1_val | (imm as u64));
             }
-            0x7 => {
+            AluOp::And => {
                 debug_log!(
                     "ANDI rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -290,20 +1217,17 @@ impl Cpu {
                 );
                 self.write_reg(rd, rs1_val & (imm as u64));
             }
-            _ => panic!("Not Implemented OP_IMM funct3: {:#x}", funct3),
+            _ => unreachable!("Not an OP_IMM operation: {:?}", op),
         }
     }
 
-    fn execute_op_imm_32(&mut self, inst: u32) {
+    fn execute_op_imm_32(&mut self, op: AluOp, rd: usize, rs1: usize, imm: i32) {
         debug_log!("OP_IMM_32");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let imm = decoder::imm_i(inst);
+        let shamt = (imm as u64) & 0x1F;
 
-        match funct3 {
-            0x0 => {
+        match op {
+            AluOp::Add => {
                 debug_log!(
                     "ADDIW rd={}, rs1={}, rs1_val={}, imm={}",
                     rd,
@@ -311,11 +1235,10 @@ impl Cpu {
                     rs1_val,
                     imm
                 );
-                let result = (rs1_val as i32).wrapping_add(imm as i32);
+                let result = (rs1_val as i32).wrapping_add(imm);
                 self.write_reg(rd, result as i64 as u64);
             }
-            0x1 => {
-                let shamt = (imm as u64) & 0x3F;
+            AluOp::Sll => {
                 debug_log!(
                     "SLLIW rd={}, rs1={}, rs1_val={}, shamt={}",
                     rd,
@@ -325,71 +1248,62 @@ impl Cpu {
                 );
                 self.write_reg(rd, ((rs1_val as u32) << shamt) as i32 as i64 as u64);
             }
-            0x5 => {
-                let funct7 = ((imm as u64) >> 5) & 0x7F;
-                let shamt = (imm as u64) & 0x3F;
-                match funct7 {
-                    0x00 => {
-                        debug_log!(
-                            "SRLIW rd={}, rs1={}, rs1_val={}, shamt={}",
-                            rd,
-                            rs1,
-                            rs1_val,
-                            shamt
-                        );
-                        self.write_reg(rd, ((rs1_val as u32) >> shamt) as u64);
-                    }
-                    0x20 => {
-                        debug_log!(
-                            "SRAIW rd={}, rs1={}, rs1_val={}, shamt={}",
-                            rd,
-                            rs1,
-                            rs1_val,
-                            shamt
-                        );
-                        self.write_reg(rd, ((rs1_val as i32) >> shamt) as i64 as u64);
-                    }
-                    _ => panic!("Not Implemented OP_IMM_32 funct7: {:#x}", funct7),
-                }
-            }
-            _ => panic!("Not Implemented OP_IMM_32 funct3: {:#x}", funct3),
+            AluOp::Srl => {
+                debug_log!(
+                    "SRLIW rd={}, rs1={}, rs1_val={}, shamt={}",
+                    rd,
+                    rs1,
+                    rs1_val,
+                    shamt
+                );
+                self.write_reg(rd, ((rs1_val as u32) >> shamt) as i32 as i64 as u64);
+            }
+            AluOp::Sra => {
+                debug_log!(
+                    "SRAIW rd={}, rs1={}, rs1_val={}, shamt={}",
+                    rd,
+                    rs1,
+                    rs1_val,
+                    shamt
+                );
+                self.write_reg(rd, ((rs1_val as i32) >> shamt) as i64 as u64);
+            }
+            _ => unreachable!("Not an OP_IMM_32 operation: {:?}", op),
         }
     }
 
-    fn execute_op(&mut self, inst: u32) {
+    fn execute_op(&mut self, op: AluOp, rd: usize, rs1: usize, rs2: usize) {
         debug_log!("OP");
-        let funct3 = decoder::funct3(inst);
-        let funct7 = decoder::funct7(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let rs2 = decoder::rs2(inst);
         let rs2_val = self.read_reg(rs2);
+        let xlen = self.xlen().bits();
+        let shamt_mask = xlen as u64 - 1;
 
-        match (funct3, funct7) {
-            (0x0, 0x0) => {
+        match op {
+            AluOp::Add => {
                 debug_log!("ADD rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
+                // rd=x0, rs1=x0이면 Zihintntl 힌트 (ntl.p1/pall/s1/all). 캐시가 없으므로 no-op
                 self.write_reg(rd, rs1_val.wrapping_add(rs2_val));
             }
-            (0x0, 0x01) => {
+            AluOp::Mul => {
                 debug_log!("MUL rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 self.write_reg(rd, rs1_val.wrapping_mul(rs2_val));
             }
-            (0x0, 0x20) => {
+            AluOp::Sub => {
                 debug_log!("SUB rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 self.write_reg(rd, rs1_val.wrapping_sub(rs2_val));
             }
-            (0x1, 0x0) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Sll => {
+                let shamt = rs2_val & shamt_mask;
                 debug_log!("SLL rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
                 self.write_reg(rd, rs1_val << shamt);
             }
-            (0x1, 0x01) => {
+            AluOp::Mulh => {
                 debug_log!("MULH rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let res = (rs1_val as i64 as i128) * (rs2_val as i64 as i128);
-                self.write_reg(rd, (res >> 64) as i64 as u64);
+                self.write_reg(rd, (res >> xlen) as i64 as u64);
             }
-            (0x2, 0x0) => {
+            AluOp::Slt => {
                 debug_log!("SLT rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let result = if (rs1_val as i64) < (rs2_val as i64) {
                     1
@@ -398,26 +1312,28 @@ impl Cpu {
                 };
                 self.write_reg(rd, result);
             }
-            (0x2, 0x01) => {
+            AluOp::Mulhsu => {
                 debug_log!("MULHSU rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
+                let rs2_val = self.truncate_xlen(rs2_val);
                 let res = (rs1_val as i64 as i128) * (rs2_val as u128 as i128);
-                self.write_reg(rd, (res >> 64) as i64 as u64);
+                self.write_reg(rd, (res >> xlen) as i64 as u64);
             }
-            (0x3, 0x0) => {
+            AluOp::Sltu => {
                 debug_log!("SLTU rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let result = if rs1_val < rs2_val { 1 } else { 0 };
                 self.write_reg(rd, result);
             }
-            (0x3, 0x01) => {
+            AluOp::Mulhu => {
                 debug_log!("MULHU rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
-                let res = (rs1_val as u128) * (rs2_val as u128);
-                self.write_reg(rd, (res >> 64) as u64);
+                let res =
+                    (self.truncate_xlen(rs1_val) as u128) * (self.truncate_xlen(rs2_val) as u128);
+                self.write_reg(rd, (res >> xlen) as u64);
             }
-            (0x4, 0x0) => {
+            AluOp::Xor => {
                 debug_log!("XOR rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 self.write_reg(rd, rs1_val ^ rs2_val);
             }
-            (0x4, 0x01) => {
+            AluOp::Div => {
                 debug_log!("DIV rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 if rs2_val == 0 {
                     self.write_reg(rd, -1 as i64 as u64);
@@ -426,30 +1342,30 @@ impl Cpu {
                     self.write_reg(rd, res as u64);
                 }
             }
-            (0x5, 0x0) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Srl => {
+                let shamt = rs2_val & shamt_mask;
                 debug_log!("SRL rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
-                self.write_reg(rd, rs1_val >> shamt);
+                self.write_reg(rd, self.truncate_xlen(rs1_val) >> shamt);
             }
-            (0x5, 0x01) => {
+            AluOp::Divu => {
                 debug_log!("DIVU rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 if rs2_val == 0 {
                     self.write_reg(rd, u64::MAX);
                 } else {
-                    let res = rs1_val.wrapping_div(rs2_val);
+                    let res = self.truncate_xlen(rs1_val) / self.truncate_xlen(rs2_val);
                     self.write_reg(rd, res);
                 }
             }
-            (0x5, 0x20) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Sra => {
+                let shamt = rs2_val & shamt_mask;
                 debug_log!("SRA rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
                 self.write_reg(rd, ((rs1_val as i64) >> shamt) as u64);
             }
-            (0x6, 0x0) => {
+            AluOp::Or => {
                 debug_log!("OR rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 self.write_reg(rd, rs1_val | rs2_val);
             }
-            (0x6, 0x01) => {
+            AluOp::Rem => {
                 debug_log!("REM rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 if rs2_val == 0 {
                     self.write_reg(rd, rs1_val as i64 as u64);
@@ -458,226 +1374,287 @@ impl Cpu {
                     self.write_reg(rd, res as u64);
                 }
             }
-            (0x7, 0x0) => {
+            AluOp::And => {
                 debug_log!("AND rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 self.write_reg(rd, rs1_val & rs2_val);
             }
-            (0x7, 0x01) => {
+            AluOp::CzeroEqz => {
+                debug_log!(
+                    "CZERO.EQZ rd={}, rs1_val={}, rs2_val={}",
+                    rd,
+                    rs1_val,
+                    rs2_val
+                );
+                self.write_reg(rd, if rs2_val == 0 { 0 } else { rs1_val });
+            }
+            AluOp::CzeroNez => {
+                debug_log!(
+                    "CZERO.NEZ rd={}, rs1_val={}, rs2_val={}",
+                    rd,
+                    rs1_val,
+                    rs2_val
+                );
+                self.write_reg(rd, if rs2_val != 0 { 0 } else { rs1_val });
+            }
+            AluOp::Remu => {
                 debug_log!("REMU rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 if rs2_val == 0 {
                     self.write_reg(rd, rs1_val);
                 } else {
-                    let res = rs1_val.wrapping_rem(rs2_val);
+                    let res = self.truncate_xlen(rs1_val) % self.truncate_xlen(rs2_val);
                     self.write_reg(rd, res);
                 }
             }
-            _ => panic!(
-                "Not Implemented OP funct3={:#x}, funct7={:#x}",
-                funct3, funct7
-            ),
         }
     }
 
-    fn execute_op_32(&mut self, inst: u32) {
+    fn execute_op_32(&mut self, op: AluOp, rd: usize, rs1: usize, rs2: usize) {
         debug_log!("OP_32");
-        let funct3 = decoder::funct3(inst);
-        let funct7 = decoder::funct7(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let rs2 = decoder::rs2(inst);
         let rs2_val = self.read_reg(rs2);
 
-        match (funct3, funct7) {
-            (0x0, 0x0) => {
+        match op {
+            AluOp::Add => {
                 debug_log!("ADDW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let result = (rs1_val as i32).wrapping_add(rs2_val as i32);
                 self.write_reg(rd, result as i64 as u64);
             }
-            (0x0, 0x01) => {
+            AluOp::Mul => {
                 debug_log!("MULW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let result = (rs1_val as i32).wrapping_mul(rs2_val as i32);
                 self.write_reg(rd, result as i64 as u64);
             }
-            (0x0, 0x20) => {
+            AluOp::Sub => {
                 debug_log!("SUBW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
                 let result = (rs1_val as i32).wrapping_sub(rs2_val as i32);
                 self.write_reg(rd, result as i64 as u64);
             }
-            (0x1, 0x0) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Sll => {
+                let shamt = rs2_val & 0x1F;
                 debug_log!("SLLW rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
                 self.write_reg(rd, ((rs1_val as u32) << shamt) as i32 as i64 as u64);
             }
-            (0x4, 0x01) => {
+            AluOp::Div => {
                 debug_log!("DIVW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
-                if rs2_val == 0 {
+                if rs2_val as u32 == 0 {
                     self.write_reg(rd, -1 as i32 as i64 as u64);
                 } else {
                     let res = (rs1_val as u32 as i32).wrapping_div(rs2_val as u32 as i32);
                     self.write_reg(rd, res as u64);
                 }
             }
-            (0x5, 0x0) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Srl => {
+                let shamt = rs2_val & 0x1F;
                 debug_log!("SRLW rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
-                self.write_reg(rd, ((rs1_val as u32) >> shamt) as u64);
+                self.write_reg(rd, ((rs1_val as u32) >> shamt) as i32 as i64 as u64);
             }
-            (0x5, 0x01) => {
+            AluOp::Divu => {
                 debug_log!("DIVUW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
-                if rs2_val == 0 {
+                if rs2_val as u32 == 0 {
                     self.write_reg(rd, -1 as i32 as u64);
                 } else {
                     let res = (rs1_val as u32).wrapping_div(rs2_val as u32) as i32 as i64;
                     self.write_reg(rd, res as u64);
                 }
             }
-            (0x5, 0x20) => {
-                let shamt = rs2_val & 0x3F;
+            AluOp::Sra => {
+                let shamt = rs2_val & 0x1F;
                 debug_log!("SRAW rd={}, rs1_val={}, shamt={}", rd, rs1_val, shamt);
                 self.write_reg(rd, ((rs1_val as i32) >> shamt) as i64 as u64);
             }
-            (0x6, 0x01) => {
+            AluOp::Rem => {
                 debug_log!("REMW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
-                if rs2_val == 0 {
+                if rs2_val as u32 == 0 {
                     self.write_reg(rd, rs1_val as u32 as i32 as i64 as u64);
                 } else {
                     let res = (rs1_val as i32).wrapping_rem(rs2_val as i32);
                     self.write_reg(rd, res as i64 as u64);
                 }
             }
-            (0x7, 0x01) => {
+            AluOp::Remu => {
                 debug_log!("REMUW rd={}, rs1_val={}, rs2_val={}", rd, rs1_val, rs2_val);
-                if rs2_val == 0 {
+                if rs2_val as u32 == 0 {
                     self.write_reg(rd, rs1_val as u32 as i32 as i64 as u64);
                 } else {
                     let res = (rs1_val as u32).wrapping_rem(rs2_val as u32) as i32 as i64;
                     self.write_reg(rd, res as u64);
                 }
             }
-            _ => panic!(
-                "Not Implemented OP_32 funct3={:#x}, funct7={:#x}",
-                funct3, funct7
-            ),
+            _ => unreachable!("Not an OP_32 operation: {:?}", op),
         }
     }
 
-    fn execute_load(&mut self, inst: u32) {
+    /// Zk 스칼라 암호 명령어 (OP/OP-32/OP-IMM/OP-IMM-32 공간)
+    /// Returns true if a trap was taken
+    fn execute_crypto(
+        &mut self,
+        inst: u32,
+        op: CryptoOp,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    ) -> bool {
+        let reserved = matches!(op, CryptoOp::Aes64ks1i(rnum) if rnum > crypto::AES_MAX_RNUM);
+        // Zk의 RV32 인코딩(aes32*, sha512*r, zip 등)은 구현하지 않음
+        let rv32 = self.xlen() == Xlen::Rv32;
+        if reserved || rv32 || !op.enabled_by(&self.extensions) {
+            debug_log!("Illegal crypto instruction: {:?}", op);
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+
+        let rs1_val = self.read_reg(rs1);
+        let rs2_val = self.read_reg(rs2);
+        let result = crypto::execute(op, rs1_val, rs2_val);
+        debug_log!(
+            "{:?} rd={}, rs1_val={:#x}, rs2_val={:#x}, result={:#x}",
+            op,
+            rd,
+            rs1_val,
+            rs2_val,
+            result
+        );
+        self.write_reg(rd, result);
+        false
+    }
+
+    /// Returns true if a trigger fired
+    fn execute_load(&mut self, op: LoadOp, rd: usize, rs1: usize, offset: i32) -> bool {
         debug_log!("LOAD");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let imm = decoder::imm_i(inst);
-        let addr = (rs1_val as i64).wrapping_add(imm as i64) as u64;
+        let addr = self.truncate_xlen((rs1_val as i64).wrapping_add(offset as i64) as u64);
+
+        let size = match op {
+            LoadOp::Lb | LoadOp::Lbu => 1,
+            LoadOp::Lh | LoadOp::Lhu => 2,
+            LoadOp::Lw | LoadOp::Lwu => 4,
+            LoadOp::Ld => 8,
+        };
+        if self.check_triggers(TriggerAccess::Load, addr, size, None) {
+            return true;
+        }
 
-        match funct3 {
-            0x0 => {
-                let val = self.bus.read8(addr) as i8 as i64 as u64;
+        let val = match op {
+            LoadOp::Lb => {
+                let val = self.read_data(addr, 1) as i8 as i64 as u64;
                 debug_log!("LB rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x1 => {
-                let val = self.bus.read16(addr) as i16 as i64 as u64;
+            LoadOp::Lh => {
+                let val = self.read_data(addr, 2) as i16 as i64 as u64;
                 debug_log!("LH rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x2 => {
-                let val = self.bus.read32(addr) as i32 as i64 as u64;
+            LoadOp::Lw => {
+                let val = self.read_data(addr, 4) as i32 as i64 as u64;
                 debug_log!("LW rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x3 => {
-                let val = self.bus.read64(addr);
+            LoadOp::Ld => {
+                let val = self.read_data(addr, 8);
                 debug_log!("LD rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x4 => {
-                let val = self.bus.read8(addr) as u64;
+            LoadOp::Lbu => {
+                let val = self.read_data(addr, 1);
                 debug_log!("LBU rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x5 => {
-                let val = self.bus.read16(addr) as u64;
+            LoadOp::Lhu => {
+                let val = self.read_data(addr, 2);
                 debug_log!("LHU rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            0x6 => {
-                let val = self.bus.read32(addr) as u64;
+            LoadOp::Lwu => {
+                let val = self.read_data(addr, 4);
                 debug_log!("LWU rd={}, addr={:#x}, val={:#x}", rd, addr, val);
-                self.write_reg(rd, val);
+                val
             }
-            _ => panic!("Not Implemented LOAD funct3: {:#x}", funct3),
+        };
+
+        // 데이터 매치 트리거는 값을 읽은 뒤, rd에 쓰기 전에 검사
+        let data = if size == 8 {
+            val
+        } else {
+            val & ((1 << (size * 8)) - 1)
+        };
+        if self.check_triggers(TriggerAccess::Load, addr, size, Some(data)) {
+            return true;
         }
+        self.write_reg(rd, val);
+        false
     }
 
-    fn execute_store(&mut self, inst: u32) {
+    /// Returns true if a trigger fired
+    fn execute_store(&mut self, width: Width, rs1: usize, rs2: usize, offset: i32) -> bool {
         debug_log!("STORE");
-        let funct3 = decoder::funct3(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let rs2 = decoder::rs2(inst);
         let rs2_val = self.read_reg(rs2);
-        let imm = decoder::imm_s(inst);
-        let addr = (rs1_val as i64).wrapping_add(imm as i64) as u64;
+        let addr = self.truncate_xlen((rs1_val as i64).wrapping_add(offset as i64) as u64);
 
-        match funct3 {
-            0x0 => {
+        let size = width.bytes();
+        let data = if size == 8 {
+            rs2_val
+        } else {
+            rs2_val & ((1 << (size * 8)) - 1)
+        };
+        if self.check_triggers(TriggerAccess::Store, addr, size, Some(data)) {
+            return true;
+        }
+
+        match width {
+            Width::B => {
                 debug_log!("SB addr={:#x}, val={:#x}", addr, rs2_val as u8);
-                self.bus.write8(addr, rs2_val as u8);
+                self.write_data(addr, 1, rs2_val);
             }
-            0x1 => {
+            Width::H => {
                 debug_log!("SH addr={:#x}, val={:#x}", addr, rs2_val as u16);
-                self.bus.write16(addr, rs2_val as u16);
+                self.write_data(addr, 2, rs2_val);
             }
-            0x2 => {
+            Width::W => {
                 debug_log!("SW addr={:#x}, val={:#x}", addr, rs2_val as u32);
-                self.bus.write32(addr, rs2_val as u32);
+                self.write_data(addr, 4, rs2_val);
             }
-            0x3 => {
+            Width::D => {
                 debug_log!("SD addr={:#x}, val={:#x}", addr, rs2_val);
-                self.bus.write64(addr, rs2_val);
+                self.write_data(addr, 8, rs2_val);
             }
-            _ => panic!("Not Implemented STORE funct3: {:#x}", funct3),
+            Width::Q => unreachable!("No 128-bit STORE"),
         }
+        false
     }
 
     /// Returns true if branch was taken
-    fn execute_branch(&mut self, inst: u32) -> bool {
+    fn execute_branch(&mut self, op: BranchOp, rs1: usize, rs2: usize, imm: i32) -> bool {
         debug_log!("BRANCH");
-        let funct3 = decoder::funct3(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let rs2 = decoder::rs2(inst);
         let rs2_val = self.read_reg(rs2);
-        let imm = decoder::imm_b(inst);
 
-        let taken = match funct3 {
-            0x0 => {
+        let taken = match op {
+            BranchOp::Beq => {
                 debug_log!("BEQ rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 rs1_val == rs2_val
             }
-            0x1 => {
+            BranchOp::Bne => {
                 debug_log!("BNE rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 rs1_val != rs2_val
             }
-            0x4 => {
+            BranchOp::Blt => {
                 debug_log!("BLT rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 (rs1_val as i64) < (rs2_val as i64)
             }
-            0x5 => {
+            BranchOp::Bge => {
                 debug_log!("BGE rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 (rs1_val as i64) >= (rs2_val as i64)
             }
-            0x6 => {
+            BranchOp::Bltu => {
                 debug_log!("BLTU rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 rs1_val < rs2_val
             }
-            0x7 => {
+            BranchOp::Bgeu => {
                 debug_log!("BGEU rs1_val={}, rs2_val={}, imm={}", rs1_val, rs2_val, imm);
                 rs1_val >= rs2_val
             }
-            _ => panic!("Not Implemented BRANCH funct3: {:#x}", funct3),
         };
 
         if taken {
@@ -686,129 +1663,130 @@ impl Cpu {
         taken
     }
 
-    fn execute_jal(&mut self, inst: u32) {
+    fn execute_jal(&mut self, rd: usize, imm: i32) {
         debug_log!("JAL");
-        let rd = decoder::rd(inst);
-        let imm = decoder::imm_j(inst);
         debug_log!("JAL rd={}, imm={}, pc={:#x}", rd, imm, self.pc);
-        self.write_reg(rd, self.pc + 4);
+        self.write_reg(rd, self.pc + self.inst_len);
         self.pc = (self.pc as i64).wrapping_add(imm as i64) as u64;
     }
 
-    fn execute_jalr(&mut self, inst: u32) {
+    fn execute_jalr(&mut self, rd: usize, rs1: usize, imm: i32) {
         debug_log!("JALR");
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
         let rs1_val = self.read_reg(rs1);
-        let imm = decoder::imm_i(inst);
         debug_log!("JALR rd={}, rs1_val={:#x}, imm={}", rd, rs1_val, imm);
-        self.write_reg(rd, self.pc + 4);
+        self.write_reg(rd, self.pc + self.inst_len);
         self.pc = ((rs1_val as i64).wrapping_add(imm as i64) as u64) & !1u64;
     }
 
-    fn execute_lui(&mut self, inst: u32) {
+    fn execute_lui(&mut self, rd: usize, imm: i32) {
         debug_log!("LUI");
-        let rd = decoder::rd(inst);
-        let imm = decoder::imm_u(inst);
         debug_log!("LUI rd={}, imm={:#x}", rd, imm);
         self.write_reg(rd, imm as u64);
     }
 
-    fn execute_auipc(&mut self, inst: u32) {
+    fn execute_auipc(&mut self, rd: usize, imm: i32) {
         debug_log!("AUIPC");
-        let rd = decoder::rd(inst);
-        let imm = decoder::imm_u(inst);
         debug_log!("AUIPC rd={}, imm={:#x}, pc={:#x}", rd, imm, self.pc);
         self.write_reg(rd, (self.pc as i64).wrapping_add(imm as i64) as u64);
     }
 
-    fn execute_system(&mut self, inst: u32) -> bool {
-        debug_log!("SYSTEM");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
-        let rs1_val = self.read_reg(rs1);
-        let csr_addr = decoder::csr_addr(inst);
-
-        let taken = match funct3 {
-            0x0 => {
-                let funct7 = decoder::funct7(inst);
-                let rs2 = decoder::rs2(inst);
-                match (funct7, rs2) {
-                    (0x00, 0x00) => {
-                        debug_log!("ECALL");
-                        match self.mode {
-                            PrivilegeMode::Machine => {
-                                debug_log!("ECALL Machine Mode");
-                                self.trap(csr::ECALL_FROM_M, 0);
-                            }
-                            PrivilegeMode::Supervisor => {
-                                debug_log!("ECALL Supervisor Mode");
-                                self.trap(csr::ECALL_FROM_S, 0);
-                            }
-                            PrivilegeMode::User => {
-                                debug_log!("ECALL User Mode");
-                                self.trap(csr::ECALL_FROM_U, 0);
-                            }
-                        }
-                        true
-                    }
-                    (0x00, 0x01) => {
-                        debug_log!("EBREAK");
-                        self.trap(csr::BREAKPOINT, 0);
-                        true
-                    }
-                    (0x18, 0x02) => {
-                        debug_log!("MRET");
-                        self.pc = self.csr.read(csr::MEPC);
-
-                        let mut mstatus = self.csr.read(csr::MSTATUS);
-                        let mpie = (mstatus & csr::MSTATUS_MPIE) != 0;
-                        if mpie {
-                            mstatus |= csr::MSTATUS_MIE;
-                        } else {
-                            mstatus &= !csr::MSTATUS_MIE;
-                        }
-                        mstatus |= csr::MSTATUS_MPIE;
-
-                        let mpp = (mstatus & csr::MSTATUS_MPP) >> 11;
-                        self.mode = match mpp {
-                            0 => PrivilegeMode::User,
-                            1 => PrivilegeMode::Supervisor,
-                            3 => PrivilegeMode::Machine,
-                            _ => panic!("Not Avaliable PrivilegeMode"),
-                        };
-                        mstatus &= !csr::MSTATUS_MPP;
-                        self.csr.write(csr::MSTATUS, mstatus);
-                        true
-                    }
-                    (0x08, 0x02) => {
-                        debug_log!("SRET");
-                        self.pc = self.csr.read(csr::SEPC);
-
-                        let mut sstatus = self.csr.read(csr::SSTATUS);
-                        let spie = (sstatus & csr::SSTATUS_SPIE) != 0;
-                        if spie {
-                            sstatus |= csr::SSTATUS_SIE;
-                        } else {
-                            sstatus &= !csr::SSTATUS_SIE;
-                        }
-                        sstatus |= csr::SSTATUS_SPIE;
-
-                        let spp = (sstatus & csr::SSTATUS_SPP) != 0;
-                        self.mode = if spp {
-                            PrivilegeMode::Supervisor
-                        } else {
-                            PrivilegeMode::User
-                        };
-                        sstatus &= !csr::SSTATUS_SPP;
-                        self.csr.write(csr::SSTATUS, sstatus);
-                        true
-                    }
-                    _ => panic!("Not Implemented!"),
-                }
+    fn execute_ecall(&mut self) {
+        debug_log!("ECALL");
+        match self.mode {
+            PrivilegeMode::Machine => {
+                debug_log!("ECALL Machine Mode");
+                self.trap(csr::ECALL_FROM_M, 0);
+            }
+            PrivilegeMode::Supervisor => {
+                debug_log!("ECALL Supervisor Mode");
+                self.trap(csr::ECALL_FROM_S, 0);
+            }
+            PrivilegeMode::User => {
+                debug_log!("ECALL User Mode");
+                self.trap(csr::ECALL_FROM_U, 0);
             }
-            0x1 => {
+        }
+    }
+
+    fn execute_ebreak(&mut self) {
+        debug_log!("EBREAK");
+        let dcsr = self.csr.read(csr::DCSR);
+        let enter_debug = match self.mode {
+            PrivilegeMode::Machine => dcsr & csr::DCSR_EBREAKM != 0,
+            PrivilegeMode::Supervisor => dcsr & csr::DCSR_EBREAKS != 0,
+            PrivilegeMode::User => dcsr & csr::DCSR_EBREAKU != 0,
+        };
+        if enter_debug && !self.debug_mode {
+            self.enter_debug_mode(csr::DEBUG_CAUSE_EBREAK);
+        } else {
+            self.trap(csr::BREAKPOINT, 0);
+        }
+    }
+
+    fn execute_mret(&mut self) {
+        debug_log!("MRET");
+        self.pc = self.csr.read(csr::MEPC);
+
+        let mut mstatus = self.csr.read(csr::MSTATUS);
+        let mpie = (mstatus & csr::MSTATUS_MPIE) != 0;
+        if mpie {
+            mstatus |= csr::MSTATUS_MIE;
+        } else {
+            mstatus &= !csr::MSTATUS_MIE;
+        }
+        mstatus |= csr::MSTATUS_MPIE;
+
+        let mpp = (mstatus & csr::MSTATUS_MPP) >> 11;
+        self.mode = match mpp {
+            0 => PrivilegeMode::User,
+            1 => PrivilegeMode::Supervisor,
+            3 => PrivilegeMode::Machine,
+            _ => panic!("Not Avaliable PrivilegeMode"),
+        };
+        mstatus &= !csr::MSTATUS_MPP;
+        self.csr.write(csr::MSTATUS, mstatus);
+    }
+
+    fn execute_sret(&mut self) {
+        debug_log!("SRET");
+        self.pc = self.csr.read(csr::SEPC);
+
+        let mut sstatus = self.csr.read(csr::SSTATUS);
+        let spie = (sstatus & csr::SSTATUS_SPIE) != 0;
+        if spie {
+            sstatus |= csr::SSTATUS_SIE;
+        } else {
+            sstatus &= !csr::SSTATUS_SIE;
+        }
+        sstatus |= csr::SSTATUS_SPIE;
+
+        let spp = (sstatus & csr::SSTATUS_SPP) != 0;
+        self.mode = if spp {
+            PrivilegeMode::Supervisor
+        } else {
+            PrivilegeMode::User
+        };
+        sstatus &= !csr::SSTATUS_SPP;
+        self.csr.write(csr::SSTATUS, sstatus);
+    }
+
+    /// Returns true if a trap was taken
+    fn execute_csr(&mut self, inst: u32, op: CsrOp, rd: usize, rs1: usize, csr_addr: u16) -> bool {
+        let rs1_val = self.read_reg(rs1);
+
+        // seed는 반드시 읽기-쓰기 명령어로 접근해야 함 (CSRRW/CSRRWI 또는 rs1≠x0)
+        let writes = matches!(op, CsrOp::Rw | CsrOp::Rwi) || rs1 != 0;
+        let seed_read_only = csr_addr == csr::SEED && !writes;
+        // 주소 상위 2비트가 11이면 읽기 전용 CSR
+        let read_only_write = csr_addr >> 10 == 0x3 && writes;
+        if !self.csr_accessible(csr_addr) || seed_read_only || read_only_write {
+            debug_log!("Illegal CSR access: csr_addr={:#x}", csr_addr);
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+
+        match op {
+            CsrOp::Rw => {
                 debug_log!(
                     "CSRRW rd={}, rs1={}, rs1_val={}, csr_addr={}",
                     rd,
@@ -816,12 +1794,11 @@ impl Cpu {
                     rs1_val,
                     csr_addr
                 );
-                let old = self.csr.read(csr_addr);
-                self.csr.write(csr_addr, rs1_val);
+                let old = self.read_csr(csr_addr);
+                self.write_csr(csr_addr, rs1_val);
                 self.write_reg(rd, old);
-                false
             }
-            0x2 => {
+            CsrOp::Rs => {
                 debug_log!(
                     "CSRRS rd={}, rs1={}, rs1_val={}, csr_addr={}",
                     rd,
@@ -829,14 +1806,13 @@ impl Cpu {
                     rs1_val,
                     csr_addr
                 );
-                let old = self.csr.read(csr_addr);
+                let old = self.read_csr(csr_addr);
                 if rs1_val != 0x0 {
-                    self.csr.write(csr_addr, old | rs1_val);
+                    self.write_csr(csr_addr, old | rs1_val);
                 }
                 self.write_reg(rd, old);
-                false
             }
-            0x3 => {
+            CsrOp::Rc => {
                 debug_log!(
                     "CSRRC rd={}, rs1={}, rs1_val={}, csr_addr={}",
                     rd,
@@ -844,199 +1820,895 @@ impl Cpu {
                     rs1_val,
                     csr_addr
                 );
-                let old = self.csr.read(csr_addr);
+                let old = self.read_csr(csr_addr);
                 if rs1_val != 0x0 {
-                    self.csr.write(csr_addr, old & !rs1_val);
+                    self.write_csr(csr_addr, old & !rs1_val);
                 }
                 self.write_reg(rd, old);
-                false
             }
-            0x5 => {
-                debug_log!(
-                    "CSRRWI rd={}, rs1={}, rs1_val={}, csr_addr={}",
-                    rd,
-                    rs1,
-                    rs1_val,
-                    csr_addr
-                );
-                let old = self.csr.read(csr_addr);
-                self.csr.write(csr_addr, rs1 as u64);
+            CsrOp::Rwi => {
+                debug_log!("CSRRWI rd={}, uimm={}, csr_addr={}", rd, rs1, csr_addr);
+                let old = self.read_csr(csr_addr);
+                self.write_csr(csr_addr, rs1 as u64);
                 self.write_reg(rd, old);
-                false
             }
-            0x6 => {
-                debug_log!(
-                    "CSRRSI rd={}, rs1={}, rs1_val={}, csr_addr={}",
-                    rd,
-                    rs1,
-                    rs1_val,
-                    csr_addr
-                );
-                let old = self.csr.read(csr_addr);
+            CsrOp::Rsi => {
+                debug_log!("CSRRSI rd={}, uimm={}, csr_addr={}", rd, rs1, csr_addr);
+                let old = self.read_csr(csr_addr);
                 if rs1 != 0x0 {
-                    self.csr.write(csr_addr, old | (rs1 as u64));
+                    self.write_csr(csr_addr, old | (rs1 as u64));
                 }
                 self.write_reg(rd, old);
-                false
             }
-            0x7 => {
-                debug_log!(
-                    "CSRRCI rd={}, rs1={}, rs1_val={}, csr_addr={}",
-                    rd,
-                    rs1,
-                    rs1_val,
-                    csr_addr
-                );
-                let old = self.csr.read(csr_addr);
+            CsrOp::Rci => {
+                debug_log!("CSRRCI rd={}, uimm={}, csr_addr={}", rd, rs1, csr_addr);
+                let old = self.read_csr(csr_addr);
                 if rs1 != 0x0 {
-                    self.csr.write(csr_addr, old & !(rs1 as u64));
+                    self.write_csr(csr_addr, old & !(rs1 as u64));
                 }
                 self.write_reg(rd, old);
-                false
             }
-            _ => panic!("Unknown SYSTEM csr_addr: {:#x}", csr_addr),
-        };
-        taken
+        }
+        false
     }
 
-    fn execute_misc_mem(&mut self, inst: u32) {
-        debug_log!("MISC_MEM");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
+    /// 인터럽트가 pending될 때까지 hart를 재움. U 모드이거나 mstatus.TW=1이면
+    /// M 모드 미만에서는 illegal instruction (대기 시간 제한은 0으로 취급)
+    /// 재운 뒤 pc는 다음 명령어로 넘어가므로 깨어나 받는 트랩의 mepc는 WFI 다음
+    /// Returns true if a trap was taken
+    fn execute_wfi(&mut self, inst: u32) -> bool {
+        let tw = self.csr.mstatus() & csr::MSTATUS_TW != 0;
+        if self.mode == PrivilegeMode::User || (tw && self.mode != PrivilegeMode::Machine) {
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+        self.wfi = true;
+        false
+    }
+
+    /// TLB 비우기. rs1=x0이면 전체, 아니면 rs1 주소의 페이지 (ASID는 구분하지 않음)
+    /// U 모드이거나 mstatus.TVM=1인 S 모드에서는 illegal instruction
+    /// Returns true if a trap was taken
+    fn execute_sfence_vma(&mut self, inst: u32, rs1: usize) -> bool {
+        debug_log!("SFENCE.VMA rs1={}", rs1);
+        let tvm = self.csr.mstatus() & csr::MSTATUS_TVM != 0;
+        if self.mode == PrivilegeMode::User || (tvm && self.mode == PrivilegeMode::Supervisor) {
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+        if rs1 == 0 {
+            self.tlb.flush();
+        } else {
+            let addr = self.truncate_xlen(self.read_reg(rs1));
+            self.tlb.flush_page(addr >> PAGE_SHIFT);
+        }
+        false
+    }
+
+    /// WFI로 재운 hart가 계속 자야 하는지 (irq_lines는 장치 인터럽트 선)
+    /// 깨어나는 조건은 mstatus.MIE와 무관하게 mip & mie != 0
+    pub fn wfi_waiting(&self, irq_lines: u64) -> bool {
+        let mip = (self.csr.mip() & !DEVICE_LINES) | irq_lines;
+        self.wfi && mip & self.csr.mie() == 0
+    }
+
+    /// Zawrs: 예약 집합이 무효화되거나 인터럽트가 pending될 때까지 대기
+    /// 대기 중에는 pc를 그대로 두고 다음 step에서 다시 실행
+    /// Returns true if a trap was taken or the hart is stalled
+    fn execute_wrs(&mut self, inst: u32, short: bool) -> bool {
+        let pending = self.csr.read(csr::MIP) & self.csr.read(csr::MIE) != 0;
+        if !self.bus.has_reservation(self.hart_id) || pending {
+            self.wrs_stall = 0;
+            return false;
+        }
+
+        self.wrs_stall += 1;
+        if short && self.wrs_stall >= WRS_STO_TIMEOUT {
+            self.wrs_stall = 0;
+            return false;
+        }
+        // mstatus.TW=1이면 M 모드 미만의 wrs.nto는 제한 시간 후 illegal instruction
+        let tw = self.csr.read(csr::MSTATUS) & csr::MSTATUS_TW != 0;
+        if !short && tw && self.mode != PrivilegeMode::Machine && self.wrs_stall >= WRS_NTO_TIMEOUT
+        {
+            self.wrs_stall = 0;
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+        }
+        true
+    }
+
+    /// Returns true if a trap was taken
+    fn execute_cbo(&mut self, inst: u32, op: CboOp, rs1: usize) -> bool {
         let rs1_val = self.read_reg(rs1);
-        let pred = decoder::fence_pred(inst);
-        let succ = decoder::fence_succ(inst);
 
-        match funct3 {
-            0x0 => {
-                debug_log!(
-                    "FENCE rd={}, rs1={}, rs1_val={}, pred={}, succ={}",
-                    rd,
-                    rs1,
-                    rs1_val,
-                    pred,
-                    succ
-                );
-                self.bus.fence(self.hart_id, pred, succ);
-            }
-            0x1 => {
-                debug_log!(
-                    "FENCE.I rd={}, rs1={}, rs1_val={}, pred={}, succ={}",
-                    rd,
-                    rs1,
-                    rs1_val,
-                    pred,
-                    succ
-                );
+        let menvcfg = self.csr.read(csr::MENVCFG);
+        let senvcfg = self.csr.read(csr::SENVCFG);
+        let cbie = |envcfg: u64| (envcfg & csr::ENVCFG_CBIE) >> 4;
+        let allows = |envcfg: u64| match op {
+            // CBIE=10은 예약된 값이므로 00처럼 illegal
+            CboOp::Inval => matches!(cbie(envcfg), csr::CBIE_FLUSH | csr::CBIE_INVAL),
+            CboOp::Clean | CboOp::Flush => envcfg & csr::ENVCFG_CBCFE != 0,
+            CboOp::Zero => envcfg & csr::ENVCFG_CBZE != 0,
+        };
+        // M 모드 미만은 menvcfg, U 모드는 senvcfg도 허용해야 함
+        let allowed = match self.mode {
+            PrivilegeMode::Machine => true,
+            PrivilegeMode::Supervisor => allows(menvcfg),
+            PrivilegeMode::User => allows(menvcfg) && allows(senvcfg),
+        };
+        if !allowed {
+            debug_log!("CBO not enabled: {:?}", op);
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+
+        let block = self.truncate_xlen(rs1_val) & !(self.cache_block_size - 1);
+        match op {
+            CboOp::Inval => {
+                // CBIE=01이면 inval은 flush로 동작. 캐시가 없으므로 둘 다 no-op
+                let as_flush = match self.mode {
+                    PrivilegeMode::Machine => false,
+                    PrivilegeMode::Supervisor => cbie(menvcfg) == csr::CBIE_FLUSH,
+                    PrivilegeMode::User => {
+                        cbie(menvcfg) == csr::CBIE_FLUSH || cbie(senvcfg) == csr::CBIE_FLUSH
+                    }
+                };
+                debug_log!("CBO.INVAL block={:#x}, as_flush={}", block, as_flush);
+            }
+            CboOp::Clean => debug_log!("CBO.CLEAN block={:#x}", block),
+            CboOp::Flush => debug_log!("CBO.FLUSH block={:#x}", block),
+            CboOp::Zero => {
+                debug_log!("CBO.ZERO block={:#x}", block);
+                if self.check_triggers(TriggerAccess::Store, block, self.cache_block_size, None) {
+                    return true;
+                }
+                for offset in (0..self.cache_block_size).step_by(8) {
+                    self.write_data(block + offset, 8, 0);
+                }
             }
-            _ => panic!("Unknown MISC_MEM funct3: {:#x}", funct3),
         }
+        false
     }
 
-    fn execute_amo(&mut self, inst: u32) {
+    /// Returns true if a trap was taken
+    fn execute_amo(
+        &mut self,
+        inst: u32,
+        op: AmoOp,
+        width: Width,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    ) -> bool {
         debug_log!("AMO");
-        let funct3 = decoder::funct3(inst);
-        let rd = decoder::rd(inst);
-        let rs1 = decoder::rs1(inst);
-        let addr = self.read_reg(rs1);
-        let rs2 = decoder::rs2(inst);
-        let rs2_val = self.read_reg(rs2);
-        let funct5 = decoder::funct5(inst);
+        if !self.amo_enabled(op, width) {
+            debug_log!("Illegal AMO: {:?}.{}", op, width_suffix(width));
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+        let addr = self.truncate_xlen(self.read_reg(rs1));
 
-        match (funct3, funct5) {
-            (0x2, 0x01) => {
-                let val = self.bus.read32(addr) as i32 as i64 as u64;
-                debug_log!(
-                    "AMOSWAP.W rd={}, addr={:#x}, val={:#x}, rs2_val={:#x}",
-                    rd,
-                    addr,
-                    val,
-                    rs2_val
-                );
-                self.write_reg(rd, val);
-                self.bus.write32(addr, rs2_val as u32);
-            }
-            (0x2, 0x02) => {
-                let val = self.bus.read32(addr) as i32 as i64 as u64;
+        // B/H/W/D/Q
+        let size = width.bytes();
+        if !addr.is_multiple_of(size) {
+            debug_log!("AMO misaligned addr={:#x}", addr);
+            let cause = if op == AmoOp::Lr {
+                csr::LOAD_ADDRESS_MISALIGNED
+            } else {
+                csr::STORE_AMO_ADDRESS_MISALIGNED
+            };
+            self.trap(cause, addr);
+            return true;
+        }
+
+        // write buffer: rl이면 이전 store 전부, 아니면 같은 주소의 이전 store가 AMO보다 먼저 보임
+        let (aq, rl) = (decoder::aq(inst), decoder::rl(inst));
+        if rl {
+            self.bus.flush_write_buffer(self.hart_id);
+        } else {
+            self.bus.drain_overlapping(self.hart_id, addr, size);
+        }
+
+        self.bus.atomic_fence_before(aq, rl);
+        let trapped = self.execute_amo_op(inst, op, width, addr, rd, rs2);
+        self.bus.atomic_fence_after(aq, rl);
+        trapped
+    }
+
+    /// AMOCAS는 Zacas, B/H 폭은 Zabha가 필요 (AMOCAS.B/H는 둘 다)
+    fn amo_enabled(&self, op: AmoOp, width: Width) -> bool {
+        let cas = op != AmoOp::Cas || self.extensions.zacas;
+        let narrow = !matches!(width, Width::B | Width::H) || self.extensions.zabha;
+        cas && narrow
+    }
+
+    /// aq/rl 처리를 뺀 AMO 본체
+    /// Returns true if a trap was taken
+    fn execute_amo_op(
+        &mut self,
+        inst: u32,
+        op: AmoOp,
+        width: Width,
+        addr: u64,
+        rd: usize,
+        rs2: usize,
+    ) -> bool {
+        let rs2_val = self.read_reg(rs2);
+        let size = width.bytes();
+        match (width, op) {
+            (Width::W, AmoOp::Lr) => {
+                let raw = self.bus.load_reserved(self.hart_id, addr, 4);
+                let val = self.data_order(raw, 4) as i32 as i64 as u64;
                 debug_log!("LR.W rd={}, addr={:#x}, val={:#x}", rd, addr, val);
                 self.write_reg(rd, val);
-                self.bus.reserve(self.hart_id, addr);
             }
-            (0x2, 0x03) => {
+            (Width::W, AmoOp::Sc) => {
                 debug_log!("SC.W rd={}, addr={:#x}, rs2_val={:#x}", rd, addr, rs2_val);
-                if self.bus.check_reservation(self.hart_id, addr) {
-                    self.bus.write32(addr, rs2_val as u32);
-                    self.write_reg(rd, 0);
-                } else {
-                    self.write_reg(rd, 1);
-                }
-                self.bus.clear_reservation(self.hart_id);
+                let raw = self.data_order(rs2_val, 4);
+                let stored = self.bus.store_conditional(self.hart_id, addr, 4, raw);
+                self.write_reg(rd, !stored as u64);
             }
-            (0x3, 0x01) => {
-                let val = self.bus.read64(addr);
+            (Width::D, AmoOp::Lr) => {
+                let raw = self.bus.load_reserved(self.hart_id, addr, 8);
+                let val = self.data_order(raw, 8);
+                debug_log!("LR.D rd={}, addr={:#x}, val={:#x}", rd, addr, val);
+                self.write_reg(rd, val);
+            }
+            (Width::D, AmoOp::Sc) => {
+                debug_log!("SC.D rd={}, addr={:#x}, rs2_val={:#x}", rd, addr, rs2_val);
+                let raw = self.data_order(rs2_val, 8);
+                let stored = self.bus.store_conditional(self.hart_id, addr, 8, raw);
+                self.write_reg(rd, !stored as u64);
+            }
+            (_, AmoOp::Cas) => return self.execute_amocas(inst, width, addr, rd, rs2),
+            (_, AmoOp::Lr | AmoOp::Sc) => unreachable!("LR/SC width: {:?}", width),
+            _ => {
+                // 빅엔디언이면 메모리 값을 뒤집어서 계산한 뒤 다시 뒤집어 저장
+                let be = self.big_endian();
+                let order = move |value: u64| if be { swap_bytes(value, size) } else { value };
+                let old = self.bus.atomic_rmw(addr, size as u8, |raw| {
+                    order(amo_alu(op, order(raw), rs2_val, size))
+                });
+                let val = sign_extend(order(old), size);
                 debug_log!(
-                    "AMOSWAP.D rd={}, addr={:#x}, val={:#x}, rs2_val={:#x}",
+                    "{}.{} rd={}, addr={:#x}, val={:#x}, rs2_val={:#x}",
+                    amo_name(op),
+                    width_suffix(width),
                     rd,
                     addr,
                     val,
                     rs2_val
                 );
                 self.write_reg(rd, val);
-                self.bus.write64(addr, rs2_val);
             }
-            (0x3, 0x02) => {
-                let val = self.bus.read64(addr);
-                debug_log!("LR.D rd={}, addr={:#x}, val={:#x}", rd, addr, val);
+        }
+        false
+    }
+
+    /// Zacas: AMOCAS.B/H/W/D/Q
+    /// Returns true if a trap was taken
+    fn execute_amocas(
+        &mut self,
+        inst: u32,
+        width: Width,
+        addr: u64,
+        rd: usize,
+        rs2: usize,
+    ) -> bool {
+        // AMOCAS.Q(RV64)/AMOCAS.D(RV32)는 2*XLEN 폭
+        let xlen = self.xlen().bits();
+        if width.bytes() * 8 == 2 * xlen as u64 {
+            // rd와 rs2는 짝수 레지스터 쌍 (x0이면 쌍 전체가 0)
+            if !rd.is_multiple_of(2) || !rs2.is_multiple_of(2) {
+                self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+                return true;
+            }
+            let expected = self.read_reg_pair(rd);
+            let new = self.read_reg_pair(rs2);
+            // 빅엔디언이면 2*XLEN 값 전체의 바이트 순서를 뒤집음
+            let be = self.big_endian();
+            let old = if width == Width::Q {
+                let order = |value: u128| if be { value.swap_bytes() } else { value };
+                order(
+                    self.bus
+                        .compare_and_swap128(addr, order(expected), order(new)),
+                )
+            } else {
+                let order = |value: u64| if be { value.swap_bytes() } else { value };
+                let old =
+                    self.bus
+                        .compare_and_swap(addr, 8, order(expected as u64), order(new as u64));
+                order(old).into()
+            };
+            debug_log!(
+                "AMOCAS.{} rd={}, addr={:#x}, old={:#x}, expected={:#x}, new={:#x}",
+                width_suffix(width),
+                rd,
+                addr,
+                old,
+                expected,
+                new
+            );
+            if rd != 0 {
+                self.write_reg(rd, old as u64);
+                self.write_reg(rd + 1, (old >> xlen) as u64);
+            }
+            return false;
+        }
+
+        let size = width.bytes();
+        let mask = if size == 8 {
+            u64::MAX
+        } else {
+            (1 << (size * 8)) - 1
+        };
+        let expected = self.read_reg(rd) & mask;
+        let new = self.read_reg(rs2) & mask;
+        let old = self.bus.compare_and_swap(
+            addr,
+            size as u8,
+            self.data_order(expected, size),
+            self.data_order(new, size),
+        );
+        let old = self.data_order(old, size);
+        let val = sign_extend(old, size);
+        debug_log!(
+            "AMOCAS.{} rd={}, addr={:#x}, val={:#x}, expected={:#x}, new={:#x}",
+            width_suffix(width),
+            rd,
+            addr,
+            val,
+            expected,
+            new
+        );
+        self.write_reg(rd, val);
+        false
+    }
+
+    fn read_reg_pair(&self, index: usize) -> u128 {
+        if index == 0 {
+            return 0;
+        }
+        let xlen = self.xlen().bits();
+        let low = self.truncate_xlen(self.read_reg(index)) as u128;
+        low | ((self.read_reg(index + 1) as u128) << xlen)
+    }
+
+    pub fn read_freg(&self, index: usize) -> u64 {
+        self.fregs[index]
+    }
+
+    pub fn write_freg(&mut self, index: usize, value: u64) {
+        self.fregs[index] = value;
+        self.mark_fp_dirty();
+    }
+
+    /// F가 없거나 mstatus.FS가 Off이면 모든 FP 명령어와 fcsr 접근은 illegal
+    fn fp_enabled(&self) -> bool {
+        self.extensions.f && self.csr.read(csr::MSTATUS) & csr::MSTATUS_FS != csr::FS_OFF
+    }
+
+    fn mark_fp_dirty(&mut self) {
+        let mstatus = self.csr.read(csr::MSTATUS);
+        self.csr
+            .write(csr::MSTATUS, mstatus | csr::FS_DIRTY | csr::MSTATUS_SD);
+    }
+
+    fn accrue_fflags(&mut self, flags: u64) {
+        if flags != 0 {
+            let fcsr = self.csr.read(csr::FCSR);
+            self.csr.write(csr::FCSR, fcsr | flags);
+            self.mark_fp_dirty();
+        }
+    }
+
+    /// rm 필드 해석 (DYN이면 frm), 예약된 값이면 None
+    fn rounding_mode(&self, rm: u32) -> Option<RoundingMode> {
+        if rm == RM_DYNAMIC {
+            let frm = (self.csr.read(csr::FCSR) & csr::FCSR_FRM) >> csr::FCSR_FRM_SHIFT;
+            RoundingMode::from_bits(frm)
+        } else {
+            RoundingMode::from_bits(rm as u64)
+        }
+    }
+
+    // FLH/FSH/FMV.X.H/FMV.H.X는 Zfh, Zfhmin, Zfbfmin 공용
+    fn half_moves_enabled(&self) -> bool {
+        self.extensions.has(Extension::Zfhmin) || self.extensions.has(Extension::Zfbfmin)
+    }
+
+    /// FLH/FLW/FLD
+    /// Returns true if a trap was taken or a trigger fired
+    fn execute_load_fp(
+        &mut self,
+        inst: u32,
+        width: Width,
+        rd: usize,
+        rs1: usize,
+        offset: i32,
+    ) -> bool {
+        debug_log!("LOAD_FP");
+        let enabled = match width {
+            Width::H => self.half_moves_enabled(),
+            Width::W => self.extensions.f,
+            Width::D => self.extensions.d,
+            _ => false,
+        };
+        if !self.fp_enabled() || !enabled {
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+
+        let rs1_val = self.read_reg(rs1);
+        let addr = self.truncate_xlen((rs1_val as i64).wrapping_add(offset as i64) as u64);
+
+        let size = width.bytes();
+        if self.check_triggers(TriggerAccess::Load, addr, size, None) {
+            return true;
+        }
+        let val = self.read_data(addr, size);
+        if self.check_triggers(TriggerAccess::Load, addr, size, Some(val)) {
+            return true;
+        }
+        let boxed = match width {
+            Width::H => HALF.nan_box(val),
+            Width::W => SINGLE.nan_box(val),
+            _ => val,
+        };
+        debug_log!(
+            "FL{} rd={}, addr={:#x}, val={:#x}",
+            width_suffix(width),
+            rd,
+            addr,
+            val
+        );
+        self.write_freg(rd, boxed);
+        false
+    }
+
+    /// FSH/FSW/FSD
+    /// Returns true if a trap was taken or a trigger fired
+    fn execute_store_fp(
+        &mut self,
+        inst: u32,
+        width: Width,
+        rs1: usize,
+        rs2: usize,
+        offset: i32,
+    ) -> bool {
+        debug_log!("STORE_FP");
+        let enabled = match width {
+            Width::H => self.half_moves_enabled(),
+            Width::W => self.extensions.f,
+            Width::D => self.extensions.d,
+            _ => false,
+        };
+        if !self.fp_enabled() || !enabled {
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+
+        let rs1_val = self.read_reg(rs1);
+        let addr = self.truncate_xlen((rs1_val as i64).wrapping_add(offset as i64) as u64);
+
+        // NaN-boxing 검사 없이 하위 비트를 그대로 저장
+        let size = width.bytes();
+        let val = if size == 8 {
+            self.read_freg(rs2)
+        } else {
+            self.read_freg(rs2) & ((1 << (size * 8)) - 1)
+        };
+        if self.check_triggers(TriggerAccess::Store, addr, size, Some(val)) {
+            return true;
+        }
+        debug_log!("FS{} addr={:#x}, val={:#x}", width_suffix(width), addr, val);
+        self.write_data(addr, size, val);
+        false
+    }
+
+    /// fmt 형식의 연산을 실행할 수 있는지 (F/D/Zfh)
+    fn fmt_enabled(&self, fmt: FpFmt) -> bool {
+        match fmt {
+            FpFmt::S => self.extensions.f,
+            FpFmt::D => self.extensions.d,
+            FpFmt::H => self.extensions.has(Extension::Zfh),
+        }
+    }
+
+    /// FCVT.fmt.fmt: 반정밀도가 끼면 Zfhmin, 배정밀도가 끼면 D가 필요
+    fn conversion_enabled(&self, from: FpFmt, to: FpFmt) -> bool {
+        let uses = |fmt| from == fmt || to == fmt;
+        (!uses(FpFmt::H) || self.extensions.has(Extension::Zfhmin))
+            && (!uses(FpFmt::D) || self.extensions.d)
+    }
+
+    /// OP-FP: F/D/Zfh 연산, Zfhmin/Zfbfmin 변환, 레지스터 이동
+    /// Returns true if a trap was taken
+    fn execute_op_fp(
+        &mut self,
+        inst: u32,
+        op: FpOp,
+        fmt: FpFmt,
+        rd: usize,
+        rs: [usize; 2],
+        rm: u32,
+    ) -> bool {
+        use FpOp::*;
+        debug_log!("OP_FP");
+
+        // funct3가 rm인 명령어는 rm이 유효해야 함
+        let rounding = self.rounding_mode(rm);
+        let enabled = match op {
+            MvXF | MvFX if fmt == FpFmt::H => self.half_moves_enabled(),
+            CvtFF(from) => self.conversion_enabled(from, fmt),
+            CvtSBf16 | CvtBf16S => self.extensions.has(Extension::Zfbfmin),
+            _ => self.fmt_enabled(fmt),
+        };
+        if !self.fp_enabled() || !enabled || (op.uses_rm() && rounding.is_none()) {
+            debug_log!("Illegal OP_FP {:?}.{:?}", op, fmt);
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        }
+        let rm = rounding.unwrap_or(RoundingMode::Rne);
+
+        let [rs1, rs2] = rs;
+        let format = float_format(fmt);
+        let sign = format.sign_bit();
+        let f1 = format.unbox(self.read_freg(rs1));
+        let f2 = format.unbox(self.read_freg(rs2));
+        let rs1_val = self.read_reg(rs1);
+        let mut flags = 0;
+
+        match op {
+            Add | Sub | Mul | Div => {
+                let result = match op {
+                    Add => softfloat::add(format, f1, f2, rm, &mut flags),
+                    Sub => softfloat::sub(format, f1, f2, rm, &mut flags),
+                    Mul => softfloat::mul(format, f1, f2, rm, &mut flags),
+                    _ => softfloat::div(format, f1, f2, rm, &mut flags),
+                };
+                debug_log!("{:?}.{:?} rd={}, result={:#x}", op, fmt, rd, result);
+                self.write_freg(rd, format.nan_box(result));
+            }
+            Sqrt => {
+                let result = softfloat::sqrt(format, f1, rm, &mut flags);
+                debug_log!("FSQRT.{:?} rd={}, result={:#x}", fmt, rd, result);
+                self.write_freg(rd, format.nan_box(result));
+            }
+            Sgnj | Sgnjn | Sgnjx => {
+                let sign_bit = match op {
+                    Sgnj => f2 & sign,
+                    Sgnjn => !f2 & sign,
+                    _ => (f1 ^ f2) & sign,
+                };
+                debug_log!("{:?}.{:?} rd={}", op, fmt, rd);
+                self.write_freg(rd, format.nan_box((f1 & !sign) | sign_bit));
+            }
+            Min | Max => {
+                let result = softfloat::min_max(format, f1, f2, op == Max, &mut flags);
+                debug_log!("FMINMAX.{:?} rd={}, result={:#x}", fmt, rd, result);
+                self.write_freg(rd, format.nan_box(result));
+            }
+            Le | Lt | Eq => {
+                let result = match op {
+                    Le => softfloat::le(format, f1, f2, &mut flags),
+                    Lt => softfloat::lt(format, f1, f2, &mut flags),
+                    _ => softfloat::eq(format, f1, f2, &mut flags),
+                };
+                debug_log!("FCMP.{:?} rd={}, result={}", fmt, rd, result);
+                self.write_reg(rd, result as u64);
+            }
+            CvtWF | CvtWuF | CvtLF | CvtLuF => {
+                let signed = matches!(op, CvtWF | CvtLF);
+                let width = if matches!(op, CvtWF | CvtWuF) { 32 } else { 64 };
+                let result = softfloat::to_int(format, f1, rm, signed, width, &mut flags);
+                debug_log!("FCVT.INT.{:?} rd={}, result={:#x}", fmt, rd, result);
+                self.write_reg(rd, result);
+            }
+            CvtFW | CvtFWu | CvtFL | CvtFLu => {
+                let signed = matches!(op, CvtFW | CvtFL);
+                let width = if matches!(op, CvtFW | CvtFWu) { 32 } else { 64 };
+                let result = softfloat::from_int(format, rs1_val, signed, width, rm, &mut flags);
+                debug_log!("FCVT.{:?}.INT rd={}, result={:#x}", fmt, rd, result);
+                self.write_freg(rd, format.nan_box(result));
+            }
+            MvXF => {
+                // NaN-boxing 검사 없이 하위 비트를 sign-extend
+                let val = sign_extend(self.read_freg(rs1), format.width() as u64 / 8);
+                debug_log!("FMV.X.{:?} rd={}, val={:#x}", fmt, rd, val);
                 self.write_reg(rd, val);
-                self.bus.reserve(self.hart_id, addr);
             }
-            (0x3, 0x03) => {
-                debug_log!("SC.D rd={}, addr={:#x}, rs2_val={:#x}", rd, addr, rs2_val);
-                if self.bus.check_reservation(self.hart_id, addr) {
-                    self.bus.write64(addr, rs2_val);
-                    self.write_reg(rd, 0);
+            Class => {
+                let result = softfloat::classify(format, f1);
+                debug_log!("FCLASS.{:?} rd={}, result={:#x}", fmt, rd, result);
+                self.write_reg(rd, result);
+            }
+            MvFX => {
+                let bits = match format.width() {
+                    64 => rs1_val,
+                    width => rs1_val & ((1 << width) - 1),
+                };
+                debug_log!("FMV.{:?}.X rd={}, val={:#x}", fmt, rd, bits);
+                self.write_freg(rd, format.nan_box(bits));
+            }
+            // 형식 변환 (F/D, Zfhmin)
+            CvtFF(from) => {
+                let source = float_format(from);
+                let value = source.unbox(self.read_freg(rs1));
+                let result = softfloat::convert(source, format, value, rm, &mut flags);
+                debug_log!("FCVT.{:?}.{:?} rd={}, result={:#x}", fmt, from, rd, result);
+                self.write_freg(rd, format.nan_box(result));
+            }
+            // bf16 변환 (Zfbfmin)
+            CvtSBf16 => {
+                let bf = BFLOAT16.unbox(self.read_freg(rs1));
+                let result = softfloat::convert(BFLOAT16, SINGLE, bf, rm, &mut flags);
+                debug_log!("FCVT.S.BF16 rd={}, result={:#x}", rd, result);
+                self.write_freg(rd, SINGLE.nan_box(result));
+            }
+            CvtBf16S => {
+                let s = SINGLE.unbox(self.read_freg(rs1));
+                let result = softfloat::convert(SINGLE, BFLOAT16, s, rm, &mut flags);
+                debug_log!("FCVT.BF16.S rd={}, result={:#x}", rd, result);
+                self.write_freg(rd, BFLOAT16.nan_box(result));
+            }
+        }
+        self.accrue_fflags(flags);
+        false
+    }
+
+    /// FMADD / FMSUB / FNMSUB / FNMADD (.S/.D/.H)
+    /// Returns true if a trap was taken
+    fn execute_fp_fused(
+        &mut self,
+        inst: u32,
+        op: FusedOp,
+        fmt: FpFmt,
+        rd: usize,
+        rs: [usize; 3],
+        rm: u32,
+    ) -> bool {
+        debug_log!("FP_FUSED");
+        let rm = self.rounding_mode(rm);
+        let (Some(rm), true, true) = (rm, self.fp_enabled(), self.fmt_enabled(fmt)) else {
+            self.trap(csr::ILLEGAL_INSTRUCTION, inst as u64);
+            return true;
+        };
+
+        let [rs1, rs2, rs3] = rs;
+        let format = float_format(fmt);
+        let sign = format.sign_bit();
+        let a = format.unbox(self.read_freg(rs1));
+        let b = format.unbox(self.read_freg(rs2));
+        let c = format.unbox(self.read_freg(rs3));
+
+        // 곱의 부호는 a의 부호 반전으로 처리
+        let (a, c) = match op {
+            FusedOp::Fmadd => (a, c),
+            FusedOp::Fmsub => (a, c ^ sign),
+            FusedOp::Fnmsub => (a ^ sign, c),
+            FusedOp::Fnmadd => (a ^ sign, c ^ sign),
+        };
+        let mut flags = 0;
+        let result = softfloat::fma(format, a, b, c, rm, &mut flags);
+        debug_log!("FMA.{:?} rd={}, result={:#x}", fmt, rd, result);
+        self.write_freg(rd, format.nan_box(result));
+        self.accrue_fflags(flags);
+        false
+    }
+
+    fn csr_accessible(&self, addr: u16) -> bool {
+        match addr {
+            // Debug Mode 전용 CSR
+            csr::DCSR..=csr::DSCRATCH1 => self.debug_mode,
+            csr::FFLAGS..=csr::FCSR => self.fp_enabled(),
+            // 상위 32비트 CSR은 RV32에만 존재
+            csr::MSTATUSH
+            | csr::CYCLEH
+            | csr::TIMEH
+            | csr::INSTRETH
+            | csr::MCYCLEH
+            | csr::MINSTRETH => self.xlen() == Xlen::Rv32,
+            csr::JVT => self.extensions.zcmt,
+            // mstatus.TVM=1이면 S 모드의 satp 접근은 illegal
+            csr::SATP => {
+                self.mode != PrivilegeMode::Supervisor || self.csr.mstatus() & csr::MSTATUS_TVM == 0
+            }
+            csr::SEED => {
+                let mseccfg = self.csr.read(csr::MSECCFG);
+                self.extensions.zkr
+                    && match self.mode {
+                        PrivilegeMode::Machine => true,
+                        PrivilegeMode::Supervisor => mseccfg & csr::MSECCFG_SSEED != 0,
+                        PrivilegeMode::User => mseccfg & csr::MSECCFG_USEED != 0,
+                    }
+            }
+            _ => true,
+        }
+    }
+
+    fn read_csr(&mut self, addr: u16) -> u64 {
+        match addr {
+            // 읽을 때마다 새 엔트로피 (쓰기 값은 무시)
+            csr::SEED => self.entropy.read_seed(),
+            csr::FFLAGS => self.csr.read(csr::FCSR) & csr::FCSR_FFLAGS,
+            csr::FRM => (self.csr.read(csr::FCSR) & csr::FCSR_FRM) >> csr::FCSR_FRM_SHIFT,
+            csr::FCSR => self.csr.read(csr::FCSR) & (csr::FCSR_FRM | csr::FCSR_FFLAGS),
+            csr::TSELECT..=csr::TINFO => self.triggers.read(addr),
+            csr::MSTATUS if self.xlen() == Xlen::Rv32 => {
+                // RV32 mstatus는 하위 절반, SD는 31번 비트
+                let mstatus = self.csr.read(csr::MSTATUS);
+                let sd = if mstatus & csr::MSTATUS_SD != 0 {
+                    csr::MSTATUS32_SD
                 } else {
-                    self.write_reg(rd, 1);
+                    0
+                };
+                (mstatus & 0x7FFF_FFFF) | sd
+            }
+            csr::MSTATUSH => (self.csr.read(csr::MSTATUS) & !csr::MSTATUS_SD) >> 32,
+            // UBE는 mstatus와 공유
+            csr::SSTATUS => {
+                let ube = self.csr.read(csr::MSTATUS) & csr::MSTATUS_UBE;
+                (self.csr.read(csr::SSTATUS) & !csr::SSTATUS_UBE) | ube
+            }
+            csr::CYCLE => self.csr.read(csr::MCYCLE),
+            csr::TIME => self.bus.mtime(),
+            csr::INSTRET => self.csr.read(csr::MINSTRET),
+            csr::CYCLEH | csr::MCYCLEH => self.csr.read(csr::MCYCLE) >> 32,
+            csr::TIMEH => self.bus.mtime() >> 32,
+            csr::INSTRETH | csr::MINSTRETH => self.csr.read(csr::MINSTRET) >> 32,
+            _ => self.csr.read(addr),
+        }
+    }
+
+    fn write_csr(&mut self, addr: u16, value: u64) {
+        // CSR 폭은 현재 XLEN
+        let value = self.truncate_xlen(value);
+        let rv32 = self.xlen() == Xlen::Rv32;
+        match addr {
+            csr::SEED => {}
+            // MODE는 Bare만 지원. 다른 MODE를 쓰면 쓰기 전체를 무시
+            csr::SATP => {
+                let mode = if rv32 { value >> 31 } else { value >> 60 };
+                if mode == 0 {
+                    self.csr.write(csr::SATP, value);
+                    self.tlb.flush();
+                }
+            }
+            // mode는 WARL: 0(jump table)만 지원
+            csr::JVT => self.csr.write(csr::JVT, value & !csr::JVT_MODE),
+            csr::MSTATUS if rv32 => {
+                let old = self.csr.read(csr::MSTATUS);
+                let low = value & !csr::MSTATUS32_SD;
+                self.csr.write(csr::MSTATUS, (old & !0xFFFF_FFFF) | low);
+            }
+            csr::MSTATUS => {
+                // SXL/UXL은 WARL: 1(32비트), 2(64비트)만 허용
+                let old = self.csr.read(csr::MSTATUS);
+                let mut mstatus = value;
+                for (field, shift) in [(csr::MSTATUS_SXL, 34), (csr::MSTATUS_UXL, 32)] {
+                    if !matches!((value & field) >> shift, 1 | 2) {
+                        mstatus = (mstatus & !field) | (old & field);
+                    }
+                }
+                self.csr.write(csr::MSTATUS, mstatus);
+            }
+            csr::SSTATUS => {
+                self.csr.write(csr::SSTATUS, value);
+                let mstatus = self.csr.read(csr::MSTATUS);
+                let ube = value & csr::SSTATUS_UBE;
+                self.csr
+                    .write(csr::MSTATUS, (mstatus & !csr::MSTATUS_UBE) | ube);
+            }
+            csr::MSTATUSH => {
+                // 쓸 수 있는 필드는 MBE/SBE뿐 (SXL/UXL은 유지)
+                let writable = csr::MSTATUS_MBE | csr::MSTATUS_SBE;
+                let old = self.csr.read(csr::MSTATUS);
+                self.csr
+                    .write(csr::MSTATUS, (old & !writable) | ((value << 32) & writable));
+            }
+            csr::MCYCLE | csr::MINSTRET if rv32 => {
+                let old = self.csr.read(addr);
+                self.csr.write(addr, (old & !0xFFFF_FFFF) | value);
+            }
+            csr::MCYCLEH | csr::MINSTRETH => {
+                let counter = addr - csr::MCYCLEH + csr::MCYCLE;
+                let old = self.csr.read(counter);
+                self.csr.write(counter, (old & 0xFFFF_FFFF) | (value << 32));
+            }
+            csr::FFLAGS..=csr::FCSR => {
+                let fcsr = self.csr.read(csr::FCSR);
+                let new = match addr {
+                    csr::FFLAGS => (fcsr & !csr::FCSR_FFLAGS) | (value & csr::FCSR_FFLAGS),
+                    csr::FRM => {
+                        (fcsr & !csr::FCSR_FRM) | ((value << csr::FCSR_FRM_SHIFT) & csr::FCSR_FRM)
+                    }
+                    _ => value & (csr::FCSR_FRM | csr::FCSR_FFLAGS),
+                };
+                self.csr.write(csr::FCSR, new);
+                self.mark_fp_dirty();
+            }
+            csr::TSELECT..=csr::TINFO if self.debug_mode => self.triggers.debug_write(addr, value),
+            csr::TSELECT..=csr::TINFO => self.triggers.write(addr, value),
+            csr::DCSR => {
+                // 쓰기 가능한 필드만 반영, prv는 지원하는 모드만 허용
+                let writable = csr::DCSR_EBREAKM
+                    | csr::DCSR_EBREAKS
+                    | csr::DCSR_EBREAKU
+                    | csr::DCSR_STEPIE
+                    | csr::DCSR_STEP;
+                let old = self.csr.read(csr::DCSR);
+                let mut dcsr = (old & !writable) | (value & writable);
+                if value & csr::DCSR_PRV != 2 {
+                    dcsr = (dcsr & !csr::DCSR_PRV) | (value & csr::DCSR_PRV);
                 }
-                self.bus.clear_reservation(self.hart_id);
+                self.csr.write(csr::DCSR, dcsr);
             }
-            _ => panic!(
-                "Not Implemented AMO funct3: {:#x}, funct5: {:#x}",
-                funct3, funct5
-            ),
+            _ => self.csr.write(addr, value),
         }
     }
 
-    fn check_pending_interrupts(&mut self) -> bool {
-        let mip = self.csr.read(csr::MIP);
-        if self.bus.check_timer_interrupt() {
-            self.csr.write(csr::MIP, mip | csr::MIP_MTIP);
-        } else {
-            self.csr.write(csr::MIP, mip & !csr::MIP_MTIP);
+    /// M 모드에서 MIE=0이면 breakpoint 트리거가 발동하지 않음 (트랩 핸들러 재진입 방지)
+    fn breakpoints_allowed(&self) -> bool {
+        self.mode != PrivilegeMode::Machine || self.csr.read(csr::MSTATUS) & csr::MSTATUS_MIE != 0
+    }
+
+    /// Returns true if a trigger fired
+    fn check_triggers(
+        &mut self,
+        access: TriggerAccess,
+        addr: u64,
+        size: u64,
+        data: Option<u64>,
+    ) -> bool {
+        // Debug Mode에서는 트리거가 매치되지 않음
+        if self.debug_mode {
+            return false;
         }
+        let allowed = self.breakpoints_allowed();
+        match self
+            .triggers
+            .check(access, addr, size, data, self.mode, allowed)
+        {
+            Some(hit) => {
+                self.fire_trigger(hit);
+                true
+            }
+            None => false,
+        }
+    }
 
-        let mip = self.csr.read(csr::MIP);
-        if self.bus.check_software_interrupt() {
-            self.csr.write(csr::MIP, mip | csr::MIP_MSIP);
-        } else {
-            self.csr.write(csr::MIP, mip & !csr::MIP_MSIP);
+    /// Returns true if a pending icount trigger fired
+    fn check_pending_triggers(&mut self) -> bool {
+        let allowed = self.breakpoints_allowed();
+        match self.triggers.take_pending(self.pc, allowed) {
+            Some(hit) => {
+                self.fire_trigger(hit);
+                true
+            }
+            None => false,
         }
+    }
 
-        let mip = self.csr.read(csr::MIP);
-        if self.bus.check_uart_interrupt() {
-            self.csr.write(csr::MIP, mip | csr::MIP_MEIP);
+    fn fire_trigger(&mut self, hit: TriggerHit) {
+        debug_log!("TRIGGER index={}, tval={:#x}", hit.index, hit.tval);
+        if hit.action == trigger::ACTION_DEBUG_MODE {
+            self.enter_debug_mode(csr::DEBUG_CAUSE_TRIGGER);
         } else {
-            self.csr.write(csr::MIP, mip & !csr::MIP_MEIP);
+            self.trap(csr::BREAKPOINT, hit.tval);
         }
+    }
 
-        let mstatus = self.csr.read(csr::MSTATUS);
-        if mstatus & csr::MSTATUS_MIE == 0 {
+    fn check_pending_interrupts(&mut self) -> bool {
+        // 장치 인터럽트 선을 MIP에 반영. 선은 장치 상태가 바뀔 때 Bus가 갱신해 둠
+        let mip = (self.csr.mip() & !DEVICE_LINES) | self.bus.irq_lines(self.hart_id);
+        self.csr.set_mip(mip);
+
+        if self.csr.mstatus() & csr::MSTATUS_MIE == 0 {
             return false;
         }
 
-        let mip = self.csr.read(csr::MIP);
-        let mie = self.csr.read(csr::MIE);
+        let mie = self.csr.mie();
 
         if (mip & csr::MIP_MSIP != 0) && (mie & csr::MIE_MSIE != 0) {
             self.trap(csr::INTERRUPT_BIT | csr::INTERRUPT_FROM_SOFTWARE, 0);
@@ -1055,3 +2727,79 @@ impl Cpu {
         false
     }
 }
+
+/// 하위 size 바이트의 바이트 순서를 뒤집음
+fn swap_bytes(value: u64, size: u64) -> u64 {
+    match size {
+        1 => value & 0xFF,
+        2 => (value as u16).swap_bytes() as u64,
+        4 => (value as u32).swap_bytes() as u64,
+        _ => value.swap_bytes(),
+    }
+}
+
+/// OP-FP/FMA fmt 필드의 부동소수점 형식
+fn float_format(fmt: FpFmt) -> Format {
+    match fmt {
+        FpFmt::S => SINGLE,
+        FpFmt::D => DOUBLE,
+        FpFmt::H => HALF,
+    }
+}
+
+fn sign_extend(value: u64, size: u64) -> u64 {
+    match size {
+        1 => value as i8 as i64 as u64,
+        2 => value as i16 as i64 as u64,
+        4 => value as i32 as i64 as u64,
+        _ => value,
+    }
+}
+
+/// AMO 연산 결과 (size 바이트 폭으로 계산, 상위 비트는 버림)
+pub(crate) fn amo_alu(op: AmoOp, old: u64, src: u64, size: u64) -> u64 {
+    let signed_old = sign_extend(old, size) as i64;
+    let signed_src = sign_extend(src, size) as i64;
+    let unsigned_src = if size == 8 {
+        src
+    } else {
+        src & ((1 << (size * 8)) - 1)
+    };
+    match op {
+        AmoOp::Add => old.wrapping_add(src),
+        AmoOp::Swap => src,
+        AmoOp::Xor => old ^ src,
+        AmoOp::Or => old | src,
+        AmoOp::And => old & src,
+        AmoOp::Min => signed_old.min(signed_src) as u64,
+        AmoOp::Max => signed_old.max(signed_src) as u64,
+        AmoOp::Minu => old.min(unsigned_src),
+        AmoOp::Maxu => old.max(unsigned_src),
+        AmoOp::Lr | AmoOp::Sc | AmoOp::Cas => unreachable!(),
+    }
+}
+
+fn amo_name(op: AmoOp) -> &'static str {
+    match op {
+        AmoOp::Add => "AMOADD",
+        AmoOp::Swap => "AMOSWAP",
+        AmoOp::Xor => "AMOXOR",
+        AmoOp::Or => "AMOOR",
+        AmoOp::And => "AMOAND",
+        AmoOp::Min => "AMOMIN",
+        AmoOp::Max => "AMOMAX",
+        AmoOp::Minu => "AMOMINU",
+        AmoOp::Maxu => "AMOMAXU",
+        _ => "AMO?",
+    }
+}
+
+fn width_suffix(width: Width) -> &'static str {
+    match width {
+        Width::B => "B",
+        Width::H => "H",
+        Width::W => "W",
+        Width::D => "D",
+        Width::Q => "Q",
+    }
+}
@@ -0,0 +1,639 @@
+//! 스칼라 암호 확장 (Zbkb, Zbkc, Zbkx, Zknd, Zkne, Zknh, Zksed, Zksh)
+//! RV64 인코딩만 지원 (aes32*, sha512*r, zip/unzip 등 RV32 전용 명령어 제외)
+
+use crate::cpu::extensions::Extensions;
+use crate::decoder;
+
+const OP_IMM: u32 = 0x13;
+const OP_IMM_32: u32 = 0x1B;
+const OP: u32 = 0x33;
+const OP_32: u32 = 0x3B;
+
+// aes64ks1i의 rnum 최대값 (0xB~0xF는 예약)
+pub const AES_MAX_RNUM: u32 = 0xA;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CryptoOp {
+    // Zbkb
+    Andn,
+    Orn,
+    Xnor,
+    Rol,
+    Ror,
+    Rori(u32),
+    Rolw,
+    Rorw,
+    Roriw(u32),
+    Pack,
+    Packh,
+    Packw,
+    Rev8,
+    Brev8,
+    // Zbkc
+    Clmul,
+    Clmulh,
+    // Zbkx
+    Xperm4,
+    Xperm8,
+    // Zkne / Zknd
+    Aes64es,
+    Aes64esm,
+    Aes64ds,
+    Aes64dsm,
+    Aes64im,
+    Aes64ks1i(u32),
+    Aes64ks2,
+    // Zknh
+    Sha256sig0,
+    Sha256sig1,
+    Sha256sum0,
+    Sha256sum1,
+    Sha512sig0,
+    Sha512sig1,
+    Sha512sum0,
+    Sha512sum1,
+    // Zksed (bs: 바이트 선택)
+    Sm4ed(u32),
+    Sm4ks(u32),
+    // Zksh
+    Sm3p0,
+    Sm3p1,
+}
+
+impl CryptoOp {
+    pub fn enabled_by(&self, ext: &Extensions) -> bool {
+        use CryptoOp::*;
+        match self {
+            Andn | Orn | Xnor | Rol | Ror | Rori(_) | Rolw | Rorw | Roriw(_) | Pack | Packh
+            | Packw | Rev8 | Brev8 => ext.zbkb,
+            Clmul | Clmulh => ext.zbkc,
+            Xperm4 | Xperm8 => ext.zbkx,
+            Aes64es | Aes64esm => ext.zkne,
+            Aes64ds | Aes64dsm | Aes64im => ext.zknd,
+            // 키 스케줄은 암호화/복호화 공용
+            Aes64ks1i(_) | Aes64ks2 => ext.zkne || ext.zknd,
+            Sha256sig0 | Sha256sig1 | Sha256sum0 | Sha256sum1 | Sha512sig0 | Sha512sig1
+            | Sha512sum0 | Sha512sum1 => ext.zknh,
+            Sm4ed(_) | Sm4ks(_) => ext.zksed,
+            Sm3p0 | Sm3p1 => ext.zksh,
+        }
+    }
+}
+
+/// 스칼라 암호 명령어가 아니면 None
+pub fn decode(inst: u32) -> Option<CryptoOp> {
+    use CryptoOp::*;
+    let funct3 = decoder::funct3(inst);
+    let funct7 = decoder::funct7(inst);
+    let imm12 = inst >> 20;
+
+    match decoder::opcode(inst) {
+        OP => match (funct3, funct7) {
+            (0x7, 0x20) => Some(Andn),
+            (0x6, 0x20) => Some(Orn),
+            (0x4, 0x20) => Some(Xnor),
+            (0x1, 0x30) => Some(Rol),
+            (0x5, 0x30) => Some(Ror),
+            (0x4, 0x04) => Some(Pack),
+            (0x7, 0x04) => Some(Packh),
+            (0x1, 0x05) => Some(Clmul),
+            (0x3, 0x05) => Some(Clmulh),
+            (0x2, 0x14) => Some(Xperm4),
+            (0x4, 0x14) => Some(Xperm8),
+            (0x0, 0x19) => Some(Aes64es),
+            (0x0, 0x1B) => Some(Aes64esm),
+            (0x0, 0x1D) => Some(Aes64ds),
+            (0x0, 0x1F) => Some(Aes64dsm),
+            (0x0, 0x3F) => Some(Aes64ks2),
+            (0x0, f7) if f7 & 0x1F == 0x18 => Some(Sm4ed(f7 >> 5)),
+            (0x0, f7) if f7 & 0x1F == 0x1A => Some(Sm4ks(f7 >> 5)),
+            _ => None,
+        },
+        OP_32 => match (funct3, funct7) {
+            (0x1, 0x30) => Some(Rolw),
+            (0x5, 0x30) => Some(Rorw),
+            (0x4, 0x04) => Some(Packw),
+            _ => None,
+        },
+        OP_IMM => match funct3 {
+            0x1 => match imm12 {
+                0x100 => Some(Sha256sum0),
+                0x101 => Some(Sha256sum1),
+                0x102 => Some(Sha256sig0),
+                0x103 => Some(Sha256sig1),
+                0x104 => Some(Sha512sum0),
+                0x105 => Some(Sha512sum1),
+                0x106 => Some(Sha512sig0),
+                0x107 => Some(Sha512sig1),
+                0x108 => Some(Sm3p0),
+                0x109 => Some(Sm3p1),
+                0x300 => Some(Aes64im),
+                _ if imm12 >> 4 == 0x31 => Some(Aes64ks1i(imm12 & 0xF)),
+                _ => None,
+            },
+            0x5 => match imm12 {
+                0x687 => Some(Brev8),
+                0x6B8 => Some(Rev8),
+                _ if imm12 >> 6 == 0x18 => Some(Rori(imm12 & 0x3F)),
+                _ => None,
+            },
+            _ => None,
+        },
+        OP_IMM_32 => match (funct3, funct7) {
+            (0x5, 0x30) => Some(Roriw(imm12 & 0x1F)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub fn execute(op: CryptoOp, rs1: u64, rs2: u64) -> u64 {
+    use CryptoOp::*;
+    match op {
+        Andn => rs1 & !rs2,
+        Orn => rs1 | !rs2,
+        Xnor => !(rs1 ^ rs2),
+        Rol => rs1.rotate_left((rs2 & 0x3F) as u32),
+        Ror => rs1.rotate_right((rs2 & 0x3F) as u32),
+        Rori(shamt) => rs1.rotate_right(shamt),
+        Rolw => sext32((rs1 as u32).rotate_left((rs2 & 0x1F) as u32)),
+        Rorw => sext32((rs1 as u32).rotate_right((rs2 & 0x1F) as u32)),
+        Roriw(shamt) => sext32((rs1 as u32).rotate_right(shamt)),
+        Pack => (rs2 << 32) | (rs1 & 0xFFFF_FFFF),
+        Packh => ((rs2 & 0xFF) << 8) | (rs1 & 0xFF),
+        Packw => sext32((((rs2 & 0xFFFF) << 16) | (rs1 & 0xFFFF)) as u32),
+        Rev8 => rs1.swap_bytes(),
+        Brev8 => brev8(rs1),
+        Clmul => clmul(rs1, rs2) as u64,
+        Clmulh => (clmul(rs1, rs2) >> 64) as u64,
+        Xperm4 => xperm(rs1, rs2, 4),
+        Xperm8 => xperm(rs1, rs2, 8),
+        Aes64es => aes64es(rs1, rs2),
+        Aes64esm => aes64esm(rs1, rs2),
+        Aes64ds => aes64ds(rs1, rs2),
+        Aes64dsm => aes64dsm(rs1, rs2),
+        Aes64im => aes64im(rs1),
+        Aes64ks1i(rnum) => aes64ks1i(rs1, rnum),
+        Aes64ks2 => aes64ks2(rs1, rs2),
+        Sha256sig0 => sha256(rs1, 7, 18, 3, false),
+        Sha256sig1 => sha256(rs1, 17, 19, 10, false),
+        Sha256sum0 => sha256(rs1, 2, 13, 22, true),
+        Sha256sum1 => sha256(rs1, 6, 11, 25, true),
+        Sha512sig0 => rs1.rotate_right(1) ^ rs1.rotate_right(8) ^ (rs1 >> 7),
+        Sha512sig1 => rs1.rotate_right(19) ^ rs1.rotate_right(61) ^ (rs1 >> 6),
+        Sha512sum0 => rs1.rotate_right(28) ^ rs1.rotate_right(34) ^ rs1.rotate_right(39),
+        Sha512sum1 => rs1.rotate_right(14) ^ rs1.rotate_right(18) ^ rs1.rotate_right(41),
+        Sm4ed(bs) => sm4(rs1, rs2, bs, sm4ed_linear),
+        Sm4ks(bs) => sm4(rs1, rs2, bs, sm4ks_linear),
+        Sm3p0 => {
+            let x = rs1 as u32;
+            sext32(x ^ x.rotate_left(9) ^ x.rotate_left(17))
+        }
+        Sm3p1 => {
+            let x = rs1 as u32;
+            sext32(x ^ x.rotate_left(15) ^ x.rotate_left(23))
+        }
+    }
+}
+
+fn sext32(value: u32) -> u64 {
+    value as i32 as i64 as u64
+}
+
+fn brev8(value: u64) -> u64 {
+    u64::from_le_bytes(value.to_le_bytes().map(u8::reverse_bits))
+}
+
+fn clmul(a: u64, b: u64) -> u128 {
+    let mut result = 0u128;
+    for i in 0..64 {
+        if (b >> i) & 1 != 0 {
+            result ^= (a as u128) << i;
+        }
+    }
+    result
+}
+
+/// xperm4/xperm8: rs2의 각 원소를 인덱스로 rs1의 원소를 선택 (범위 밖이면 0)
+fn xperm(rs1: u64, rs2: u64, width: u32) -> u64 {
+    let mask = (1u64 << width) - 1;
+    let count = 64 / width;
+    let mut result = 0;
+    for i in 0..count {
+        let index = (rs2 >> (i * width)) & mask;
+        if index < count as u64 {
+            let element = (rs1 >> (index as u32 * width)) & mask;
+            result |= element << (i * width);
+        }
+    }
+    result
+}
+
+// sum(ror, ror, ror) 또는 sig(ror, ror, shr)
+fn sha256(rs1: u64, a: u32, b: u32, c: u32, sum: bool) -> u64 {
+    let x = rs1 as u32;
+    let last = if sum { x.rotate_right(c) } else { x >> c };
+    sext32(x.rotate_right(a) ^ x.rotate_right(b) ^ last)
+}
+
+// ==================== AES ====================
+
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const AES_INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+// aes64ks1i 라운드 상수
+const AES_RCON: [u8; 11] = [
+    0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36, 0x00,
+];
+
+// 128비트 상태 = rs2(열 2,3) : rs1(열 0,1), 바이트 인덱스 = 4*열 + 행
+// 결과의 하위 64비트(열 0,1)만 계산
+fn shift_rows_fwd(rs1: u64, rs2: u64) -> [u8; 8] {
+    let a = rs1.to_le_bytes();
+    let b = rs2.to_le_bytes();
+    [a[0], a[5], b[2], b[7], a[4], b[1], b[6], a[3]]
+}
+
+fn shift_rows_inv(rs1: u64, rs2: u64) -> [u8; 8] {
+    let a = rs1.to_le_bytes();
+    let b = rs2.to_le_bytes();
+    [a[0], b[5], b[2], a[7], a[4], a[1], b[6], b[3]]
+}
+
+fn xtime(x: u8) -> u8 {
+    (x << 1) ^ if x & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+// GF(2^8) 곱셈
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    result
+}
+
+fn mix_column(col: u32, coeffs: [u8; 4]) -> u32 {
+    let c = col.to_le_bytes();
+    let mut out = [0u8; 4];
+    for (row, byte) in out.iter_mut().enumerate() {
+        for (i, &value) in c.iter().enumerate() {
+            *byte ^= gf_mul(value, coeffs[(i + 4 - row) % 4]);
+        }
+    }
+    u32::from_le_bytes(out)
+}
+
+fn mix_columns(value: u64, coeffs: [u8; 4]) -> u64 {
+    let lo = mix_column(value as u32, coeffs) as u64;
+    let hi = mix_column((value >> 32) as u32, coeffs) as u64;
+    (hi << 32) | lo
+}
+
+const MIX_FWD: [u8; 4] = [0x02, 0x03, 0x01, 0x01];
+const MIX_INV: [u8; 4] = [0x0e, 0x0b, 0x0d, 0x09];
+
+fn sub_bytes(bytes: [u8; 8], sbox: &[u8; 256]) -> u64 {
+    u64::from_le_bytes(bytes.map(|b| sbox[b as usize]))
+}
+
+fn sub_word(word: u32) -> u32 {
+    u32::from_le_bytes(word.to_le_bytes().map(|b| AES_SBOX[b as usize]))
+}
+
+fn aes64es(rs1: u64, rs2: u64) -> u64 {
+    sub_bytes(shift_rows_fwd(rs1, rs2), &AES_SBOX)
+}
+
+fn aes64esm(rs1: u64, rs2: u64) -> u64 {
+    mix_columns(aes64es(rs1, rs2), MIX_FWD)
+}
+
+fn aes64ds(rs1: u64, rs2: u64) -> u64 {
+    sub_bytes(shift_rows_inv(rs1, rs2), &AES_INV_SBOX)
+}
+
+fn aes64dsm(rs1: u64, rs2: u64) -> u64 {
+    mix_columns(aes64ds(rs1, rs2), MIX_INV)
+}
+
+fn aes64im(rs1: u64) -> u64 {
+    mix_columns(rs1, MIX_INV)
+}
+
+fn aes64ks1i(rs1: u64, rnum: u32) -> u64 {
+    let word = (rs1 >> 32) as u32;
+    // 마지막 라운드(rnum=0xA)는 AES-256의 RotWord 없는 단계
+    let rotated = if rnum == AES_MAX_RNUM {
+        word
+    } else {
+        word.rotate_right(8)
+    };
+    let result = (sub_word(rotated) ^ AES_RCON[rnum as usize] as u32) as u64;
+    (result << 32) | result
+}
+
+fn aes64ks2(rs1: u64, rs2: u64) -> u64 {
+    let w0 = ((rs1 >> 32) as u32) ^ (rs2 as u32);
+    let w1 = w0 ^ ((rs2 >> 32) as u32);
+    ((w1 as u64) << 32) | w0 as u64
+}
+
+// ==================== SM4 ====================
+
+const SM4_SBOX: [u8; 256] = [
+    0xd6, 0x90, 0xe9, 0xfe, 0xcc, 0xe1, 0x3d, 0xb7, 0x16, 0xb6, 0x14, 0xc2, 0x28, 0xfb, 0x2c, 0x05,
+    0x2b, 0x67, 0x9a, 0x76, 0x2a, 0xbe, 0x04, 0xc3, 0xaa, 0x44, 0x13, 0x26, 0x49, 0x86, 0x06, 0x99,
+    0x9c, 0x42, 0x50, 0xf4, 0x91, 0xef, 0x98, 0x7a, 0x33, 0x54, 0x0b, 0x43, 0xed, 0xcf, 0xac, 0x62,
+    0xe4, 0xb3, 0x1c, 0xa9, 0xc9, 0x08, 0xe8, 0x95, 0x80, 0xdf, 0x94, 0xfa, 0x75, 0x8f, 0x3f, 0xa6,
+    0x47, 0x07, 0xa7, 0xfc, 0xf3, 0x73, 0x17, 0xba, 0x83, 0x59, 0x3c, 0x19, 0xe6, 0x85, 0x4f, 0xa8,
+    0x68, 0x6b, 0x81, 0xb2, 0x71, 0x64, 0xda, 0x8b, 0xf8, 0xeb, 0x0f, 0x4b, 0x70, 0x56, 0x9d, 0x35,
+    0x1e, 0x24, 0x0e, 0x5e, 0x63, 0x58, 0xd1, 0xa2, 0x25, 0x22, 0x7c, 0x3b, 0x01, 0x21, 0x78, 0x87,
+    0xd4, 0x00, 0x46, 0x57, 0x9f, 0xd3, 0x27, 0x52, 0x4c, 0x36, 0x02, 0xe7, 0xa0, 0xc4, 0xc8, 0x9e,
+    0xea, 0xbf, 0x8a, 0xd2, 0x40, 0xc7, 0x38, 0xb5, 0xa3, 0xf7, 0xf2, 0xce, 0xf9, 0x61, 0x15, 0xa1,
+    0xe0, 0xae, 0x5d, 0xa4, 0x9b, 0x34, 0x1a, 0x55, 0xad, 0x93, 0x32, 0x30, 0xf5, 0x8c, 0xb1, 0xe3,
+    0x1d, 0xf6, 0xe2, 0x2e, 0x82, 0x66, 0xca, 0x60, 0xc0, 0x29, 0x23, 0xab, 0x0d, 0x53, 0x4e, 0x6f,
+    0xd5, 0xdb, 0x37, 0x45, 0xde, 0xfd, 0x8e, 0x2f, 0x03, 0xff, 0x6a, 0x72, 0x6d, 0x6c, 0x5b, 0x51,
+    0x8d, 0x1b, 0xaf, 0x92, 0xbb, 0xdd, 0xbc, 0x7f, 0x11, 0xd9, 0x5c, 0x41, 0x1f, 0x10, 0x5a, 0xd8,
+    0x0a, 0xc1, 0x31, 0x88, 0xa5, 0xcd, 0x7b, 0xbd, 0x2d, 0x74, 0xd0, 0x12, 0xb8, 0xe5, 0xb4, 0xb0,
+    0x89, 0x69, 0x97, 0x4a, 0x0c, 0x96, 0x77, 0x7e, 0x65, 0xb9, 0xf1, 0x09, 0xc5, 0x6e, 0xc6, 0x84,
+    0x18, 0xf0, 0x7d, 0xec, 0x3a, 0xdc, 0x4d, 0x20, 0x79, 0xee, 0x5f, 0x3e, 0xd7, 0xcb, 0x39, 0x48,
+];
+
+fn sm4ed_linear(x: u32) -> u32 {
+    x ^ (x << 8) ^ (x << 2) ^ (x << 18) ^ ((x & 0x3F) << 26) ^ ((x & 0xC0) << 10)
+}
+
+fn sm4ks_linear(x: u32) -> u32 {
+    x ^ ((x & 0x07) << 29) ^ ((x & 0xFE) << 7) ^ ((x & 0x01) << 23) ^ ((x & 0xF8) << 13)
+}
+
+fn sm4(rs1: u64, rs2: u64, bs: u32, linear: fn(u32) -> u32) -> u64 {
+    let shamt = bs * 8;
+    let x = SM4_SBOX[((rs2 >> shamt) & 0xFF) as usize] as u32;
+    let y = linear(x).rotate_left(shamt);
+    sext32(y ^ rs1 as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use CryptoOp::*;
+
+    // ---- Zbkb / Zbkc / Zbkx ----
+    #[test]
+    fn test_logic_with_negate() {
+        assert_eq!(execute(Andn, 0b1100, 0b1010), 0b0100);
+        assert_eq!(execute(Orn, 0, u64::MAX - 1), 1);
+        assert_eq!(execute(Xnor, 0xF0, 0xFF), !0x0F);
+    }
+
+    #[test]
+    fn test_rotates() {
+        assert_eq!(execute(Ror, 0x1, 1), 0x8000_0000_0000_0000);
+        assert_eq!(execute(Rol, 0x8000_0000_0000_0000, 65), 0x1); // shamt는 하위 6비트
+        assert_eq!(execute(Rori(4), 0x12, 0), 0x2000_0000_0000_0001);
+        assert_eq!(execute(Rorw, 0x1, 1), 0xFFFF_FFFF_8000_0000);
+        assert_eq!(execute(Roriw(8), 0x1234_5678, 0), 0x7812_3456);
+    }
+
+    #[test]
+    fn test_pack() {
+        assert_eq!(
+            execute(Pack, 0xAAAA_1111_2222, 0xBBBB_3333_4444),
+            0x3333_4444_1111_2222
+        );
+        assert_eq!(execute(Packh, 0x1234, 0x5678), 0x7834);
+        assert_eq!(execute(Packw, 0x1234, 0x8765), 0xFFFF_FFFF_8765_1234);
+    }
+
+    #[test]
+    fn test_rev8_brev8() {
+        assert_eq!(
+            execute(Rev8, 0x0102_0304_0506_0708, 0),
+            0x0807_0605_0403_0201
+        );
+        assert_eq!(execute(Brev8, 0x0180_0001, 0), 0x8001_0080);
+    }
+
+    #[test]
+    fn test_clmul() {
+        // (x+1)*(x+1) = x^2+1
+        assert_eq!(execute(Clmul, 0b11, 0b11), 0b101);
+        assert_eq!(execute(Clmulh, 1 << 63, 1 << 63), 1 << 62);
+        assert_eq!(execute(Clmulh, 0b11, 0b11), 0);
+    }
+
+    #[test]
+    fn test_xperm() {
+        // 인덱스 0은 rs1의 0번째, 8 이상은 0
+        assert_eq!(
+            execute(Xperm8, 0x8877_6655_4433_2211, 0xFFFF_FFFF_0908_0100),
+            0x2211
+        );
+        assert_eq!(execute(Xperm4, 0xFEDC_BA98_7654_3210, 0x0123), 0x0123);
+    }
+
+    // ---- Zknh / Zksh ----
+    #[test]
+    fn test_sha256_sign_extends() {
+        // 상위 비트가 1이면 sign-extend
+        assert_eq!(execute(Sha256sum0, 2, 0), 0xFFFF_FFFF_8010_0800);
+        assert_eq!(execute(Sha256sig0, 8, 0), 0x1002_0001);
+    }
+
+    #[test]
+    fn test_sha512() {
+        assert_eq!(
+            execute(Sha512sig0, 0x80, 0),
+            0x40 ^ 0x8000_0000_0000_0000 ^ 0x1
+        );
+        assert_eq!(execute(Sha512sum1, 1, 0), (1 << 50) | (1 << 46) | (1 << 23));
+    }
+
+    #[test]
+    fn test_sm3() {
+        assert_eq!(execute(Sm3p0, 1, 0), 1 | (1 << 9) | (1 << 17));
+        assert_eq!(execute(Sm3p1, 1, 0), 1 | (1 << 15) | (1 << 23));
+    }
+
+    // ---- Zkne / Zknd: FIPS-197 AES-128 ----
+    const AES_KEY: [u8; 16] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f,
+        0x3c,
+    ];
+    const AES_PLAIN: [u8; 16] = [
+        0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07,
+        0x34,
+    ];
+    const AES_CIPHER: [u8; 16] = [
+        0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b,
+        0x32,
+    ];
+
+    fn split(bytes: [u8; 16]) -> (u64, u64) {
+        (
+            u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+        )
+    }
+
+    fn aes128_round_keys() -> Vec<(u64, u64)> {
+        let (mut k0, mut k1) = split(AES_KEY);
+        let mut keys = vec![(k0, k1)];
+        for rnum in 0..10 {
+            let t = execute(Aes64ks1i(rnum), k1, 0);
+            k0 = execute(Aes64ks2, t, k0);
+            k1 = execute(Aes64ks2, k0, k1);
+            keys.push((k0, k1));
+        }
+        keys
+    }
+
+    #[test]
+    fn test_aes_key_schedule_round1() {
+        let keys = aes128_round_keys();
+        // a0fafe17 88542cb1 (FIPS-197 Appendix A.1)
+        assert_eq!(keys[1].0, 0xb12c_5488_17fe_faa0);
+    }
+
+    #[test]
+    fn test_aes128_encrypt() {
+        let keys = aes128_round_keys();
+        let (mut s0, mut s1) = split(AES_PLAIN);
+        s0 ^= keys[0].0;
+        s1 ^= keys[0].1;
+        for key in &keys[1..10] {
+            let n0 = execute(Aes64esm, s0, s1);
+            let n1 = execute(Aes64esm, s1, s0);
+            s0 = n0 ^ key.0;
+            s1 = n1 ^ key.1;
+        }
+        let n0 = execute(Aes64es, s0, s1);
+        let n1 = execute(Aes64es, s1, s0);
+        assert_eq!((n0 ^ keys[10].0, n1 ^ keys[10].1), split(AES_CIPHER));
+    }
+
+    #[test]
+    fn test_aes128_decrypt() {
+        let keys = aes128_round_keys();
+        let (mut s0, mut s1) = split(AES_CIPHER);
+        s0 ^= keys[10].0;
+        s1 ^= keys[10].1;
+        for key in keys[1..10].iter().rev() {
+            let n0 = execute(Aes64dsm, s0, s1);
+            let n1 = execute(Aes64dsm, s1, s0);
+            s0 = n0 ^ execute(Aes64im, key.0, 0);
+            s1 = n1 ^ execute(Aes64im, key.1, 0);
+        }
+        let n0 = execute(Aes64ds, s0, s1);
+        let n1 = execute(Aes64ds, s1, s0);
+        assert_eq!((n0 ^ keys[0].0, n1 ^ keys[0].1), split(AES_PLAIN));
+    }
+
+    // ---- Zksed: GB/T 32907 SM4 표준 벡터 ----
+    fn sm4_t(x: u32, op: fn(u32) -> CryptoOp) -> u32 {
+        (0..4).fold(0, |acc, bs| execute(op(bs), acc as u64, x as u64) as u32)
+    }
+
+    #[test]
+    fn test_sm4_encrypt() {
+        let key: [u8; 16] = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54,
+            0x32, 0x10,
+        ];
+        let expected: [u8; 16] = [
+            0x68, 0x1e, 0xdf, 0x34, 0xd2, 0x06, 0x96, 0x5e, 0x86, 0xb3, 0xe9, 0x4f, 0x53, 0x6e,
+            0x42, 0x46,
+        ];
+        const FK: [u32; 4] = [0xa3b1bac6, 0x56aa3350, 0x677d9197, 0xb27022dc];
+        // 레지스터에는 메모리에서 little-endian으로 읽은 워드가 들어감
+        let word = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+
+        let mut k: Vec<u32> = (0..4)
+            .map(|i| word(&key[4 * i..4 * i + 4]) ^ FK[i].swap_bytes())
+            .collect();
+        for i in 0..32 {
+            let ck = u32::from_le_bytes(std::array::from_fn(|j| ((4 * i + j) * 7) as u8));
+            let x = k[i + 1] ^ k[i + 2] ^ k[i + 3] ^ ck;
+            k.push(k[i] ^ sm4_t(x, Sm4ks));
+        }
+
+        let mut x: Vec<u32> = (0..4).map(|i| word(&key[4 * i..4 * i + 4])).collect();
+        for i in 0..32 {
+            let t = x[i + 1] ^ x[i + 2] ^ x[i + 3] ^ k[i + 4];
+            x.push(x[i] ^ sm4_t(t, Sm4ed));
+        }
+        let cipher: Vec<u8> = x[32..36]
+            .iter()
+            .rev()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        assert_eq!(cipher, expected);
+    }
+
+    // ---- decode ----
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(0x4020F1B3), Some(Andn)); // andn x3, x1, x2
+        assert_eq!(decode(0x0A2091B3), Some(Clmul)); // clmul x3, x1, x2
+        assert_eq!(decode(0x0620F1B3), None); // funct7 0x03은 정의되지 않음
+        assert_eq!(decode(0x6870D193), Some(Brev8)); // brev8 x3, x1
+        assert_eq!(decode(0x6B80D193), Some(Rev8)); // rev8 x3, x1
+        assert_eq!(decode(0x6040D193), Some(Rori(4))); // rori x3, x1, 4
+        assert_eq!(decode(0x31A09193), Some(Aes64ks1i(0xA))); // aes64ks1i x3, x1, 10
+        assert_eq!(decode(0x10209193), Some(Sha256sig0)); // sha256sig0 x3, x1
+        assert_eq!(decode(0xF02081B3), Some(Sm4ed(3))); // sm4ed x3, x1, x2, 3
+        assert_eq!(decode(0x00209193), None); // slli x3, x1, 2
+        assert_eq!(decode(0x002081B3), None); // add x3, x1, x2
+    }
+
+    #[test]
+    fn test_enabled_by() {
+        let mut ext = Extensions::none();
+        assert!(!Aes64ks2.enabled_by(&ext));
+        ext.zknd = true;
+        assert!(Aes64ks2.enabled_by(&ext));
+        assert!(Aes64im.enabled_by(&ext));
+        assert!(!Aes64es.enabled_by(&ext));
+    }
+}
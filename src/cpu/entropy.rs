@@ -0,0 +1,68 @@
+//! Zkr seed CSR의 엔트로피 소스
+//! 재현 가능한 테스트를 위해 시드를 지정할 수 있는 결정적 RNG (SplitMix64)
+
+pub const DEFAULT_SEED: u64 = 0x5EED_5EED_5EED_5EED;
+
+// seed CSR 필드
+pub const SEED_OPST_ES16: u64 = 0b10 << 30;
+pub const SEED_ENTROPY_MASK: u64 = 0xFFFF;
+
+#[derive(Debug, Clone)]
+pub struct EntropySource {
+    state: u64,
+}
+
+impl EntropySource {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// seed CSR 읽기 값: 항상 ES16 상태 + 16비트 엔트로피
+    pub fn read_seed(&mut self) -> u64 {
+        SEED_OPST_ES16 | (self.next_u64() & SEED_ENTROPY_MASK)
+    }
+}
+
+impl Default for EntropySource {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = EntropySource::new(42);
+        let mut b = EntropySource::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_different_seed_different_sequence() {
+        let mut a = EntropySource::new(1);
+        let mut b = EntropySource::new(2);
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn test_read_seed_format() {
+        let mut source = EntropySource::default();
+        for _ in 0..10 {
+            let value = source.read_seed();
+            assert_eq!(value & !SEED_ENTROPY_MASK, SEED_OPST_ES16);
+        }
+    }
+}
@@ -0,0 +1,482 @@
+/// 선택 가능한 ISA 확장 목록
+/// 꺼진 확장의 명령어는 illegal instruction 예외를 발생시킴
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extensions {
+    // 원자 연산 (Zacas, Zabha)
+    pub zacas: bool,
+    pub zabha: bool,
+    // RVA23 필수 확장 (Zicond, Zimop, Zawrs)
+    pub zicond: bool,
+    pub zimop: bool,
+    pub zawrs: bool,
+    // 스칼라 암호 확장 (Zk)
+    pub zbkb: bool,
+    pub zbkc: bool,
+    pub zbkx: bool,
+    pub zknd: bool,
+    pub zkne: bool,
+    pub zknh: bool,
+    pub zksed: bool,
+    pub zksh: bool,
+    pub zkr: bool,
+    // 단정밀도 / 배정밀도 부동소수점 (misa.F, misa.D)
+    pub f: bool,
+    pub d: bool,
+    // 반정밀도 / bfloat16 부동소수점
+    pub zfh: bool,
+    pub zfhmin: bool,
+    pub zfbfmin: bool,
+    // 압축 명령어 (Zc)
+    pub zca: bool,
+    pub zcb: bool,
+    pub zcd: bool,
+    pub zcmp: bool,
+    pub zcmt: bool,
+    pub zcmop: bool,
+}
+
+impl Extensions {
+    /// 선택 가능한 확장을 모두 끈 상태
+    pub fn none() -> Self {
+        Self {
+            zacas: false,
+            zabha: false,
+            zicond: false,
+            zimop: false,
+            zawrs: false,
+            zbkb: false,
+            zbkc: false,
+            zbkx: false,
+            zknd: false,
+            zkne: false,
+            zknh: false,
+            zksed: false,
+            zksh: false,
+            zkr: false,
+            f: false,
+            d: false,
+            zfh: false,
+            zfhmin: false,
+            zfbfmin: false,
+            zca: false,
+            zcb: false,
+            zcd: false,
+            zcmp: false,
+            zcmt: false,
+            zcmop: false,
+        }
+    }
+
+    /// ISA 문자열로 확장 선택 (-march 형식 "rv32imac_zcb_zcmp",
+    /// .riscv.attributes 형식 "rv32i2p1_m2p0_c2p0_zcb1p0" 모두 가능)
+    /// 문자열에 없는 선택 가능 확장은 꺼짐. XLEN은 ELF class를 따르므로 rv32/rv64 접두사는 확인만 함
+    /// C는 Zca이고, D와 함께면 Zcd도 포함 (Zcf는 지원하지 않으므로 무시)
+    pub fn from_isa(isa: &str) -> Result<Self, ExtensionError> {
+        let lower = isa.to_ascii_lowercase();
+        let invalid = || ExtensionError::InvalidIsa(isa.to_string());
+        let rest = lower
+            .strip_prefix("rv32")
+            .or_else(|| lower.strip_prefix("rv64"))
+            .ok_or_else(invalid)?;
+        let mut ext = Self::none();
+        let mut c = false;
+        for (index, token) in rest.split('_').enumerate() {
+            if token.is_empty() {
+                return Err(invalid());
+            }
+            if index > 0 && token.starts_with(['z', 's', 'x']) {
+                ext.enable(strip_version(token))?;
+                continue;
+            }
+            // 한 글자 확장. 각각 뒤에 버전이 붙을 수 있음 (i2p1)
+            let mut letters = token.chars().peekable();
+            while let Some(letter) = letters.next() {
+                match letter {
+                    // I/M/A는 항상 구현됨
+                    'i' | 'm' | 'a' => {}
+                    'f' => ext.f = true,
+                    'd' => ext.d = true,
+                    'g' => {
+                        ext.f = true;
+                        ext.d = true;
+                    }
+                    'c' => c = true,
+                    _ => return Err(ExtensionError::Unsupported(letter.to_string())),
+                }
+                let mut versioned = false;
+                while letters.next_if(char::is_ascii_digit).is_some() {
+                    versioned = true;
+                }
+                if versioned && letters.next_if_eq(&'p').is_some() {
+                    while letters.next_if(char::is_ascii_digit).is_some() {}
+                }
+            }
+        }
+        if c {
+            ext.zca = true;
+            ext.zcd |= ext.d;
+        }
+        ext.validate()?;
+        Ok(ext)
+    }
+
+    fn enable(&mut self, name: &str) -> Result<(), ExtensionError> {
+        match name {
+            // 항상 구현된 확장
+            "zicsr" | "zifencei" | "zicntr" | "zihintpause" | "zmmul" | "zaamo" | "zalrsc" => {}
+            "zacas" => self.zacas = true,
+            "zabha" => self.zabha = true,
+            "zicond" => self.zicond = true,
+            "zimop" => self.zimop = true,
+            "zawrs" => self.zawrs = true,
+            "zbkb" => self.zbkb = true,
+            "zbkc" => self.zbkc = true,
+            "zbkx" => self.zbkx = true,
+            "zknd" => self.zknd = true,
+            "zkne" => self.zkne = true,
+            "zknh" => self.zknh = true,
+            "zksed" => self.zksed = true,
+            "zksh" => self.zksh = true,
+            "zkr" => self.zkr = true,
+            "zkn" => {
+                (self.zbkb, self.zbkc, self.zbkx) = (true, true, true);
+                (self.zkne, self.zknd, self.zknh) = (true, true, true);
+            }
+            "zks" => {
+                (self.zbkb, self.zbkc, self.zbkx) = (true, true, true);
+                (self.zksed, self.zksh) = (true, true);
+            }
+            "zfh" => self.zfh = true,
+            "zfhmin" => self.zfhmin = true,
+            "zfbfmin" => self.zfbfmin = true,
+            "zca" => self.zca = true,
+            "zcb" => self.zcb = true,
+            "zcd" => self.zcd = true,
+            "zcmp" => self.zcmp = true,
+            "zcmt" => self.zcmt = true,
+            "zcmop" => self.zcmop = true,
+            "zce" => {
+                (self.zca, self.zcb) = (true, true);
+                (self.zcmp, self.zcmt) = (true, true);
+            }
+            _ => return Err(ExtensionError::Unsupported(name.to_string())),
+        }
+        Ok(())
+    }
+
+    pub fn has(&self, ext: Extension) -> bool {
+        match ext {
+            Extension::Zacas => self.zacas,
+            Extension::Zabha => self.zabha,
+            Extension::Zicond => self.zicond,
+            Extension::Zimop => self.zimop,
+            Extension::Zawrs => self.zawrs,
+            Extension::Zbkb => self.zbkb,
+            Extension::Zbkc => self.zbkc,
+            Extension::Zbkx => self.zbkx,
+            Extension::Zknd => self.zknd,
+            Extension::Zkne => self.zkne,
+            Extension::Zknh => self.zknh,
+            Extension::Zksed => self.zksed,
+            Extension::Zksh => self.zksh,
+            Extension::Zkr => self.zkr,
+            Extension::F => self.f,
+            Extension::D => self.d,
+            Extension::Zfh => self.zfh,
+            // Zfh는 Zfhmin을 포함
+            Extension::Zfhmin => self.zfh || self.zfhmin,
+            Extension::Zfbfmin => self.zfbfmin,
+            Extension::Zca => self.zca,
+            Extension::Zcb => self.zcb,
+            Extension::Zcd => self.zcd,
+            Extension::Zcmp => self.zcmp,
+            Extension::Zcmt => self.zcmt,
+            Extension::Zcmop => self.zcmop,
+        }
+    }
+
+    /// 확장 조합 검증
+    /// Zcmp/Zcmt는 C.FSDSP 계열 인코딩을 재사용하므로 Zcd와 함께 켤 수 없음
+    /// c.mop.n은 mop.r.n으로 정의되므로 Zcmop은 Zimop이 필요
+    /// D와 반정밀도/bf16 확장은 F 레지스터 파일과 fcsr을 쓰므로 F가 필요, Zcd는 D가 필요
+    pub fn validate(&self) -> Result<(), ExtensionError> {
+        const F_DEPENDENTS: [Extension; 4] = [
+            Extension::D,
+            Extension::Zfh,
+            Extension::Zfhmin,
+            Extension::Zfbfmin,
+        ];
+        for ext in F_DEPENDENTS {
+            if self.has(ext) && !self.f {
+                return Err(ExtensionError::Requires(ext, Extension::F));
+            }
+        }
+        // C.FLD/C.FSD 계열은 FLD/FSD로 확장됨
+        if self.zcd && !self.d {
+            return Err(ExtensionError::Requires(Extension::Zcd, Extension::D));
+        }
+        const ZCA_DEPENDENTS: [Extension; 5] = [
+            Extension::Zcb,
+            Extension::Zcd,
+            Extension::Zcmp,
+            Extension::Zcmt,
+            Extension::Zcmop,
+        ];
+        for ext in ZCA_DEPENDENTS {
+            if self.has(ext) && !self.zca {
+                return Err(ExtensionError::Requires(ext, Extension::Zca));
+            }
+        }
+        if self.zcmop && !self.zimop {
+            return Err(ExtensionError::Requires(Extension::Zcmop, Extension::Zimop));
+        }
+        for ext in [Extension::Zcmp, Extension::Zcmt] {
+            if self.has(ext) && self.zcd {
+                return Err(ExtensionError::Conflict(Extension::Zcd, ext));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Extensions {
+    /// 기본값: 모두 켜짐 (단, Zcd와 겹치는 Zcmp/Zcmt는 꺼짐)
+    fn default() -> Self {
+        Self {
+            zacas: true,
+            zabha: true,
+            zicond: true,
+            zimop: true,
+            zawrs: true,
+            zbkb: true,
+            zbkc: true,
+            zbkx: true,
+            zknd: true,
+            zkne: true,
+            zknh: true,
+            zksed: true,
+            zksh: true,
+            zkr: true,
+            f: true,
+            d: true,
+            zfh: true,
+            zfhmin: true,
+            zfbfmin: true,
+            zca: true,
+            zcb: true,
+            zcd: true,
+            zcmp: false,
+            zcmt: false,
+            zcmop: true,
+        }
+    }
+}
+
+// 여러 글자 확장 뒤의 버전 제거 ("zcb1p0" → "zcb")
+fn strip_version(name: &str) -> &str {
+    let name = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    match name.strip_suffix('p') {
+        Some(major) if major.ends_with(|c: char| c.is_ascii_digit()) => {
+            major.trim_end_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => name,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Extension {
+    Zacas,
+    Zabha,
+    Zicond,
+    Zimop,
+    Zawrs,
+    Zbkb,
+    Zbkc,
+    Zbkx,
+    Zknd,
+    Zkne,
+    Zknh,
+    Zksed,
+    Zksh,
+    Zkr,
+    F,
+    D,
+    Zfh,
+    Zfhmin,
+    Zfbfmin,
+    Zca,
+    Zcb,
+    Zcd,
+    Zcmp,
+    Zcmt,
+    Zcmop,
+}
+
+/// 잘못된 확장 조합 또는 ISA 문자열
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionError {
+    /// 두 확장이 같은 인코딩 공간을 사용함
+    Conflict(Extension, Extension),
+    /// 앞의 확장이 뒤의 확장을 필요로 함
+    Requires(Extension, Extension),
+    /// rv32/rv64로 시작하지 않는 ISA 문자열
+    InvalidIsa(String),
+    /// 구현되지 않은 확장
+    Unsupported(String),
+}
+
+impl std::fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtensionError::Conflict(a, b) => {
+                write!(f, "{:?} and {:?} are mutually exclusive", a, b)
+            }
+            ExtensionError::Requires(a, b) => write!(f, "{:?} requires {:?}", a, b),
+            ExtensionError::InvalidIsa(isa) => write!(f, "invalid ISA string: {}", isa),
+            ExtensionError::Unsupported(name) => write!(f, "unsupported extension: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ExtensionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_valid() {
+        assert_eq!(Extensions::default().validate(), Ok(()));
+        assert_eq!(Extensions::none().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_zcd_conflicts_with_zcmp_zcmt() {
+        let ext = Extensions {
+            zcmp: true,
+            ..Extensions::default()
+        };
+        assert_eq!(
+            ext.validate(),
+            Err(ExtensionError::Conflict(Extension::Zcd, Extension::Zcmp))
+        );
+        let ext = Extensions {
+            zcmt: true,
+            ..Extensions::default()
+        };
+        assert!(ext.validate().is_err());
+        let ext = Extensions {
+            zcd: false,
+            zcmp: true,
+            zcmt: true,
+            ..Extensions::default()
+        };
+        assert_eq!(ext.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_fp_extensions_require_f() {
+        let ext = Extensions {
+            f: false,
+            ..Extensions::default()
+        };
+        assert_eq!(
+            ext.validate(),
+            Err(ExtensionError::Requires(Extension::D, Extension::F))
+        );
+        let ext = Extensions {
+            f: false,
+            d: false,
+            zcd: false,
+            zfh: false,
+            zfhmin: false,
+            ..Extensions::default()
+        };
+        assert_eq!(
+            ext.validate(),
+            Err(ExtensionError::Requires(Extension::Zfbfmin, Extension::F))
+        );
+    }
+
+    #[test]
+    fn test_zcmop_requires_zimop() {
+        let ext = Extensions {
+            zimop: false,
+            ..Extensions::default()
+        };
+        assert_eq!(
+            ext.validate(),
+            Err(ExtensionError::Requires(Extension::Zcmop, Extension::Zimop))
+        );
+        let ext = Extensions {
+            zimop: false,
+            zcmop: false,
+            ..Extensions::default()
+        };
+        assert_eq!(ext.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_from_isa_march() {
+        let ext = Extensions::from_isa("rv32imac_zcb_zcmp").unwrap();
+        assert_eq!(
+            ext,
+            Extensions {
+                zca: true,
+                zcb: true,
+                zcmp: true,
+                ..Extensions::none()
+            }
+        );
+        // G = IMAFD_Zicsr_Zifencei, C + D면 Zcd
+        let ext = Extensions::from_isa("RV64GC").unwrap();
+        assert!(ext.f && ext.d && ext.zca && ext.zcd);
+        assert!(!ext.zcb && !ext.zacas);
+        let ext = Extensions::from_isa("rv64imac_zkn_zicond").unwrap();
+        assert!(ext.zbkb && ext.zkne && ext.zknh && ext.zicond);
+        assert!(!ext.zksed && !ext.f);
+    }
+
+    #[test]
+    fn test_from_isa_attributes() {
+        let ext = Extensions::from_isa(
+            "rv32i2p1_m2p0_a2p1_c2p0_zicsr2p0_zmmul1p0_zaamo1p0_zalrsc1p0_zca1p0_zcb1p0_zcmp1p0",
+        )
+        .unwrap();
+        assert!(ext.zca && ext.zcb && ext.zcmp);
+        assert!(!ext.zcd && !ext.zcmt);
+    }
+
+    #[test]
+    fn test_from_isa_errors() {
+        assert_eq!(
+            Extensions::from_isa("imac"),
+            Err(ExtensionError::InvalidIsa("imac".to_string()))
+        );
+        assert_eq!(
+            Extensions::from_isa("rv64gcv"),
+            Err(ExtensionError::Unsupported("v".to_string()))
+        );
+        assert_eq!(
+            Extensions::from_isa("rv64imac_zba"),
+            Err(ExtensionError::Unsupported("zba".to_string()))
+        );
+        // C + D는 Zcd를 포함하므로 Zcmp와 함께 쓸 수 없음
+        assert_eq!(
+            Extensions::from_isa("rv64gc_zcmp"),
+            Err(ExtensionError::Conflict(Extension::Zcd, Extension::Zcmp))
+        );
+    }
+
+    #[test]
+    fn test_zc_requires_zca() {
+        let ext = Extensions {
+            zca: false,
+            ..Extensions::default()
+        };
+        assert_eq!(
+            ext.validate(),
+            Err(ExtensionError::Requires(Extension::Zcb, Extension::Zca))
+        );
+    }
+}
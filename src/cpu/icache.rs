@@ -0,0 +1,183 @@
+//! 디코딩된 명령어 캐시
+//! 물리 주소 4KiB 페이지 단위로 2바이트 간격 슬롯에 디코딩 결과를 보관
+//! 페이지 무효화는 Bus가 코드 페이지에 대한 쓰기를 알려줄 때와 FENCE.I에서 수행
+
+use std::collections::HashMap;
+
+use crate::cpu::Xlen;
+use crate::cpu::compressed::CompressedOp;
+use crate::cpu::extensions::Extensions;
+use crate::decoder::{DecodeError, Instruction};
+
+pub const PAGE_SHIFT: u64 = 12;
+pub const PAGE_SIZE: u64 = 1 << PAGE_SHIFT;
+
+// 압축 명령어 때문에 2바이트마다 슬롯 하나
+const SLOTS_PER_PAGE: usize = (PAGE_SIZE / 2) as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecodedOp {
+    /// 32비트 명령어 또는 32비트로 확장된 압축 명령어
+    /// inst는 확장된 인코딩 (예외 시 mtval에 기록)
+    Base {
+        inst: u32,
+        decoded: Result<Instruction, DecodeError>,
+    },
+    /// 32비트로 확장할 수 없는 압축 명령어 (None이면 illegal)
+    Compressed(Option<CompressedOp>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodedInst {
+    /// 메모리에서 읽은 원본 인코딩 (압축 명령어는 하위 16비트)
+    pub raw: u32,
+    /// 명령어 길이 (2 또는 4)
+    pub len: u64,
+    pub op: DecodedOp,
+}
+
+pub struct DecodeCache {
+    pages: HashMap<u64, Box<[Option<DecodedInst>]>>,
+    // 캐시된 디코딩 결과가 전제하는 XLEN과 확장 조합
+    xlen: Xlen,
+    extensions: Extensions,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecodeCache {
+    pub fn new() -> Self {
+        DecodeCache {
+            pages: HashMap::new(),
+            xlen: Xlen::Rv64,
+            extensions: Extensions::default(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn slot(addr: u64) -> (u64, usize) {
+        (addr >> PAGE_SHIFT, ((addr & (PAGE_SIZE - 1)) >> 1) as usize)
+    }
+
+    /// XLEN이나 확장 조합이 바뀌면 디코딩 결과가 달라지므로 전체 무효화
+    /// 무효화했으면 true
+    pub fn configure(&mut self, xlen: Xlen, extensions: Extensions) -> bool {
+        if self.xlen == xlen && self.extensions == extensions {
+            return false;
+        }
+        self.flush();
+        self.xlen = xlen;
+        self.extensions = extensions;
+        true
+    }
+
+    pub fn get(&mut self, addr: u64) -> Option<DecodedInst> {
+        let (page, index) = Self::slot(addr);
+        let entry = self.pages.get(&page).and_then(|slots| slots[index]);
+        match entry {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        entry
+    }
+
+    pub fn insert(&mut self, addr: u64, inst: DecodedInst) {
+        let (page, index) = Self::slot(addr);
+        self.pages
+            .entry(page)
+            .or_insert_with(|| vec![None; SLOTS_PER_PAGE].into_boxed_slice())[index] = Some(inst);
+    }
+
+    /// 페이지 번호(addr >> PAGE_SHIFT)의 디코딩 결과 제거
+    /// 이전 페이지 끝에서 시작해 이 페이지로 걸친 32비트 명령어도 함께 제거
+    pub fn invalidate_page(&mut self, page: u64) {
+        self.pages.remove(&page);
+        if let Some(slots) = page
+            .checked_sub(1)
+            .and_then(|prev| self.pages.get_mut(&prev))
+        {
+            slots[SLOTS_PER_PAGE - 1] = None;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.pages.clear();
+    }
+
+    /// 캐시된 명령어 수
+    pub fn len(&self) -> usize {
+        self.pages
+            .values()
+            .map(|slots| slots.iter().filter(|slot| slot.is_some()).count())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nop() -> DecodedInst {
+        DecodedInst {
+            raw: 0x13,
+            len: 4,
+            op: DecodedOp::Base {
+                inst: 0x13,
+                decoded: crate::decoder::decode(0x13),
+            },
+        }
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut cache = DecodeCache::new();
+        assert_eq!(cache.get(0x80000000), None);
+        cache.insert(0x80000000, nop());
+        assert_eq!(cache.get(0x80000000), Some(nop()));
+        assert_eq!(cache.get(0x80000002), None);
+        assert_eq!(cache.hits, 1);
+        assert_eq!(cache.misses, 2);
+    }
+
+    #[test]
+    fn test_invalidate_page() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x80000000, nop());
+        cache.insert(0x80001000, nop());
+        cache.invalidate_page(0x80000000 >> PAGE_SHIFT);
+        assert_eq!(cache.get(0x80000000), None);
+        assert_eq!(cache.get(0x80001000), Some(nop()));
+    }
+
+    #[test]
+    fn test_invalidate_page_drops_straddling_instruction() {
+        let mut cache = DecodeCache::new();
+        // 0x80000FFE에서 시작하는 32비트 명령어는 다음 페이지에 걸침
+        cache.insert(0x80000FFE, nop());
+        cache.insert(0x80000FFC, nop());
+        cache.invalidate_page(0x80001000 >> PAGE_SHIFT);
+        assert_eq!(cache.get(0x80000FFE), None);
+        assert_eq!(cache.get(0x80000FFC), Some(nop()));
+    }
+
+    #[test]
+    fn test_configure_flushes_on_change() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x80000000, nop());
+        assert!(!cache.configure(Xlen::Rv64, Extensions::default()));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.configure(Xlen::Rv32, Extensions::default()));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,897 @@
+//! x86-64 JIT (cargo feature "jit")
+//! 자주 실행되는 기본 블록을 정수 연산, 로드/스토어, 블록 끝의 분기/JAL까지 네이티브 코드로 번역
+//! 번역하지 않는 명령어(CSR, AMO, 부동소수점 등)를 만나면 거기서부터 인터프리터가 이어서 실행
+//! 로드/스토어는 정렬된 DRAM 접근만 직접 처리하고, 장치 주소, misaligned, 코드 페이지 쓰기,
+//! write buffer나 빅엔디언 모드처럼 Bus를 거쳐야 하는 접근은 그 명령어 앞에서 빠져나가
+//! 인터프리터가 실행하므로 네이티브 코드는 trap을 일으키지 않음
+//! 생성된 코드는 블록에 붙어 있어서 블록이 무효화(코드 쓰기, FENCE.I)되면 함께 버려짐
+
+#[cfg(not(all(target_arch = "x86_64", target_os = "linux")))]
+compile_error!("the jit feature requires x86_64 Linux");
+
+use std::ffi::c_void;
+
+use crate::cpu::block::MAX_BLOCK_INSTS;
+use crate::cpu::icache::{DecodedInst, DecodedOp, PAGE_SHIFT};
+use crate::decoder::{AluOp, BranchOp, Instruction, LoadOp, Width};
+use crate::devices::DRAM_BASE;
+use crate::devices::memory::{GRANULE_SHIFT, GRANULES};
+
+/// 블록이 이 횟수만큼 실행되면 번역
+pub const DEFAULT_THRESHOLD: u32 = 16;
+
+// 코드 영역 크기. 가득 차면 블록 캐시와 함께 비움
+const CODE_SIZE: usize = 32 << 20;
+// 명령어 하나가 생성하는 최대 바이트 수 (스토어 검사와 빠져나가는 코드 포함)
+const MAX_INST_BYTES: usize = 160;
+
+const PROT_READ: i32 = 1;
+const PROT_WRITE: i32 = 2;
+const PROT_EXEC: i32 = 4;
+const MAP_PRIVATE: i32 = 0x02;
+const MAP_ANONYMOUS: i32 = 0x20;
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+unsafe extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, off: i64)
+    -> *mut c_void;
+    fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> i32;
+    fn munmap(addr: *mut c_void, len: usize) -> i32;
+}
+
+type NativeFn = unsafe extern "sysv64" fn(regs: *mut u64, context: *mut NativeContext) -> u64;
+
+/// 번역된 코드가 쓰는 실행 환경 (필드 순서는 CONTEXT_* 오프셋과 같음)
+#[repr(C)]
+pub struct NativeContext {
+    /// 번역된 코드가 끝난 뒤 이어서 실행할 pc (번역된 코드가 씀)
+    pub pc: u64,
+    /// DRAM 시작의 호스트 주소
+    pub ram: *mut u8,
+    /// 로드가 직접 읽을 수 있는 DRAM 크기. 0이면 모든 로드를 인터프리터에 넘김
+    pub load_limit: u64,
+    /// 스토어가 직접 쓸 수 있는 DRAM 크기. 0이면 모든 스토어를 인터프리터에 넘김
+    pub store_limit: u64,
+    /// 그래뉼 store 순번 테이블 (devices::Memory)
+    pub granules: *mut u64,
+    /// 코드 페이지 비트맵 (Bus). 표시된 페이지에 대한 스토어는 인터프리터가 실행
+    pub code_pages: *const u64,
+}
+
+/// 번역된 블록 (앞부분 또는 끝의 분기까지 전체)
+#[derive(Clone, Copy)]
+pub struct NativeBlock {
+    entry: NativeFn,
+    /// 번역된 명령어 수
+    pub insts: usize,
+}
+
+impl NativeBlock {
+    /// 실행한 명령어 수를 반환하고 다음 pc를 context.pc에 남김
+    /// 번역된 명령어를 모두 실행하기 전에 빠져나오면 context.pc는 실행하지 않은 첫 명령어
+    pub fn call(&self, regs: &mut [u64; 32], context: &mut NativeContext) -> usize {
+        // SAFETY: entry는 Jit::compile이 생성한 코드로 regs[1..32], context.pc, 그리고
+        // context가 가리키는 DRAM/순번 테이블의 범위 검사를 통과한 위치만 읽고 쓰고 ret함.
+        // 코드 영역은 이 블록이 살아 있는 동안 해제되거나 덮어써지지 않음
+        // (Jit::flush는 블록 캐시 flush와 함께만 호출)
+        unsafe { (self.entry)(regs.as_mut_ptr(), context) as usize }
+    }
+}
+
+// W^X: 쓰는 동안만 쓰기 가능, 그 외에는 읽기/실행만
+struct CodeBuffer {
+    base: *mut u8,
+    capacity: usize,
+    used: usize,
+}
+
+// SAFETY: 매핑은 이 버퍼(를 가진 hart)만 쓰고 해제함. 병렬 실행에서 hart와 함께 스레드로 옮겨짐
+unsafe impl Send for CodeBuffer {}
+
+impl CodeBuffer {
+    fn new(capacity: usize) -> Self {
+        // SAFETY: 새 익명 매핑을 요청할 뿐 기존 메모리에 영향 없음
+        let base = unsafe {
+            mmap(
+                std::ptr::null_mut(),
+                capacity,
+                PROT_READ | PROT_EXEC,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if base == MAP_FAILED {
+            // 매핑 실패 시 번역 없이 인터프리터로만 실행
+            return CodeBuffer {
+                base: std::ptr::null_mut(),
+                capacity: 0,
+                used: 0,
+            };
+        }
+        CodeBuffer {
+            base: base as *mut u8,
+            capacity,
+            used: 0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.capacity - self.used
+    }
+
+    fn push(&mut self, code: &[u8]) -> Option<*const u8> {
+        if code.len() > self.remaining() {
+            return None;
+        }
+        // SAFETY: [base, base+capacity)는 이 버퍼가 소유한 매핑이고 범위를 위에서 확인함
+        unsafe {
+            let ptr = self.base.add(self.used);
+            if mprotect(
+                self.base as *mut c_void,
+                self.capacity,
+                PROT_READ | PROT_WRITE,
+            ) != 0
+            {
+                return None;
+            }
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr, code.len());
+            let protected = mprotect(
+                self.base as *mut c_void,
+                self.capacity,
+                PROT_READ | PROT_EXEC,
+            ) == 0;
+            assert!(protected, "mprotect failed on JIT code buffer");
+            self.used += code.len();
+            Some(ptr)
+        }
+    }
+}
+
+impl Drop for CodeBuffer {
+    fn drop(&mut self) {
+        if !self.base.is_null() {
+            // SAFETY: new()에서 얻은 매핑을 한 번만 해제
+            unsafe {
+                munmap(self.base as *mut c_void, self.capacity);
+            }
+        }
+    }
+}
+
+pub struct Jit {
+    code: CodeBuffer,
+    /// 번역을 시도할 블록 실행 횟수
+    pub threshold: u32,
+    pub compiled: u64,
+    pub native_runs: u64,
+}
+
+impl Default for Jit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Jit {
+    pub fn new() -> Self {
+        Jit {
+            code: CodeBuffer::new(CODE_SIZE),
+            threshold: DEFAULT_THRESHOLD,
+            compiled: 0,
+            native_runs: 0,
+        }
+    }
+
+    /// 블록 하나를 더 번역할 공간이 없음 (블록 캐시와 함께 flush 필요)
+    pub fn is_full(&self) -> bool {
+        self.code.capacity != 0 && self.code.remaining() < MAX_BLOCK_INSTS * MAX_INST_BYTES + 1
+    }
+
+    /// 생성한 코드를 모두 버림. 이 코드를 가리키는 NativeBlock이 남아 있으면 안 됨
+    pub fn flush(&mut self) {
+        self.code.used = 0;
+    }
+
+    /// pc에서 시작하는 블록을 번역할 수 없는 첫 명령어 앞까지, 또는 끝의 분기/JAL까지 번역
+    /// 번역할 수 있는 명령어가 없으면 None
+    pub fn compile(&mut self, pc: u64, insts: &[DecodedInst]) -> Option<NativeBlock> {
+        let mut emitter = Emitter::default();
+        let mut addr = pc;
+        let mut count = 0;
+        let mut terminated = false;
+        for inst in insts {
+            let DecodedOp::Base {
+                decoded: Ok(instruction),
+                ..
+            } = inst.op
+            else {
+                break;
+            };
+            if !emitter.instruction(instruction, addr, inst.len, count) {
+                break;
+            }
+            addr += inst.len;
+            count += 1;
+            if matches!(
+                instruction,
+                Instruction::Branch { .. } | Instruction::Jal { .. }
+            ) {
+                terminated = true;
+                break;
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+        if !terminated {
+            emitter.set_pc(addr);
+        }
+        emitter.ret(count);
+        emitter.exits();
+        let entry = self.code.push(&emitter.code)?;
+        self.compiled += 1;
+        Some(NativeBlock {
+            // SAFETY: entry는 방금 기록한 sysv64 함수의 시작 주소
+            entry: unsafe { std::mem::transmute::<*const u8, NativeFn>(entry) },
+            insts: count,
+        })
+    }
+}
+
+// 호스트 레지스터: rdi = &regs[0], rsi = &NativeContext, rax/rcx/rdx = 작업용
+const RAX: u8 = 0;
+const RCX: u8 = 1;
+
+// NativeContext 필드 오프셋
+const CONTEXT_RAM: u8 = 8;
+const CONTEXT_LOAD_LIMIT: u8 = 16;
+const CONTEXT_STORE_LIMIT: u8 = 24;
+const CONTEXT_GRANULES: u8 = 32;
+const CONTEXT_CODE_PAGES: u8 = 40;
+
+// x86 조건 코드 (jcc = 0x0F 0x80|cc, cmovcc = 0x0F 0x40|cc)
+const CC_B: u8 = 0x2;
+const CC_AE: u8 = 0x3;
+const CC_E: u8 = 0x4;
+const CC_NE: u8 = 0x5;
+const CC_L: u8 = 0xC;
+const CC_GE: u8 = 0xD;
+
+#[derive(Default)]
+struct Emitter {
+    code: Vec<u8>,
+    // 인터프리터로 빠져나가는 jcc rel32의 위치와 (명령어 번호, pc)
+    exits: Vec<(usize, usize, u64)>,
+    // 번역 중인 명령어의 번호와 pc
+    index: usize,
+    pc: u64,
+}
+
+impl Emitter {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.code.extend_from_slice(bytes);
+    }
+
+    fn disp(reg: usize) -> [u8; 4] {
+        ((reg * 8) as u32).to_le_bytes()
+    }
+
+    /// host = regs[guest] (x0은 0)
+    fn load(&mut self, host: u8, guest: usize) {
+        if guest == 0 {
+            // xor r32, r32
+            self.bytes(&[0x31, 0xC0 | (host << 3) | host]);
+        } else {
+            // mov r64, [rdi + disp32]
+            self.bytes(&[0x48, 0x8B, 0x87 | (host << 3)]);
+            self.bytes(&Self::disp(guest));
+        }
+    }
+
+    /// regs[guest] = rax (x0은 버림)
+    fn store(&mut self, guest: usize) {
+        if guest != 0 {
+            // mov [rdi + disp32], rax
+            self.bytes(&[0x48, 0x89, 0x87]);
+            self.bytes(&Self::disp(guest));
+        }
+    }
+
+    fn mov_imm(&mut self, host: u8, imm: i64) {
+        match i32::try_from(imm) {
+            // mov r64, imm32 (부호 확장)
+            Ok(imm) => {
+                self.bytes(&[0x48, 0xC7, 0xC0 | host]);
+                self.bytes(&imm.to_le_bytes());
+            }
+            // movabs r64, imm64
+            Err(_) => {
+                self.bytes(&[0x48, 0xB8 | host]);
+                self.bytes(&imm.to_le_bytes());
+            }
+        }
+    }
+
+    /// context.pc = pc
+    fn set_pc(&mut self, pc: u64) {
+        self.mov_imm(RAX, pc as i64);
+        // mov [rsi], rax
+        self.bytes(&[0x48, 0x89, 0x06]);
+    }
+
+    /// 실행한 명령어 수를 반환
+    fn ret(&mut self, count: usize) {
+        // mov eax, imm32; ret
+        self.bytes(&[0xB8]);
+        self.bytes(&(count as u32).to_le_bytes());
+        self.bytes(&[0xC3]);
+    }
+
+    /// 조건 cc가 참이면 번역 중인 명령어 앞에서 인터프리터로 빠져나감
+    fn exit_if(&mut self, cc: u8) {
+        self.bytes(&[0x0F, 0x80 | cc, 0, 0, 0, 0]);
+        self.exits.push((self.code.len() - 4, self.index, self.pc));
+    }
+
+    /// 빠져나가는 코드를 명령어마다 하나씩 생성하고 jcc를 연결
+    fn exits(&mut self) {
+        let mut stub: Option<(usize, usize)> = None;
+        for (at, index, pc) in std::mem::take(&mut self.exits) {
+            let target = match stub {
+                Some((stub_index, target)) if stub_index == index => target,
+                _ => {
+                    let target = self.code.len();
+                    self.set_pc(pc);
+                    self.ret(index);
+                    stub = Some((index, target));
+                    target
+                }
+            };
+            let rel = (target as i32 - (at as i32 + 4)).to_le_bytes();
+            self.code[at..at + 4].copy_from_slice(&rel);
+        }
+    }
+
+    /// rax = rax op rcx. 지원하지 않는 연산이면 false
+    fn alu64(&mut self, op: AluOp) -> bool {
+        match op {
+            AluOp::Add => self.bytes(&[0x48, 0x01, 0xC8]),
+            AluOp::Sub => self.bytes(&[0x48, 0x29, 0xC8]),
+            AluOp::And => self.bytes(&[0x48, 0x21, 0xC8]),
+            AluOp::Or => self.bytes(&[0x48, 0x09, 0xC8]),
+            AluOp::Xor => self.bytes(&[0x48, 0x31, 0xC8]),
+            // x86 64비트 시프트는 cl의 하위 6비트만 사용 (RV64의 & 0x3F와 같음)
+            AluOp::Sll => self.bytes(&[0x48, 0xD3, 0xE0]),
+            AluOp::Srl => self.bytes(&[0x48, 0xD3, 0xE8]),
+            AluOp::Sra => self.bytes(&[0x48, 0xD3, 0xF8]),
+            AluOp::Mul => self.bytes(&[0x48, 0x0F, 0xAF, 0xC1]),
+            // cmp rax, rcx; setl/setb al; movzx eax, al
+            AluOp::Slt => self.bytes(&[0x48, 0x39, 0xC8, 0x0F, 0x9C, 0xC0, 0x0F, 0xB6, 0xC0]),
+            AluOp::Sltu => self.bytes(&[0x48, 0x39, 0xC8, 0x0F, 0x92, 0xC0, 0x0F, 0xB6, 0xC0]),
+            _ => return false,
+        }
+        true
+    }
+
+    /// eax = eax op ecx 후 rax로 부호 확장
+    fn alu32(&mut self, op: AluOp) -> bool {
+        match op {
+            AluOp::Add => self.bytes(&[0x01, 0xC8]),
+            AluOp::Sub => self.bytes(&[0x29, 0xC8]),
+            AluOp::Mul => self.bytes(&[0x0F, 0xAF, 0xC1]),
+            // and ecx, 31: 시프트 양은 rs2의 하위 5비트
+            AluOp::Sll => self.bytes(&[0x83, 0xE1, 0x1F, 0xD3, 0xE0]),
+            AluOp::Srl => self.bytes(&[0x83, 0xE1, 0x1F, 0xD3, 0xE8]),
+            AluOp::Sra => self.bytes(&[0x83, 0xE1, 0x1F, 0xD3, 0xF8]),
+            AluOp::Div | AluOp::Divu | AluOp::Rem | AluOp::Remu => self.div32(op),
+            _ => return false,
+        }
+        // movsxd rax, eax
+        self.bytes(&[0x48, 0x63, 0xC0]);
+        true
+    }
+
+    /// eax = eax op ecx (DIVW/DIVUW/REMW/REMUW)
+    /// 제수가 0이면 몫은 -1, 나머지는 피제수. i32::MIN / -1은 몫 i32::MIN, 나머지 0
+    /// (x86 div/idiv는 두 경우 모두 예외를 일으키므로 먼저 처리)
+    fn div32(&mut self, op: AluOp) {
+        let signed = matches!(op, AluOp::Div | AluOp::Rem);
+        let rem = matches!(op, AluOp::Rem | AluOp::Remu);
+        // test ecx, ecx; jz zero
+        self.bytes(&[0x85, 0xC9]);
+        let zero = self.jump8(0x74);
+        let mut overflow = None;
+        if signed {
+            // cmp ecx, -1; jne divide; cmp eax, i32::MIN; je overflow
+            self.bytes(&[0x83, 0xF9, 0xFF]);
+            let divide = self.jump8(0x75);
+            self.bytes(&[0x3D, 0x00, 0x00, 0x00, 0x80]);
+            overflow = Some(self.jump8(0x74));
+            self.patch8(divide);
+            // cdq; idiv ecx
+            self.bytes(&[0x99, 0xF7, 0xF9]);
+        } else {
+            // xor edx, edx; div ecx
+            self.bytes(&[0x31, 0xD2, 0xF7, 0xF1]);
+        }
+        if rem {
+            // mov eax, edx
+            self.bytes(&[0x89, 0xD0]);
+        }
+        let done = self.jump8(0xEB);
+        self.patch8(zero);
+        if !rem {
+            // mov eax, -1
+            self.bytes(&[0xB8, 0xFF, 0xFF, 0xFF, 0xFF]);
+        }
+        if let Some(overflow) = overflow {
+            let skip = self.jump8(0xEB);
+            self.patch8(overflow);
+            if rem {
+                // xor eax, eax
+                self.bytes(&[0x31, 0xC0]);
+            }
+            self.patch8(skip);
+        }
+        self.patch8(done);
+    }
+
+    // rel8 점프를 생성하고 나중에 patch8로 채울 위치를 반환
+    fn jump8(&mut self, opcode: u8) -> usize {
+        self.bytes(&[opcode, 0]);
+        self.code.len() - 1
+    }
+
+    fn patch8(&mut self, at: usize) {
+        self.code[at] = (self.code.len() - at - 1) as u8;
+    }
+
+    /// rax = regs[rs1] + offset - DRAM_BASE. 정렬되지 않았거나 [0, limit)를 벗어나면 빠져나감
+    fn dram_offset(&mut self, rs1: usize, offset: i32, size: u64, limit: u8) {
+        self.load(RAX, rs1);
+        if offset != 0 {
+            // add rax, imm32
+            self.bytes(&[0x48, 0x05]);
+            self.bytes(&offset.to_le_bytes());
+        }
+        // mov edx, DRAM_BASE; sub rax, rdx
+        self.bytes(&[0xBA]);
+        self.bytes(&(DRAM_BASE as u32).to_le_bytes());
+        self.bytes(&[0x48, 0x29, 0xD0]);
+        if size > 1 {
+            // test al, size - 1
+            self.bytes(&[0xA8, size as u8 - 1]);
+            self.exit_if(CC_NE);
+        }
+        // cmp rax, [rsi + limit] (음수 오프셋은 부호 없는 비교에서 큰 값)
+        self.bytes(&[0x48, 0x3B, 0x46, limit]);
+        self.exit_if(CC_AE);
+    }
+
+    fn load_memory(&mut self, op: LoadOp, rd: usize, rs1: usize, offset: i32) {
+        let size = match op {
+            LoadOp::Lb | LoadOp::Lbu => 1,
+            LoadOp::Lh | LoadOp::Lhu => 2,
+            LoadOp::Lw | LoadOp::Lwu => 4,
+            LoadOp::Ld => 8,
+        };
+        self.dram_offset(rs1, offset, size, CONTEXT_LOAD_LIMIT);
+        // add rax, [rsi + ram]
+        self.bytes(&[0x48, 0x03, 0x46, CONTEXT_RAM]);
+        match op {
+            // movsx rax, byte [rax]
+            LoadOp::Lb => self.bytes(&[0x48, 0x0F, 0xBE, 0x00]),
+            // movzx eax, byte [rax]
+            LoadOp::Lbu => self.bytes(&[0x0F, 0xB6, 0x00]),
+            // movsx rax, word [rax]
+            LoadOp::Lh => self.bytes(&[0x48, 0x0F, 0xBF, 0x00]),
+            // movzx eax, word [rax]
+            LoadOp::Lhu => self.bytes(&[0x0F, 0xB7, 0x00]),
+            // movsxd rax, dword [rax]
+            LoadOp::Lw => self.bytes(&[0x48, 0x63, 0x00]),
+            // mov eax, [rax]
+            LoadOp::Lwu => self.bytes(&[0x8B, 0x00]),
+            // mov rax, [rax]
+            LoadOp::Ld => self.bytes(&[0x48, 0x8B, 0x00]),
+        }
+        self.store(rd);
+    }
+
+    /// Bus::store_ram과 같은 일: 코드 페이지면 빠져나가고, 그래뉼 순번을 올린 뒤 씀
+    fn store_memory(&mut self, width: Width, rs1: usize, rs2: usize, offset: i32) {
+        let size = width.bytes();
+        self.dram_offset(rs1, offset, size, CONTEXT_STORE_LIMIT);
+        // 코드 페이지 비트: rcx = code_pages[offset >> (PAGE_SHIFT + 6)]; bt rcx, offset >> PAGE_SHIFT
+        self.bytes(&[0x48, 0x89, 0xC2, 0x48, 0xC1, 0xEA, PAGE_SHIFT as u8 + 6]);
+        self.bytes(&[0x48, 0x8B, 0x4E, CONTEXT_CODE_PAGES, 0x48, 0x8B, 0x0C, 0xD1]);
+        self.bytes(&[0x48, 0x89, 0xC2, 0x48, 0xC1, 0xEA, PAGE_SHIFT as u8]);
+        self.bytes(&[0x48, 0x0F, 0xA3, 0xD1]);
+        self.exit_if(CC_B);
+        // granules[(offset >> GRANULE_SHIFT) & (GRANULES - 1)] += 2
+        self.bytes(&[0x48, 0x89, 0xC2, 0x48, 0xC1, 0xEA, GRANULE_SHIFT as u8]);
+        self.bytes(&[0x81, 0xE2]);
+        self.bytes(&(GRANULES as u32 - 1).to_le_bytes());
+        self.bytes(&[
+            0x48,
+            0x8B,
+            0x4E,
+            CONTEXT_GRANULES,
+            0x48,
+            0x83,
+            0x04,
+            0xD1,
+            0x02,
+        ]);
+        self.load(RCX, rs2);
+        // add rax, [rsi + ram]
+        self.bytes(&[0x48, 0x03, 0x46, CONTEXT_RAM]);
+        match width {
+            // mov [rax], cl
+            Width::B => self.bytes(&[0x88, 0x08]),
+            // mov [rax], cx
+            Width::H => self.bytes(&[0x66, 0x89, 0x08]),
+            // mov [rax], ecx
+            Width::W => self.bytes(&[0x89, 0x08]),
+            // mov [rax], rcx
+            Width::D => self.bytes(&[0x48, 0x89, 0x08]),
+            Width::Q => unreachable!("No 128-bit STORE"),
+        }
+    }
+
+    /// 블록 끝 분기: context.pc = 조건이 참이면 target, 아니면 다음 명령어
+    fn branch(&mut self, op: BranchOp, rs1: usize, rs2: usize, next: u64, target: u64) {
+        let cc = match op {
+            BranchOp::Beq => CC_E,
+            BranchOp::Bne => CC_NE,
+            BranchOp::Blt => CC_L,
+            BranchOp::Bge => CC_GE,
+            BranchOp::Bltu => CC_B,
+            BranchOp::Bgeu => CC_AE,
+        };
+        self.load(RAX, rs1);
+        self.load(RCX, rs2);
+        // cmp rax, rcx (mov는 플래그를 바꾸지 않음)
+        self.bytes(&[0x48, 0x39, 0xC8]);
+        self.mov_imm(RAX, next as i64);
+        self.mov_imm(RCX, target as i64);
+        // cmovcc rax, rcx; mov [rsi], rax
+        self.bytes(&[0x48, 0x0F, 0x40 | cc, 0xC1, 0x48, 0x89, 0x06]);
+    }
+
+    /// 명령어 하나(index번째, 주소 pc, 길이 len)를 번역. 번역하지 않는 명령어면 아무것도 생성하지 않고 false
+    fn instruction(&mut self, instruction: Instruction, pc: u64, len: u64, index: usize) -> bool {
+        let mark = self.code.len();
+        (self.index, self.pc) = (index, pc);
+        let target = |offset: i32| (pc as i64).wrapping_add(offset as i64) as u64;
+        let ok = match instruction {
+            Instruction::Lui { rd, imm } => {
+                self.mov_imm(RAX, imm as i64);
+                self.store(rd);
+                true
+            }
+            Instruction::Auipc { rd, imm } => {
+                self.mov_imm(RAX, (pc as i64).wrapping_add(imm as i64));
+                self.store(rd);
+                true
+            }
+            Instruction::OpImm { op, rd, rs1, imm } => {
+                let imm = match op {
+                    AluOp::Sll | AluOp::Srl | AluOp::Sra => imm as i64 & 0x3F,
+                    _ => imm as i64,
+                };
+                self.load(RAX, rs1);
+                self.mov_imm(RCX, imm);
+                self.alu64(op) && {
+                    self.store(rd);
+                    true
+                }
+            }
+            Instruction::Op32 { op, rd, rs1, rs2 } => {
+                self.load(RAX, rs1);
+                self.load(RCX, rs2);
+                self.alu32(op) && {
+                    self.store(rd);
+                    true
+                }
+            }
+            Instruction::OpImm32 { op, rd, rs1, imm } => {
+                let imm = match op {
+                    AluOp::Add => imm as i64,
+                    _ => imm as i64 & 0x1F,
+                };
+                self.load(RAX, rs1);
+                self.mov_imm(RCX, imm);
+                self.alu32(op) && {
+                    self.store(rd);
+                    true
+                }
+            }
+            Instruction::Op { op, rd, rs1, rs2 } => {
+                self.load(RAX, rs1);
+                self.load(RCX, rs2);
+                self.alu64(op) && {
+                    self.store(rd);
+                    true
+                }
+            }
+            Instruction::Load {
+                op,
+                rd,
+                rs1,
+                offset,
+            } => {
+                self.load_memory(op, rd, rs1, offset);
+                true
+            }
+            Instruction::Store {
+                width,
+                rs1,
+                rs2,
+                offset,
+            } => {
+                self.store_memory(width, rs1, rs2, offset);
+                true
+            }
+            Instruction::Branch {
+                op,
+                rs1,
+                rs2,
+                offset,
+            } => {
+                self.branch(op, rs1, rs2, pc + len, target(offset));
+                true
+            }
+            Instruction::Jal { rd, offset } => {
+                self.mov_imm(RAX, (pc + len) as i64);
+                self.store(rd);
+                self.set_pc(target(offset));
+                true
+            }
+            _ => false,
+        };
+        if !ok {
+            self.code.truncate(mark);
+            self.exits.retain(|&(at, _, _)| at < mark);
+        }
+        ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm;
+    use crate::decoder;
+
+    fn decoded(raw: u32) -> DecodedInst {
+        DecodedInst {
+            raw,
+            len: 4,
+            op: DecodedOp::Base {
+                inst: raw,
+                decoded: decoder::decode(raw),
+            },
+        }
+    }
+
+    fn compile(jit: &mut Jit, program: &[u32]) -> Option<NativeBlock> {
+        let insts: Vec<_> = program.iter().map(|&raw| decoded(raw)).collect();
+        jit.compile(0x80000000, &insts)
+    }
+
+    // 번역된 코드가 접근할 작은 DRAM과 순번 테이블, 코드 페이지 비트맵
+    struct TestMemory {
+        ram: Vec<u64>,
+        granules: Vec<u64>,
+        code_pages: Vec<u64>,
+    }
+
+    impl TestMemory {
+        fn new() -> Self {
+            TestMemory {
+                ram: vec![0; 1024],
+                granules: vec![0; GRANULES],
+                code_pages: vec![0; 1],
+            }
+        }
+
+        fn context(&mut self) -> NativeContext {
+            let size = (self.ram.len() * 8) as u64;
+            NativeContext {
+                pc: 0,
+                ram: self.ram.as_mut_ptr().cast(),
+                load_limit: size,
+                store_limit: size,
+                granules: self.granules.as_mut_ptr(),
+                code_pages: self.code_pages.as_ptr(),
+            }
+        }
+    }
+
+    fn run(program: &[u32], regs: &mut [u64; 32]) -> (usize, NativeContext) {
+        let mut jit = Jit::new();
+        let native = compile(&mut jit, program).unwrap();
+        let mut memory = TestMemory::new();
+        let mut context = memory.context();
+        let executed = native.call(regs, &mut context);
+        (executed, context)
+    }
+
+    #[test]
+    fn test_compile_and_run_alu_block() {
+        let mut regs = [0u64; 32];
+        let (executed, context) = run(
+            &[
+                asm::addi(10, 0, -5),
+                asm::slli(11, 10, 4),
+                asm::sub(12, 11, 10),
+                asm::sltu(13, 0, 12),
+                asm::addiw(14, 10, 0x7FF),
+                asm::lui(15, 0x80000),
+                asm::auipc(16, 1),
+                asm::addi(0, 10, 1),
+            ],
+            &mut regs,
+        );
+        assert_eq!(executed, 8);
+        assert_eq!(context.pc, 0x80000020);
+        assert_eq!(regs[0], 0);
+        assert_eq!(regs[10], -5i64 as u64);
+        assert_eq!(regs[11], -80i64 as u64);
+        assert_eq!(regs[12], -75i64 as u64);
+        assert_eq!(regs[13], 1);
+        assert_eq!(regs[14], 2042);
+        assert_eq!(regs[15], 0xFFFF_FFFF_8000_0000);
+        assert_eq!(regs[16], 0x80001018);
+    }
+
+    #[test]
+    fn test_word_ops_match_interpreter() {
+        let mut regs = [0u64; 32];
+        regs[5] = 0xFFFF_FFFF_8000_0001;
+        regs[6] = 33; // 하위 5비트만 쓰면 1
+        regs[7] = 1 << 32; // 하위 32비트가 0인 제수
+        regs[28] = -1i64 as u64;
+        regs[29] = 0x8000_0000; // i32::MIN
+        let (executed, _) = run(
+            &[
+                asm::sllw(10, 5, 6),
+                asm::sraw(11, 5, 6),
+                asm::srliw(12, 5, 0),
+                asm::divw(13, 5, 7),
+                asm::divuw(14, 5, 7),
+                asm::remw(15, 5, 7),
+                asm::remuw(16, 5, 7),
+                asm::divw(17, 29, 28),
+                asm::remw(18, 29, 28),
+                asm::divw(19, 5, 28),
+                asm::remuw(20, 5, 6),
+            ],
+            &mut regs,
+        );
+        assert_eq!(executed, 11);
+        assert_eq!(regs[10], 2);
+        assert_eq!(regs[11], 0xFFFF_FFFF_C000_0000);
+        assert_eq!(regs[12], 0xFFFF_FFFF_8000_0001);
+        assert_eq!(regs[13], u64::MAX);
+        assert_eq!(regs[14], u64::MAX);
+        assert_eq!(regs[15], 0xFFFF_FFFF_8000_0001);
+        assert_eq!(regs[16], 0xFFFF_FFFF_8000_0001);
+        assert_eq!(regs[17], 0xFFFF_FFFF_8000_0000);
+        assert_eq!(regs[18], 0);
+        assert_eq!(regs[19], 0x7FFF_FFFF);
+        assert_eq!(regs[20], 0x8000_0001 % 33);
+    }
+
+    #[test]
+    fn test_loads_and_stores_use_dram() {
+        let mut jit = Jit::new();
+        let native = compile(
+            &mut jit,
+            &[
+                asm::lui(5, 0x80000),
+                asm::slli(5, 5, 32),
+                asm::srli(5, 5, 32),
+                asm::sd(6, 0x100, 5),
+                asm::lb(10, 0x107, 5),
+                asm::lw(11, 0x100, 5),
+                asm::sw(6, 0x108, 5),
+                asm::ld(12, 0x108, 5),
+            ],
+        )
+        .unwrap();
+        let mut memory = TestMemory::new();
+        let mut context = memory.context();
+        let mut regs = [0u64; 32];
+        regs[6] = 0x8000_0000_FFFF_FFFE;
+        assert_eq!(native.call(&mut regs, &mut context), 8);
+        assert_eq!(regs[10], -128i64 as u64);
+        assert_eq!(regs[11], -2i64 as u64);
+        assert_eq!(regs[12], 0xFFFF_FFFE);
+        assert_eq!(memory.ram[0x100 / 8], 0x8000_0000_FFFF_FFFE);
+        // store마다 그래뉼 순번이 올라감 (0x100과 0x108은 같은 그래뉼)
+        assert_eq!(memory.granules[0x100 >> GRANULE_SHIFT], 4);
+    }
+
+    #[test]
+    fn test_memory_access_outside_fast_path_exits() {
+        let program = [asm::addi(10, 0, 1), asm::lw(11, 0, 5), asm::addi(12, 0, 1)];
+        let mut jit = Jit::new();
+        let native = compile(&mut jit, &program).unwrap();
+        assert_eq!(native.insts, 3);
+        let mut memory = TestMemory::new();
+        // 장치 주소, misaligned, DRAM 직접 접근이 꺼진 경우 모두 lw 앞에서 빠져나감
+        for (addr, limit) in [(0x1000_0000, 8192), (0x8000_0002, 8192), (0x8000_0000, 0)] {
+            let mut context = memory.context();
+            context.load_limit = limit;
+            let mut regs = [0u64; 32];
+            regs[5] = addr;
+            assert_eq!(native.call(&mut regs, &mut context), 1);
+            assert_eq!(context.pc, 0x80000004);
+            assert_eq!((regs[10], regs[11], regs[12]), (1, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_store_to_code_page_exits() {
+        let mut jit = Jit::new();
+        let native = compile(&mut jit, &[asm::sw(6, 0, 5)]).unwrap();
+        let mut memory = TestMemory::new();
+        memory.code_pages[0] = 1;
+        let mut context = memory.context();
+        let mut regs = [0u64; 32];
+        regs[5] = 0x8000_0010;
+        regs[6] = 7;
+        assert_eq!(native.call(&mut regs, &mut context), 0);
+        assert_eq!(context.pc, 0x80000000);
+        assert_eq!(memory.ram[2], 0);
+        assert_eq!(memory.granules[1], 0);
+    }
+
+    #[test]
+    fn test_branch_ends_native_block() {
+        let mut jit = Jit::new();
+        let native = compile(
+            &mut jit,
+            &[
+                asm::addi(10, 10, 1),
+                asm::blt(10, 11, -4),
+                asm::addi(12, 0, 1),
+            ],
+        )
+        .unwrap();
+        assert_eq!(native.insts, 2);
+        let mut memory = TestMemory::new();
+        let mut regs = [0u64; 32];
+        regs[11] = 2;
+        let mut context = memory.context();
+        assert_eq!(native.call(&mut regs, &mut context), 2);
+        assert_eq!(context.pc, 0x80000000);
+        assert_eq!(native.call(&mut regs, &mut context), 2);
+        assert_eq!(context.pc, 0x80000008);
+        assert_eq!(regs[12], 0);
+
+        let native = compile(&mut jit, &[asm::jal(1, 0x100)]).unwrap();
+        assert_eq!(native.call(&mut regs, &mut context), 1);
+        assert_eq!(context.pc, 0x80000100);
+        assert_eq!(regs[1], 0x80000004);
+    }
+
+    #[test]
+    fn test_compile_stops_at_unsupported_instruction() {
+        let mut jit = Jit::new();
+        let native = compile(
+            &mut jit,
+            &[
+                asm::addi(10, 0, 1),
+                asm::csrrs(11, 0xB00, 0),
+                asm::addi(12, 0, 1),
+            ],
+        )
+        .unwrap();
+        assert_eq!(native.insts, 1);
+        assert!(compile(&mut jit, &[asm::csrrs(11, 0xB00, 0)]).is_none());
+        assert_eq!(jit.compiled, 1);
+    }
+}
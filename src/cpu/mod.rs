@@ -1,6 +1,21 @@
+pub mod block;
+pub mod compressed;
 mod cpu;
+pub mod crypto;
+pub mod entropy;
+pub mod extensions;
+pub mod icache;
+#[cfg(feature = "jit")]
+pub mod jit;
+pub mod softfloat;
 #[cfg(test)]
 mod tests;
+pub mod timing;
+pub mod tlb;
+pub mod trigger;
 
 pub use cpu::Cpu;
 pub use cpu::PrivilegeMode;
+pub use cpu::Xlen;
+pub(crate) use cpu::amo_alu;
+pub(crate) use cpu::rv64_only;
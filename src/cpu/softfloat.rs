@@ -0,0 +1,1003 @@
+//! 소프트웨어 부동소수점 (IEEE 754 binary16 / bfloat16 / binary32 / binary64)
+//! 정수 연산만 사용하므로 호스트 FPU의 반올림 모드나 예외 플래그에 의존하지 않음
+//! RISC-V 규칙: NaN 결과는 항상 canonical NaN, tininess는 반올림 후 판정
+
+use std::cmp::Ordering;
+
+// fflags 비트
+pub const FLAG_NX: u64 = 1 << 0;
+pub const FLAG_UF: u64 = 1 << 1;
+pub const FLAG_OF: u64 = 1 << 2;
+pub const FLAG_DZ: u64 = 1 << 3;
+pub const FLAG_NV: u64 = 1 << 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundingMode {
+    Rne = 0,
+    Rtz = 1,
+    Rdn = 2,
+    Rup = 3,
+    Rmm = 4,
+}
+
+impl RoundingMode {
+    /// 5, 6, 7(dynamic)은 유효한 모드가 아님
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        match bits {
+            0 => Some(RoundingMode::Rne),
+            1 => Some(RoundingMode::Rtz),
+            2 => Some(RoundingMode::Rdn),
+            3 => Some(RoundingMode::Rup),
+            4 => Some(RoundingMode::Rmm),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Format {
+    pub exp_bits: u32,
+    pub frac_bits: u32,
+}
+
+pub const HALF: Format = Format {
+    exp_bits: 5,
+    frac_bits: 10,
+};
+pub const BFLOAT16: Format = Format {
+    exp_bits: 8,
+    frac_bits: 7,
+};
+pub const SINGLE: Format = Format {
+    exp_bits: 8,
+    frac_bits: 23,
+};
+pub const DOUBLE: Format = Format {
+    exp_bits: 11,
+    frac_bits: 52,
+};
+
+impl Format {
+    pub fn width(&self) -> u32 {
+        1 + self.exp_bits + self.frac_bits
+    }
+
+    fn bias(&self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    fn max_biased_exp(&self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    pub fn sign_bit(&self) -> u64 {
+        1 << (self.exp_bits + self.frac_bits)
+    }
+
+    fn frac_mask(&self) -> u64 {
+        (1 << self.frac_bits) - 1
+    }
+
+    pub fn canonical_nan(&self) -> u64 {
+        (self.max_biased_exp() << self.frac_bits) | (1 << (self.frac_bits - 1))
+    }
+
+    fn zero(&self, sign: bool) -> u64 {
+        if sign { self.sign_bit() } else { 0 }
+    }
+
+    fn infinity(&self, sign: bool) -> u64 {
+        self.zero(sign) | (self.max_biased_exp() << self.frac_bits)
+    }
+
+    fn max_finite(&self, sign: bool) -> u64 {
+        self.infinity(sign) - 1
+    }
+
+    /// f 레지스터 값에서 꺼내기: NaN-boxing이 깨져 있으면 canonical NaN
+    pub fn unbox(&self, reg: u64) -> u64 {
+        let width = self.width();
+        if width == 64 {
+            return reg;
+        }
+        if reg >> width == u64::MAX >> width {
+            reg & ((1 << width) - 1)
+        } else {
+            self.canonical_nan()
+        }
+    }
+
+    /// f 레지스터에 넣기: 상위 비트를 1로 채움
+    pub fn nan_box(&self, bits: u64) -> u64 {
+        let width = self.width();
+        if width == 64 {
+            bits
+        } else {
+            bits | (u64::MAX << width)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Zero(bool),
+    // 값 = (-1)^sign * sig * 2^exp
+    Finite { sign: bool, exp: i32, sig: u128 },
+    Inf(bool),
+    NaN { signaling: bool },
+}
+
+fn unpack(fmt: Format, bits: u64) -> Value {
+    let sign = bits & fmt.sign_bit() != 0;
+    let biased = (bits >> fmt.frac_bits) & fmt.max_biased_exp();
+    let frac = bits & fmt.frac_mask();
+    let f = fmt.frac_bits as i32;
+    if biased == fmt.max_biased_exp() {
+        if frac == 0 {
+            Value::Inf(sign)
+        } else {
+            Value::NaN {
+                signaling: frac & (1 << (fmt.frac_bits - 1)) == 0,
+            }
+        }
+    } else if biased == 0 {
+        if frac == 0 {
+            Value::Zero(sign)
+        } else {
+            Value::Finite {
+                sign,
+                exp: 1 - fmt.bias() - f,
+                sig: frac as u128,
+            }
+        }
+    } else {
+        Value::Finite {
+            sign,
+            exp: biased as i32 - fmt.bias() - f,
+            sig: (frac | (1 << fmt.frac_bits)) as u128,
+        }
+    }
+}
+
+fn is_signaling(fmt: Format, bits: u64) -> bool {
+    matches!(unpack(fmt, bits), Value::NaN { signaling: true })
+}
+
+fn is_nan(fmt: Format, bits: u64) -> bool {
+    matches!(unpack(fmt, bits), Value::NaN { .. })
+}
+
+/// NaN 입력이 있는 연산의 결과: sNaN이면 NV, 결과는 canonical NaN
+fn propagate_nan(fmt: Format, inputs: &[u64], flags: &mut u64) -> u64 {
+    if inputs.iter().any(|&bits| is_signaling(fmt, bits)) {
+        *flags |= FLAG_NV;
+    }
+    fmt.canonical_nan()
+}
+
+fn invalid(fmt: Format, flags: &mut u64) -> u64 {
+    *flags |= FLAG_NV;
+    fmt.canonical_nan()
+}
+
+/// sig를 오른쪽으로 shift하며 반올림, (결과, inexact) 반환
+fn shift_round(sig: u128, shift: i32, sign: bool, rm: RoundingMode) -> (u128, bool) {
+    if shift <= 0 {
+        return (sig << -shift, false);
+    }
+    let (q, cmp, inexact) = if shift > 128 {
+        (0, Ordering::Less, sig != 0)
+    } else if shift == 128 {
+        (0, sig.cmp(&(1 << 127)), sig != 0)
+    } else {
+        let rem = sig & ((1 << shift) - 1);
+        (sig >> shift, rem.cmp(&(1 << (shift - 1))), rem != 0)
+    };
+    let increment = match rm {
+        RoundingMode::Rne => cmp == Ordering::Greater || (cmp == Ordering::Equal && q & 1 == 1),
+        RoundingMode::Rtz => false,
+        RoundingMode::Rdn => sign && inexact,
+        RoundingMode::Rup => !sign && inexact,
+        RoundingMode::Rmm => cmp != Ordering::Less,
+    };
+    (q + increment as u128, inexact)
+}
+
+fn overflow(fmt: Format, sign: bool, rm: RoundingMode, flags: &mut u64) -> u64 {
+    *flags |= FLAG_OF | FLAG_NX;
+    let to_infinity = match rm {
+        RoundingMode::Rne | RoundingMode::Rmm => true,
+        RoundingMode::Rtz => false,
+        RoundingMode::Rdn => sign,
+        RoundingMode::Rup => !sign,
+    };
+    if to_infinity {
+        fmt.infinity(sign)
+    } else {
+        fmt.max_finite(sign)
+    }
+}
+
+/// (-1)^sign * sig * 2^exp 를 fmt로 반올림 (sig의 최하위 비트는 sticky일 수 있음)
+fn round_pack(
+    fmt: Format,
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    rm: RoundingMode,
+    flags: &mut u64,
+) -> u64 {
+    if sig == 0 {
+        return fmt.zero(sign);
+    }
+    let f = fmt.frac_bits as i32;
+    let emin = 1 - fmt.bias();
+    // 값 = 1.xxx * 2^e
+    let e = exp + (127 - sig.leading_zeros() as i32);
+    let quantum = e.max(emin) - f;
+    let (mut q, inexact) = shift_round(sig, quantum - exp, sign, rm);
+
+    if inexact {
+        *flags |= FLAG_NX;
+        // 지수 범위가 무한하다고 가정하고 반올림한 결과가 2^emin 미만이면 tiny
+        let tiny = e < emin - 1
+            || (e == emin - 1 && shift_round(sig, e - f - exp, sign, rm).0 >> (f + 1) == 0);
+        if tiny {
+            *flags |= FLAG_UF;
+        }
+    }
+
+    if e < emin {
+        // subnormal: q == 2^f이면 그대로 최소 normal로 인코딩됨
+        return fmt.zero(sign) | q as u64;
+    }
+    let mut biased = (e + fmt.bias()) as u64;
+    if q >> (f + 1) != 0 {
+        q >>= 1;
+        biased += 1;
+    }
+    if biased >= fmt.max_biased_exp() {
+        return overflow(fmt, sign, rm, flags);
+    }
+    fmt.zero(sign) | (biased << f) | (q as u64 & fmt.frac_mask())
+}
+
+/// sig의 최상위 비트를 bit 위치로 맞춤
+fn normalize(exp: i32, sig: u128, bit: u32) -> (i32, u128) {
+    let msb = 127 - sig.leading_zeros();
+    if msb < bit {
+        (exp - (bit - msb) as i32, sig << (bit - msb))
+    } else {
+        (exp + (msb - bit) as i32, shift_right_jam(sig, msb - bit))
+    }
+}
+
+fn shift_right_jam(sig: u128, shift: u32) -> u128 {
+    if shift == 0 {
+        sig
+    } else if shift >= 128 {
+        (sig != 0) as u128
+    } else {
+        (sig >> shift) | ((sig & ((1 << shift) - 1)) != 0) as u128
+    }
+}
+
+type Operand = (bool, i32, u128);
+
+fn add_finite(fmt: Format, a: Operand, b: Operand, rm: RoundingMode, flags: &mut u64) -> u64 {
+    // 자리 올림 여유를 두고 bit 120에 정규화한 뒤 작은 쪽을 jam 시프트
+    let (ea, siga) = normalize(a.1, a.2, 120);
+    let (eb, sigb) = normalize(b.1, b.2, 120);
+    let ((big_sign, big_exp, big_sig), (small_sign, small_exp, small_sig)) = if ea >= eb {
+        ((a.0, ea, siga), (b.0, eb, sigb))
+    } else {
+        ((b.0, eb, sigb), (a.0, ea, siga))
+    };
+    let small_sig = shift_right_jam(small_sig, (big_exp - small_exp) as u32);
+
+    if big_sign == small_sign {
+        return round_pack(fmt, big_sign, big_exp, big_sig + small_sig, rm, flags);
+    }
+    let (sign, sig) = match big_sig.cmp(&small_sig) {
+        Ordering::Greater => (big_sign, big_sig - small_sig),
+        Ordering::Less => (small_sign, small_sig - big_sig),
+        // 정확히 0: RDN에서만 -0
+        Ordering::Equal => return fmt.zero(rm == RoundingMode::Rdn),
+    };
+    round_pack(fmt, sign, big_exp, sig, rm, flags)
+}
+
+pub fn add(fmt: Format, a: u64, b: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Value::NaN { .. }, _) | (_, Value::NaN { .. }) => propagate_nan(fmt, &[a, b], flags),
+        (Value::Inf(sa), Value::Inf(sb)) if sa != sb => invalid(fmt, flags),
+        (Value::Inf(sign), _) | (_, Value::Inf(sign)) => fmt.infinity(sign),
+        (Value::Zero(sa), Value::Zero(sb)) => {
+            if sa == sb {
+                fmt.zero(sa)
+            } else {
+                fmt.zero(rm == RoundingMode::Rdn)
+            }
+        }
+        (Value::Zero(_), _) => b,
+        (_, Value::Zero(_)) => a,
+        (
+            Value::Finite {
+                sign: sa,
+                exp: ea,
+                sig: siga,
+            },
+            Value::Finite {
+                sign: sb,
+                exp: eb,
+                sig: sigb,
+            },
+        ) => add_finite(fmt, (sa, ea, siga), (sb, eb, sigb), rm, flags),
+    }
+}
+
+pub fn sub(fmt: Format, a: u64, b: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    if is_nan(fmt, b) {
+        return propagate_nan(fmt, &[a, b], flags);
+    }
+    add(fmt, a, b ^ fmt.sign_bit(), rm, flags)
+}
+
+pub fn mul(fmt: Format, a: u64, b: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Value::NaN { .. }, _) | (_, Value::NaN { .. }) => propagate_nan(fmt, &[a, b], flags),
+        (Value::Inf(_), Value::Zero(_)) | (Value::Zero(_), Value::Inf(_)) => invalid(fmt, flags),
+        (Value::Inf(sa), other) | (other, Value::Inf(sa)) => fmt.infinity(sa ^ sign_of(other)),
+        (Value::Zero(sa), other) | (other, Value::Zero(sa)) => fmt.zero(sa ^ sign_of(other)),
+        (
+            Value::Finite {
+                sign: sa,
+                exp: ea,
+                sig: siga,
+            },
+            Value::Finite {
+                sign: sb,
+                exp: eb,
+                sig: sigb,
+            },
+        ) => round_pack(fmt, sa ^ sb, ea + eb, siga * sigb, rm, flags),
+    }
+}
+
+fn sign_of(value: Value) -> bool {
+    match value {
+        Value::Zero(sign) | Value::Inf(sign) | Value::Finite { sign, .. } => sign,
+        Value::NaN { .. } => false,
+    }
+}
+
+pub fn div(fmt: Format, a: u64, b: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    match (unpack(fmt, a), unpack(fmt, b)) {
+        (Value::NaN { .. }, _) | (_, Value::NaN { .. }) => propagate_nan(fmt, &[a, b], flags),
+        (Value::Inf(_), Value::Inf(_)) | (Value::Zero(_), Value::Zero(_)) => invalid(fmt, flags),
+        (Value::Inf(sa), other) => fmt.infinity(sa ^ sign_of(other)),
+        (other, Value::Inf(sb)) => fmt.zero(sb ^ sign_of(other)),
+        (other, Value::Zero(sb)) => {
+            *flags |= FLAG_DZ;
+            fmt.infinity(sb ^ sign_of(other))
+        }
+        (Value::Zero(sa), other) => fmt.zero(sa ^ sign_of(other)),
+        (
+            Value::Finite {
+                sign: sa,
+                exp: ea,
+                sig: siga,
+            },
+            Value::Finite {
+                sign: sb,
+                exp: eb,
+                sig: sigb,
+            },
+        ) => {
+            // 몫이 최소 62비트가 되도록 정규화, 나머지는 sticky로
+            let (ea, siga) = normalize(ea, siga, 126);
+            let (eb, sigb) = normalize(eb, sigb, 63);
+            let q = siga / sigb;
+            let sticky = (siga % sigb != 0) as u128;
+            round_pack(fmt, sa ^ sb, ea - eb, q | sticky, rm, flags)
+        }
+    }
+}
+
+pub fn sqrt(fmt: Format, a: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    match unpack(fmt, a) {
+        Value::NaN { .. } => propagate_nan(fmt, &[a], flags),
+        Value::Zero(_) => a,
+        Value::Inf(false) => a,
+        Value::Inf(true) | Value::Finite { sign: true, .. } => invalid(fmt, flags),
+        Value::Finite { exp, sig, .. } => {
+            // 지수를 짝수로 맞춰 정수 제곱근
+            let (mut exp, mut sig) = normalize(exp, sig, 124);
+            if exp % 2 != 0 {
+                exp -= 1;
+                sig <<= 1;
+            }
+            let root = isqrt(sig);
+            let sticky = (root * root != sig) as u128;
+            round_pack(fmt, false, exp / 2, root | sticky, rm, flags)
+        }
+    }
+}
+
+fn isqrt(n: u128) -> u128 {
+    // f64 근사값보다 크게 시작하면 Newton 반복이 floor(sqrt(n))으로 단조 감소
+    let mut x = (n as f64).sqrt() as u128 + (1 << 12);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// a * b + c (반올림 1회)
+pub fn fma(fmt: Format, a: u64, b: u64, c: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    let (va, vb, vc) = (unpack(fmt, a), unpack(fmt, b), unpack(fmt, c));
+    let inf_times_zero = matches!(
+        (va, vb),
+        (Value::Inf(_), Value::Zero(_)) | (Value::Zero(_), Value::Inf(_))
+    );
+    if matches!(va, Value::NaN { .. })
+        || matches!(vb, Value::NaN { .. })
+        || matches!(vc, Value::NaN { .. })
+    {
+        // ∞ × 0은 addend가 qNaN이어도 NV
+        if inf_times_zero {
+            *flags |= FLAG_NV;
+        }
+        return propagate_nan(fmt, &[a, b, c], flags);
+    }
+    if inf_times_zero {
+        return invalid(fmt, flags);
+    }
+
+    let product_sign = sign_of(va) ^ sign_of(vb);
+    match (va, vb, vc) {
+        (Value::Inf(_), _, _) | (_, Value::Inf(_), _) => match vc {
+            Value::Inf(sc) if sc != product_sign => invalid(fmt, flags),
+            _ => fmt.infinity(product_sign),
+        },
+        (_, _, Value::Inf(_)) => c,
+        (Value::Zero(_), _, _) | (_, Value::Zero(_), _) => match vc {
+            Value::Zero(sc) if sc == product_sign => c,
+            Value::Zero(_) => fmt.zero(rm == RoundingMode::Rdn),
+            _ => c,
+        },
+        (
+            Value::Finite {
+                exp: ea, sig: siga, ..
+            },
+            Value::Finite {
+                exp: eb, sig: sigb, ..
+            },
+            _,
+        ) => {
+            let product = (product_sign, ea + eb, siga * sigb);
+            match vc {
+                Value::Finite { sign, exp, sig } => {
+                    add_finite(fmt, product, (sign, exp, sig), rm, flags)
+                }
+                _ => round_pack(fmt, product.0, product.1, product.2, rm, flags),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+// 비교용 정렬 키 (-0 == +0)
+fn order_key(fmt: Format, bits: u64) -> i128 {
+    let magnitude = (bits & (fmt.sign_bit() - 1)) as i128;
+    if bits & fmt.sign_bit() != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// IEEE 754-2019 minimumNumber/maximumNumber
+pub fn min_max(fmt: Format, a: u64, b: u64, is_max: bool, flags: &mut u64) -> u64 {
+    if is_signaling(fmt, a) || is_signaling(fmt, b) {
+        *flags |= FLAG_NV;
+    }
+    match (is_nan(fmt, a), is_nan(fmt, b)) {
+        (true, true) => fmt.canonical_nan(),
+        (true, false) => b,
+        (false, true) => a,
+        (false, false) => {
+            let (ka, kb) = (order_key(fmt, a), order_key(fmt, b));
+            // -0 < +0
+            let a_less = ka < kb || (ka == kb && a & fmt.sign_bit() != 0);
+            if a_less != is_max { a } else { b }
+        }
+    }
+}
+
+/// FEQ: quiet 비교 (sNaN만 NV)
+pub fn eq(fmt: Format, a: u64, b: u64, flags: &mut u64) -> bool {
+    if is_nan(fmt, a) || is_nan(fmt, b) {
+        if is_signaling(fmt, a) || is_signaling(fmt, b) {
+            *flags |= FLAG_NV;
+        }
+        return false;
+    }
+    order_key(fmt, a) == order_key(fmt, b)
+}
+
+/// FLT: signaling 비교 (모든 NaN에 NV)
+pub fn lt(fmt: Format, a: u64, b: u64, flags: &mut u64) -> bool {
+    if is_nan(fmt, a) || is_nan(fmt, b) {
+        *flags |= FLAG_NV;
+        return false;
+    }
+    order_key(fmt, a) < order_key(fmt, b)
+}
+
+/// FLE: signaling 비교 (모든 NaN에 NV)
+pub fn le(fmt: Format, a: u64, b: u64, flags: &mut u64) -> bool {
+    if is_nan(fmt, a) || is_nan(fmt, b) {
+        *flags |= FLAG_NV;
+        return false;
+    }
+    order_key(fmt, a) <= order_key(fmt, b)
+}
+
+/// FCLASS 결과 비트마스크
+pub fn classify(fmt: Format, bits: u64) -> u64 {
+    let subnormal = (bits >> fmt.frac_bits) & fmt.max_biased_exp() == 0;
+    let bit = match unpack(fmt, bits) {
+        Value::Inf(true) => 0,
+        Value::Finite { sign: true, .. } if subnormal => 2,
+        Value::Finite { sign: true, .. } => 1,
+        Value::Zero(true) => 3,
+        Value::Zero(false) => 4,
+        Value::Finite { sign: false, .. } if subnormal => 5,
+        Value::Finite { sign: false, .. } => 6,
+        Value::Inf(false) => 7,
+        Value::NaN { signaling: true } => 8,
+        Value::NaN { signaling: false } => 9,
+    };
+    1 << bit
+}
+
+/// 부동소수점 형식 간 변환
+pub fn convert(from: Format, to: Format, bits: u64, rm: RoundingMode, flags: &mut u64) -> u64 {
+    match unpack(from, bits) {
+        Value::NaN { signaling } => {
+            if signaling {
+                *flags |= FLAG_NV;
+            }
+            to.canonical_nan()
+        }
+        Value::Inf(sign) => to.infinity(sign),
+        Value::Zero(sign) => to.zero(sign),
+        Value::Finite { sign, exp, sig } => round_pack(to, sign, exp, sig, rm, flags),
+    }
+}
+
+/// 부동소수점 → 정수 (width: 32/64), 32비트 결과는 sign-extend
+/// 범위를 벗어나거나 NaN이면 NV와 함께 포화
+pub fn to_int(
+    fmt: Format,
+    bits: u64,
+    rm: RoundingMode,
+    signed: bool,
+    width: u32,
+    flags: &mut u64,
+) -> u64 {
+    let (min, max): (i128, i128) = if signed {
+        (-(1 << (width - 1)), (1 << (width - 1)) - 1)
+    } else {
+        (0, (1 << width) - 1)
+    };
+    let result: i128 = match unpack(fmt, bits) {
+        Value::NaN { .. } | Value::Inf(false) => {
+            *flags |= FLAG_NV;
+            max
+        }
+        Value::Inf(true) => {
+            *flags |= FLAG_NV;
+            min
+        }
+        Value::Zero(_) => 0,
+        Value::Finite { sign, exp, sig } => {
+            // 2^127 이상은 확실히 범위 밖
+            let (q, inexact) = if exp > 127 - (128 - sig.leading_zeros() as i32) {
+                (u128::MAX, false)
+            } else {
+                shift_round(sig, -exp, sign, rm)
+            };
+            let value = if q > i128::MAX as u128 {
+                None
+            } else if sign {
+                Some(-(q as i128))
+            } else {
+                Some(q as i128)
+            };
+            match value {
+                Some(value) if value >= min && value <= max => {
+                    if inexact {
+                        *flags |= FLAG_NX;
+                    }
+                    value
+                }
+                _ => {
+                    *flags |= FLAG_NV;
+                    if sign { min } else { max }
+                }
+            }
+        }
+    };
+    if width == 32 {
+        result as u32 as i32 as i64 as u64
+    } else {
+        result as u64
+    }
+}
+
+/// 정수 → 부동소수점 (value의 하위 width 비트 사용)
+pub fn from_int(
+    fmt: Format,
+    value: u64,
+    signed: bool,
+    width: u32,
+    rm: RoundingMode,
+    flags: &mut u64,
+) -> u64 {
+    let value: i128 = match (signed, width) {
+        (true, 32) => value as i32 as i128,
+        (false, 32) => value as u32 as i128,
+        (true, _) => value as i64 as i128,
+        (false, _) => value as i128,
+    };
+    round_pack(fmt, value < 0, 0, value.unsigned_abs(), rm, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RNE: RoundingMode = RoundingMode::Rne;
+
+    // binary16 상수
+    const H_ONE: u64 = 0x3C00;
+    const H_TWO: u64 = 0x4000;
+    const H_THREE: u64 = 0x4200;
+    const H_HALF: u64 = 0x3800;
+    const H_MAX: u64 = 0x7BFF; // 65504
+    const H_INF: u64 = 0x7C00;
+    const H_NEG_INF: u64 = 0xFC00;
+    const H_NEG_ZERO: u64 = 0x8000;
+    const H_MIN_SUBNORMAL: u64 = 0x0001; // 2^-24
+    const H_MIN_NORMAL: u64 = 0x0400; // 2^-14
+    const H_QNAN: u64 = 0x7E00;
+    const H_SNAN: u64 = 0x7C01;
+
+    #[test]
+    fn test_add_basic() {
+        let mut flags = 0;
+        assert_eq!(add(HALF, H_ONE, H_TWO, RNE, &mut flags), H_THREE);
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_add_inexact_rounding_modes() {
+        // 2048 + 1 = 2049는 half로 표현 불가 (2048과 2050 사이의 정확한 중간)
+        let h_2048 = 0x6800;
+        let mut flags = 0;
+        assert_eq!(add(HALF, h_2048, H_ONE, RNE, &mut flags), 0x6800); // 짝수 쪽
+        assert_eq!(flags, FLAG_NX);
+        assert_eq!(
+            add(HALF, h_2048, H_ONE, RoundingMode::Rup, &mut flags),
+            0x6801
+        );
+        assert_eq!(
+            add(HALF, h_2048, H_ONE, RoundingMode::Rmm, &mut flags),
+            0x6801
+        );
+        assert_eq!(
+            add(HALF, h_2048, H_ONE, RoundingMode::Rtz, &mut flags),
+            0x6800
+        );
+    }
+
+    #[test]
+    fn test_add_exact_zero_sign() {
+        let mut flags = 0;
+        assert_eq!(add(HALF, H_ONE, H_ONE | 0x8000, RNE, &mut flags), 0);
+        assert_eq!(
+            add(HALF, H_ONE, H_ONE | 0x8000, RoundingMode::Rdn, &mut flags),
+            H_NEG_ZERO
+        );
+    }
+
+    #[test]
+    fn test_add_overflow() {
+        let mut flags = 0;
+        assert_eq!(add(HALF, H_MAX, H_MAX, RNE, &mut flags), H_INF);
+        assert_eq!(flags, FLAG_OF | FLAG_NX);
+        assert_eq!(
+            add(HALF, H_MAX, H_MAX, RoundingMode::Rtz, &mut flags),
+            H_MAX
+        );
+    }
+
+    #[test]
+    fn test_inf_minus_inf_is_invalid() {
+        let mut flags = 0;
+        assert_eq!(add(HALF, H_INF, H_NEG_INF, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_nan_propagation() {
+        let mut flags = 0;
+        assert_eq!(add(HALF, 0x7E55, H_ONE, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, 0);
+        assert_eq!(mul(HALF, H_SNAN, H_ONE, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_mul_underflow() {
+        // 2^-14 * 2^-11 = 2^-25: 최소 subnormal의 절반 → RNE에서 0, UF|NX
+        let mut flags = 0;
+        let h_2_pow_m11 = 0x1000;
+        assert_eq!(
+            mul(HALF, H_MIN_NORMAL, h_2_pow_m11, RNE, &mut flags),
+            0x0000
+        );
+        assert_eq!(flags, FLAG_UF | FLAG_NX);
+
+        // 정확한 subnormal 결과는 플래그 없음
+        let mut flags = 0;
+        assert_eq!(
+            mul(HALF, H_MIN_NORMAL, 0x1400, RNE, &mut flags), // 2^-14 * 2^-10
+            H_MIN_SUBNORMAL
+        );
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_div() {
+        let mut flags = 0;
+        assert_eq!(div(HALF, H_ONE, H_TWO, RNE, &mut flags), H_HALF);
+        assert_eq!(flags, 0);
+        // 1/3 = 0x3555 (RNE), inexact
+        assert_eq!(div(HALF, H_ONE, H_THREE, RNE, &mut flags), 0x3555);
+        assert_eq!(flags, FLAG_NX);
+        assert_eq!(
+            div(HALF, H_ONE, H_THREE, RoundingMode::Rup, &mut flags),
+            0x3556
+        );
+    }
+
+    #[test]
+    fn test_div_by_zero() {
+        let mut flags = 0;
+        assert_eq!(div(HALF, H_ONE, H_NEG_ZERO, RNE, &mut flags), H_NEG_INF);
+        assert_eq!(flags, FLAG_DZ);
+        let mut flags = 0;
+        assert_eq!(div(HALF, 0, 0, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let mut flags = 0;
+        let h_four = 0x4400;
+        assert_eq!(sqrt(HALF, h_four, RNE, &mut flags), H_TWO);
+        assert_eq!(flags, 0);
+        // sqrt(2) = 1.4142 → 0x3DA8
+        assert_eq!(sqrt(HALF, H_TWO, RNE, &mut flags), 0x3DA8);
+        assert_eq!(flags, FLAG_NX);
+        let mut flags = 0;
+        assert_eq!(sqrt(HALF, H_ONE | 0x8000, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+        assert_eq!(sqrt(HALF, H_NEG_ZERO, RNE, &mut flags), H_NEG_ZERO);
+    }
+
+    #[test]
+    fn test_fma_single_rounding() {
+        // (1 + 2^-10) * (1 - 2^-10) - 1 = -2^-20: 분리 계산하면 0
+        let mut flags = 0;
+        let a = 0x3C01;
+        let b = 0x3BFE; // 1 - 2^-10
+        let result = fma(HALF, a, b, H_ONE | 0x8000, RNE, &mut flags);
+        assert_eq!(result, 0x8000 | 0x0010); // -2^-20 (subnormal)
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_fma_inf_times_zero_with_qnan_is_invalid() {
+        let mut flags = 0;
+        assert_eq!(fma(HALF, H_INF, 0, H_QNAN, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let mut flags = 0;
+        assert_eq!(min_max(HALF, H_ONE, H_TWO, false, &mut flags), H_ONE);
+        assert_eq!(min_max(HALF, H_ONE, H_TWO, true, &mut flags), H_TWO);
+        assert_eq!(min_max(HALF, 0, H_NEG_ZERO, false, &mut flags), H_NEG_ZERO);
+        assert_eq!(min_max(HALF, 0, H_NEG_ZERO, true, &mut flags), 0);
+        assert_eq!(min_max(HALF, H_QNAN, H_ONE, false, &mut flags), H_ONE);
+        assert_eq!(flags, 0);
+        assert_eq!(min_max(HALF, H_SNAN, H_ONE, false, &mut flags), H_ONE);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut flags = 0;
+        assert!(eq(HALF, 0, H_NEG_ZERO, &mut flags));
+        assert!(lt(HALF, H_NEG_INF, H_ONE, &mut flags));
+        assert!(le(HALF, H_ONE, H_ONE, &mut flags));
+        assert!(!eq(HALF, H_QNAN, H_QNAN, &mut flags));
+        assert_eq!(flags, 0);
+        assert!(!lt(HALF, H_QNAN, H_ONE, &mut flags));
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(HALF, H_NEG_INF), 1 << 0);
+        assert_eq!(classify(HALF, H_ONE | 0x8000), 1 << 1);
+        assert_eq!(classify(HALF, 0x8001), 1 << 2);
+        assert_eq!(classify(HALF, H_NEG_ZERO), 1 << 3);
+        assert_eq!(classify(HALF, 0), 1 << 4);
+        assert_eq!(classify(HALF, H_MIN_SUBNORMAL), 1 << 5);
+        assert_eq!(classify(HALF, H_ONE), 1 << 6);
+        assert_eq!(classify(HALF, H_INF), 1 << 7);
+        assert_eq!(classify(HALF, H_SNAN), 1 << 8);
+        assert_eq!(classify(HALF, H_QNAN), 1 << 9);
+    }
+
+    #[test]
+    fn test_convert_widen_is_exact() {
+        let mut flags = 0;
+        assert_eq!(convert(HALF, SINGLE, H_ONE, RNE, &mut flags), 0x3F80_0000);
+        assert_eq!(
+            convert(HALF, DOUBLE, H_MIN_SUBNORMAL, RNE, &mut flags),
+            (2.0f64).powi(-24).to_bits()
+        );
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_convert_narrow() {
+        let mut flags = 0;
+        // 1/3 (single) → half
+        let third = (1.0f32 / 3.0).to_bits() as u64;
+        assert_eq!(convert(SINGLE, HALF, third, RNE, &mut flags), 0x3555);
+        assert_eq!(flags, FLAG_NX);
+
+        let mut flags = 0;
+        let big = 1.0e6f64.to_bits();
+        assert_eq!(convert(DOUBLE, HALF, big, RNE, &mut flags), H_INF);
+        assert_eq!(flags, FLAG_OF | FLAG_NX);
+    }
+
+    #[test]
+    fn test_convert_nan_is_canonical() {
+        let mut flags = 0;
+        assert_eq!(convert(SINGLE, HALF, 0x7F80_0001, RNE, &mut flags), H_QNAN);
+        assert_eq!(flags, FLAG_NV);
+    }
+
+    #[test]
+    fn test_bfloat16_convert() {
+        let mut flags = 0;
+        // 1.0f32 = 0x3F800000 → bf16 0x3F80 (상위 16비트)
+        assert_eq!(
+            convert(SINGLE, BFLOAT16, 0x3F80_0000, RNE, &mut flags),
+            0x3F80
+        );
+        assert_eq!(
+            convert(BFLOAT16, SINGLE, 0x3F80, RNE, &mut flags),
+            0x3F80_0000
+        );
+        assert_eq!(flags, 0);
+        // 0x3F808000: 정확한 중간값 → 짝수(0x3F80)
+        assert_eq!(
+            convert(SINGLE, BFLOAT16, 0x3F80_8000, RNE, &mut flags),
+            0x3F80
+        );
+        assert_eq!(flags, FLAG_NX);
+        assert_eq!(BFLOAT16.canonical_nan(), 0x7FC0);
+    }
+
+    #[test]
+    fn test_to_int() {
+        let mut flags = 0;
+        assert_eq!(to_int(HALF, 0x4100, RNE, true, 32, &mut flags), 2); // 2.5 → 2
+        assert_eq!(flags, FLAG_NX);
+        let mut flags = 0;
+        assert_eq!(
+            to_int(HALF, 0x4100, RoundingMode::Rmm, true, 32, &mut flags),
+            3
+        );
+        assert_eq!(
+            to_int(HALF, 0xC100, RoundingMode::Rtz, true, 64, &mut flags),
+            (-2i64) as u64
+        );
+    }
+
+    #[test]
+    fn test_to_int_saturates() {
+        let mut flags = 0;
+        assert_eq!(to_int(HALF, H_NEG_INF, RNE, false, 32, &mut flags), 0);
+        assert_eq!(flags, FLAG_NV);
+        let mut flags = 0;
+        // -1.0 → unsigned: 범위 밖
+        assert_eq!(to_int(HALF, 0xBC00, RNE, false, 64, &mut flags), 0);
+        assert_eq!(flags, FLAG_NV);
+        let mut flags = 0;
+        // NaN → 최대값, 32비트 결과는 sign-extend
+        assert_eq!(to_int(HALF, H_QNAN, RNE, false, 32, &mut flags), u64::MAX);
+        assert_eq!(to_int(HALF, H_QNAN, RNE, true, 32, &mut flags), 0x7FFF_FFFF);
+        let mut flags = 0;
+        // 65504 → i64 정확
+        assert_eq!(to_int(HALF, H_MAX, RNE, true, 64, &mut flags), 65504);
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_from_int() {
+        let mut flags = 0;
+        assert_eq!(from_int(HALF, 3, true, 64, RNE, &mut flags), H_THREE);
+        assert_eq!(
+            from_int(HALF, (-2i64) as u64, true, 64, RNE, &mut flags),
+            0xC000
+        );
+        assert_eq!(flags, 0);
+        // 65520은 half 범위를 넘어 반올림 → inf
+        assert_eq!(from_int(HALF, 65520, false, 32, RNE, &mut flags), H_INF);
+        assert_eq!(flags, FLAG_OF | FLAG_NX);
+        // unsigned 32비트: 0xFFFFFFFF
+        let mut flags = 0;
+        assert_eq!(
+            from_int(SINGLE, u64::MAX, false, 32, RNE, &mut flags),
+            0x4F80_0000
+        );
+        assert_eq!(flags, FLAG_NX);
+    }
+
+    #[test]
+    fn test_double_arithmetic() {
+        let d = |value: f64| value.to_bits();
+        let mut flags = 0;
+        assert_eq!(add(DOUBLE, d(0.1), d(0.2), RNE, &mut flags), d(0.1 + 0.2));
+        assert_eq!(mul(DOUBLE, d(1.5), d(-3.0), RNE, &mut flags), d(-4.5));
+        assert_eq!(div(DOUBLE, d(1.0), d(3.0), RNE, &mut flags), d(1.0 / 3.0));
+        assert_eq!(sqrt(DOUBLE, d(2.0), RNE, &mut flags), d(2.0f64.sqrt()));
+        assert_eq!(
+            fma(DOUBLE, d(0.1), d(10.0), d(-1.0), RNE, &mut flags),
+            d(0.1f64.mul_add(10.0, -1.0))
+        );
+        assert_eq!(flags, FLAG_NX);
+
+        let mut flags = 0;
+        assert_eq!(min_max(DOUBLE, d(-0.0), d(0.0), false, &mut flags), d(-0.0));
+        assert!(lt(DOUBLE, d(-1.0), d(0.5), &mut flags));
+        assert!(eq(DOUBLE, d(0.0), d(-0.0), &mut flags));
+        assert_eq!(classify(DOUBLE, d(f64::NEG_INFINITY)), 1 << 0);
+        assert_eq!(flags, 0);
+    }
+
+    #[test]
+    fn test_nan_boxing() {
+        assert_eq!(HALF.nan_box(H_ONE), 0xFFFF_FFFF_FFFF_3C00);
+        assert_eq!(HALF.unbox(0xFFFF_FFFF_FFFF_3C00), H_ONE);
+        // 잘못 boxing된 값은 canonical NaN
+        assert_eq!(HALF.unbox(0x0000_0000_0000_3C00), H_QNAN);
+        assert_eq!(SINGLE.unbox(0xFFFF_FFFF_3F80_0000), 0x3F80_0000);
+        assert_eq!(DOUBLE.unbox(0x1234), 0x1234);
+    }
+}
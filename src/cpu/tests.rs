@@ -1,5 +1,11 @@
 use super::*;
+use crate::cpu::entropy;
+use crate::cpu::extensions::Extensions;
+use crate::cpu::softfloat;
+use crate::cpu::timing;
+use crate::cpu::trigger;
 use crate::csr;
+use crate::devices;
 
 #[test]
 fn test_cpu_init() {
@@ -974,6 +980,51 @@ fn test_sraw_positive() {
     assert_eq!(cpu.read_reg(3), 0x04000000);
 }
 
+#[test]
+fn test_shift_w_uses_low_five_bits() {
+    // rs2 = 32 + 4: *W 시프트는 하위 5비트(4)만 사용
+    for (inst, expected) in [
+        (0x002091BB, 0x0000_0000_0000_0010), // sllw x3, x1, x2
+        (0x0020D1BB, 0x0000_0000_0000_0000), // srlw x3, x1, x2
+        (0x4020D1BB, 0x0000_0000_0000_0000), // sraw x3, x1, x2
+    ] {
+        let mut cpu = Cpu::new(0);
+        cpu.write_reg(1, 1);
+        cpu.write_reg(2, 36);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.read_reg(3), expected, "{:#x}", inst);
+    }
+    // rs2 = 32: 시프트 없이 32비트 결과를 부호 확장
+    for inst in [0x002091BB, 0x0020D1BB, 0x4020D1BB] {
+        let mut cpu = Cpu::new(0);
+        cpu.write_reg(1, 0x8000_0000);
+        cpu.write_reg(2, 32);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_8000_0000, "{:#x}", inst);
+    }
+}
+
+#[test]
+fn test_srliw_zero_shift_sign_extends() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x80000000);
+    cpu.bus.write32(0x80000000, 0x0000D11B); // srliw x2, x1, 0
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0xFFFF_FFFF_8000_0000);
+}
+
+#[test]
+fn test_srlw_zero_shift_sign_extends() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x80000000);
+    cpu.write_reg(2, 0);
+    cpu.bus.write32(0x80000000, 0x0020D1BB); // srlw x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_8000_0000);
+}
+
 // === CSR Instructions ===
 
 #[test]
@@ -1272,6 +1323,70 @@ fn test_mtime_increments_multiple_steps() {
     assert_eq!(cpu.bus.read64(0x200BFF8), 10);
 }
 
+#[test]
+fn test_icount_timebase_ratio() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus
+        .set_timebase(devices::Timebase::icount(1_000_000, 10));
+    for i in 0..40 {
+        cpu.bus.write32(0x80000000 + i * 4, 0x00000013);
+    }
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    cpu.csr.write(csr::MIE, csr::MIE_MTIE);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    // mtimecmp = 3 → 명령어 30개 뒤에 인터럽트
+    cpu.bus.write64(0x2004000, 3);
+
+    for _ in 0..9 {
+        cpu.step();
+    }
+    assert_eq!(cpu.bus.read64(0x200BFF8), 0);
+    cpu.step();
+    assert_eq!(cpu.bus.read64(0x200BFF8), 1);
+
+    for _ in 0..19 {
+        cpu.step();
+    }
+    assert_eq!(cpu.pc, 0x80000000 + 29 * 4);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(
+        cpu.csr.read(csr::MCAUSE),
+        csr::INTERRUPT_BIT | csr::INTERRUPT_FROM_TIMER
+    );
+}
+
+#[test]
+fn test_set_timebase_keeps_elapsed_mtime() {
+    let mut cpu = Cpu::new(0);
+    for i in 0..20 {
+        cpu.bus.write32(0x80000000 + i * 4, 0x00000013);
+    }
+    for _ in 0..5 {
+        cpu.step();
+    }
+    // 기준을 바꿔도 이미 흐른 mtime은 유지되고 이후부터 새 비율 적용
+    cpu.bus
+        .set_timebase(devices::Timebase::icount(1_000_000, 4));
+    assert_eq!(cpu.bus.read64(0x200BFF8), 5);
+    for _ in 0..8 {
+        cpu.step();
+    }
+    assert_eq!(cpu.bus.read64(0x200BFF8), 7);
+}
+
+#[test]
+fn test_load_device_tree_sets_boot_registers() {
+    let mut cpu = Cpu::new(0);
+    let dtb = crate::fdt::machine(Xlen::Rv64, 1, cpu.bus.timebase().frequency);
+    let addr = cpu.load_device_tree(&dtb);
+    assert_eq!(cpu.read_reg(10), 0);
+    assert_eq!(cpu.read_reg(11), addr);
+    assert_eq!(addr % 8, 0);
+    assert!(addr + dtb.len() as u64 <= devices::DRAM_BASE + devices::DRAM_SIZE);
+    assert_eq!(cpu.bus.read32(addr), crate::fdt::FDT_MAGIC.swap_bytes());
+}
+
 // === 타이머 인터럽트 테스트 ===
 
 #[test]
@@ -1308,6 +1423,29 @@ fn test_timer_interrupt_triggers() {
     );
 }
 
+#[test]
+fn test_timer_interrupt_cleared_when_mtimecmp_moves() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MIE, csr::MIE_MTIE);
+    cpu.bus.write64(0x2004000, 2);
+    for i in 0..4 {
+        cpu.bus.write32(0x80000000 + i * 4, 0x00000013);
+    }
+    cpu.step();
+    cpu.step();
+    assert_ne!(cpu.csr.read(csr::MIP) & csr::MIP_MTIP, 0);
+
+    // 핸들러가 mtimecmp를 뒤로 옮기면 다음 step에서 MTIP가 내려감
+    cpu.bus.write64(0x2004000, 100);
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MIP) & csr::MIP_MTIP, 0);
+
+    // MTIP는 장치 선을 따르므로 소프트웨어가 설정해도 유지되지 않음
+    cpu.csr.write(csr::MIP, csr::MIP_MTIP);
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MIP) & csr::MIP_MTIP, 0);
+}
+
 #[test]
 fn test_timer_interrupt_disabled_mie() {
     let mut cpu = Cpu::new(0);
@@ -1405,13 +1543,13 @@ fn test_timer_interrupt_clear_by_mtimecmp_update() {
     for _ in 0..3 {
         cpu.step();
     }
-    assert!(cpu.bus.check_timer_interrupt()); // mtime >= mtimecmp
+    assert!(cpu.bus.check_timer_interrupt(0)); // mtime >= mtimecmp
 
     // mtimecmp를 더 큰 값으로 업데이트 (인터럽트 클리어)
     cpu.bus.write64(0x2004000, 100);
 
     // 인터럽트 조건 해제됨
-    assert!(!cpu.bus.check_timer_interrupt());
+    assert!(!cpu.bus.check_timer_interrupt(0));
 }
 
 #[test]
@@ -2560,6 +2698,24 @@ fn test_remuw_sign_extend() {
     assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_8000_0001);
 }
 
+#[test]
+fn test_div_w_ignores_upper_divisor_bits() {
+    // 하위 32비트가 0인 제수는 0으로 나눈 것
+    for (inst, expected) in [
+        (0x0220C1BB, 0xFFFF_FFFF_FFFF_FFFF), // divw x3, x1, x2
+        (0x0220D1BB, 0xFFFF_FFFF_FFFF_FFFF), // divuw x3, x1, x2
+        (0x0220E1BB, 0xFFFF_FFFF_8000_0007), // remw x3, x1, x2
+        (0x0220F1BB, 0xFFFF_FFFF_8000_0007), // remuw x3, x1, x2
+    ] {
+        let mut cpu = Cpu::new(0);
+        cpu.write_reg(1, 0x8000_0007);
+        cpu.write_reg(2, 1 << 32);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.read_reg(3), expected, "{:#x}", inst);
+    }
+}
+
 #[test]
 fn test_remw_by_zero_sign_extend() {
     // x % 0 = x, with x having bit 31 set
@@ -2819,6 +2975,267 @@ fn test_amoswap_invalidates_reservation() {
     assert!(!cpu.bus.check_reservation(0, addr));
 }
 
+// ==================== A Extension: AMO 산술/논리 ====================
+
+#[test]
+fn test_amoadd_w() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 5);
+    cpu.bus.write32(addr, 0xFFFFFFFF); // -1
+    cpu.bus.write32(0x80000000, 0x0020A1AF); // AMOADD.W x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_FFFFFFFF);
+    assert_eq!(cpu.bus.read32(addr), 4);
+}
+
+#[test]
+fn test_amomin_w_signed() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, (-3i64) as u64);
+    cpu.bus.write32(addr, 7);
+    cpu.bus.write32(0x80000000, 0x8020A1AF); // AMOMIN.W x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 7);
+    assert_eq!(cpu.bus.read32(addr), (-3i32) as u32);
+}
+
+#[test]
+fn test_amo_misaligned_traps() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.write_reg(1, 0x80001002);
+    cpu.bus.write32(0x80000000, 0x0020A1AF); // AMOADD.W x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::STORE_AMO_ADDRESS_MISALIGNED);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x80001002);
+}
+
+// ==================== Zabha: Byte/Halfword AMO ====================
+
+#[test]
+fn test_amoadd_b_wraps_within_byte() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001001;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 1);
+    cpu.bus.write32(0x80001000, 0x1122FF44);
+    cpu.bus.write32(0x80000000, 0x002081AF); // AMOADD.B x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_FFFFFFFF); // 0xFF sign-extend
+    assert_eq!(cpu.bus.read32(0x80001000), 0x11220044); // 이웃 바이트 유지
+}
+
+#[test]
+fn test_amoadd_h() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001002;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0x10);
+    cpu.bus.write16(addr, 0x1234);
+    cpu.bus.write32(0x80000000, 0x002091AF); // AMOADD.H x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x1234);
+    assert_eq!(cpu.bus.read16(addr), 0x1244);
+}
+
+#[test]
+fn test_amomax_h_signed_vs_unsigned() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0x0001);
+
+    // signed: 0x8000(-32768) < 1
+    cpu.bus.write16(addr, 0x8000);
+    cpu.bus.write32(0x80000000, 0xA02091AF); // AMOMAX.H x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_FFFF8000);
+    assert_eq!(cpu.bus.read16(addr), 0x0001);
+
+    // unsigned: 0x8000 > 1
+    cpu.bus.write16(addr, 0x8000);
+    cpu.bus.write32(0x80000004, 0xE02091AF); // AMOMAXU.H x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.bus.read16(addr), 0x8000);
+}
+
+#[test]
+fn test_amoswap_b() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001003;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0xAB);
+    cpu.bus.write8(addr, 0x12);
+    cpu.bus.write32(0x80000000, 0x082081AF); // AMOSWAP.B x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x12);
+    assert_eq!(cpu.bus.read8(addr), 0xAB);
+}
+
+#[test]
+fn test_amoadd_h_misaligned_traps() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.write_reg(1, 0x80001001);
+    cpu.bus.write32(0x80000000, 0x002091AF); // AMOADD.H x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::STORE_AMO_ADDRESS_MISALIGNED);
+}
+
+// ==================== Zacas: Compare-and-Swap ====================
+
+#[test]
+fn test_amocas_w_success() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0x55);
+    cpu.write_reg(3, 0xFFFFFFFF_DEADBEEF); // expected (하위 32비트만 비교)
+    cpu.bus.write32(addr, 0xDEADBEEF);
+    cpu.bus.write32(0x80000000, 0x2820A1AF); // AMOCAS.W x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_DEADBEEF);
+    assert_eq!(cpu.bus.read32(addr), 0x55);
+}
+
+#[test]
+fn test_amocas_w_failure_does_not_store() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0x55);
+    cpu.write_reg(3, 0x1);
+    cpu.bus.write32(addr, 0x2);
+    cpu.bus.write32(0x80000000, 0x2820A1AF); // AMOCAS.W x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x2);
+    assert_eq!(cpu.bus.read32(addr), 0x2);
+}
+
+#[test]
+fn test_amocas_d() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0xCAFEBABE_00000000);
+    cpu.write_reg(3, 0x12345678_9ABCDEF0);
+    cpu.bus.write64(addr, 0x12345678_9ABCDEF0);
+    cpu.bus.write32(0x80000000, 0x2820B1AF); // AMOCAS.D x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x12345678_9ABCDEF0);
+    assert_eq!(cpu.bus.read64(addr), 0xCAFEBABE_00000000);
+}
+
+#[test]
+fn test_amocas_b() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001001;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(2, 0x7F);
+    cpu.write_reg(3, 0xFFFFFFFF_FFFFFF80); // expected = 0x80
+    cpu.bus.write8(addr, 0x80);
+    cpu.bus.write32(0x80000000, 0x282081AF); // AMOCAS.B x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_FFFFFF80);
+    assert_eq!(cpu.bus.read8(addr), 0x7F);
+}
+
+#[test]
+fn test_amocas_q_success() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(4, 0x1111); // expected (low)
+    cpu.write_reg(5, 0x2222); // expected (high)
+    cpu.write_reg(6, 0xAAAA); // new (low)
+    cpu.write_reg(7, 0xBBBB); // new (high)
+    cpu.bus.write64(addr, 0x1111);
+    cpu.bus.write64(addr + 8, 0x2222);
+    cpu.bus.write32(0x80000000, 0x2860C22F); // AMOCAS.Q x4, x6, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(4), 0x1111);
+    assert_eq!(cpu.read_reg(5), 0x2222);
+    assert_eq!(cpu.bus.read64(addr), 0xAAAA);
+    assert_eq!(cpu.bus.read64(addr + 8), 0xBBBB);
+}
+
+#[test]
+fn test_amocas_q_failure_returns_old_pair() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80001000;
+    cpu.write_reg(1, addr);
+    cpu.write_reg(4, 0x1111);
+    cpu.write_reg(5, 0x0); // high 불일치
+    cpu.write_reg(6, 0xAAAA);
+    cpu.write_reg(7, 0xBBBB);
+    cpu.bus.write64(addr, 0x1111);
+    cpu.bus.write64(addr + 8, 0x2222);
+    cpu.bus.write32(0x80000000, 0x2860C22F); // AMOCAS.Q x4, x6, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(4), 0x1111);
+    assert_eq!(cpu.read_reg(5), 0x2222);
+    assert_eq!(cpu.bus.read64(addr), 0x1111);
+    assert_eq!(cpu.bus.read64(addr + 8), 0x2222);
+}
+
+#[test]
+fn test_amocas_q_odd_register_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.write_reg(1, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x2860C1AF); // AMOCAS.Q x3, x6, (x1)
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x2860C1AF);
+}
+
+#[test]
+fn test_amocas_and_narrow_amo_illegal_when_disabled() {
+    let without = |ext: Extensions, inst: u32| {
+        let mut cpu = Cpu::new(0);
+        cpu.csr.write(csr::MTVEC, 0x80000100);
+        cpu.set_extensions(ext).unwrap();
+        cpu.write_reg(1, 0x80001000);
+        cpu.write_reg(2, 5);
+        cpu.bus.write64(0x80001000, 7);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80000100, "{inst:#x}");
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+        assert_eq!(cpu.csr.read(csr::MTVAL), inst as u64);
+        assert_eq!(cpu.bus.read64(0x80001000), 7);
+    };
+    let no_zacas = Extensions {
+        zacas: false,
+        ..Extensions::default()
+    };
+    let no_zabha = Extensions {
+        zabha: false,
+        ..Extensions::default()
+    };
+    without(no_zacas, 0x2820A1AF); // AMOCAS.W x3, x2, (x1)
+    without(no_zacas, 0x282081AF); // AMOCAS.B x3, x2, (x1)
+    without(no_zabha, 0x282081AF); // AMOCAS.B x3, x2, (x1)
+    without(no_zabha, 0x002081AF); // AMOADD.B x3, x2, (x1)
+
+    // Zabha 없이도 W/D AMO는 그대로 동작
+    let mut cpu = Cpu::new(0);
+    cpu.set_extensions(no_zabha).unwrap();
+    cpu.write_reg(1, 0x80001000);
+    cpu.write_reg(2, 5);
+    cpu.bus.write64(0x80001000, 7);
+    cpu.bus.write32(0x80000000, 0x0020B1AF); // AMOADD.D x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 7);
+    assert_eq!(cpu.bus.read64(0x80001000), 12);
+}
+
 // ==================== RV64I Large Shift Tests ====================
 
 #[test]
@@ -2870,3 +3287,2623 @@ fn test_slli_shamt_32() {
     cpu.step();
     assert_eq!(cpu.read_reg(3), 0xFFFFFFFF_00000000);
 }
+
+// ==================== Sdtrig: 트리거 ====================
+
+fn mcontrol6(flags: u64, match_type: u64) -> u64 {
+    (trigger::TYPE_MCONTROL6 << 60) | (match_type << 7) | flags
+}
+
+#[test]
+fn test_trigger_csr_access_via_instructions() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 1);
+    cpu.bus.write32(0x80000000, 0x7A009073); // csrrw x0, tselect, x1
+    cpu.bus.write32(0x80000004, 0x7A002173); // csrrs x2, tselect, x0
+    cpu.bus.write32(0x80000008, 0x7A4021F3); // csrrs x3, tinfo, x0
+    cpu.step();
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 1);
+    assert_ne!(cpu.read_reg(3) & (1 << trigger::TYPE_MCONTROL6), 0);
+}
+
+#[test]
+fn test_execute_trigger_raises_breakpoint() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let flags = trigger::MCONTROL6_EXECUTE | trigger::MCONTROL6_U;
+    cpu.triggers
+        .write(csr::TDATA1, mcontrol6(flags, trigger::MATCH_EQUAL));
+    cpu.triggers.write(csr::TDATA2, 0x80000004);
+
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.bus.write32(0x80000004, 0x00200113); // addi x2, x0, 2
+
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 1);
+
+    cpu.step();
+    // 명령어 실행 전에 breakpoint 예외
+    assert_eq!(cpu.read_reg(2), 0);
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::BREAKPOINT);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000004);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x80000004);
+    assert_eq!(cpu.mode, PrivilegeMode::Machine);
+}
+
+#[test]
+fn test_execute_trigger_ignored_in_m_mode_with_mie_clear() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let flags = trigger::MCONTROL6_EXECUTE | trigger::MCONTROL6_M;
+    cpu.triggers
+        .write(csr::TDATA1, mcontrol6(flags, trigger::MATCH_EQUAL));
+    cpu.triggers.write(csr::TDATA2, 0x80000000);
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 1);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_store_watchpoint() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let flags = trigger::MCONTROL6_STORE | trigger::MCONTROL6_S;
+    cpu.triggers
+        .write(csr::TDATA1, mcontrol6(flags, trigger::MATCH_EQUAL));
+    cpu.triggers.write(csr::TDATA2, 0x80002008);
+
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(2, 0x55);
+    cpu.bus.write32(0x80000000, 0x0020A423); // sw x2, 8(x1)
+    cpu.step();
+
+    // store는 수행되지 않음
+    assert_eq!(cpu.bus.read32(0x80002008), 0);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::BREAKPOINT);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000000);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x80002008);
+}
+
+#[test]
+fn test_load_data_watchpoint() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let flags = trigger::MCONTROL6_LOAD | trigger::MCONTROL6_U | trigger::MCONTROL6_SELECT;
+    cpu.triggers
+        .write(csr::TDATA1, mcontrol6(flags, trigger::MATCH_EQUAL));
+    cpu.triggers.write(csr::TDATA2, 0xFF);
+
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write8(0x80002000, 0x7F);
+    cpu.bus.write8(0x80002001, 0xFF);
+    cpu.bus.write32(0x80000000, 0x00008103); // lb x2, 0(x1)
+    cpu.bus.write32(0x80000004, 0x00108183); // lb x3, 1(x1)
+
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0x7F);
+
+    cpu.step();
+    // 값이 일치하면 rd에 쓰기 전에 breakpoint
+    assert_eq!(cpu.read_reg(3), 0);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::BREAKPOINT);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000004);
+}
+
+#[test]
+fn test_icount_single_step() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    // icount: count=1, u=1
+    cpu.triggers.write(
+        csr::TDATA1,
+        (trigger::TYPE_ICOUNT << 60) | (1 << 10) | trigger::ICOUNT_U,
+    );
+
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.bus.write32(0x80000004, 0x00200113); // addi x2, x0, 2
+
+    cpu.step(); // 한 명령어 실행 후 pending
+    assert_eq!(cpu.read_reg(1), 1);
+    assert_eq!(cpu.pc, 0x80000004);
+
+    cpu.step(); // 다음 명령어 실행 전에 breakpoint
+    assert_eq!(cpu.read_reg(2), 0);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::BREAKPOINT);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000004);
+    assert_ne!(cpu.triggers.read(csr::TDATA1) & trigger::ICOUNT_HIT, 0);
+}
+
+// ==================== Sdext: Debug Mode ====================
+
+#[test]
+fn test_ebreak_enters_debug_mode_when_ebreakm_set() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let dcsr = cpu.csr.read(csr::DCSR);
+    cpu.csr.write(csr::DCSR, dcsr | csr::DCSR_EBREAKM);
+
+    cpu.bus.write32(0x80000000, 0x00100073); // ebreak
+    cpu.step();
+
+    assert!(cpu.debug_mode);
+    assert_eq!(cpu.pc, 0x80000000);
+    assert_eq!(cpu.csr.read(csr::DPC), 0x80000000);
+    let dcsr = cpu.csr.read(csr::DCSR);
+    assert_eq!((dcsr & csr::DCSR_CAUSE) >> 6, csr::DEBUG_CAUSE_EBREAK);
+    assert_eq!(dcsr & csr::DCSR_PRV, 3);
+    // mcause는 변경되지 않음
+    assert_eq!(cpu.csr.read(csr::MCAUSE), 0);
+}
+
+#[test]
+fn test_ebreak_traps_when_ebreaku_clear() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let dcsr = cpu.csr.read(csr::DCSR);
+    cpu.csr.write(csr::DCSR, dcsr | csr::DCSR_EBREAKM); // U 모드는 해당 없음
+
+    cpu.bus.write32(0x80000000, 0x00100073); // ebreak
+    cpu.step();
+
+    assert!(!cpu.debug_mode);
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::BREAKPOINT);
+}
+
+#[test]
+fn test_debug_mode_halts_hart() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.halt_request();
+
+    let mtime = cpu.bus.read64(0x200BFF8);
+    cpu.step();
+    cpu.step();
+
+    assert_eq!(cpu.read_reg(1), 0);
+    assert_eq!(cpu.pc, 0x80000000);
+    assert_eq!(cpu.bus.read64(0x200BFF8), mtime); // stoptime
+    let dcsr = cpu.csr.read(csr::DCSR);
+    assert_eq!((dcsr & csr::DCSR_CAUSE) >> 6, csr::DEBUG_CAUSE_HALTREQ);
+}
+
+#[test]
+fn test_halt_inspect_resume() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.bus.write32(0x80000004, 0x00108093); // addi x1, x1, 1
+    cpu.step();
+
+    cpu.halt_request();
+    assert_eq!(cpu.mode, PrivilegeMode::Machine);
+    assert_eq!(cpu.read_reg(1), 1);
+    // 디버거가 레지스터 수정
+    cpu.write_reg(1, 10);
+
+    cpu.resume();
+    assert!(!cpu.debug_mode);
+    assert_eq!(cpu.mode, PrivilegeMode::User);
+    assert_eq!(cpu.pc, 0x80000004);
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 11);
+}
+
+#[test]
+fn test_dcsr_illegal_outside_debug_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x7B0022F3); // csrrs x5, dcsr, x0
+    cpu.step();
+
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x7B0022F3);
+}
+
+#[test]
+fn test_dret_illegal_outside_debug_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x7B200073); // dret
+    cpu.step();
+
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_execute_debug_program_buffer() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.halt_request();
+
+    // csrrs x5, dcsr, x0
+    assert!(cpu.execute_debug(0x7B0022F3));
+    assert_ne!(cpu.read_reg(5) & csr::DCSR_XDEBUGVER, 0);
+    assert_eq!(cpu.pc, 0x80000000);
+
+    // 예외는 보고만 되고 트랩하지 않음
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    assert!(!cpu.execute_debug(0x00000073)); // ecall
+    assert!(cpu.debug_mode);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), 0);
+
+    // dret
+    assert!(cpu.execute_debug(0x7B200073));
+    assert!(!cpu.debug_mode);
+    assert_eq!(cpu.pc, 0x80000000);
+}
+
+#[test]
+fn test_dcsr_step_single_steps() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.bus.write32(0x80000004, 0x00200113); // addi x2, x0, 2
+    cpu.halt_request();
+
+    // csrrw x0, dcsr, x1 (step=1)
+    let dcsr = cpu.csr.read(csr::DCSR);
+    cpu.write_reg(1, dcsr | csr::DCSR_STEP);
+    assert!(cpu.execute_debug(0x7B009073));
+    cpu.write_reg(1, 0);
+
+    cpu.resume();
+    cpu.step();
+    assert!(cpu.debug_mode);
+    assert_eq!(cpu.read_reg(1), 1);
+    assert_eq!(cpu.csr.read(csr::DPC), 0x80000004);
+    let dcsr = cpu.csr.read(csr::DCSR);
+    assert_eq!((dcsr & csr::DCSR_CAUSE) >> 6, csr::DEBUG_CAUSE_STEP);
+
+    cpu.resume();
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 2);
+    assert_eq!(cpu.csr.read(csr::DPC), 0x80000008);
+}
+
+#[test]
+fn test_single_step_into_trap_handler() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x00000073); // ecall
+    cpu.halt_request();
+    let dcsr = cpu.csr.read(csr::DCSR);
+    cpu.csr.write(csr::DCSR, dcsr | csr::DCSR_STEP);
+
+    cpu.resume();
+    cpu.step();
+    // 트랩 핸들러 첫 명령어에서 정지
+    assert!(cpu.debug_mode);
+    assert_eq!(cpu.csr.read(csr::DPC), 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ECALL_FROM_M);
+}
+
+#[test]
+fn test_trigger_action_enters_debug_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00100093); // addi x1, x0, 1
+    cpu.bus.write32(0x80000004, 0x00200113); // addi x2, x0, 2
+    cpu.halt_request();
+
+    let flags = trigger::MCONTROL6_EXECUTE | trigger::MCONTROL6_M | trigger::TDATA1_DMODE;
+    cpu.triggers.debug_write(
+        csr::TDATA1,
+        mcontrol6(flags, trigger::MATCH_EQUAL) | (trigger::ACTION_DEBUG_MODE << 12),
+    );
+    cpu.triggers.debug_write(csr::TDATA2, 0x80000004);
+    cpu.resume();
+
+    cpu.step();
+    cpu.step();
+    assert!(cpu.debug_mode);
+    assert_eq!(cpu.read_reg(2), 0);
+    assert_eq!(cpu.csr.read(csr::DPC), 0x80000004);
+    let dcsr = cpu.csr.read(csr::DCSR);
+    assert_eq!((dcsr & csr::DCSR_CAUSE) >> 6, csr::DEBUG_CAUSE_TRIGGER);
+}
+
+// ==================== Zicbom/Zicboz/Zicbop ====================
+
+#[test]
+fn test_cbo_zero_clears_block() {
+    let mut cpu = Cpu::new(0);
+    for offset in (0..0x100).step_by(8) {
+        cpu.bus.write64(0x80002000 + offset, 0xFFFF_FFFF_FFFF_FFFF);
+    }
+    cpu.write_reg(1, 0x80002050); // 블록 중간 주소
+    cpu.bus.write32(0x80000000, 0x0040A00F); // cbo.zero (x1)
+    cpu.step();
+
+    assert_eq!(cpu.pc, 0x80000004);
+    // 0x80002040 ~ 0x8000207F (64바이트) 0으로
+    assert_eq!(cpu.bus.read64(0x80002038), 0xFFFF_FFFF_FFFF_FFFF);
+    for offset in (0x40..0x80).step_by(8) {
+        assert_eq!(cpu.bus.read64(0x80002000 + offset), 0);
+    }
+    assert_eq!(cpu.bus.read64(0x80002080), 0xFFFF_FFFF_FFFF_FFFF);
+}
+
+#[test]
+fn test_cbo_zero_configurable_block_size() {
+    let mut cpu = Cpu::new(0);
+    cpu.cache_block_size = 16;
+    cpu.bus.write64(0x80002010, 0x1111);
+    cpu.bus.write64(0x80002018, 0x2222);
+    cpu.bus.write64(0x80002020, 0x3333);
+    cpu.write_reg(1, 0x8000201C);
+    cpu.bus.write32(0x80000000, 0x0040A00F); // cbo.zero (x1)
+    cpu.step();
+
+    assert_eq!(cpu.bus.read64(0x80002010), 0);
+    assert_eq!(cpu.bus.read64(0x80002018), 0);
+    assert_eq!(cpu.bus.read64(0x80002020), 0x3333);
+}
+
+#[test]
+fn test_cbo_zero_requires_menvcfg_cbze_in_s_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write64(0x80002000, 0x1234);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0040A00F); // cbo.zero (x1)
+    cpu.step();
+
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x0040A00F);
+    assert_eq!(cpu.bus.read64(0x80002000), 0x1234);
+
+    // CBZE 활성화 후 재실행
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.pc = 0x80000000;
+    cpu.csr.write(csr::MENVCFG, csr::ENVCFG_CBZE);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert_eq!(cpu.bus.read64(0x80002000), 0);
+}
+
+#[test]
+fn test_cbo_zero_in_u_mode_requires_senvcfg() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.csr.write(csr::MENVCFG, csr::ENVCFG_CBZE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0040A00F); // cbo.zero (x1)
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+
+    cpu.mode = PrivilegeMode::User;
+    cpu.pc = 0x80000000;
+    cpu.csr.write(csr::SENVCFG, csr::ENVCFG_CBZE);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_cbo_clean_flush_require_cbcfe() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0010A00F); // cbo.clean (x1)
+    cpu.bus.write32(0x80000004, 0x0020A00F); // cbo.flush (x1)
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.pc = 0x80000000;
+    cpu.csr.write(csr::MENVCFG, csr::ENVCFG_CBCFE);
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000008);
+}
+
+#[test]
+fn test_cbo_inval_requires_cbie() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0000A00F); // cbo.inval (x1)
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+
+    // CBIE=01 (flush로 동작)
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.pc = 0x80000000;
+    cpu.csr.write(csr::MENVCFG, csr::CBIE_FLUSH << 4);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+
+    // CBIE=10은 예약된 값이므로 illegal
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.pc = 0x80000000;
+    cpu.csr.write(csr::MENVCFG, 0b10 << 4);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_cbo_always_allowed_in_m_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0000A00F); // cbo.inval (x1)
+    cpu.bus.write32(0x80000004, 0x0010A00F); // cbo.clean (x1)
+    cpu.bus.write32(0x80000008, 0x0020A00F); // cbo.flush (x1)
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.pc, 0x8000000C);
+}
+
+#[test]
+fn test_prefetch_hint_is_noop() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x0010E013); // prefetch.r 0(x1)
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert_eq!(cpu.read_reg(0), 0);
+}
+
+// ==================== Zk: 스칼라 암호 ====================
+
+#[test]
+fn test_andn_executes() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0b1100);
+    cpu.write_reg(2, 0b1010);
+    cpu.bus.write32(0x80000000, 0x4020F1B3); // andn x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0b0100);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_aes64esm_executes() {
+    // FIPS-197 Appendix B: round 1 (SubBytes → ShiftRows → MixColumns)
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x2be2f4a0_bee33d19);
+    cpu.write_reg(2, 0x0848f8e9_2a8dc69a);
+    cpu.bus.write32(0x80000000, 0x362081B3); // aes64esm x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x9a19cbe0_e5816604);
+}
+
+#[test]
+fn test_sha256sig0_executes() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 8);
+    cpu.bus.write32(0x80000000, 0x10209193); // sha256sig0 x3, x1
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x1002_0001);
+}
+
+#[test]
+fn test_crypto_disabled_extension_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.extensions.zkne = false;
+    cpu.bus.write32(0x80000000, 0x362081B3); // aes64esm x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x362081B3);
+}
+
+#[test]
+fn test_crypto_other_extensions_unaffected() {
+    // Zkne만 꺼도 Zbkb는 동작
+    let mut cpu = Cpu::new(0);
+    cpu.extensions.zkne = false;
+    cpu.write_reg(1, 0b1100);
+    cpu.write_reg(2, 0b1010);
+    cpu.bus.write32(0x80000000, 0x4020F1B3); // andn x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0b0100);
+}
+
+#[test]
+fn test_aes64ks1i_reserved_rnum_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.bus.write32(0x80000000, 0x31B09193); // aes64ks1i x3, x1, 0xB
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_seed_csr_read_write() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x015012F3); // csrrw x5, seed, x0
+    cpu.step();
+    let value = cpu.read_reg(5);
+    assert_eq!(value & !entropy::SEED_ENTROPY_MASK, entropy::SEED_OPST_ES16);
+}
+
+#[test]
+fn test_seed_csr_is_deterministic_per_seed() {
+    let read_seed = |seed: u64| {
+        let mut cpu = Cpu::new(0);
+        cpu.entropy = entropy::EntropySource::new(seed);
+        cpu.bus.write32(0x80000000, 0x015012F3); // csrrw x5, seed, x0
+        cpu.bus.write32(0x80000004, 0x01501373); // csrrw x6, seed, x0
+        cpu.step();
+        cpu.step();
+        (cpu.read_reg(5), cpu.read_reg(6))
+    };
+    assert_eq!(read_seed(7), read_seed(7));
+    assert_ne!(read_seed(7), read_seed(8));
+}
+
+#[test]
+fn test_seed_csr_read_only_access_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.bus.write32(0x80000000, 0x015022F3); // csrrs x5, seed, x0
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_seed_csr_supervisor_needs_sseed() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.bus.write32(0x80000000, 0x015012F3); // csrrw x5, seed, x0
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.csr.write(csr::MSECCFG, csr::MSECCFG_SSEED);
+    cpu.bus.write32(0x80000000, 0x015012F3);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert_ne!(cpu.read_reg(5), 0);
+}
+
+#[test]
+fn test_seed_csr_disabled_zkr_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.extensions.zkr = false;
+    cpu.bus.write32(0x80000000, 0x015012F3); // csrrw x5, seed, x0
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+// ==================== Zfh/Zfhmin/Zfbfmin: 반정밀도 부동소수점 ====================
+
+// NaN-boxing된 half 값의 상위 비트
+const HALF_BOX: u64 = 0xFFFF_FFFF_FFFF_0000;
+
+fn fp_cpu() -> Cpu {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.csr.write(csr::MSTATUS, csr::FS_INITIAL);
+    cpu
+}
+
+#[test]
+fn test_fp_disabled_when_fs_off() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80000100);
+    cpu.bus.write32(0x80000000, 0x0420F1D3); // fadd.h f3, f1, f2
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_flh_fsh_nan_boxing() {
+    let mut cpu = fp_cpu();
+    cpu.write_reg(1, 0x80001000);
+    cpu.bus.write16(0x80001000, 0x3C00); // 1.0
+    cpu.bus.write32(0x80000000, 0x00009087); // flh f1, 0(x1)
+    cpu.bus.write32(0x80000004, 0x00109227); // fsh f1, 4(x1)
+    cpu.step();
+    assert_eq!(cpu.read_freg(1), 0xFFFF_FFFF_FFFF_3C00);
+    cpu.step();
+    assert_eq!(cpu.bus.read16(0x80001004), 0x3C00);
+    // FS = Dirty
+    assert_eq!(cpu.csr.read(csr::MSTATUS) & csr::MSTATUS_FS, csr::FS_DIRTY);
+}
+
+#[test]
+fn test_fadd_h() {
+    let mut cpu = fp_cpu();
+    cpu.write_reg(1, 0x3C00); // 1.0
+    cpu.write_reg(2, 0x4000); // 2.0
+    cpu.bus.write32(0x80000000, 0xF40080D3); // fmv.h.x f1, x1
+    cpu.bus.write32(0x80000004, 0xF4010153); // fmv.h.x f2, x2
+    cpu.bus.write32(0x80000008, 0x0420F1D3); // fadd.h f3, f1, f2
+    cpu.bus.write32(0x8000000C, 0xE40181D3); // fmv.x.h x3, f3
+    for _ in 0..4 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_reg(3), 0x4200); // 3.0
+    assert_eq!(cpu.csr.read(csr::FCSR), 0);
+}
+
+#[test]
+fn test_fmv_x_h_sign_extends() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[3] = 0xFFFF_FFFF_FFFF_BC00; // -1.0
+    cpu.bus.write32(0x80000000, 0xE40181D3); // fmv.x.h x3, f3
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_FFFF_BC00);
+}
+
+#[test]
+fn test_fdiv_h_sets_fflags() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x3C00; // 1.0
+    cpu.fregs[2] = HALF_BOX; // +0.0
+    cpu.bus.write32(0x80000000, 0x1C20F1D3); // fdiv.h f3, f1, f2
+    cpu.bus.write32(0x80000004, 0x00102573); // csrrs x10, fflags, x0
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0x7C00); // +inf
+    assert_eq!(cpu.read_reg(10), softfloat::FLAG_DZ);
+}
+
+#[test]
+fn test_fadd_h_static_and_dynamic_rounding() {
+    // 2048 + 1: RNE → 2048, RUP → 2050
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x6800;
+    cpu.fregs[2] = HALF_BOX | 0x3C00;
+    cpu.bus.write32(0x80000000, 0x0420B1D3); // fadd.h f3, f1, f2, rup
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0x6801);
+    assert_eq!(cpu.csr.read(csr::FCSR), softfloat::FLAG_NX);
+
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x6800;
+    cpu.fregs[2] = HALF_BOX | 0x3C00;
+    cpu.write_reg(11, 3); // frm = RUP
+    cpu.bus.write32(0x80000000, 0x00259073); // csrrw x0, frm, x11
+    cpu.bus.write32(0x80000004, 0x0420F1D3); // fadd.h f3, f1, f2, dyn
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0x6801);
+}
+
+#[test]
+fn test_fp_reserved_rounding_mode_is_illegal() {
+    let mut cpu = fp_cpu();
+    cpu.bus.write32(0x80000000, 0x0420D1D3); // fadd.h f3, f1, f2, rm=5
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_unboxed_half_reads_as_nan() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = 0x3C00; // boxing 안 됨 → canonical NaN
+    cpu.fregs[2] = HALF_BOX | 0x3C00;
+    cpu.bus.write32(0x80000000, 0x0420F1D3); // fadd.h f3, f1, f2
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0x7E00);
+}
+
+#[test]
+fn test_fcvt_between_half_single_double() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x3555; // ~1/3
+    cpu.bus.write32(0x80000000, 0x40208253); // fcvt.s.h f4, f1
+    cpu.bus.write32(0x80000004, 0xE0020253); // fmv.x.w x4, f4
+    cpu.bus.write32(0x80000008, 0x42208353); // fcvt.d.h f6, f1
+    cpu.bus.write32(0x8000000C, 0xE2030353); // fmv.x.d x6, f6
+    cpu.bus.write32(0x80000010, 0x440272D3); // fcvt.h.s f5, f4
+    for _ in 0..5 {
+        cpu.step();
+    }
+    let expected = 0.333251953125f64;
+    assert_eq!(
+        cpu.read_reg(4),
+        (expected as f32).to_bits() as i32 as i64 as u64
+    );
+    assert_eq!(cpu.read_reg(6), expected.to_bits());
+    assert_eq!(cpu.read_freg(5), HALF_BOX | 0x3555);
+    assert_eq!(cpu.csr.read(csr::FCSR), 0);
+}
+
+#[test]
+fn test_fcvt_int_half() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0xC100; // -2.5
+    cpu.bus.write32(0x80000000, 0xC40093D3); // fcvt.w.h x7, f1, rtz
+    cpu.bus.write32(0x80000004, 0xD403F3D3); // fcvt.h.w f7, x7
+    cpu.step();
+    assert_eq!(cpu.read_reg(7), (-2i64) as u64);
+    assert_eq!(cpu.csr.read(csr::FCSR), softfloat::FLAG_NX);
+    cpu.step();
+    assert_eq!(cpu.read_freg(7), HALF_BOX | 0xC000);
+}
+
+#[test]
+fn test_fcompare_and_fclass_half() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x3C00;
+    cpu.fregs[2] = HALF_BOX | 0x4000;
+    cpu.bus.write32(0x80000000, 0xA4209453); // flt.h x8, f1, f2
+    cpu.bus.write32(0x80000004, 0xE40094D3); // fclass.h x9, f1
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_reg(8), 1);
+    assert_eq!(cpu.read_reg(9), 1 << 6); // positive normal
+}
+
+#[test]
+fn test_fsgnjn_h_negates() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x3C00;
+    cpu.bus.write32(0x80000000, 0x241091D3); // fsgnjn.h f3, f1, f1 (fneg.h)
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0xBC00);
+}
+
+#[test]
+fn test_fmadd_h() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = HALF_BOX | 0x4000; // 2.0
+    cpu.fregs[2] = HALF_BOX | 0x4200; // 3.0
+    cpu.fregs[4] = HALF_BOX | 0x3C00; // 1.0
+    cpu.bus.write32(0x80000000, 0x2420F1C3); // fmadd.h f3, f1, f2, f4
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), HALF_BOX | 0x4700); // 7.0
+}
+
+#[test]
+fn test_bf16_conversions() {
+    let mut cpu = fp_cpu();
+    cpu.write_reg(4, 0x4049_0FDB); // pi (single)
+    cpu.bus.write32(0x80000000, 0xF0020253); // fmv.w.x f4, x4
+    cpu.bus.write32(0x80000004, 0x448272D3); // fcvt.bf16.s f5, f4
+    cpu.bus.write32(0x80000008, 0x4062F253); // fcvt.s.bf16 f4, f5
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_freg(5), HALF_BOX | 0x4049);
+    assert_eq!(cpu.csr.read(csr::FCSR), softfloat::FLAG_NX);
+    cpu.step();
+    assert_eq!(cpu.read_freg(4), 0xFFFF_FFFF_4049_0000);
+}
+
+#[test]
+fn test_zfhmin_only_rejects_arithmetic() {
+    let mut cpu = fp_cpu();
+    cpu.extensions.zfh = false;
+    cpu.extensions.zfhmin = true;
+    cpu.fregs[1] = HALF_BOX | 0x3C00;
+    // 변환은 허용
+    cpu.bus.write32(0x80000000, 0x40208253); // fcvt.s.h f4, f1
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    // 산술은 illegal
+    cpu.bus.write32(0x80000004, 0x0420F1D3); // fadd.h f3, f1, f2
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_zfbfmin_only_allows_flh_and_bf16() {
+    let mut cpu = fp_cpu();
+    cpu.extensions.zfh = false;
+    cpu.extensions.zfhmin = false;
+    cpu.write_reg(1, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x00009087); // flh f1, 0(x1)
+    cpu.bus.write32(0x80000004, 0x40208253); // fcvt.s.h f4, f1
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+// ==================== F/D: 단정밀도/배정밀도 부동소수점 ====================
+
+// NaN-boxing된 single 값의 상위 비트
+const SINGLE_BOX: u64 = 0xFFFF_FFFF_0000_0000;
+
+fn single(value: f32) -> u64 {
+    SINGLE_BOX | value.to_bits() as u64
+}
+
+#[test]
+fn test_misa_advertises_f_and_d() {
+    let misa = Cpu::new(0).csr.read(csr::MISA);
+    assert_ne!(misa & csr::MISA_F, 0);
+    assert_ne!(misa & csr::MISA_D, 0);
+}
+
+#[test]
+fn test_fadd_s() {
+    let mut cpu = fp_cpu();
+    cpu.write_reg(1, 1.5f32.to_bits() as u64);
+    cpu.write_reg(2, 2.25f32.to_bits() as u64);
+    cpu.bus.write32(0x80000000, 0xF00080D3); // fmv.w.x f1, x1
+    cpu.bus.write32(0x80000004, 0xF0010153); // fmv.w.x f2, x2
+    cpu.bus.write32(0x80000008, 0x0020F1D3); // fadd.s f3, f1, f2
+    cpu.bus.write32(0x8000000C, 0xE00181D3); // fmv.x.w x3, f3
+    for _ in 0..4 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_freg(3), single(3.75));
+    assert_eq!(cpu.read_reg(3), 3.75f32.to_bits() as u64);
+    assert_eq!(cpu.csr.read(csr::FCSR), 0);
+}
+
+#[test]
+fn test_fdiv_s_sets_fflags() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = single(1.0);
+    cpu.fregs[2] = single(3.0);
+    cpu.bus.write32(0x80000000, 0x1820F1D3); // fdiv.s f3, f1, f2
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), single(1.0 / 3.0));
+    assert_eq!(cpu.csr.read(csr::FCSR), softfloat::FLAG_NX);
+}
+
+#[test]
+fn test_fld_fmul_d_fsd() {
+    let mut cpu = fp_cpu();
+    cpu.write_reg(1, 0x80001000);
+    cpu.bus.write64(0x80001000, 1.5f64.to_bits());
+    cpu.bus.write64(0x80001008, (-0.1f64).to_bits());
+    cpu.bus.write32(0x80000000, 0x0000B087); // fld f1, 0(x1)
+    cpu.bus.write32(0x80000004, 0x0080B107); // fld f2, 8(x1)
+    cpu.bus.write32(0x80000008, 0x1220F1D3); // fmul.d f3, f1, f2
+    cpu.bus.write32(0x8000000C, 0x0030B827); // fsd f3, 16(x1)
+    for _ in 0..4 {
+        cpu.step();
+    }
+    assert_eq!(cpu.bus.read64(0x80001010), (1.5f64 * -0.1).to_bits());
+}
+
+#[test]
+fn test_fcvt_between_single_double_and_int() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[3] = single(-2.75);
+    cpu.bus.write32(0x80000000, 0x4201F253); // fcvt.d.s f4, f3
+    cpu.bus.write32(0x80000004, 0xC22212D3); // fcvt.l.d x5, f4, rtz
+    cpu.bus.write32(0x80000008, 0x401272D3); // fcvt.s.d f5, f4
+    cpu.bus.write32(0x8000000C, 0xD222F353); // fcvt.d.l f6, x5
+    cpu.step();
+    assert_eq!(cpu.read_freg(4), (-2.75f64).to_bits());
+    cpu.step();
+    assert_eq!(cpu.read_reg(5), (-2i64) as u64);
+    assert_eq!(cpu.csr.read(csr::FCSR), softfloat::FLAG_NX);
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_freg(5), single(-2.75));
+    assert_eq!(cpu.read_freg(6), (-2.0f64).to_bits());
+}
+
+#[test]
+fn test_fmadd_d_compare_and_fclass() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = 0.1f64.to_bits();
+    cpu.fregs[2] = 10.0f64.to_bits();
+    cpu.fregs[4] = (-1.0f64).to_bits();
+    cpu.bus.write32(0x80000000, 0x2220F1C3); // fmadd.d f3, f1, f2, f4
+    cpu.bus.write32(0x80000004, 0xA2209453); // flt.d x8, f1, f2
+    cpu.bus.write32(0x80000008, 0xE20094D3); // fclass.d x9, f1
+    for _ in 0..3 {
+        cpu.step();
+    }
+    // 반올림이 한 번이므로 0이 아님
+    assert_eq!(cpu.read_freg(3), 0.1f64.mul_add(10.0, -1.0).to_bits());
+    assert_eq!(cpu.read_reg(8), 1);
+    assert_eq!(cpu.read_reg(9), 1 << 6);
+}
+
+#[test]
+fn test_unboxed_single_reads_as_nan() {
+    let mut cpu = fp_cpu();
+    cpu.fregs[1] = 1.0f32.to_bits() as u64; // boxing 안 됨 → canonical NaN
+    cpu.fregs[2] = single(1.0);
+    cpu.bus.write32(0x80000000, 0x0020F1D3); // fadd.s f3, f1, f2
+    cpu.step();
+    assert_eq!(cpu.read_freg(3), SINGLE_BOX | 0x7FC0_0000);
+}
+
+#[test]
+fn test_fp_illegal_without_f_and_d() {
+    let ext = Extensions {
+        f: false,
+        d: false,
+        zfh: false,
+        zfhmin: false,
+        zfbfmin: false,
+        zcd: false,
+        ..Extensions::default()
+    };
+    for inst in [
+        0x0000A087, // flw f1, 0(x1)
+        0x0000B087, // fld f1, 0(x1)
+        0xE00181D3, // fmv.x.w x3, f3
+        0xF00080D3, // fmv.w.x f1, x1
+        0x0020F1D3, // fadd.s f3, f1, f2
+        0x1220F1D3, // fmul.d f3, f1, f2
+        0x00302573, // csrrs x10, fcsr, x0
+    ] {
+        let mut cpu = fp_cpu();
+        cpu.set_extensions(ext).unwrap();
+        assert_eq!(cpu.csr.read(csr::MISA) & (csr::MISA_F | csr::MISA_D), 0);
+        cpu.write_reg(1, 0x80001000);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80000100, "{inst:#x}");
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    }
+}
+
+#[test]
+fn test_f_without_d() {
+    let ext = Extensions {
+        d: false,
+        zcd: false,
+        ..Extensions::default()
+    };
+    let run = |inst: u32| {
+        let mut cpu = fp_cpu();
+        cpu.set_extensions(ext).unwrap();
+        cpu.write_reg(1, 0x80001000);
+        cpu.fregs[1] = HALF_BOX | 0x3C00;
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.csr.read(csr::MISA) & csr::MISA_D, 0);
+        assert_ne!(cpu.csr.read(csr::MISA) & csr::MISA_F, 0);
+        cpu.pc
+    };
+    assert_eq!(run(0x0000A087), 0x80000004); // flw f1, 0(x1)
+    assert_eq!(run(0x40208253), 0x80000004); // fcvt.s.h f4, f1
+    assert_eq!(run(0x0000B087), 0x80000100); // fld f1, 0(x1)
+    assert_eq!(run(0x4220F353), 0x80000100); // fcvt.d.h f6, f1
+    assert_eq!(run(0xE20094D3), 0x80000100); // fclass.d x9, f1
+}
+
+// ==================== Zicond/Zimop/Zawrs/Zihint ====================
+
+#[test]
+fn test_czero_eqz() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x1234);
+    cpu.write_reg(2, 0);
+    cpu.bus.write32(0x80000000, 0x0E20D1B3); // czero.eqz x3, x1, x2
+    cpu.bus.write32(0x80000004, 0x0E20D233); // czero.eqz x4, x1, x2
+    cpu.write_reg(3, 0xFF);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0);
+
+    cpu.write_reg(2, 7);
+    cpu.step();
+    assert_eq!(cpu.read_reg(4), 0x1234);
+}
+
+#[test]
+fn test_czero_nez() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x1234);
+    cpu.write_reg(2, 0);
+    cpu.bus.write32(0x80000000, 0x0E20F1B3); // czero.nez x3, x1, x2
+    cpu.bus.write32(0x80000004, 0x0E20F233); // czero.nez x4, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x1234);
+
+    cpu.write_reg(2, 7);
+    cpu.write_reg(4, 0xFF);
+    cpu.step();
+    assert_eq!(cpu.read_reg(4), 0);
+}
+
+#[test]
+fn test_mop_r_writes_zero() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x1234);
+    cpu.write_reg(3, 0xFF);
+    cpu.bus.write32(0x80000000, 0x81C0C1F3); // mop.r.0 x3, x1
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_mop_rr_writes_zero() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(3, 0xFF);
+    // mop.rr.7 (n=7: bit30, bits27:26 모두 1)
+    cpu.bus.write32(0x80000000, 0xCE20C1F3);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0);
+}
+
+#[test]
+fn test_reserved_system_funct3_4_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x0000C1F3);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_pause_and_ntl_are_noops() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x0100000F); // pause
+    cpu.bus.write32(0x80000004, 0x00500033); // ntl.all
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000008);
+    assert_eq!(cpu.regs, [0; 32]);
+}
+
+#[test]
+fn test_wrs_without_reservation_completes() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00D00073); // wrs.nto
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_wrs_nto_waits_until_reservation_invalidated() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x1000B12F); // lr.d x2, (x1)
+    cpu.bus.write32(0x80000004, 0x00D00073); // wrs.nto
+    cpu.step();
+    for _ in 0..10 {
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80000004);
+    }
+
+    // 다른 hart의 store가 예약을 무효화
+    cpu.bus.write64(0x80002000, 1);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000008);
+    assert_eq!(cpu.wrs_stall, 0);
+}
+
+#[test]
+fn test_wrs_sto_times_out() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.reserve(0, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x01D00073); // wrs.sto
+    for _ in 0..cpu::WRS_STO_TIMEOUT - 1 {
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80000000);
+    }
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_wrs_completes_on_pending_interrupt() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.reserve(0, 0x80002000);
+    cpu.csr.write(csr::MIE, csr::MIE_MSIE);
+    cpu.bus.write32(0x80000000, 0x00D00073); // wrs.nto
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000000);
+
+    // mstatus.MIE=0이어도 pending이면 대기 종료
+    cpu.bus.write32(crate::devices::clint::CLINT_BASE, 1); // msip
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert_eq!(cpu.wrs_stall, 0);
+}
+
+#[test]
+fn test_wrs_nto_traps_with_tw_in_user_mode() {
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::User;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_TW);
+    cpu.bus.reserve(0, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x00D00073); // wrs.nto
+    for _ in 0..cpu::WRS_NTO_TIMEOUT {
+        cpu.step();
+    }
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_zicond_zimop_zawrs_illegal_when_disabled() {
+    let ext = Extensions {
+        zicond: false,
+        zimop: false,
+        zawrs: false,
+        zcmop: false,
+        ..Extensions::default()
+    };
+    for inst in [
+        0x0E20D1B3, // czero.eqz x3, x1, x2
+        0x0E20F1B3, // czero.nez x3, x1, x2
+        0x81C0C1F3, // mop.r.0 x3, x1
+        0xCE20C1F3, // mop.rr.7 x3, x1, x2
+        0x00D00073, // wrs.nto
+        0x01D00073, // wrs.sto
+    ] {
+        let mut cpu = Cpu::new(0);
+        cpu.csr.write(csr::MTVEC, 0x80001000);
+        cpu.set_extensions(ext).unwrap();
+        cpu.write_reg(1, 0x1234);
+        cpu.write_reg(3, 0xFF);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80001000, "{inst:#x}");
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+        assert_eq!(cpu.csr.read(csr::MTVAL), inst as u64);
+        assert_eq!(cpu.read_reg(3), 0xFF);
+    }
+}
+
+// ==================== WFI ====================
+
+#[test]
+fn test_wfi_waits_for_timer_interrupt() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    cpu.csr.write(csr::MIE, csr::MIE_MTIE);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write64(0x2004000, 10); // mtimecmp
+    cpu.bus.write32(0x80000000, 0x10500073); // wfi
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert!(cpu.wfi);
+    assert_eq!(cpu.csr.read(csr::MINSTRET), 1);
+
+    // 자는 동안에는 명령어를 실행하지 않음
+    while cpu.bus.mtime() < 10 {
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80000004);
+    }
+    assert_eq!(cpu.csr.read(csr::MINSTRET), 1);
+    cpu.step();
+    assert!(!cpu.wfi);
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000004);
+    assert_eq!(
+        cpu.csr.read(csr::MCAUSE),
+        csr::INTERRUPT_BIT | csr::INTERRUPT_FROM_TIMER
+    );
+}
+
+#[test]
+fn test_wfi_wakes_without_global_enable() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MIE, csr::MIE_MSIE);
+    cpu.bus.write32(0x80000000, 0x10500073); // wfi
+    cpu.bus.write32(0x80000004, 0x00100093); // addi x1, x0, 1
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 0);
+
+    // mstatus.MIE=0이면 트랩 없이 다음 명령어부터 실행
+    cpu.bus.write32(crate::devices::clint::CLINT_BASE, 1); // msip
+    cpu.step();
+    assert!(!cpu.wfi);
+    assert_eq!(cpu.read_reg(1), 1);
+    assert_eq!(cpu.pc, 0x80000008);
+}
+
+#[test]
+fn test_wfi_illegal_in_user_mode_or_with_tw() {
+    for (mode, mstatus) in [
+        (PrivilegeMode::User, 0),
+        (PrivilegeMode::Supervisor, csr::MSTATUS_TW),
+    ] {
+        let mut cpu = Cpu::new(0);
+        cpu.mode = mode;
+        cpu.csr.write(csr::MSTATUS, mstatus);
+        cpu.csr.write(csr::MTVEC, 0x80001000);
+        cpu.bus.write32(0x80000000, 0x10500073); // wfi
+        cpu.step();
+        assert!(!cpu.wfi);
+        assert_eq!(cpu.pc, 0x80001000);
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    }
+
+    // M 모드는 TW와 무관하게 대기
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_TW);
+    cpu.bus.write32(0x80000000, 0x10500073); // wfi
+    cpu.step();
+    assert!(cpu.wfi);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+// ==================== RV32 모드 ====================
+
+fn rv32_cpu() -> Cpu {
+    let mut cpu = Cpu::with_xlen(0, Xlen::Rv32);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu
+}
+
+#[test]
+fn test_rv32_misa_mxl() {
+    let cpu = rv32_cpu();
+    assert_eq!(cpu.csr.read(csr::MISA), 0x40140128);
+    assert_eq!(cpu.xlen(), Xlen::Rv32);
+}
+
+#[test]
+fn test_rv32_addi_wraps_to_32_bits() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0x7FFF_FFFF);
+    cpu.bus.write32(0x80000000, 0x00108113); // addi x2, x1, 1
+    cpu.step();
+    // 레지스터는 부호 확장된 32비트 값
+    assert_eq!(cpu.read_reg(2), 0xFFFF_FFFF_8000_0000);
+}
+
+#[test]
+fn test_rv32_add_overflow() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0xFFFF_FFFF);
+    cpu.write_reg(2, 1);
+    cpu.bus.write32(0x80000000, 0x002081B3); // add x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0);
+}
+
+#[test]
+fn test_rv32_shifts() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0xFFFF_FFF0);
+    cpu.write_reg(2, 36); // shamt는 하위 5비트 = 4
+    cpu.bus.write32(0x80000000, 0x0020D1B3); // srl x3, x1, x2
+    cpu.bus.write32(0x80000004, 0x4020D1B3); // sra x3, x1, x2
+    cpu.bus.write32(0x80000008, 0x002091B3); // sll x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x0FFF_FFFF);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), u64::MAX);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_FFFF_FF00);
+}
+
+#[test]
+fn test_rv32_mulh_variants() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0xFFFF_FFFF);
+    cpu.write_reg(2, 0xFFFF_FFFF);
+    cpu.bus.write32(0x80000000, 0x0220B1B3); // mulhu x3, x1, x2
+    cpu.bus.write32(0x80000004, 0x022091B3); // mulh x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3) as u32, 0xFFFF_FFFE);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0);
+}
+
+#[test]
+fn test_rv32_divu_remu() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0xFFFF_FFFF);
+    cpu.write_reg(2, 0x10);
+    cpu.bus.write32(0x80000000, 0x0220D1B3); // divu x3, x1, x2
+    cpu.bus.write32(0x80000004, 0x0220F1B3); // remu x3, x1, x2
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x0FFF_FFFF);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xF);
+}
+
+#[test]
+fn test_rv32_rejects_rv64_only_instructions() {
+    for inst in [
+        0x0010811B, // addiw x2, x1, 1
+        0x0000B103, // ld x2, 0(x1)
+        0x02009113, // slli x2, x1, 32
+    ] {
+        let mut cpu = rv32_cpu();
+        cpu.write_reg(1, 0x80002000);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80001000, "inst={:#x}", inst);
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    }
+}
+
+#[test]
+fn test_rv32_sign_extended_address() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(2, 0x1234_5678);
+    cpu.bus.write32(0x80000000, 0x800020B7); // lui x1, 0x80002
+    cpu.bus.write32(0x80000004, 0x0020A023); // sw x2, 0(x1)
+    cpu.bus.write32(0x80000008, 0x0000A183); // lw x3, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 0xFFFF_FFFF_8000_2000);
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.bus.read32(0x80002000), 0x1234_5678);
+    assert_eq!(cpu.read_reg(3), 0x1234_5678);
+}
+
+#[test]
+fn test_rv32_jalr_wraps_pc() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(5, 0xFFFF_FFFF_8000_0100);
+    cpu.bus.write32(0x80000000, 0x000280E7); // jalr x1, 0(x5)
+    cpu.bus.write32(0x80000100, 0x00108113); // addi x2, x1, 1
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000104);
+    assert_eq!(cpu.read_reg(1), 0xFFFF_FFFF_8000_0004);
+    assert_eq!(cpu.read_reg(2), 0xFFFF_FFFF_8000_0005);
+}
+
+#[test]
+fn test_rv32_amocas_d_register_pair() {
+    let mut cpu = rv32_cpu();
+    cpu.bus.write64(0x80002000, 0x1111_2222_3333_4444);
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(4, 0x3333_4444); // expected (low)
+    cpu.write_reg(5, 0x1111_2222); // expected (high)
+    cpu.write_reg(6, 0x5555_6666); // new (low)
+    cpu.write_reg(7, 0x7777_8888); // new (high)
+    cpu.bus.write32(0x80000000, 0x2860B22F); // amocas.d x4, x6, (x1)
+    cpu.step();
+    assert_eq!(cpu.bus.read64(0x80002000), 0x7777_8888_5555_6666);
+    assert_eq!(cpu.read_reg(4), 0x3333_4444);
+    assert_eq!(cpu.read_reg(5), 0x1111_2222);
+}
+
+#[test]
+fn test_rv32_cycleh() {
+    let mut cpu = rv32_cpu();
+    cpu.csr.write(csr::MCYCLE, 0x0000_0001_FFFF_FFF0);
+    cpu.bus.write32(0x80000000, 0xC80020F3); // csrr x1, cycleh
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 1);
+}
+
+#[test]
+fn test_rv32_mcycle_halves_written_separately() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0xAAAA_0000);
+    cpu.write_reg(2, 0x5);
+    cpu.bus.write32(0x80000000, 0xB0009073); // csrw mcycle, x1
+    cpu.bus.write32(0x80000004, 0xB8011073); // csrw mcycleh, x2
+    cpu.step();
+    cpu.step();
+    // 두 번째 step의 증가분 1 포함
+    assert_eq!(cpu.csr.read(csr::MCYCLE), 0x0000_0005_AAAA_0001);
+}
+
+#[test]
+fn test_cycleh_illegal_in_rv64() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0xC80020F3); // csrr x1, cycleh
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_instret_counts_retired_instructions() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x00000013); // nop
+    cpu.bus.write32(0x80000004, 0x00000013); // nop
+    cpu.bus.write32(0x80000008, 0xC02020F3); // csrr x1, instret
+    cpu.step();
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.read_reg(1), 2);
+    assert_eq!(cpu.csr.read(csr::MCYCLE), 3);
+}
+
+#[test]
+fn test_write_to_read_only_counter_is_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0xC0009073); // csrw cycle, x1
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+#[test]
+fn test_rv32_mstatush() {
+    let mut cpu = rv32_cpu();
+    cpu.write_reg(1, 0x30);
+    cpu.bus.write32(0x80000000, 0x31009073); // csrw mstatush, x1
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MSTATUS) >> 32, 0x30);
+}
+
+#[test]
+fn test_rv32_mstatush_write_keeps_xl_fields() {
+    let mut cpu = rv32_cpu();
+    let xl = cpu.csr.read(csr::MSTATUS) & (csr::MSTATUS_SXL | csr::MSTATUS_UXL);
+    cpu.write_reg(1, 0xFFFF_FFFF);
+    cpu.bus.write32(0x80000000, 0x31009073); // csrw mstatush, x1
+    cpu.step();
+    let mstatus = cpu.csr.read(csr::MSTATUS);
+    assert_eq!(mstatus & (csr::MSTATUS_SXL | csr::MSTATUS_UXL), xl);
+    assert_eq!(
+        mstatus & !0xFFFF_FFFF & !csr::MSTATUS_SD,
+        xl | csr::MSTATUS_MBE | csr::MSTATUS_SBE
+    );
+}
+
+#[test]
+fn test_rv32_mstatus_sd_at_bit_31() {
+    let mut cpu = rv32_cpu();
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_SD | csr::FS_DIRTY);
+    cpu.bus.write32(0x80000000, 0x30002173); // csrr x2, mstatus
+    cpu.step();
+    assert_eq!(
+        cpu.read_reg(2) as u32 as u64,
+        csr::MSTATUS32_SD | csr::FS_DIRTY
+    );
+}
+
+#[test]
+fn test_rv32_interrupt_mcause() {
+    let mut cpu = rv32_cpu();
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    cpu.csr.write(csr::MIE, csr::MIE_MSIE);
+    cpu.bus.write32(crate::devices::clint::CLINT_BASE, 1); // msip
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), 0x8000_0003);
+}
+
+#[test]
+fn test_uxl_32_user_process_under_rv64() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    let mstatus = cpu.csr.read(csr::MSTATUS);
+    cpu.csr
+        .write(csr::MSTATUS, (mstatus & !csr::MSTATUS_UXL) | (1 << 32));
+    cpu.mode = PrivilegeMode::User;
+    assert_eq!(cpu.xlen(), Xlen::Rv32);
+
+    cpu.write_reg(1, 0x7FFF_FFFF);
+    cpu.bus.write32(0x80000000, 0x00108113); // addi x2, x1, 1
+    cpu.bus.write32(0x80000004, 0x0010811B); // addiw x2, x1, 1
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0xFFFF_FFFF_8000_0000);
+    cpu.step();
+    assert_eq!(cpu.mode, PrivilegeMode::Machine);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.xlen(), Xlen::Rv64);
+}
+
+#[test]
+fn test_mstatus_xl_fields_are_warl() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, 0);
+    cpu.bus.write32(0x80000000, 0x30009073); // csrw mstatus, x1
+    cpu.step();
+    let mstatus = cpu.csr.read(csr::MSTATUS);
+    assert_eq!(mstatus & csr::MSTATUS_UXL, 2 << 32);
+    assert_eq!(mstatus & csr::MSTATUS_SXL, 2 << 34);
+}
+
+// ==================== 빅엔디언 데이터 접근 (MBE/SBE/UBE) ====================
+
+#[test]
+fn test_mbe_store_and_load_word() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(2, 0x1122_3344);
+    cpu.bus.write32(0x80000000, 0x0020A023); // sw x2, 0(x1)
+    cpu.bus.write32(0x80000004, 0x0000A183); // lw x3, 0(x1)
+    cpu.step();
+    // 메모리에는 최상위 바이트가 낮은 주소에
+    assert_eq!(cpu.bus.read8(0x80002000), 0x11);
+    assert_eq!(cpu.bus.read32(0x80002000), 0x4433_2211);
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x1122_3344);
+}
+
+#[test]
+fn test_mbe_load_halfword_sign_extends() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write16(0x80002000, 0x0080); // 바이트: 80 00
+    cpu.bus.write32(0x80000000, 0x00009183); // lh x3, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFFFF_FFFF_FFFF_8000);
+}
+
+#[test]
+fn test_mbe_load_doubleword() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write64(0x80002000, 0x0807_0605_0403_0201);
+    cpu.bus.write32(0x80000000, 0x0000B183); // ld x3, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x0102_0304_0506_0708);
+}
+
+#[test]
+fn test_endianness_selected_by_privilege_mode() {
+    let mut cpu = Cpu::new(0);
+    // M 모드만 빅엔디언, U 모드는 리틀엔디언
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.mode = PrivilegeMode::User;
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80002000, 0x1122_3344);
+    cpu.bus.write32(0x80000000, 0x0000A183); // lw x3, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x1122_3344);
+}
+
+#[test]
+fn test_ube_user_mode_big_endian() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_UBE);
+    cpu.mode = PrivilegeMode::User;
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80002000, 0x1122_3344);
+    cpu.bus.write32(0x80000000, 0x0000A183); // lw x3, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0x4433_2211);
+}
+
+#[test]
+fn test_sbe_supervisor_mode_big_endian() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_SBE);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(2, 0x1122_3344);
+    cpu.bus.write32(0x80000000, 0x0020A023); // sw x2, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.bus.read32(0x80002000), 0x4433_2211);
+}
+
+#[test]
+fn test_sstatus_ube_aliases_mstatus() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(1, csr::SSTATUS_UBE);
+    cpu.bus.write32(0x80000000, 0x10009073); // csrw sstatus, x1
+    cpu.step();
+    assert_ne!(cpu.csr.read(csr::MSTATUS) & csr::MSTATUS_UBE, 0);
+}
+
+#[test]
+fn test_mbe_amoadd() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(2, 1);
+    cpu.bus.write32(0x80002000, 0xFF00_0000); // 빅엔디언 값 0x000000FF
+    cpu.bus.write32(0x80000000, 0x0020A1AF); // amoadd.w x3, x2, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(3), 0xFF);
+    assert_eq!(cpu.bus.read32(0x80002000), 0x0001_0000); // 0x00000100
+}
+
+#[test]
+fn test_mbe_amocas_w() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x80002000);
+    cpu.write_reg(4, 0x1122_3344);
+    cpu.write_reg(6, 0x5566_7788);
+    cpu.bus.write32(0x80002000, 0x4433_2211);
+    cpu.bus.write32(0x80000000, 0x2860A22F); // amocas.w x4, x6, (x1)
+    cpu.step();
+    assert_eq!(cpu.read_reg(4), 0x1122_3344);
+    assert_eq!(cpu.bus.read32(0x80002000), 0x8877_6655);
+}
+
+#[test]
+fn test_mbe_flw() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr
+        .write(csr::MSTATUS, csr::MSTATUS_MBE | csr::FS_INITIAL);
+    cpu.write_reg(1, 0x80002000);
+    cpu.bus.write32(0x80002000, 0x0000_803F); // 1.0f 빅엔디언
+    cpu.bus.write32(0x80000000, 0x0000A087); // flw f1, 0(x1)
+    cpu.step();
+    assert_eq!(cpu.read_freg(1), 0xFFFF_FFFF_3F80_0000);
+}
+
+#[test]
+fn test_mbe_instruction_fetch_stays_little_endian() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MBE);
+    cpu.write_reg(1, 0x7FFF_FFFF);
+    cpu.bus.write32(0x80000000, 0x00108113); // addi x2, x1, 1
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0x8000_0000);
+}
+
+#[test]
+fn test_rv32_mstatush_mbe() {
+    let mut cpu = Cpu::with_xlen(0, Xlen::Rv32);
+    cpu.write_reg(1, 0x20); // mstatush.MBE
+    cpu.write_reg(2, 0x1122_3344);
+    cpu.write_reg(5, 0x80002000);
+    cpu.bus.write32(0x80000000, 0x31009073); // csrw mstatush, x1
+    cpu.bus.write32(0x80000004, 0x0022A023); // sw x2, 0(x5)
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.bus.read32(0x80002000), 0x4433_2211);
+}
+
+// ==================== 압축 명령어 (Zca/Zcb/Zcmp/Zcmt) ====================
+
+fn zcmp_cpu() -> Cpu {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.set_extensions(Extensions {
+        zcd: false,
+        zcmp: true,
+        zcmt: true,
+        ..Extensions::default()
+    })
+    .unwrap();
+    cpu
+}
+
+#[test]
+fn test_compressed_mixed_with_32bit() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write16(0x80000000, 0x557D); // c.li x10, -1
+    cpu.bus.write16(0x80000002, 0x0593); // addi x11, x10, 2 (2바이트 정렬)
+    cpu.bus.write16(0x80000004, 0x0025);
+    cpu.bus.write16(0x80000006, 0x952E); // c.add x10, x11
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000002);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000006);
+    assert_eq!(cpu.read_reg(11), 1);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000008);
+    assert_eq!(cpu.read_reg(10), 0);
+}
+
+#[test]
+fn test_c_jalr_links_pc_plus_2() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(5, 0x80000100);
+    cpu.bus.write16(0x80000000, 0x9282); // c.jalr x5
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000100);
+    assert_eq!(cpu.read_reg(1), 0x80000002);
+}
+
+#[test]
+fn test_rv32_c_jal() {
+    let mut cpu = rv32_cpu();
+    cpu.bus.write16(0x80000000, 0x2011); // c.jal 4
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+    assert_eq!(cpu.read_reg(1), 0xFFFF_FFFF_8000_0002);
+}
+
+#[test]
+fn test_compressed_illegal_all_zero() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write16(0x80000000, 0x0000);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0);
+}
+
+#[test]
+fn test_compressed_illegal_without_zca() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.set_extensions(Extensions {
+        zca: false,
+        zcb: false,
+        zcd: false,
+        zcmop: false,
+        ..Extensions::default()
+    })
+    .unwrap();
+    cpu.bus.write16(0x80000000, 0x557D); // c.li x10, -1
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x557D);
+    assert_eq!(cpu.read_reg(10), 0);
+}
+
+#[test]
+fn test_set_extensions_rejects_zcd_with_zcmp() {
+    let mut cpu = Cpu::new(0);
+    let ext = Extensions {
+        zcmp: true,
+        ..Extensions::default()
+    };
+    assert!(cpu.set_extensions(ext).is_err());
+    assert!(!cpu.extensions.zcmp);
+}
+
+#[test]
+fn test_c_mop_is_nop() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write16(0x80000000, 0x6081); // c.mop.1
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000002);
+    assert_eq!(cpu.read_reg(1), 0);
+}
+
+#[test]
+fn test_zcb_sext_b_and_mul() {
+    let mut cpu = Cpu::new(0);
+    cpu.write_reg(10, 0x80);
+    cpu.write_reg(12, 6);
+    cpu.write_reg(13, 7);
+    cpu.bus.write16(0x80000000, 0x9D65); // c.sext.b x10
+    cpu.bus.write16(0x80000002, 0x9E75); // c.not x12
+    cpu.bus.write16(0x80000004, 0x9E75); // c.not x12
+    cpu.bus.write16(0x80000006, 0x9E55); // c.mul x12, x13
+    for _ in 0..4 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_reg(10), (-128i64) as u64);
+    assert_eq!(cpu.read_reg(12), 42);
+}
+
+#[test]
+fn test_cm_push_popret() {
+    let mut cpu = zcmp_cpu();
+    cpu.write_reg(2, 0x80002000);
+    cpu.write_reg(1, 0x80000100);
+    cpu.write_reg(8, 0x2222);
+    cpu.write_reg(9, 0x3333);
+    cpu.bus.write16(0x80000000, 0xB862); // cm.push {ra, s0-s1}, -32
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0x80001FE0);
+    assert_eq!(cpu.bus.read64(0x80001FF8), 0x3333);
+    assert_eq!(cpu.bus.read64(0x80001FF0), 0x2222);
+    assert_eq!(cpu.bus.read64(0x80001FE8), 0x80000100);
+
+    cpu.write_reg(1, 0);
+    cpu.write_reg(8, 0);
+    cpu.write_reg(9, 0);
+    cpu.bus.write16(0x80000002, 0xBE62); // cm.popret {ra, s0-s1}, 32
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0x80002000);
+    assert_eq!(cpu.read_reg(8), 0x2222);
+    assert_eq!(cpu.read_reg(9), 0x3333);
+    assert_eq!(cpu.pc, 0x80000100);
+}
+
+#[test]
+fn test_cm_popretz_clears_a0() {
+    let mut cpu = zcmp_cpu();
+    cpu.write_reg(2, 0x80002000);
+    cpu.write_reg(10, 5);
+    cpu.bus.write64(0x80002008, 0x80000200); // ra 슬롯
+    cpu.bus.write16(0x80000000, 0xBC42); // cm.popretz {ra}, 16
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 0);
+    assert_eq!(cpu.read_reg(2), 0x80002010);
+    assert_eq!(cpu.pc, 0x80000200);
+}
+
+#[test]
+fn test_rv32_cm_push_stack_adj() {
+    let mut cpu = rv32_cpu();
+    cpu.set_extensions(Extensions {
+        zcd: false,
+        zcmp: true,
+        ..Extensions::default()
+    })
+    .unwrap();
+    cpu.write_reg(2, 0x80002000);
+    cpu.write_reg(1, 0x1111);
+    cpu.write_reg(8, 0x2222);
+    cpu.write_reg(9, 0x3333);
+    cpu.bus.write16(0x80000000, 0xB866); // cm.push {ra, s0-s1}, -32
+    cpu.step();
+    assert_eq!(cpu.read_reg(2), 0xFFFF_FFFF_8000_1FE0);
+    assert_eq!(cpu.bus.read32(0x80001FFC), 0x3333);
+    assert_eq!(cpu.bus.read32(0x80001FF4), 0x1111);
+}
+
+#[test]
+fn test_cm_mvsa01_mva01s() {
+    let mut cpu = zcmp_cpu();
+    cpu.write_reg(10, 1);
+    cpu.write_reg(11, 2);
+    cpu.bus.write16(0x80000000, 0xAC26); // cm.mvsa01 s0, s1
+    cpu.bus.write16(0x80000002, 0xAC66); // cm.mva01s s0, s1
+    cpu.step();
+    assert_eq!(cpu.read_reg(8), 1);
+    assert_eq!(cpu.read_reg(9), 2);
+    cpu.write_reg(10, 0);
+    cpu.write_reg(11, 0);
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 1);
+    assert_eq!(cpu.read_reg(11), 2);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_cm_jalt_through_jvt() {
+    let mut cpu = zcmp_cpu();
+    cpu.write_reg(5, 0x80003000 | 0x3);
+    cpu.bus.write32(0x80000000, 0x01729073); // csrw jvt, x5
+    cpu.bus.write16(0x80000004, 0xA082); // cm.jalt 32
+    cpu.bus.write64(0x80003100, 0x80000201);
+    cpu.step();
+    // mode는 0만 지원
+    assert_eq!(cpu.csr.read(csr::JVT), 0x80003000);
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000200);
+    assert_eq!(cpu.read_reg(1), 0x80000006);
+}
+
+#[test]
+fn test_cm_jt_does_not_link() {
+    let mut cpu = zcmp_cpu();
+    cpu.csr.write(csr::JVT, 0x80003000);
+    cpu.bus.write64(0x80003008, 0x80000300);
+    cpu.bus.write16(0x80000000, 0xA006); // cm.jt 1
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000300);
+    assert_eq!(cpu.read_reg(1), 0);
+}
+
+#[test]
+fn test_jvt_illegal_without_zcmt() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x017022F3); // csrr x5, jvt
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+// ==================== 디코딩 실패 ====================
+
+#[test]
+fn test_unknown_opcode_traps_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x0000007F); // 48비트 이상 명령어 공간
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    assert_eq!(cpu.csr.read(csr::MTVAL), 0x7F);
+}
+
+#[test]
+fn test_reserved_system_encoding_traps_illegal() {
+    let mut cpu = Cpu::new(0);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write32(0x80000000, 0x10600073); // funct12=0x106 (예약)
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+// ==================== 디스어셈블 ====================
+
+#[test]
+fn test_cpu_disassemble_uses_symbols() {
+    let mut cpu = Cpu::new(0);
+    cpu.symbols = crate::elf::SymbolTable::new(vec![crate::elf::Symbol {
+        name: "loop".to_string(),
+        addr: 0x80000000,
+        size: 0,
+    }]);
+    // bnez a0, -4
+    assert_eq!(
+        cpu.disassemble(0xFE051EE3, 0x80000004),
+        "bnez a0,0x80000000 <loop>"
+    );
+}
+
+#[test]
+fn test_cpu_disassemble_follows_xlen() {
+    let cpu = Cpu::with_xlen(0, Xlen::Rv32);
+    // ld은 RV32에서 예약
+    assert_eq!(cpu.disassemble(0x00853583, 0x80000000), ".word 0x00853583");
+    // c.jal (RV32 전용) +4
+    assert_eq!(cpu.disassemble(0x2011, 0x80000000), "jal 0x80000004");
+}
+
+#[test]
+fn test_trace_does_not_change_execution() {
+    let mut cpu = Cpu::new(0);
+    cpu.trace = true;
+    cpu.load_program(&[0x02A00513]); // li a0,42
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 42);
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+// ==================== 어셈블러로 작성한 프로그램 ====================
+
+#[test]
+fn test_asm_sum_loop() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            li a0, 0
+            li t0, 10
+        loop:
+            add a0, a0, t0
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    while cpu.pc < 0x80000000 + program.len() as u64 * 4 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_reg(10), 55);
+}
+
+#[test]
+fn test_asm_call_and_trap_handler() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            auipc t0, 0
+            addi t0, t0, 0x40   # 핸들러 주소
+            csrw mtvec, t0
+            call double
+            ecall
+        double:
+            add a0, a0, a0
+            ret
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    cpu.write_reg(10, 21);
+    for _ in 0..7 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_reg(10), 42);
+    assert_eq!(cpu.pc, 0x80000040);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ECALL_FROM_M);
+}
+
+// ==================== 디코딩 캐시 ====================
+
+#[test]
+fn test_decode_cache_hits_in_loop() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            li t0, 100
+        loop:
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    for _ in 0..201 {
+        cpu.step();
+    }
+    assert_eq!(cpu.read_reg(5), 0);
+    assert_eq!(cpu.decode_cache.len(), 3);
+    assert_eq!(cpu.decode_cache.misses, 3);
+    assert_eq!(cpu.decode_cache.hits, 198);
+}
+
+#[test]
+fn test_self_modifying_store_invalidates_cache() {
+    let mut cpu = Cpu::new(0);
+    // 루프 두 번째 바퀴에서 target의 명령어를 addi a0,a0,100으로 바꿈
+    let program = crate::asm::assemble(
+        "
+            auipc t1, 0
+            addi t1, t1, 20     # target
+            li t2, 0x06450513   # addi a0, a0, 100
+            li t0, 2
+        loop:
+        target:
+            addi a0, a0, 1
+            sw t2, 0(t1)
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    while cpu.pc < 0x80000000 + program.len() as u64 * 4 {
+        cpu.step();
+    }
+    // 첫 바퀴 +1, 두 번째 바퀴는 수정된 명령어로 +100
+    assert_eq!(cpu.read_reg(10), 101);
+}
+
+#[test]
+fn test_external_write_invalidates_cache() {
+    let mut cpu = Cpu::new(0);
+    cpu.load_program(&[crate::asm::addi(10, 0, 1)]);
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 1);
+    // 디버거/로더가 버스로 직접 코드를 덮어씀
+    cpu.bus.write32(0x80000000, crate::asm::addi(10, 0, 2));
+    cpu.pc = 0x80000000;
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 2);
+}
+
+#[test]
+fn test_write_to_next_page_invalidates_straddling_instruction() {
+    let mut cpu = Cpu::new(0);
+    let addr = 0x80000FFE;
+    let first = crate::asm::addi(10, 0, 1);
+    let second = crate::asm::addi(10, 0, 2);
+    cpu.bus.write16(addr, first as u16);
+    cpu.bus.write16(addr + 2, (first >> 16) as u16);
+    cpu.pc = addr;
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 1);
+    // 명령어의 상위 절반만 다음 페이지에 있음
+    cpu.bus.write16(addr + 2, (second >> 16) as u16);
+    cpu.pc = addr;
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 2);
+}
+
+#[test]
+fn test_fence_i_flushes_decode_cache() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble("nop\nnop\nfence.i").unwrap();
+    cpu.load_program(&program);
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.decode_cache.len(), 2);
+    cpu.step();
+    assert!(cpu.decode_cache.is_empty());
+    assert_eq!(cpu.pc, 0x8000000C);
+}
+
+#[test]
+fn test_decode_cache_follows_extension_changes() {
+    let mut cpu = Cpu::new(0);
+    // sext.b a0 (c.sext.b, Zcb)
+    cpu.bus.write16(0x80000000, 0x9D65);
+    cpu.write_reg(10, 0x80);
+    cpu.step();
+    assert_eq!(cpu.read_reg(10), 0xFFFF_FFFF_FFFF_FF80);
+    cpu.extensions.zcb = false;
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.pc = 0x80000000;
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+}
+
+// ==================== 기본 블록 실행 ====================
+
+fn run_blocks_until(cpu: &mut Cpu, end: u64) {
+    while cpu.pc < end {
+        cpu.step_block();
+    }
+}
+
+#[test]
+fn test_step_block_matches_step() {
+    let program = crate::asm::assemble(
+        "
+            li a0, 0
+            li t0, 10
+        loop:
+            add a0, a0, t0
+            slli a1, a0, 2
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    let end = 0x80000000 + program.len() as u64 * 4;
+    let mut stepped = Cpu::new(0);
+    stepped.load_program(&program);
+    while stepped.pc < end {
+        stepped.step();
+    }
+    let mut blocked = Cpu::new(0);
+    blocked.load_program(&program);
+    run_blocks_until(&mut blocked, end);
+    for reg in 0..32 {
+        assert_eq!(blocked.read_reg(reg), stepped.read_reg(reg));
+    }
+    assert_eq!(blocked.read_reg(10), 55);
+    assert_eq!(
+        blocked.csr.read(csr::MINSTRET),
+        stepped.csr.read(csr::MINSTRET)
+    );
+    assert_eq!(blocked.csr.read(csr::MCYCLE), stepped.csr.read(csr::MCYCLE));
+}
+
+#[test]
+fn test_step_block_exception_is_precise() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            li a0, 1
+            auipc t1, 0
+            addi t1, t1, 1
+            amoadd.w t2, a0, (t1)   # misaligned
+            li a1, 5
+            j 0
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.step_block();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x8000000C);
+    assert_eq!(cpu.csr.read(csr::MCAUSE), csr::STORE_AMO_ADDRESS_MISALIGNED);
+    // 예외 이후의 명령어는 실행되지 않음
+    assert_eq!(cpu.read_reg(11), 0);
+}
+
+#[test]
+fn test_step_block_takes_interrupt_at_block_boundary() {
+    let mut cpu = Cpu::new(0);
+    let mut program = vec![crate::asm::addi(0, 0, 0); 8];
+    program.push(crate::asm::jal(0, -32));
+    cpu.load_program(&program);
+    cpu.csr.write(csr::MSTATUS, csr::MSTATUS_MIE);
+    cpu.csr.write(csr::MIE, csr::MIE_MTIE);
+    cpu.csr.write(csr::MTVEC, 0x80001000);
+    cpu.bus.write64(0x2004000, 5);
+
+    // mtime이 블록 도중 mtimecmp에 도달해도 블록은 끝까지 실행
+    cpu.step_block();
+    assert_eq!(cpu.pc, 0x80000000);
+    assert_eq!(cpu.csr.read(csr::MINSTRET), 9);
+    cpu.step_block();
+    assert_eq!(cpu.pc, 0x80001000);
+    assert_eq!(cpu.csr.read(csr::MEPC), 0x80000000);
+    assert_eq!(
+        cpu.csr.read(csr::MCAUSE),
+        csr::INTERRUPT_BIT | csr::INTERRUPT_FROM_TIMER
+    );
+}
+
+#[test]
+fn test_step_block_self_modifying_code_in_same_block() {
+    let mut cpu = Cpu::new(0);
+    // sw가 자신이 속한 블록의 첫 명령어를 바꿈
+    let program = crate::asm::assemble(
+        "
+            auipc t1, 0
+            addi t1, t1, 20     # target
+            li t2, 0x06450513   # addi a0, a0, 100
+            li t0, 2
+        loop:
+        target:
+            addi a0, a0, 1
+            sw t2, 0(t1)
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    run_blocks_until(&mut cpu, 0x80000000 + program.len() as u64 * 4);
+    assert_eq!(cpu.read_reg(10), 101);
+}
+
+#[test]
+fn test_step_block_chains_loop_block() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            li t0, 100
+        loop:
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    run_blocks_until(&mut cpu, 0x80000000 + program.len() as u64 * 4);
+    assert_eq!(cpu.read_reg(5), 0);
+    assert_eq!(cpu.blocks.len(), 2);
+    // 처음 두 블록은 생성, 루프 블록의 첫 자기 연결만 해시 조회
+    assert_eq!(cpu.blocks.lookups, 3);
+    assert_eq!(cpu.blocks.chained, 97);
+    assert_eq!(cpu.csr.read(csr::MINSTRET), 201);
+}
+
+// ==================== JIT ====================
+
+// 같은 프로그램을 step()과 JIT 블록 실행으로 돌려서 레지스터, 메모리, 카운터를 비교
+#[cfg(feature = "jit")]
+fn assert_jit_matches_step(source: &str, memory: std::ops::Range<u64>) {
+    let program = crate::asm::assemble(source).unwrap();
+    let end = 0x80000000 + program.len() as u64 * 4;
+    let mut stepped = Cpu::new(0);
+    stepped.load_program(&program);
+    stepped.write_reg(2, 0x80001000);
+    while stepped.pc < end {
+        stepped.step();
+    }
+    let mut jitted = Cpu::new(0);
+    jitted.load_program(&program);
+    jitted.write_reg(2, 0x80001000);
+    jitted.jit.threshold = 2;
+    run_blocks_until(&mut jitted, end);
+    assert!(jitted.jit.native_runs > 0);
+    assert_eq!(jitted.regs, stepped.regs);
+    for addr in memory.step_by(8) {
+        assert_eq!(
+            jitted.bus.read64(addr),
+            stepped.bus.read64(addr),
+            "{addr:#x}"
+        );
+    }
+    assert_eq!(
+        jitted.csr.read(csr::MINSTRET),
+        stepped.csr.read(csr::MINSTRET)
+    );
+    assert_eq!(jitted.csr.read(csr::MCYCLE), stepped.csr.read(csr::MCYCLE));
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn test_jit_matches_step() {
+    assert_jit_matches_step(
+        "
+            li a0, 0
+            li a1, 1
+            li t0, 50
+        loop:
+            add a0, a0, a1
+            slli a2, a0, 3
+            sraiw a3, a2, 2
+            subw a4, a3, a0
+            mul a5, a4, a1
+            sltu a6, a5, a0
+            xori a1, a1, 7
+            sd a5, 0(sp)
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+        0x80001000..0x80001008,
+    );
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn test_jit_memory_and_word_ops_match_step() {
+    // misaligned 로드와 CLINT mtime 로드는 번역된 코드에서 빠져나가 인터프리터가 실행
+    assert_jit_matches_step(
+        "
+            li a0, 0
+            li t0, 40
+            li s1, 0x200bff8
+        loop:
+            sd t0, 0(sp)
+            sw a0, 12(sp)
+            sb t0, 9(sp)
+            lb a1, 0(sp)
+            lhu a2, 12(sp)
+            lwu a3, 8(sp)
+            lw a4, 1(sp)
+            ld a5, 0(s1)
+            sllw a6, a0, t0
+            srliw a7, a4, 0
+            divw s2, a0, t0
+            remw s3, t0, a0
+            divuw s4, a3, zero
+            sraw s5, a0, a3
+            addi sp, sp, 16
+            addi a0, a0, -3
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+        0x80001000..0x80001000 + 40 * 16,
+    );
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn test_jit_self_modifying_code_discards_native_block() {
+    let mut cpu = Cpu::new(0);
+    // 세 번째 바퀴에서 이미 번역된 루프의 첫 명령어를 addi a0,a0,100으로 바꿈
+    let program = crate::asm::assemble(
+        "
+            auipc t1, 0
+            addi t1, t1, 24     # target
+            li t2, 0x06450513   # addi a0, a0, 100
+            li t0, 4
+            li t3, 2
+        loop:
+        target:
+            addi a0, a0, 1
+            addi t0, t0, -1
+            bne t0, t3, skip
+            sw t2, 0(t1)
+        skip:
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    cpu.jit.threshold = 1;
+    run_blocks_until(&mut cpu, 0x80000000 + program.len() as u64 * 4);
+    // 두 바퀴 +1, 나머지 두 바퀴는 수정된 명령어로 +100
+    assert_eq!(cpu.read_reg(10), 202);
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn test_jit_fence_i_flushes_native_code() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+        start:
+            addi a0, a0, 1
+            fence.i
+            j start
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    cpu.jit.threshold = 1;
+    cpu.step_block();
+    assert_eq!(cpu.jit.compiled, 1);
+    assert!(cpu.blocks.is_empty());
+    cpu.step_block();
+    assert_eq!(cpu.pc, 0x80000000);
+    // j start 블록도 번역됨
+    assert_eq!(cpu.jit.compiled, 2);
+    cpu.step_block();
+    assert_eq!(cpu.read_reg(10), 2);
+    assert_eq!(cpu.jit.compiled, 3);
+}
+
+// ==================== 소프트 TLB ====================
+
+#[test]
+fn test_tlb_serves_ram_loads_and_stores() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            auipc t0, 2
+            li t1, -2
+            sd t1, 8(t0)
+            lw t2, 12(t0)
+            lbu t3, 8(t0)
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    for _ in 0..program.len() {
+        cpu.step();
+    }
+    assert_eq!(cpu.bus.read64(0x80002008), -2i64 as u64);
+    assert_eq!(cpu.read_reg(7), u64::MAX);
+    assert_eq!(cpu.read_reg(28), 0xFE);
+}
+
+#[test]
+fn test_tlb_keeps_mmio_on_slow_path() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            lui t0, 0x2004      # mtimecmp
+            li t1, 1234
+            sd t1, 0(t0)
+            ld t2, 0(t0)
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    for _ in 0..program.len() - 1 {
+        cpu.step();
+    }
+    // MMIO 페이지는 첫 접근에서 분류한 뒤로는 miss 없이 느린 경로
+    let misses = cpu.tlb.misses;
+    cpu.step();
+    assert_eq!(cpu.tlb.misses, misses);
+    assert_eq!(cpu.read_reg(7), 1234);
+    assert_eq!(cpu.bus.read64(0x2004000), 1234);
+}
+
+#[test]
+fn test_tlb_page_straddling_store() {
+    let mut cpu = Cpu::new(0);
+    let program = crate::asm::assemble(
+        "
+            auipc t0, 3
+            addi t0, t0, -4
+            li t1, 0x1234
+            slli t1, t1, 32
+            addi t1, t1, 0x567
+            sd t1, 0(t0)
+            ld t2, 0(t0)
+        ",
+    )
+    .unwrap();
+    cpu.load_program(&program);
+    for _ in 0..program.len() {
+        cpu.step();
+    }
+    assert_eq!(cpu.bus.read32(0x80002FFC), 0x567);
+    assert_eq!(cpu.bus.read32(0x80003000), 0x1234);
+    assert_eq!(cpu.read_reg(7), 0x1234_0000_0567);
+}
+
+// 엔트리가 남아 있으면 0, 비워졌으면 1
+fn tlb_refill(cpu: &mut Cpu, addr: u64) -> u64 {
+    let misses = cpu.tlb.misses;
+    cpu.tlb.translate(addr, 8);
+    cpu.tlb.misses - misses
+}
+
+#[test]
+fn test_satp_write_flushes_tlb() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x18051073); // csrw satp, a0
+    cpu.bus.write32(0x80000004, 0x18059073); // csrw satp, a1
+    cpu.write_reg(10, 0x1234); // Bare, PPN만 기록
+    cpu.write_reg(11, 8 << 60); // Sv39 (미지원)
+    tlb_refill(&mut cpu, 0x80002000);
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::SATP), 0x1234);
+    assert_eq!(tlb_refill(&mut cpu, 0x80002000), 1);
+
+    // 지원하지 않는 MODE는 쓰기 전체를 무시하고 TLB도 유지
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::SATP), 0x1234);
+    assert_eq!(tlb_refill(&mut cpu, 0x80002000), 0);
+}
+
+#[test]
+fn test_sfence_vma_flushes_tlb() {
+    let mut cpu = Cpu::new(0);
+    cpu.bus.write32(0x80000000, 0x12050073); // sfence.vma a0
+    cpu.bus.write32(0x80000004, 0x12000073); // sfence.vma
+    cpu.write_reg(10, 0x80002010);
+    tlb_refill(&mut cpu, 0x80002000);
+    tlb_refill(&mut cpu, 0x80003000);
+    cpu.step();
+    assert_eq!(tlb_refill(&mut cpu, 0x80002000), 1);
+    assert_eq!(tlb_refill(&mut cpu, 0x80003000), 0);
+    cpu.step();
+    assert_eq!(tlb_refill(&mut cpu, 0x80002000), 1);
+    assert_eq!(tlb_refill(&mut cpu, 0x80003000), 1);
+}
+
+#[test]
+fn test_sfence_vma_and_satp_respect_tvm() {
+    // sfence.vma, csrw satp, x0
+    for (mode, mstatus, inst) in [
+        (PrivilegeMode::User, 0, 0x12000073),
+        (PrivilegeMode::Supervisor, csr::MSTATUS_TVM, 0x12000073),
+        (PrivilegeMode::Supervisor, csr::MSTATUS_TVM, 0x18001073),
+    ] {
+        let mut cpu = Cpu::new(0);
+        cpu.mode = mode;
+        cpu.csr.write(csr::MSTATUS, mstatus);
+        cpu.csr.write(csr::MTVEC, 0x80001000);
+        cpu.bus.write32(0x80000000, inst);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x80001000, "{inst:#x}");
+        assert_eq!(cpu.csr.read(csr::MCAUSE), csr::ILLEGAL_INSTRUCTION);
+    }
+
+    // S 모드도 TVM=0이면 허용
+    let mut cpu = Cpu::new(0);
+    cpu.mode = PrivilegeMode::Supervisor;
+    cpu.bus.write32(0x80000000, 0x12000073); // sfence.vma
+    cpu.step();
+    assert_eq!(cpu.pc, 0x80000004);
+}
+
+#[test]
+fn test_code_write_flushes_tlb_page() {
+    let mut cpu = Cpu::new(0);
+    tlb_refill(&mut cpu, 0x80004000);
+    tlb_refill(&mut cpu, 0x80005000);
+    // 디코딩 캐시에 올라간 페이지에 쓰면 그 페이지의 엔트리만 비움
+    cpu.bus.mark_code_page(0x80004000);
+    cpu.bus.write32(0x80004000, 0x00000013);
+    cpu.bus.write32(0x80000000, 0x00000013); // nop
+    cpu.step();
+    assert_eq!(tlb_refill(&mut cpu, 0x80004000), 1);
+    assert_eq!(tlb_refill(&mut cpu, 0x80005000), 0);
+}
+
+// ==================== 타이밍 모델 ====================
+
+fn timed_cpu(program: &[u32]) -> Cpu {
+    let mut cpu = Cpu::new(0);
+    cpu.load_program(program);
+    cpu.timing = Some(timing::Timing::new(Box::new(
+        timing::InOrderPipeline::default(),
+    )));
+    cpu
+}
+
+#[test]
+fn test_timing_model_feeds_mcycle() {
+    let program = crate::asm::assemble(
+        "
+            lw a0, 0(t0)
+            addi a1, a0, 1
+            addi a2, a1, 1
+        ",
+    )
+    .unwrap();
+    let mut cpu = timed_cpu(&program);
+    cpu.write_reg(5, 0x80001000);
+    for _ in 0..3 {
+        cpu.step();
+    }
+    // load-use로 addi 하나가 1 사이클 멈춤
+    assert_eq!(cpu.csr.read(csr::MINSTRET), 3);
+    assert_eq!(cpu.csr.read(csr::MCYCLE), 4);
+    let profile = &cpu.timing.as_ref().unwrap().profile;
+    assert_eq!(profile.total().cycles, 4);
+    assert_eq!(profile.range(0x80000004..0x80000008).cycles, 2);
+}
+
+#[test]
+fn test_timing_model_block_matches_step() {
+    let program = crate::asm::assemble(
+        "
+            li a0, 0
+            li t0, 10
+        loop:
+            mul a1, t0, t0
+            add a0, a0, a1
+            addi t0, t0, -1
+            bnez t0, loop
+        ",
+    )
+    .unwrap();
+    let end = 0x80000000 + program.len() as u64 * 4;
+    let mut stepped = timed_cpu(&program);
+    while stepped.pc < end {
+        stepped.step();
+    }
+    let mut blocked = timed_cpu(&program);
+    run_blocks_until(&mut blocked, end);
+    assert_eq!(blocked.read_reg(10), 385);
+    let cycles = stepped.csr.read(csr::MCYCLE);
+    assert_eq!(blocked.csr.read(csr::MCYCLE), cycles);
+    assert!(cycles > stepped.csr.read(csr::MINSTRET));
+    assert_eq!(
+        stepped.timing.as_ref().unwrap().profile.total().cycles,
+        cycles
+    );
+}
+
+#[test]
+fn test_timing_model_keeps_written_mcycle() {
+    let mut cpu = timed_cpu(&[crate::asm::csrrwi(0, csr::MCYCLE, 5)]);
+    cpu.step();
+    assert_eq!(cpu.csr.read(csr::MCYCLE), 5);
+}
@@ -0,0 +1,609 @@
+//! 사이클 근사 타이밍 모델
+//! 타이밍 모델이 없으면 명령어 하나가 mcycle 1. 있으면 retire한 명령어마다 모델이 정한 사이클을 더함
+//! InOrderPipeline은 단일 이슈 in-order 파이프라인
+//! - 명령어 종류별 결과 지연. 앞 명령어의 결과가 준비될 때까지 이슈가 멈춤 (load-use 등)
+//! - 나눗셈, CSR, AMO, FENCE, 시스템 명령어는 끝날 때까지 다음 명령어를 이슈하지 않음
+//! - 조건 분기는 2비트 카운터로 예측하고, 틀리면 mispredict 패널티
+//! - 예측할 수 없는 pc 변경 (jalr, trap, xRET)은 redirect 패널티
+//!
+//! 사이클은 pc별로 Profile에 모아 두고 심볼이나 주소 구간 단위로 보고
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use crate::cpu::compressed::CompressedOp;
+use crate::cpu::icache::{DecodedInst, DecodedOp};
+use crate::decoder::{AluOp, FpOp, Instruction};
+use crate::elf::SymbolTable;
+
+/// f 레지스터의 피연산자 번호 = FP_REG_BASE + 레지스터 번호 (x 레지스터는 그대로)
+pub const FP_REG_BASE: usize = 32;
+
+// 분기 예측기 카운터 수 (2의 거듭제곱)
+const PREDICTOR_ENTRIES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstClass {
+    Alu,
+    Mul,
+    Div,
+    Load,
+    Store,
+    /// 조건 분기
+    Branch,
+    /// jal (대상이 명령어에 있음)
+    Jump,
+    /// jalr, Zcmt 테이블 점프
+    IndirectJump,
+    Csr,
+    Atomic,
+    Fence,
+    Fp,
+    FpDiv,
+    /// ecall, ebreak, xRET, WRS
+    System,
+}
+
+/// retire한 명령어
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retired {
+    pub pc: u64,
+    /// 실행 후 pc (trap이면 핸들러 주소)
+    pub next_pc: u64,
+    pub len: u64,
+    pub class: InstClass,
+    /// 결과를 쓰는 레지스터 (f 레지스터는 FP_REG_BASE부터)
+    pub dest: Option<usize>,
+    pub sources: [Option<usize>; 3],
+}
+
+impl Retired {
+    /// 디코딩된 명령어의 종류와 피연산자
+    pub fn new(pc: u64, next_pc: u64, inst: &DecodedInst) -> Self {
+        let (class, dest, sources) = classify(&inst.op);
+        Retired {
+            pc,
+            next_pc,
+            len: inst.len,
+            class,
+            dest,
+            sources,
+        }
+    }
+
+    /// 다음 명령어가 바로 뒤에 있지 않음
+    pub fn redirected(&self) -> bool {
+        self.next_pc != self.pc + self.len
+    }
+}
+
+pub trait TimingModel: Send {
+    /// 명령어 하나가 retire할 때 호출. 이 명령어에 걸린 사이클 수 (1 이상)
+    fn retire(&mut self, retired: &Retired) -> u64;
+}
+
+/// 종류별 결과 지연 (사이클)과 패널티
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Latencies {
+    pub alu: u64,
+    pub mul: u64,
+    pub div: u64,
+    /// 결과를 바로 다음 명령어가 쓰면 load - 1 사이클 멈춤 (load-use)
+    pub load: u64,
+    pub store: u64,
+    pub branch: u64,
+    pub jump: u64,
+    pub csr: u64,
+    pub atomic: u64,
+    pub fence: u64,
+    pub fp: u64,
+    pub fp_div: u64,
+    pub system: u64,
+    /// 조건 분기 예측이 틀렸을 때 추가 사이클
+    pub mispredict_penalty: u64,
+    /// 예측할 수 없는 pc 변경에 추가 사이클
+    pub redirect_penalty: u64,
+}
+
+impl Default for Latencies {
+    /// 5단 in-order 코어 정도의 값
+    fn default() -> Self {
+        Latencies {
+            alu: 1,
+            mul: 3,
+            div: 20,
+            load: 2,
+            store: 1,
+            branch: 1,
+            jump: 1,
+            csr: 3,
+            atomic: 4,
+            fence: 3,
+            fp: 4,
+            fp_div: 15,
+            system: 5,
+            mispredict_penalty: 3,
+            redirect_penalty: 3,
+        }
+    }
+}
+
+impl Latencies {
+    pub fn of(&self, class: InstClass) -> u64 {
+        match class {
+            InstClass::Alu => self.alu,
+            InstClass::Mul => self.mul,
+            InstClass::Div => self.div,
+            InstClass::Load => self.load,
+            InstClass::Store => self.store,
+            InstClass::Branch => self.branch,
+            InstClass::Jump | InstClass::IndirectJump => self.jump,
+            InstClass::Csr => self.csr,
+            InstClass::Atomic => self.atomic,
+            InstClass::Fence => self.fence,
+            InstClass::Fp => self.fp,
+            InstClass::FpDiv => self.fp_div,
+            InstClass::System => self.system,
+        }
+    }
+}
+
+// 파이프라인을 점유해서 끝날 때까지 다음 명령어를 이슈하지 않는 종류
+fn blocking(class: InstClass) -> bool {
+    matches!(
+        class,
+        InstClass::Div
+            | InstClass::FpDiv
+            | InstClass::Csr
+            | InstClass::Atomic
+            | InstClass::Fence
+            | InstClass::System
+    )
+}
+
+/// 단일 이슈 in-order 파이프라인
+pub struct InOrderPipeline {
+    pub latencies: Latencies,
+    // 다음 명령어를 이슈할 수 있는 가장 이른 사이클
+    next_issue: u64,
+    // 레지스터 값이 준비되는 사이클 (x0-x31, f0-f31)
+    ready: [u64; 2 * FP_REG_BASE],
+    // pc별 2비트 포화 카운터 (2 이상이면 taken 예측)
+    counters: Box<[u8]>,
+    /// 조건 분기 수와 예측 실패 수
+    pub branches: u64,
+    pub mispredicts: u64,
+}
+
+impl Default for InOrderPipeline {
+    fn default() -> Self {
+        Self::new(Latencies::default())
+    }
+}
+
+impl InOrderPipeline {
+    pub fn new(latencies: Latencies) -> Self {
+        InOrderPipeline {
+            latencies,
+            next_issue: 0,
+            ready: [0; 2 * FP_REG_BASE],
+            // 처음에는 약한 not-taken
+            counters: vec![1; PREDICTOR_ENTRIES].into_boxed_slice(),
+            branches: 0,
+            mispredicts: 0,
+        }
+    }
+
+    // 예측한 뒤 결과로 카운터를 갱신. 예측이 맞았으면 true
+    fn predict(&mut self, pc: u64, taken: bool) -> bool {
+        let counter = &mut self.counters[(pc >> 1) as usize & (PREDICTOR_ENTRIES - 1)];
+        let predicted = *counter >= 2;
+        *counter = match taken {
+            true => (*counter + 1).min(3),
+            false => counter.saturating_sub(1),
+        };
+        predicted == taken
+    }
+}
+
+impl TimingModel for InOrderPipeline {
+    fn retire(&mut self, retired: &Retired) -> u64 {
+        let start = self.next_issue;
+        let issue = retired
+            .sources
+            .iter()
+            .flatten()
+            .map(|&source| self.ready[source])
+            .fold(start, u64::max);
+        let latency = self.latencies.of(retired.class);
+        if let Some(dest) = retired.dest.filter(|&dest| dest != 0) {
+            self.ready[dest] = issue + latency;
+        }
+        let mut next = match blocking(retired.class) {
+            true => issue + latency.max(1),
+            false => issue + 1,
+        };
+        match retired.class {
+            InstClass::Branch => {
+                self.branches += 1;
+                if !self.predict(retired.pc, retired.redirected()) {
+                    self.mispredicts += 1;
+                    next += self.latencies.mispredict_penalty;
+                }
+            }
+            // jal 대상은 디코딩 단계에서 알 수 있음
+            InstClass::Jump => {}
+            _ if retired.redirected() => next += self.latencies.redirect_penalty,
+            _ => {}
+        }
+        self.next_issue = next;
+        next - start
+    }
+}
+
+/// Cpu에 붙이는 타이밍 모델과 그 결과
+pub struct Timing {
+    pub model: Box<dyn TimingModel>,
+    pub profile: Profile,
+}
+
+impl Timing {
+    pub fn new(model: Box<dyn TimingModel>) -> Self {
+        Timing {
+            model,
+            profile: Profile::default(),
+        }
+    }
+
+    /// 명령어 하나의 사이클을 계산해서 프로파일에 기록
+    pub fn retire(&mut self, retired: &Retired) -> u64 {
+        let cycles = self.model.retire(retired);
+        self.profile.record(retired.pc, cycles);
+        cycles
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Count {
+    pub instructions: u64,
+    pub cycles: u64,
+}
+
+impl Count {
+    fn add(&mut self, other: Count) {
+        self.instructions += other.instructions;
+        self.cycles += other.cycles;
+    }
+
+    /// 명령어당 사이클
+    pub fn cpi(&self) -> f64 {
+        match self.instructions {
+            0 => 0.0,
+            count => self.cycles as f64 / count as f64,
+        }
+    }
+}
+
+/// 이름 붙은 구간의 합계
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub name: String,
+    pub count: Count,
+}
+
+/// "  cycles  instructions  CPI  name"
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>12} {:>12} {:>6.2}  {}",
+            self.count.cycles,
+            self.count.instructions,
+            self.count.cpi(),
+            self.name
+        )
+    }
+}
+
+/// pc별 retire 수와 사이클
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    counts: HashMap<u64, Count>,
+}
+
+impl Profile {
+    pub fn record(&mut self, pc: u64, cycles: u64) {
+        self.counts.entry(pc).or_default().add(Count {
+            instructions: 1,
+            cycles,
+        });
+    }
+
+    pub fn total(&self) -> Count {
+        let mut total = Count::default();
+        for count in self.counts.values() {
+            total.add(*count);
+        }
+        total
+    }
+
+    /// [range.start, range.end) 안의 pc 합계
+    pub fn range(&self, range: Range<u64>) -> Count {
+        let mut total = Count::default();
+        for (_, count) in self.counts.iter().filter(|(pc, _)| range.contains(pc)) {
+            total.add(*count);
+        }
+        total
+    }
+
+    /// 심볼(함수) 단위 합계를 사이클이 많은 순서로. 심볼 밖의 pc는 "?"로 묶음
+    pub fn by_symbol(&self, symbols: &SymbolTable) -> Vec<Region> {
+        let mut regions: HashMap<&str, Count> = HashMap::new();
+        for (&pc, &count) in &self.counts {
+            let name = symbols.lookup(pc).map_or("?", |(sym, _)| sym.name.as_str());
+            regions.entry(name).or_default().add(count);
+        }
+        let mut regions: Vec<Region> = regions
+            .into_iter()
+            .map(|(name, count)| Region {
+                name: name.to_string(),
+                count,
+            })
+            .collect();
+        regions.sort_by(|a, b| {
+            b.count
+                .cycles
+                .cmp(&a.count.cycles)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        regions
+    }
+}
+
+// 명령어 종류, 결과 레지스터, 원본 레지스터
+fn classify(op: &DecodedOp) -> (InstClass, Option<usize>, [Option<usize>; 3]) {
+    let fp = |reg: usize| Some(FP_REG_BASE + reg);
+    let decoded = match op {
+        DecodedOp::Base {
+            decoded: Ok(decoded),
+            ..
+        } => decoded,
+        DecodedOp::Compressed(Some(op)) => return classify_compressed(op),
+        // illegal 명령어는 trap
+        _ => return (InstClass::System, None, [None; 3]),
+    };
+    match *decoded {
+        Instruction::Lui { rd, .. } | Instruction::Auipc { rd, .. } => {
+            (InstClass::Alu, Some(rd), [None; 3])
+        }
+        Instruction::Jal { rd, .. } => (InstClass::Jump, Some(rd), [None; 3]),
+        Instruction::Jalr { rd, rs1, .. } => {
+            (InstClass::IndirectJump, Some(rd), [Some(rs1), None, None])
+        }
+        Instruction::Branch { rs1, rs2, .. } => {
+            (InstClass::Branch, None, [Some(rs1), Some(rs2), None])
+        }
+        Instruction::Load { rd, rs1, .. } => (InstClass::Load, Some(rd), [Some(rs1), None, None]),
+        Instruction::Store { rs1, rs2, .. } => {
+            (InstClass::Store, None, [Some(rs1), Some(rs2), None])
+        }
+        Instruction::OpImm { rd, rs1, .. } | Instruction::OpImm32 { rd, rs1, .. } => {
+            (InstClass::Alu, Some(rd), [Some(rs1), None, None])
+        }
+        Instruction::Op { op, rd, rs1, rs2 } | Instruction::Op32 { op, rd, rs1, rs2 } => {
+            let class = match op {
+                AluOp::Mul | AluOp::Mulh | AluOp::Mulhsu | AluOp::Mulhu => InstClass::Mul,
+                AluOp::Div | AluOp::Divu | AluOp::Rem | AluOp::Remu => InstClass::Div,
+                _ => InstClass::Alu,
+            };
+            (class, Some(rd), [Some(rs1), Some(rs2), None])
+        }
+        Instruction::Crypto { rd, rs1, rs2, .. } | Instruction::MopRr { rd, rs1, rs2, .. } => {
+            (InstClass::Alu, Some(rd), [Some(rs1), Some(rs2), None])
+        }
+        Instruction::MopR { rd, rs1, .. } => (InstClass::Alu, Some(rd), [Some(rs1), None, None]),
+        Instruction::Fence { .. } | Instruction::FenceI | Instruction::Pause => {
+            (InstClass::Fence, None, [None; 3])
+        }
+        Instruction::Cbo { rs1, .. } => (InstClass::Fence, None, [Some(rs1), None, None]),
+        Instruction::SfenceVma { rs1, rs2 } => {
+            (InstClass::Fence, None, [Some(rs1), Some(rs2), None])
+        }
+        Instruction::Ecall
+        | Instruction::Ebreak
+        | Instruction::Mret
+        | Instruction::Sret
+        | Instruction::Dret
+        | Instruction::Wfi
+        | Instruction::WrsNto
+        | Instruction::WrsSto => (InstClass::System, None, [None; 3]),
+        Instruction::Csr { rd, rs1, .. } => (InstClass::Csr, Some(rd), [Some(rs1), None, None]),
+        // AMOCAS는 rd도 비교값으로 읽음
+        Instruction::Amo { rd, rs1, rs2, .. } => (
+            InstClass::Atomic,
+            Some(rd),
+            [Some(rs1), Some(rs2), Some(rd)],
+        ),
+        Instruction::FpLoad { rd, rs1, .. } => (InstClass::Load, fp(rd), [Some(rs1), None, None]),
+        Instruction::FpStore { rs1, rs2, .. } => {
+            (InstClass::Store, None, [Some(rs1), fp(rs2), None])
+        }
+        Instruction::FpOp {
+            op, rd, rs1, rs2, ..
+        } => {
+            use FpOp::*;
+            let class = match op {
+                Div | Sqrt => InstClass::FpDiv,
+                _ => InstClass::Fp,
+            };
+            let dest = match op {
+                Le | Lt | Eq | CvtWF | CvtWuF | CvtLF | CvtLuF | MvXF | Class => Some(rd),
+                _ => fp(rd),
+            };
+            let sources = match op {
+                CvtFW | CvtFWu | CvtFL | CvtFLu | MvFX => [Some(rs1), None, None],
+                _ => [fp(rs1), fp(rs2), None],
+            };
+            (class, dest, sources)
+        }
+        Instruction::FpFused {
+            rd, rs1, rs2, rs3, ..
+        } => (InstClass::Fp, fp(rd), [fp(rs1), fp(rs2), fp(rs3)]),
+    }
+}
+
+// Zcmp의 여러 레지스터 쓰기는 마지막으로 쓰는 레지스터 하나로 근사
+fn classify_compressed(op: &CompressedOp) -> (InstClass, Option<usize>, [Option<usize>; 3]) {
+    const SP: usize = 2;
+    match *op {
+        CompressedOp::SextB(rd)
+        | CompressedOp::SextH(rd)
+        | CompressedOp::ZextH(rd)
+        | CompressedOp::ZextW(rd) => (InstClass::Alu, Some(rd), [Some(rd), None, None]),
+        CompressedOp::Push { .. } => (InstClass::Store, Some(SP), [Some(SP), None, None]),
+        CompressedOp::Pop { .. } | CompressedOp::Popret { .. } | CompressedOp::Popretz { .. } => {
+            (InstClass::Load, Some(SP), [Some(SP), None, None])
+        }
+        CompressedOp::MvSa01 { r2s, .. } => (InstClass::Alu, Some(r2s), [Some(10), Some(11), None]),
+        CompressedOp::MvA01s { r1s, r2s } => {
+            (InstClass::Alu, Some(11), [Some(r1s), Some(r2s), None])
+        }
+        CompressedOp::TableJump { .. } => (InstClass::IndirectJump, None, [None; 3]),
+        // 32비트로 확장되는 명령어는 DecodedOp::Base로 디코딩됨
+        CompressedOp::Expanded(_) => (InstClass::Alu, None, [None; 3]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm;
+    use crate::decoder;
+    use crate::elf::Symbol;
+
+    fn retired(pc: u64, next_pc: u64, inst: u32) -> Retired {
+        let decoded = DecodedInst {
+            raw: inst,
+            len: 4,
+            op: DecodedOp::Base {
+                inst,
+                decoded: decoder::decode(inst),
+            },
+        };
+        Retired::new(pc, next_pc, &decoded)
+    }
+
+    fn sequential(pipeline: &mut InOrderPipeline, program: &[u32]) -> Vec<u64> {
+        program
+            .iter()
+            .enumerate()
+            .map(|(index, &inst)| {
+                let pc = 4 * index as u64;
+                pipeline.retire(&retired(pc, pc + 4, inst))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let load = retired(0, 4, asm::lw(10, 0, 5));
+        assert_eq!(load.class, InstClass::Load);
+        assert_eq!(load.dest, Some(10));
+        assert_eq!(load.sources, [Some(5), None, None]);
+        assert_eq!(retired(0, 4, asm::div(1, 2, 3)).class, InstClass::Div);
+        assert_eq!(retired(0, 4, asm::mul(1, 2, 3)).class, InstClass::Mul);
+        assert_eq!(retired(0, 4, asm::csrrs(1, 0xB00, 0)).class, InstClass::Csr);
+        assert_eq!(retired(0, 8, asm::beq(1, 2, 8)).class, InstClass::Branch);
+    }
+
+    #[test]
+    fn test_load_use_stall() {
+        let mut pipeline = InOrderPipeline::default();
+        let cycles = sequential(
+            &mut pipeline,
+            &[
+                asm::lw(10, 0, 5),
+                asm::addi(11, 10, 1),
+                asm::addi(12, 13, 1),
+            ],
+        );
+        assert_eq!(cycles, [1, 2, 1]);
+        // 사이에 독립적인 명령어가 있으면 멈추지 않음
+        let mut pipeline = InOrderPipeline::default();
+        let cycles = sequential(
+            &mut pipeline,
+            &[
+                asm::lw(10, 0, 5),
+                asm::addi(12, 13, 1),
+                asm::addi(11, 10, 1),
+            ],
+        );
+        assert_eq!(cycles, [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_divide_blocks_issue() {
+        let mut pipeline = InOrderPipeline::default();
+        let cycles = sequential(&mut pipeline, &[asm::div(1, 2, 3), asm::addi(4, 5, 1)]);
+        assert_eq!(cycles, [20, 1]);
+        let mut pipeline = InOrderPipeline::default();
+        let cycles = sequential(&mut pipeline, &[asm::mul(1, 2, 3), asm::addi(4, 5, 1)]);
+        assert_eq!(cycles, [1, 1]);
+    }
+
+    #[test]
+    fn test_branch_predictor_learns_loop() {
+        let mut pipeline = InOrderPipeline::default();
+        let branch = asm::bne(5, 0, -4);
+        // 처음에는 not-taken으로 예측해서 틀리고, 한 번 taken이면 taken으로 예측
+        let cycles: Vec<u64> = (0..4)
+            .map(|_| pipeline.retire(&retired(0x104, 0x100, branch)))
+            .collect();
+        assert_eq!(cycles, [4, 1, 1, 1]);
+        assert_eq!(pipeline.retire(&retired(0x104, 0x108, branch)), 4);
+        assert_eq!((pipeline.branches, pipeline.mispredicts), (5, 2));
+    }
+
+    #[test]
+    fn test_indirect_jump_redirect() {
+        let mut pipeline = InOrderPipeline::default();
+        assert_eq!(pipeline.retire(&retired(0, 0x40, asm::jal(1, 0x40))), 1);
+        assert_eq!(pipeline.retire(&retired(0x40, 4, asm::jalr(0, 0, 1))), 4);
+    }
+
+    #[test]
+    fn test_profile_by_symbol() {
+        let mut profile = Profile::default();
+        profile.record(0x100, 1);
+        profile.record(0x104, 3);
+        profile.record(0x200, 10);
+        profile.record(0x200, 10);
+        profile.record(0x900, 1);
+        let symbols = SymbolTable::new(vec![
+            Symbol {
+                name: "main".to_string(),
+                addr: 0x100,
+                size: 0x10,
+            },
+            Symbol {
+                name: "loop".to_string(),
+                addr: 0x200,
+                size: 0x10,
+            },
+        ]);
+        let regions = profile.by_symbol(&symbols);
+        let names: Vec<&str> = regions.iter().map(|region| region.name.as_str()).collect();
+        assert_eq!(names, ["loop", "main", "?"]);
+        assert_eq!(
+            regions[0].count,
+            Count {
+                instructions: 2,
+                cycles: 20
+            }
+        );
+        assert_eq!(regions[1].count.cpi(), 2.0);
+        assert_eq!(profile.total().cycles, 25);
+        assert_eq!(profile.range(0x100..0x108).instructions, 2);
+    }
+}
@@ -0,0 +1,139 @@
+//! 소프트 TLB
+//! hart마다 게스트 페이지 → 호스트 DRAM 슬라이스 위치(DRAM 오프셋)를 캐시해서
+//! RAM 로드/스토어/fetch가 Bus의 장치 주소 분기를 거치지 않게 함
+//! MMIO 페이지도 엔트리로 분류해 두고 항상 Bus의 느린 경로로 보냄
+//! 주소 변환은 satp Bare 모드뿐이므로 게스트 가상 주소는 물리 주소와 같음.
+//! 그래도 satp 쓰기와 SFENCE.VMA에서 비우고, 코드 쓰기가 일어난 페이지도 비움
+
+use crate::bus::Bus;
+use crate::cpu::icache::{PAGE_SHIFT, PAGE_SIZE};
+
+/// 엔트리 수 (direct-mapped)
+pub const TLB_ENTRIES: usize = 256;
+
+const INVALID: u64 = u64::MAX;
+
+#[derive(Clone, Copy)]
+struct Entry {
+    // 게스트 페이지 번호 (addr >> PAGE_SHIFT)
+    vpn: u64,
+    // RAM 페이지면 페이지 시작의 DRAM 오프셋, MMIO(또는 매핑되지 않은) 페이지면 None
+    base: Option<usize>,
+}
+
+const EMPTY: Entry = Entry {
+    vpn: INVALID,
+    base: None,
+};
+
+pub struct Tlb {
+    entries: Box<[Entry]>,
+    // 엔트리를 채운 횟수 (페이지 분류는 miss에서만 일어남)
+    pub misses: u64,
+}
+
+impl Default for Tlb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tlb {
+    pub fn new() -> Self {
+        Tlb {
+            entries: vec![EMPTY; TLB_ENTRIES].into_boxed_slice(),
+            misses: 0,
+        }
+    }
+
+    /// addr부터 size바이트가 RAM 페이지 하나 안에 있으면 DRAM 오프셋
+    /// MMIO 페이지와 페이지에 걸친 접근은 None (Bus의 느린 경로 사용)
+    pub fn translate(&mut self, addr: u64, size: u64) -> Option<usize> {
+        let vpn = addr >> PAGE_SHIFT;
+        let page_offset = addr & (PAGE_SIZE - 1);
+        if page_offset + size > PAGE_SIZE {
+            return None;
+        }
+        let entry = &mut self.entries[vpn as usize % TLB_ENTRIES];
+        if entry.vpn != vpn {
+            self.misses += 1;
+            *entry = Entry {
+                vpn,
+                base: Bus::ram_offset(vpn << PAGE_SHIFT),
+            };
+        }
+        entry.base.map(|base| base + page_offset as usize)
+    }
+
+    /// 페이지 하나의 엔트리 제거 (SFENCE.VMA rs1≠x0, 코드 쓰기)
+    pub fn flush_page(&mut self, vpn: u64) {
+        let entry = &mut self.entries[vpn as usize % TLB_ENTRIES];
+        if entry.vpn == vpn {
+            *entry = EMPTY;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.entries.fill(EMPTY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices;
+
+    #[test]
+    fn test_translate_ram_page() {
+        let mut tlb = Tlb::new();
+        assert_eq!(tlb.translate(0x80001008, 8), Some(0x1008));
+        assert_eq!(tlb.translate(0x80001FF8, 8), Some(0x1FF8));
+        assert_eq!(tlb.misses, 1);
+    }
+
+    #[test]
+    fn test_mmio_pages_stay_on_slow_path() {
+        let mut tlb = Tlb::new();
+        assert_eq!(tlb.translate(devices::UART_BASE, 1), None);
+        assert_eq!(tlb.translate(devices::UART_BASE + 5, 1), None);
+        // MMIO 페이지도 분류 결과를 캐시
+        assert_eq!(tlb.misses, 1);
+        assert_eq!(tlb.translate(devices::CLINT_BASE + 0x4000, 8), None);
+        assert_eq!(
+            tlb.translate(devices::DRAM_BASE + devices::DRAM_SIZE, 4),
+            None
+        );
+        // 페이지에 걸친 접근은 엔트리를 보지 않음
+        assert_eq!(tlb.translate(0x80000FFC, 8), None);
+        assert_eq!(tlb.misses, 3);
+    }
+
+    #[test]
+    fn test_conflicting_pages_evict_each_other() {
+        let mut tlb = Tlb::new();
+        let stride = (TLB_ENTRIES as u64) << PAGE_SHIFT;
+        assert_eq!(tlb.translate(0x80000000, 4), Some(0));
+        assert_eq!(tlb.translate(0x80000000 + stride, 4), Some(stride as usize));
+        assert_eq!(tlb.translate(0x80000000, 4), Some(0));
+        assert_eq!(tlb.misses, 3);
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut tlb = Tlb::new();
+        tlb.translate(0x80000000, 4);
+        tlb.translate(0x80001000, 4);
+        tlb.flush_page(0x80001);
+        tlb.translate(0x80000000, 4);
+        tlb.translate(0x80001000, 4);
+        assert_eq!(tlb.misses, 3);
+        // 다른 페이지가 차지한 슬롯은 건드리지 않음
+        tlb.flush_page(0x80001 + TLB_ENTRIES as u64);
+        tlb.translate(0x80001000, 4);
+        assert_eq!(tlb.misses, 3);
+        tlb.flush();
+        tlb.translate(0x80000000, 4);
+        tlb.translate(0x80001000, 4);
+        assert_eq!(tlb.misses, 5);
+    }
+}
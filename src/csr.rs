@@ -1,43 +1,141 @@
-use std::collections::HashMap;
-
 // ========================================
 // CSR Addresses
 // ========================================
 
+// Unprivileged CSRs
+pub const FFLAGS: u16 = 0x001;
+pub const FRM: u16 = 0x002;
+pub const FCSR: u16 = 0x003;
+pub const SEED: u16 = 0x015;
+pub const JVT: u16 = 0x017;
+pub const CYCLE: u16 = 0xC00;
+pub const TIME: u16 = 0xC01;
+pub const INSTRET: u16 = 0xC02;
+// RV32 전용: 카운터 상위 32비트
+pub const CYCLEH: u16 = 0xC80;
+pub const TIMEH: u16 = 0xC81;
+pub const INSTRETH: u16 = 0xC82;
+
 // Supervisor Mode CSRs
 pub const SSTATUS: u16 = 0x100;
 pub const STVEC: u16 = 0x105;
+pub const SENVCFG: u16 = 0x10A;
 pub const SEPC: u16 = 0x141;
 pub const SCAUSE: u16 = 0x142;
 pub const STVAL: u16 = 0x143;
+pub const SATP: u16 = 0x180;
 
 // Machine Mode CSRs
 pub const MSTATUS: u16 = 0x300;
 pub const MISA: u16 = 0x301;
 pub const MIE: u16 = 0x304;
 pub const MTVEC: u16 = 0x305;
+pub const MENVCFG: u16 = 0x30A;
+// RV32 전용: mstatus 상위 32비트
+pub const MSTATUSH: u16 = 0x310;
 pub const MEPC: u16 = 0x341;
 pub const MCAUSE: u16 = 0x342;
 pub const MTVAL: u16 = 0x343;
 pub const MIP: u16 = 0x344;
+pub const MSECCFG: u16 = 0x747;
+pub const MCYCLE: u16 = 0xB00;
+pub const MINSTRET: u16 = 0xB02;
+// RV32 전용: 카운터 상위 32비트
+pub const MCYCLEH: u16 = 0xB80;
+pub const MINSTRETH: u16 = 0xB82;
 pub const MHARTID: u16 = 0xF14;
 
+// Debug/Trace CSRs (Sdtrig)
+pub const TSELECT: u16 = 0x7A0;
+pub const TDATA1: u16 = 0x7A1;
+pub const TDATA2: u16 = 0x7A2;
+pub const TDATA3: u16 = 0x7A3;
+pub const TINFO: u16 = 0x7A4;
+
+// Debug Mode CSRs (Sdext)
+pub const DCSR: u16 = 0x7B0;
+pub const DPC: u16 = 0x7B1;
+pub const DSCRATCH0: u16 = 0x7B2;
+pub const DSCRATCH1: u16 = 0x7B3;
+
 // ========================================
 // Bit Masks
 // ========================================
 
+// MISA extension bits
+pub const MISA_D: u64 = 1 << 3;
+pub const MISA_F: u64 = 1 << 5;
+
 // MSTATUS bits
 pub const MSTATUS_SIE: u64 = 1 << 1;
 pub const MSTATUS_MIE: u64 = 1 << 3;
 pub const MSTATUS_SPIE: u64 = 1 << 5;
+pub const MSTATUS_UBE: u64 = 1 << 6;
 pub const MSTATUS_MPIE: u64 = 1 << 7;
 pub const MSTATUS_SPP: u64 = 1 << 8;
 pub const MSTATUS_MPP: u64 = 0x3 << 11;
+pub const MSTATUS_FS: u64 = 0x3 << 13;
+pub const MSTATUS_TVM: u64 = 1 << 20;
+pub const MSTATUS_TW: u64 = 1 << 21;
+pub const MSTATUS_UXL: u64 = 0x3 << 32;
+pub const MSTATUS_SXL: u64 = 0x3 << 34;
+// 엔디언 (RV32에서는 mstatush의 4, 5번 비트)
+pub const MSTATUS_SBE: u64 = 1 << 36;
+pub const MSTATUS_MBE: u64 = 1 << 37;
+pub const MSTATUS_SD: u64 = 1 << 63;
+// RV32 mstatus의 SD 위치
+pub const MSTATUS32_SD: u64 = 1 << 31;
+
+// MSTATUS.FS values
+pub const FS_OFF: u64 = 0;
+pub const FS_INITIAL: u64 = 1 << 13;
+pub const FS_DIRTY: u64 = 3 << 13;
+
+// FCSR fields
+pub const FCSR_FFLAGS: u64 = 0x1F;
+pub const FCSR_FRM_SHIFT: u64 = 5;
+pub const FCSR_FRM: u64 = 0x7 << FCSR_FRM_SHIFT;
+
+// MENVCFG/SENVCFG bits (Zicbom/Zicboz)
+pub const ENVCFG_CBIE: u64 = 0x3 << 4;
+pub const ENVCFG_CBCFE: u64 = 1 << 6;
+pub const ENVCFG_CBZE: u64 = 1 << 7;
+
+// ENVCFG.CBIE values
+pub const CBIE_ILLEGAL: u64 = 0b00;
+pub const CBIE_FLUSH: u64 = 0b01;
+pub const CBIE_INVAL: u64 = 0b11;
+
+// MSECCFG bits (Zkr: S/U 모드의 seed 접근 허용)
+pub const MSECCFG_USEED: u64 = 1 << 8;
+pub const MSECCFG_SSEED: u64 = 1 << 9;
+
+// JVT fields (Zcmt: base[XLEN-1:6], mode[5:0])
+pub const JVT_MODE: u64 = 0x3F;
+
+// DCSR bits
+pub const DCSR_XDEBUGVER: u64 = 4 << 28;
+pub const DCSR_EBREAKM: u64 = 1 << 15;
+pub const DCSR_EBREAKS: u64 = 1 << 13;
+pub const DCSR_EBREAKU: u64 = 1 << 12;
+pub const DCSR_STEPIE: u64 = 1 << 11;
+pub const DCSR_STOPCOUNT: u64 = 1 << 10;
+pub const DCSR_STOPTIME: u64 = 1 << 9;
+pub const DCSR_CAUSE: u64 = 0x7 << 6;
+pub const DCSR_STEP: u64 = 1 << 2;
+pub const DCSR_PRV: u64 = 0x3;
+
+// DCSR.cause values
+pub const DEBUG_CAUSE_EBREAK: u64 = 1;
+pub const DEBUG_CAUSE_TRIGGER: u64 = 2;
+pub const DEBUG_CAUSE_HALTREQ: u64 = 3;
+pub const DEBUG_CAUSE_STEP: u64 = 4;
 
 // SSTATUS aliases (same bit positions as MSTATUS)
 pub const SSTATUS_SIE: u64 = MSTATUS_SIE;
 pub const SSTATUS_SPIE: u64 = MSTATUS_SPIE;
 pub const SSTATUS_SPP: u64 = MSTATUS_SPP;
+pub const SSTATUS_UBE: u64 = MSTATUS_UBE;
 
 // MIE bits (Interrupt Enable)
 pub const MIE_MSIE: u64 = 1 << 3;
@@ -54,38 +152,158 @@ pub const MIP_MEIP: u64 = 1 << 11;
 // ========================================
 
 // Exception codes
+pub const ILLEGAL_INSTRUCTION: u64 = 2;
 pub const BREAKPOINT: u64 = 3;
+pub const LOAD_ADDRESS_MISALIGNED: u64 = 4;
+pub const STORE_AMO_ADDRESS_MISALIGNED: u64 = 6;
 pub const ECALL_FROM_U: u64 = 8;
 pub const ECALL_FROM_S: u64 = 9;
 pub const ECALL_FROM_M: u64 = 11;
 
 // Interrupt codes (use with INTERRUPT_BIT)
 pub const INTERRUPT_BIT: u64 = 1 << 63;
+// RV32 mcause의 interrupt 비트
+pub const INTERRUPT_BIT32: u64 = 1 << 31;
 pub const INTERRUPT_FROM_SOFTWARE: u64 = 3;
 pub const INTERRUPT_FROM_TIMER: u64 = 7;
 pub const INTERRUPT_FROM_EXTERNAL: u64 = 11;
 
+/// CSR 주소 공간 크기 (12비트)
+pub const CSR_COUNT: usize = 1 << 12;
+
+/// Cpu가 사용하는 CSR 파일
+/// 매 step 접근하는 CSR의 접근자는 기본적으로 read/write를 거치고, Csr는 필드 접근으로 재정의
+/// (benches/csr.rs가 이전 HashMap 구현을 같은 Cpu에 넣어서 비교)
+pub trait CsrFile {
+    fn read(&self, addr: u16) -> u64;
+
+    fn write(&mut self, addr: u16, value: u64);
+
+    fn mstatus(&self) -> u64 {
+        self.read(MSTATUS)
+    }
+
+    fn mie(&self) -> u64 {
+        self.read(MIE)
+    }
+
+    fn mip(&self) -> u64 {
+        self.read(MIP)
+    }
+
+    fn set_mip(&mut self, value: u64) {
+        self.write(MIP, value);
+    }
+
+    fn mcycle(&self) -> u64 {
+        self.read(MCYCLE)
+    }
+
+    fn set_mcycle(&mut self, value: u64) {
+        self.write(MCYCLE, value);
+    }
+
+    fn minstret(&self) -> u64 {
+        self.read(MINSTRET)
+    }
+
+    fn set_minstret(&mut self, value: u64) {
+        self.write(MINSTRET, value);
+    }
+}
+
+/// 주소로 인덱싱하는 고정 크기 CSR 파일
+/// 매 step 접근하는 CSR은 필드로 분리해서 직접 접근할 수 있고,
+/// read/write로 주소를 통해 접근해도 같은 필드를 사용
 pub struct Csr {
-    data: HashMap<u16, u64>,
+    pub mstatus: u64,
+    pub mie: u64,
+    pub mip: u64,
+    pub mcycle: u64,
+    pub minstret: u64,
+    data: Box<[u64]>,
+}
+
+impl Default for Csr {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Csr {
     pub fn new() -> Self {
         Csr {
-            data: HashMap::new(),
+            mstatus: 0,
+            mie: 0,
+            mip: 0,
+            mcycle: 0,
+            minstret: 0,
+            data: vec![0; CSR_COUNT].into_boxed_slice(),
         }
     }
 
     pub fn read(&self, addr: u16) -> u64 {
-        if self.data.contains_key(&addr) {
-            self.data[&addr]
-        } else {
-            0
+        match addr {
+            MSTATUS => self.mstatus,
+            MIE => self.mie,
+            MIP => self.mip,
+            MCYCLE => self.mcycle,
+            MINSTRET => self.minstret,
+            _ => self.data[addr as usize],
         }
     }
 
     pub fn write(&mut self, addr: u16, value: u64) {
-        self.data.insert(addr, value);
+        match addr {
+            MSTATUS => self.mstatus = value,
+            MIE => self.mie = value,
+            MIP => self.mip = value,
+            MCYCLE => self.mcycle = value,
+            MINSTRET => self.minstret = value,
+            _ => self.data[addr as usize] = value,
+        }
+    }
+}
+
+impl CsrFile for Csr {
+    fn read(&self, addr: u16) -> u64 {
+        Csr::read(self, addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u64) {
+        Csr::write(self, addr, value);
+    }
+
+    fn mstatus(&self) -> u64 {
+        self.mstatus
+    }
+
+    fn mie(&self) -> u64 {
+        self.mie
+    }
+
+    fn mip(&self) -> u64 {
+        self.mip
+    }
+
+    fn set_mip(&mut self, value: u64) {
+        self.mip = value;
+    }
+
+    fn mcycle(&self) -> u64 {
+        self.mcycle
+    }
+
+    fn set_mcycle(&mut self, value: u64) {
+        self.mcycle = value;
+    }
+
+    fn minstret(&self) -> u64 {
+        self.minstret
+    }
+
+    fn set_minstret(&mut self, value: u64) {
+        self.minstret = value;
     }
 }
 
@@ -134,6 +352,17 @@ mod tests {
         assert_eq!(csr.read(0x342), 0xCCCC);
     }
 
+    #[test]
+    fn test_hot_csr_fields_alias_addresses() {
+        let mut csr = Csr::new();
+        csr.write(MIP, MIP_MTIP);
+        assert_eq!(csr.mip, MIP_MTIP);
+        csr.mstatus = MSTATUS_MIE;
+        csr.minstret += 3;
+        assert_eq!(csr.read(MSTATUS), MSTATUS_MIE);
+        assert_eq!(csr.read(MINSTRET), 3);
+    }
+
     #[test]
     fn test_csr_64bit_value() {
         let mut csr = Csr::new();
@@ -141,4 +370,36 @@ mod tests {
         csr.write(0x300, large_value);
         assert_eq!(csr.read(0x300), large_value);
     }
+
+    // 기본 접근자만 쓰는 CSR 파일
+    struct SparseCsr(Vec<(u16, u64)>);
+
+    impl CsrFile for SparseCsr {
+        fn read(&self, addr: u16) -> u64 {
+            self.0
+                .iter()
+                .rev()
+                .find(|(a, _)| *a == addr)
+                .map_or(0, |(_, v)| *v)
+        }
+
+        fn write(&mut self, addr: u16, value: u64) {
+            self.0.push((addr, value));
+        }
+    }
+
+    #[test]
+    fn test_csr_file_accessors() {
+        let mut sparse = SparseCsr(Vec::new());
+        let mut array = Csr::new();
+        for file in [&mut sparse as &mut dyn CsrFile, &mut array] {
+            file.set_mip(MIP_MTIP);
+            file.set_mcycle(10);
+            file.write(MINSTRET, 4);
+            assert_eq!(file.read(MIP), MIP_MTIP);
+            assert_eq!(file.mcycle(), 10);
+            assert_eq!(file.minstret(), 4);
+        }
+        assert_eq!(array.mip, MIP_MTIP);
+    }
 }
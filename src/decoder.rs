@@ -1,3 +1,5 @@
+use crate::cpu::crypto::{self, CryptoOp};
+
 pub fn opcode(inst: u32) -> u32 {
     inst & 0x7F
 }
@@ -67,10 +69,763 @@ pub fn fence_succ(inst: u32) -> u32 {
     (inst >> 20) & 0xF
 }
 
+/// FENCE.TSO (fm=1000): W→R을 제외한 rw,rw 순서
+pub fn fence_tso(inst: u32) -> bool {
+    inst >> 28 == 0b1000
+}
+
+/// AMO acquire 비트
+pub fn aq(inst: u32) -> bool {
+    inst & (1 << 26) != 0
+}
+
+/// AMO release 비트
+pub fn rl(inst: u32) -> bool {
+    inst & (1 << 25) != 0
+}
+
 pub fn funct5(inst: u32) -> u32 {
     (inst >> 27) & 0x1F
 }
 
+// ========================================
+// Typed Decoder
+// ========================================
+
+const OP_IMM: u32 = 0x13;
+const OP_IMM_32: u32 = 0x1B;
+const OP: u32 = 0x33;
+const OP_32: u32 = 0x3B;
+const LOAD: u32 = 0x03;
+const STORE: u32 = 0x23;
+const BRANCH: u32 = 0x63;
+const JAL: u32 = 0x6F;
+const JALR: u32 = 0x67;
+const LUI: u32 = 0x37;
+const AUIPC: u32 = 0x17;
+const SYSTEM: u32 = 0x73;
+const MISC_MEM: u32 = 0x0F;
+const AMO: u32 = 0x2F;
+const LOAD_FP: u32 = 0x07;
+const STORE_FP: u32 = 0x27;
+const OP_FP: u32 = 0x53;
+const FMADD: u32 = 0x43;
+const FMSUB: u32 = 0x47;
+const FNMSUB: u32 = 0x4B;
+const FNMADD: u32 = 0x4F;
+
+// Zihintpause: FENCE pred=W, succ=0
+const PAUSE: u32 = 0x0100000F;
+
+/// 디코딩된 명령어
+/// 확장 활성화 여부, XLEN, 권한 검사는 실행 시점에 처리
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Lui {
+        rd: usize,
+        imm: i32,
+    },
+    Auipc {
+        rd: usize,
+        imm: i32,
+    },
+    Jal {
+        rd: usize,
+        offset: i32,
+    },
+    Jalr {
+        rd: usize,
+        rs1: usize,
+        offset: i32,
+    },
+    Branch {
+        op: BranchOp,
+        rs1: usize,
+        rs2: usize,
+        offset: i32,
+    },
+    Load {
+        op: LoadOp,
+        rd: usize,
+        rs1: usize,
+        offset: i32,
+    },
+    Store {
+        width: Width,
+        rs1: usize,
+        rs2: usize,
+        offset: i32,
+    },
+    // 시프트의 imm은 shamt
+    OpImm {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        imm: i32,
+    },
+    OpImm32 {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        imm: i32,
+    },
+    Op {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+    Op32 {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+    Fence {
+        pred: u32,
+        succ: u32,
+    },
+    FenceI,
+    Pause,
+    Cbo {
+        op: CboOp,
+        rs1: usize,
+    },
+    Ecall,
+    Ebreak,
+    Mret,
+    Sret,
+    Dret,
+    Wfi,
+    SfenceVma {
+        rs1: usize,
+        rs2: usize,
+    },
+    WrsNto,
+    WrsSto,
+    // 즉시값 형식(CSRRWI 등)의 rs1은 uimm
+    Csr {
+        op: CsrOp,
+        rd: usize,
+        rs1: usize,
+        csr: u16,
+    },
+    MopR {
+        n: u32,
+        rd: usize,
+        rs1: usize,
+    },
+    MopRr {
+        n: u32,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+    Amo {
+        op: AmoOp,
+        width: Width,
+        aq: bool,
+        rl: bool,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+    FpLoad {
+        width: Width,
+        rd: usize,
+        rs1: usize,
+        offset: i32,
+    },
+    FpStore {
+        width: Width,
+        rs1: usize,
+        rs2: usize,
+        offset: i32,
+    },
+    FpOp {
+        op: FpOp,
+        fmt: FpFmt,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+        rm: u32,
+    },
+    FpFused {
+        op: FusedOp,
+        fmt: FpFmt,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+        rs3: usize,
+        rm: u32,
+    },
+    Crypto {
+        op: CryptoOp,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchOp {
+    Beq,
+    Bne,
+    Blt,
+    Bge,
+    Bltu,
+    Bgeu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadOp {
+    Lb,
+    Lh,
+    Lw,
+    Ld,
+    Lbu,
+    Lhu,
+    Lwu,
+}
+
+/// 메모리 접근 폭 (B/H/W/D/Q)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Width {
+    B,
+    H,
+    W,
+    D,
+    Q,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AluOp {
+    Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
+    Mul,
+    Mulh,
+    Mulhsu,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+    CzeroEqz,
+    CzeroNez,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CboOp {
+    Inval,
+    Clean,
+    Flush,
+    Zero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsrOp {
+    Rw,
+    Rs,
+    Rc,
+    Rwi,
+    Rsi,
+    Rci,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmoOp {
+    Lr,
+    Sc,
+    Swap,
+    Add,
+    Xor,
+    And,
+    Or,
+    Min,
+    Max,
+    Minu,
+    Maxu,
+    Cas,
+}
+
+/// OP-FP/FMA 명령어의 fmt 필드 (Q는 지원하지 않음)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FpFmt {
+    S,
+    D,
+    H,
+}
+
+impl FpFmt {
+    fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0 => Some(FpFmt::S),
+            1 => Some(FpFmt::D),
+            2 => Some(FpFmt::H),
+            _ => None,
+        }
+    }
+}
+
+/// OP-FP 명령어 (F/D/Zfh/Zfhmin/Zfbfmin)
+/// 이름의 F는 명령어의 fmt 형식 (예: CvtWF는 fcvt.w.fmt, MvXF는 fmv.x.fmt)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FpOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Sqrt,
+    Sgnj,
+    Sgnjn,
+    Sgnjx,
+    Min,
+    Max,
+    Le,
+    Lt,
+    Eq,
+    CvtWF,
+    CvtWuF,
+    CvtLF,
+    CvtLuF,
+    CvtFW,
+    CvtFWu,
+    CvtFL,
+    CvtFLu,
+    MvXF,
+    Class,
+    MvFX,
+    /// 다른 형식(원본)에서 fmt로 변환
+    CvtFF(FpFmt),
+    CvtSBf16,
+    CvtBf16S,
+}
+
+impl FpOp {
+    /// funct3가 rm(반올림 모드)인 명령어
+    pub fn uses_rm(&self) -> bool {
+        use FpOp::*;
+        matches!(
+            self,
+            Add | Sub
+                | Mul
+                | Div
+                | Sqrt
+                | CvtWF
+                | CvtWuF
+                | CvtLF
+                | CvtLuF
+                | CvtFW
+                | CvtFWu
+                | CvtFL
+                | CvtFLu
+                | CvtFF(_)
+                | CvtSBf16
+                | CvtBf16S
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FusedOp {
+    Fmadd,
+    Fmsub,
+    Fnmsub,
+    Fnmadd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecodeError {
+    /// 하위 2비트가 11이 아닌 16비트 명령어
+    Compressed(u32),
+    /// 지원하지 않거나 예약된 인코딩
+    Illegal(u32),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Compressed(inst) => {
+                write!(f, "Compressed instruction: {:#06x}", inst & 0xFFFF)
+            }
+            DecodeError::Illegal(inst) => write!(f, "Illegal instruction: {:#010x}", inst),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// 32비트 명령어 디코딩 (RV64 인코딩 기준)
+pub fn decode(inst: u32) -> Result<Instruction, DecodeError> {
+    if inst & 0x3 != 0x3 {
+        return Err(DecodeError::Compressed(inst));
+    }
+    let illegal = Err(DecodeError::Illegal(inst));
+    let rd = rd(inst);
+    let rs1 = rs1(inst);
+    let rs2 = rs2(inst);
+    let funct3 = funct3(inst);
+    let funct7 = funct7(inst);
+
+    // 스칼라 암호 명령어는 OP/OP-IMM 공간을 공유하므로 먼저 확인
+    if let Some(op) = crypto::decode(inst) {
+        return Ok(Instruction::Crypto { op, rd, rs1, rs2 });
+    }
+
+    let decoded = match opcode(inst) {
+        LUI => Instruction::Lui {
+            rd,
+            imm: imm_u(inst),
+        },
+        AUIPC => Instruction::Auipc {
+            rd,
+            imm: imm_u(inst),
+        },
+        JAL => Instruction::Jal {
+            rd,
+            offset: imm_j(inst),
+        },
+        JALR if funct3 == 0 => Instruction::Jalr {
+            rd,
+            rs1,
+            offset: imm_i(inst),
+        },
+        BRANCH => {
+            let op = match funct3 {
+                0x0 => BranchOp::Beq,
+                0x1 => BranchOp::Bne,
+                0x4 => BranchOp::Blt,
+                0x5 => BranchOp::Bge,
+                0x6 => BranchOp::Bltu,
+                0x7 => BranchOp::Bgeu,
+                _ => return illegal,
+            };
+            Instruction::Branch {
+                op,
+                rs1,
+                rs2,
+                offset: imm_b(inst),
+            }
+        }
+        LOAD => {
+            let op = match funct3 {
+                0x0 => LoadOp::Lb,
+                0x1 => LoadOp::Lh,
+                0x2 => LoadOp::Lw,
+                0x3 => LoadOp::Ld,
+                0x4 => LoadOp::Lbu,
+                0x5 => LoadOp::Lhu,
+                0x6 => LoadOp::Lwu,
+                _ => return illegal,
+            };
+            Instruction::Load {
+                op,
+                rd,
+                rs1,
+                offset: imm_i(inst),
+            }
+        }
+        STORE if funct3 <= 0x3 => Instruction::Store {
+            width: width(funct3),
+            rs1,
+            rs2,
+            offset: imm_s(inst),
+        },
+        OP_IMM => {
+            let imm = imm_i(inst);
+            let shamt = imm & 0x3F;
+            let funct6 = (inst >> 26) & 0x3F;
+            let (op, imm) = match funct3 {
+                0x0 => (AluOp::Add, imm),
+                0x1 if funct6 == 0x00 => (AluOp::Sll, shamt),
+                0x2 => (AluOp::Slt, imm),
+                0x3 => (AluOp::Sltu, imm),
+                0x4 => (AluOp::Xor, imm),
+                0x5 if funct6 == 0x00 => (AluOp::Srl, shamt),
+                0x5 if funct6 == 0x10 => (AluOp::Sra, shamt),
+                0x6 => (AluOp::Or, imm),
+                0x7 => (AluOp::And, imm),
+                _ => return illegal,
+            };
+            Instruction::OpImm { op, rd, rs1, imm }
+        }
+        OP_IMM_32 => {
+            let imm = imm_i(inst);
+            let shamt = imm & 0x1F;
+            let (op, imm) = match (funct3, funct7) {
+                (0x0, _) => (AluOp::Add, imm),
+                (0x1, 0x00) => (AluOp::Sll, shamt),
+                (0x5, 0x00) => (AluOp::Srl, shamt),
+                (0x5, 0x20) => (AluOp::Sra, shamt),
+                _ => return illegal,
+            };
+            Instruction::OpImm32 { op, rd, rs1, imm }
+        }
+        OP => {
+            let op = match (funct3, funct7) {
+                (0x0, 0x00) => AluOp::Add,
+                (0x0, 0x20) => AluOp::Sub,
+                (0x1, 0x00) => AluOp::Sll,
+                (0x2, 0x00) => AluOp::Slt,
+                (0x3, 0x00) => AluOp::Sltu,
+                (0x4, 0x00) => AluOp::Xor,
+                (0x5, 0x00) => AluOp::Srl,
+                (0x5, 0x20) => AluOp::Sra,
+                (0x6, 0x00) => AluOp::Or,
+                (0x7, 0x00) => AluOp::And,
+                (0x0, 0x01) => AluOp::Mul,
+                (0x1, 0x01) => AluOp::Mulh,
+                (0x2, 0x01) => AluOp::Mulhsu,
+                (0x3, 0x01) => AluOp::Mulhu,
+                (0x4, 0x01) => AluOp::Div,
+                (0x5, 0x01) => AluOp::Divu,
+                (0x6, 0x01) => AluOp::Rem,
+                (0x7, 0x01) => AluOp::Remu,
+                (0x5, 0x07) => AluOp::CzeroEqz,
+                (0x7, 0x07) => AluOp::CzeroNez,
+                _ => return illegal,
+            };
+            Instruction::Op { op, rd, rs1, rs2 }
+        }
+        OP_32 => {
+            let op = match (funct3, funct7) {
+                (0x0, 0x00) => AluOp::Add,
+                (0x0, 0x20) => AluOp::Sub,
+                (0x1, 0x00) => AluOp::Sll,
+                (0x5, 0x00) => AluOp::Srl,
+                (0x5, 0x20) => AluOp::Sra,
+                (0x0, 0x01) => AluOp::Mul,
+                (0x4, 0x01) => AluOp::Div,
+                (0x5, 0x01) => AluOp::Divu,
+                (0x6, 0x01) => AluOp::Rem,
+                (0x7, 0x01) => AluOp::Remu,
+                _ => return illegal,
+            };
+            Instruction::Op32 { op, rd, rs1, rs2 }
+        }
+        MISC_MEM => match funct3 {
+            0x0 if inst == PAUSE => Instruction::Pause,
+            0x0 => Instruction::Fence {
+                pred: fence_pred(inst),
+                succ: fence_succ(inst),
+            },
+            0x1 => Instruction::FenceI,
+            0x2 if rd == 0 => {
+                let op = match csr_addr(inst) {
+                    0x0 => CboOp::Inval,
+                    0x1 => CboOp::Clean,
+                    0x2 => CboOp::Flush,
+                    0x4 => CboOp::Zero,
+                    _ => return illegal,
+                };
+                Instruction::Cbo { op, rs1 }
+            }
+            _ => return illegal,
+        },
+        SYSTEM => return decode_system(inst),
+        AMO => return decode_amo(inst),
+        LOAD_FP if (0x1..=0x3).contains(&funct3) => Instruction::FpLoad {
+            width: width(funct3),
+            rd,
+            rs1,
+            offset: imm_i(inst),
+        },
+        STORE_FP if (0x1..=0x3).contains(&funct3) => Instruction::FpStore {
+            width: width(funct3),
+            rs1,
+            rs2,
+            offset: imm_s(inst),
+        },
+        OP_FP => return decode_op_fp(inst),
+        FMADD | FMSUB | FNMSUB | FNMADD => {
+            let op = match opcode(inst) {
+                FMADD => FusedOp::Fmadd,
+                FMSUB => FusedOp::Fmsub,
+                FNMSUB => FusedOp::Fnmsub,
+                _ => FusedOp::Fnmadd,
+            };
+            let Some(fmt) = FpFmt::from_bits(funct7 & 0x3) else {
+                return illegal;
+            };
+            Instruction::FpFused {
+                op,
+                fmt,
+                rd,
+                rs1,
+                rs2,
+                rs3: (inst >> 27) as usize,
+                rm: funct3,
+            }
+        }
+        _ => return illegal,
+    };
+    Ok(decoded)
+}
+
+fn decode_system(inst: u32) -> Result<Instruction, DecodeError> {
+    let rd = rd(inst);
+    let rs1 = rs1(inst);
+    let rs2 = rs2(inst);
+    let csr = csr_addr(inst);
+    let op = match funct3(inst) {
+        0x0 => {
+            return match (funct7(inst), rs2, rs1, rd) {
+                (0x00, 0x00, 0, 0) => Ok(Instruction::Ecall),
+                (0x00, 0x01, 0, 0) => Ok(Instruction::Ebreak),
+                (0x18, 0x02, 0, 0) => Ok(Instruction::Mret),
+                (0x08, 0x02, 0, 0) => Ok(Instruction::Sret),
+                (0x3D, 0x12, 0, 0) => Ok(Instruction::Dret),
+                (0x08, 0x05, 0, 0) => Ok(Instruction::Wfi),
+                (0x09, rs2, rs1, 0) => Ok(Instruction::SfenceVma { rs1, rs2 }),
+                (0x00, 0x0D, 0, 0) => Ok(Instruction::WrsNto),
+                (0x00, 0x1D, 0, 0) => Ok(Instruction::WrsSto),
+                _ => Err(DecodeError::Illegal(inst)),
+            };
+        }
+        0x1 => CsrOp::Rw,
+        0x2 => CsrOp::Rs,
+        0x3 => CsrOp::Rc,
+        0x5 => CsrOp::Rwi,
+        0x6 => CsrOp::Rsi,
+        0x7 => CsrOp::Rci,
+        // Zimop: mop.r.n / mop.rr.n
+        _ => {
+            let funct12 = csr as u32;
+            let funct7 = funct7(inst);
+            return if funct12 & 0xB3C == 0x81C {
+                let n = ((inst >> 26) & 0x10) | ((inst >> 24) & 0xC) | ((inst >> 20) & 0x3);
+                Ok(Instruction::MopR { n, rd, rs1 })
+            } else if funct7 & 0x59 == 0x41 {
+                let n = ((inst >> 28) & 0x4) | ((inst >> 26) & 0x3);
+                Ok(Instruction::MopRr { n, rd, rs1, rs2 })
+            } else {
+                Err(DecodeError::Illegal(inst))
+            };
+        }
+    };
+    Ok(Instruction::Csr { op, rd, rs1, csr })
+}
+
+fn decode_amo(inst: u32) -> Result<Instruction, DecodeError> {
+    let funct3 = funct3(inst);
+    let rs2 = rs2(inst);
+    let op = match funct5(inst) {
+        0x02 if rs2 == 0 => AmoOp::Lr,
+        0x03 => AmoOp::Sc,
+        0x01 => AmoOp::Swap,
+        0x00 => AmoOp::Add,
+        0x04 => AmoOp::Xor,
+        0x0C => AmoOp::And,
+        0x08 => AmoOp::Or,
+        0x10 => AmoOp::Min,
+        0x14 => AmoOp::Max,
+        0x18 => AmoOp::Minu,
+        0x1C => AmoOp::Maxu,
+        0x05 => AmoOp::Cas,
+        _ => return Err(DecodeError::Illegal(inst)),
+    };
+    // LR/SC는 W/D, 일반 AMO는 B~D (Zabha), AMOCAS는 B~Q
+    let valid = match op {
+        AmoOp::Lr | AmoOp::Sc => funct3 == 0x2 || funct3 == 0x3,
+        AmoOp::Cas => funct3 <= 0x4,
+        _ => funct3 <= 0x3,
+    };
+    if !valid {
+        return Err(DecodeError::Illegal(inst));
+    }
+    Ok(Instruction::Amo {
+        op,
+        width: width(funct3),
+        aq: aq(inst),
+        rl: rl(inst),
+        rd: rd(inst),
+        rs1: rs1(inst),
+        rs2,
+    })
+}
+
+fn decode_op_fp(inst: u32) -> Result<Instruction, DecodeError> {
+    use FpOp::*;
+    let rm = funct3(inst);
+    let rs2 = rs2(inst);
+    let Some(fmt) = FpFmt::from_bits(funct7(inst) & 0x3) else {
+        return Err(DecodeError::Illegal(inst));
+    };
+    let op = match (funct7(inst) >> 2, rm, rs2) {
+        (0x00, _, _) => Add,
+        (0x01, _, _) => Sub,
+        (0x02, _, _) => Mul,
+        (0x03, _, _) => Div,
+        (0x0B, _, 0) => Sqrt,
+        (0x04, 0x0, _) => Sgnj,
+        (0x04, 0x1, _) => Sgnjn,
+        (0x04, 0x2, _) => Sgnjx,
+        (0x05, 0x0, _) => Min,
+        (0x05, 0x1, _) => Max,
+        (0x14, 0x0, _) => Le,
+        (0x14, 0x1, _) => Lt,
+        (0x14, 0x2, _) => Eq,
+        (0x18, _, 0) => CvtWF,
+        (0x18, _, 1) => CvtWuF,
+        (0x18, _, 2) => CvtLF,
+        (0x18, _, 3) => CvtLuF,
+        (0x1A, _, 0) => CvtFW,
+        (0x1A, _, 1) => CvtFWu,
+        (0x1A, _, 2) => CvtFL,
+        (0x1A, _, 3) => CvtFLu,
+        (0x1C, 0x0, 0) => MvXF,
+        (0x1C, 0x1, 0) => Class,
+        (0x1E, 0x0, 0) => MvFX,
+        // 형식 변환: rs2가 원본 형식 (6, 8은 bf16)
+        (0x08, _, 6) if fmt == FpFmt::S => CvtSBf16,
+        (0x08, _, 8) if fmt == FpFmt::H => CvtBf16S,
+        (0x08, _, _) => match FpFmt::from_bits(rs2 as u32) {
+            Some(from) if from != fmt => CvtFF(from),
+            _ => return Err(DecodeError::Illegal(inst)),
+        },
+        _ => return Err(DecodeError::Illegal(inst)),
+    };
+    Ok(Instruction::FpOp {
+        op,
+        fmt,
+        rd: rd(inst),
+        rs1: rs1(inst),
+        rs2,
+        rm,
+    })
+}
+
+fn width(funct3: u32) -> Width {
+    match funct3 & 0x7 {
+        0x0 => Width::B,
+        0x1 => Width::H,
+        0x2 => Width::W,
+        0x3 => Width::D,
+        _ => Width::Q,
+    }
+}
+
+impl Width {
+    /// 바이트 단위 크기
+    pub fn bytes(&self) -> u64 {
+        match self {
+            Width::B => 1,
+            Width::H => 2,
+            Width::W => 4,
+            Width::D => 8,
+            Width::Q => 16,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +925,248 @@ mod tests {
         let inst = 0xF14020F3;
         assert_eq!(csr_addr(inst), 0xF14);
     }
+
+    // === 타입 디코딩 ===
+    #[test]
+    fn test_decode_op() {
+        // ADD x3, x1, x2
+        assert_eq!(
+            decode(0x002081B3),
+            Ok(Instruction::Op {
+                op: AluOp::Add,
+                rd: 3,
+                rs1: 1,
+                rs2: 2
+            })
+        );
+        // czero.eqz x3, x1, x2
+        assert_eq!(
+            decode(0x0E20D1B3),
+            Ok(Instruction::Op {
+                op: AluOp::CzeroEqz,
+                rd: 3,
+                rs1: 1,
+                rs2: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_op_imm_shift() {
+        // SRAI x1, x2, 63
+        assert_eq!(
+            decode(0x43F15093),
+            Ok(Instruction::OpImm {
+                op: AluOp::Sra,
+                rd: 1,
+                rs1: 2,
+                imm: 63
+            })
+        );
+        // SLLI에 funct6가 0이 아니면 예약
+        assert_eq!(decode(0x20111093), Err(DecodeError::Illegal(0x20111093)));
+    }
+
+    #[test]
+    fn test_decode_branch_and_jump() {
+        assert_eq!(
+            decode(0xFE208CE3),
+            Ok(Instruction::Branch {
+                op: BranchOp::Beq,
+                rs1: 1,
+                rs2: 2,
+                offset: -8
+            })
+        );
+        assert_eq!(
+            decode(0xFFDFF0EF),
+            Ok(Instruction::Jal { rd: 1, offset: -4 })
+        );
+        // BRANCH funct3=2는 예약
+        assert!(decode(0x0020A063).is_err());
+    }
+
+    #[test]
+    fn test_decode_load_store() {
+        // LD x5, 8(x10)
+        assert_eq!(
+            decode(0x00853283),
+            Ok(Instruction::Load {
+                op: LoadOp::Ld,
+                rd: 5,
+                rs1: 10,
+                offset: 8
+            })
+        );
+        // SW x2, -4(x1)
+        assert_eq!(
+            decode(0xFE20AE23),
+            Ok(Instruction::Store {
+                width: Width::W,
+                rs1: 1,
+                rs2: 2,
+                offset: -4
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_system() {
+        assert_eq!(decode(0x00000073), Ok(Instruction::Ecall));
+        assert_eq!(decode(0x30200073), Ok(Instruction::Mret));
+        assert_eq!(decode(0x00D00073), Ok(Instruction::WrsNto));
+        // CSRRS x1, mhartid, x0
+        assert_eq!(
+            decode(0xF14020F3),
+            Ok(Instruction::Csr {
+                op: CsrOp::Rs,
+                rd: 1,
+                rs1: 0,
+                csr: 0xF14
+            })
+        );
+        // mop.r.0 x1, x2 / mop.rr.7 x1, x2, x3
+        assert_eq!(
+            decode(0x81C140F3),
+            Ok(Instruction::MopR {
+                n: 0,
+                rd: 1,
+                rs1: 2
+            })
+        );
+        assert_eq!(
+            decode(0xCE3140F3),
+            Ok(Instruction::MopRr {
+                n: 7,
+                rd: 1,
+                rs1: 2,
+                rs2: 3
+            })
+        );
+        assert_eq!(decode(0x10500073), Ok(Instruction::Wfi));
+        // sfence.vma x10, x11
+        assert_eq!(
+            decode(0x12B50073),
+            Ok(Instruction::SfenceVma { rs1: 10, rs2: 11 })
+        );
+    }
+
+    #[test]
+    fn test_decode_misc_mem() {
+        assert_eq!(decode(0x0100000F), Ok(Instruction::Pause));
+        assert_eq!(
+            decode(0x0FF0000F),
+            Ok(Instruction::Fence {
+                pred: 0xF,
+                succ: 0xF
+            })
+        );
+        assert_eq!(decode(0x0000100F), Ok(Instruction::FenceI));
+        // cbo.zero (x10)
+        assert_eq!(
+            decode(0x0045200F),
+            Ok(Instruction::Cbo {
+                op: CboOp::Zero,
+                rs1: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_amo() {
+        // amoadd.w.aqrl x3, x2, (x1)
+        assert_eq!(
+            decode(0x0620A1AF),
+            Ok(Instruction::Amo {
+                op: AmoOp::Add,
+                width: Width::W,
+                aq: true,
+                rl: true,
+                rd: 3,
+                rs1: 1,
+                rs2: 2
+            })
+        );
+        // lr.w의 rs2는 0이어야 함
+        assert!(decode(0x1020A1AF).is_err());
+        // amoadd.q는 없음
+        assert!(decode(0x0020C1AF).is_err());
+    }
+
+    #[test]
+    fn test_decode_fp() {
+        // fadd.h f1, f2, f3, dyn
+        assert_eq!(
+            decode(0x043170D3),
+            Ok(Instruction::FpOp {
+                op: FpOp::Add,
+                fmt: FpFmt::H,
+                rd: 1,
+                rs1: 2,
+                rs2: 3,
+                rm: 7
+            })
+        );
+        // flw f1, 0(x1)
+        assert_eq!(
+            decode(0x0000A087),
+            Ok(Instruction::FpLoad {
+                width: Width::W,
+                rd: 1,
+                rs1: 1,
+                offset: 0
+            })
+        );
+        // fadd.s f1, f2, f3, rne
+        assert_eq!(
+            decode(0x003100D3),
+            Ok(Instruction::FpOp {
+                op: FpOp::Add,
+                fmt: FpFmt::S,
+                rd: 1,
+                rs1: 2,
+                rs2: 3,
+                rm: 0
+            })
+        );
+        // fcvt.s.d f1, f2 / fcvt.s.s는 없음
+        assert_eq!(
+            decode(0x401170D3),
+            Ok(Instruction::FpOp {
+                op: FpOp::CvtFF(FpFmt::D),
+                fmt: FpFmt::S,
+                rd: 1,
+                rs1: 2,
+                rs2: 1,
+                rm: 7
+            })
+        );
+        assert!(decode(0x400170D3).is_err());
+        // fadd.q (fmt=3)는 지원하지 않음
+        assert!(decode(0x063100D3).is_err());
+    }
+
+    #[test]
+    fn test_decode_crypto() {
+        // andn x3, x1, x2
+        assert_eq!(
+            decode(0x4020F1B3),
+            Ok(Instruction::Crypto {
+                op: CryptoOp::Andn,
+                rd: 3,
+                rs1: 1,
+                rs2: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode(0x00000000), Err(DecodeError::Compressed(0)));
+        assert_eq!(decode(0xFFFFFFFF), Err(DecodeError::Illegal(0xFFFFFFFF)));
+        assert_eq!(
+            DecodeError::Illegal(0x7F).to_string(),
+            "Illegal instruction: 0x0000007f"
+        );
+    }
 }
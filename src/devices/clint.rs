@@ -4,68 +4,150 @@ pub const CLINT_SIZE: u64 = 0x10000;
 const MSIP_OFFSET: u64 = 0x0000;
 const MTIMECMP_OFFSET: u64 = 0x4000;
 const MTIME_OFFSET: u64 = 0xBFF8;
+const MTIME_HI_OFFSET: u64 = MTIME_OFFSET + 4;
 
+fn set_low(old: u64, value: u32) -> u64 {
+    (old & !0xFFFF_FFFF) | value as u64
+}
+
+fn set_high(old: u64, value: u32) -> u64 {
+    (old & 0xFFFF_FFFF) | ((value as u64) << 32)
+}
+
+/// hart별 레지스터 위치 (SiFive CLINT 배치)
+enum Register {
+    Msip(usize),
+    Mtimecmp(usize),
+    MtimecmpHi(usize),
+    Mtime,
+    MtimeHi,
+}
+
+/// mtime은 모든 hart가 공유하고 msip/mtimecmp는 hart마다 하나씩
 pub struct Clint {
     mtime: u64,
-    mtimecmp: u64,
-    msip: bool,
+    mtimecmp: Vec<u64>,
+    msip: Vec<bool>,
 }
 
 impl Clint {
     pub fn new() -> Self {
+        Self::with_harts(1)
+    }
+
+    pub fn with_harts(harts: usize) -> Self {
         Clint {
             mtime: 0,
-            mtimecmp: 0,
-            msip: false,
+            mtimecmp: vec![0; harts],
+            msip: vec![false; harts],
         }
     }
 
-    pub fn read32(&self, offset: u64) -> u32 {
-        match offset {
-            MSIP_OFFSET => self.msip as u32,
+    pub fn harts(&self) -> usize {
+        self.msip.len()
+    }
+
+    fn decode(&self, offset: u64) -> Register {
+        let register = match offset {
+            MTIME_OFFSET => Register::Mtime,
+            MTIME_HI_OFFSET => Register::MtimeHi,
+            MSIP_OFFSET..MTIMECMP_OFFSET if offset.is_multiple_of(4) => {
+                Register::Msip(((offset - MSIP_OFFSET) / 4) as usize)
+            }
+            MTIMECMP_OFFSET..MTIME_OFFSET => {
+                let hart = ((offset - MTIMECMP_OFFSET) / 8) as usize;
+                match (offset - MTIMECMP_OFFSET) % 8 {
+                    0 => Register::Mtimecmp(hart),
+                    4 => Register::MtimecmpHi(hart),
+                    _ => panic!("Not Implemented"),
+                }
+            }
             _ => panic!("Not Implemented"),
+        };
+        match register {
+            Register::Msip(hart) | Register::Mtimecmp(hart) | Register::MtimecmpHi(hart)
+                if hart >= self.harts() =>
+            {
+                panic!("Not Implemented")
+            }
+            register => register,
+        }
+    }
+
+    pub fn read32(&self, offset: u64) -> u32 {
+        match self.decode(offset) {
+            Register::Msip(hart) => self.msip[hart] as u32,
+            // RV32 소프트웨어는 64비트 레지스터를 절반씩 접근
+            Register::Mtimecmp(hart) => self.mtimecmp[hart] as u32,
+            Register::MtimecmpHi(hart) => (self.mtimecmp[hart] >> 32) as u32,
+            Register::Mtime => self.mtime as u32,
+            Register::MtimeHi => (self.mtime >> 32) as u32,
         }
     }
 
     pub fn read64(&self, offset: u64) -> u64 {
-        match offset {
-            MTIMECMP_OFFSET => self.mtimecmp,
-            MTIME_OFFSET => self.mtime,
+        match self.decode(offset) {
+            Register::Mtimecmp(hart) => self.mtimecmp[hart],
+            Register::Mtime => self.mtime,
             _ => panic!("Not Implemented"),
         }
     }
 
     pub fn write32(&mut self, offset: u64, value: u32) {
-        match offset {
-            MSIP_OFFSET => {
-                self.msip = value > 0;
+        match self.decode(offset) {
+            Register::Msip(hart) => {
+                self.msip[hart] = value > 0;
             }
-            _ => panic!("Not Implemented"),
+            Register::Mtimecmp(hart) => {
+                self.mtimecmp[hart] = set_low(self.mtimecmp[hart], value);
+            }
+            Register::MtimecmpHi(hart) => {
+                self.mtimecmp[hart] = set_high(self.mtimecmp[hart], value);
+            }
+            Register::Mtime => self.mtime = set_low(self.mtime, value),
+            Register::MtimeHi => self.mtime = set_high(self.mtime, value),
         }
     }
 
     pub fn write64(&mut self, offset: u64, value: u64) {
-        match offset {
-            MTIMECMP_OFFSET => {
-                self.mtimecmp = value;
+        match self.decode(offset) {
+            Register::Mtimecmp(hart) => {
+                self.mtimecmp[hart] = value;
             }
-            MTIME_OFFSET => {
+            Register::Mtime => {
                 self.mtime = value;
             }
             _ => panic!("Not Implemented"),
         }
     }
 
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
     pub fn tick(&mut self) {
-        self.mtime += 1;
+        self.advance(1);
     }
 
-    pub fn check_timer_interrupt(&self) -> bool {
-        self.mtimecmp != 0 && self.mtime >= self.mtimecmp
+    /// 버스가 밀린 tick을 한 번에 반영
+    pub fn advance(&mut self, ticks: u64) {
+        self.mtime = self.mtime.wrapping_add(ticks);
     }
 
-    pub fn check_software_interrupt(&self) -> bool {
-        self.msip
+    pub fn check_timer_interrupt(&self, hart: usize) -> bool {
+        self.mtimecmp[hart] != 0 && self.mtime >= self.mtimecmp[hart]
+    }
+
+    /// hart의 타이머 인터럽트가 걸리기까지 남은 tick 수 (이미 걸려 있으면 0, mtimecmp 미설정이면 None)
+    pub fn ticks_until_timer(&self, hart: usize) -> Option<u64> {
+        if self.mtimecmp[hart] == 0 {
+            return None;
+        }
+        Some(self.mtimecmp[hart].saturating_sub(self.mtime))
+    }
+
+    pub fn check_software_interrupt(&self, hart: usize) -> bool {
+        self.msip[hart]
     }
 }
 
@@ -144,12 +226,24 @@ mod tests {
         assert_eq!(clint.read64(MTIME_OFFSET), 100);
     }
 
+    #[test]
+    fn test_ticks_until_timer() {
+        let mut clint = Clint::new();
+        assert_eq!(clint.ticks_until_timer(0), None);
+        clint.write64(MTIMECMP_OFFSET, 5);
+        clint.advance(3);
+        assert_eq!(clint.ticks_until_timer(0), Some(2));
+        clint.advance(4);
+        assert_eq!(clint.ticks_until_timer(0), Some(0));
+        assert!(clint.check_timer_interrupt(0));
+    }
+
     #[test]
     fn test_check_timer_interrupt_not_triggered() {
         let mut clint = Clint::new();
         clint.write64(MTIMECMP_OFFSET, 5);
         clint.write64(MTIME_OFFSET, 3);
-        assert!(!clint.check_timer_interrupt());
+        assert!(!clint.check_timer_interrupt(0));
     }
 
     #[test]
@@ -157,7 +251,7 @@ mod tests {
         let mut clint = Clint::new();
         clint.write64(MTIMECMP_OFFSET, 5);
         clint.write64(MTIME_OFFSET, 5);
-        assert!(clint.check_timer_interrupt());
+        assert!(clint.check_timer_interrupt(0));
     }
 
     #[test]
@@ -165,7 +259,7 @@ mod tests {
         let mut clint = Clint::new();
         clint.write64(MTIMECMP_OFFSET, 5);
         clint.write64(MTIME_OFFSET, 10);
-        assert!(clint.check_timer_interrupt());
+        assert!(clint.check_timer_interrupt(0));
     }
 
     #[test]
@@ -173,6 +267,42 @@ mod tests {
         let mut clint = Clint::new();
         clint.write64(MTIMECMP_OFFSET, 0);
         clint.write64(MTIME_OFFSET, 100);
-        assert!(!clint.check_timer_interrupt()); // mtimecmp=0은 미설정
+        assert!(!clint.check_timer_interrupt(0)); // mtimecmp=0은 미설정
+    }
+
+    #[test]
+    fn test_32bit_halves() {
+        let mut clint = Clint::new();
+        clint.write32(MTIMECMP_OFFSET, 0xDEAD_BEEF);
+        clint.write32(MTIMECMP_OFFSET + 4, 0x1);
+        assert_eq!(clint.read64(MTIMECMP_OFFSET), 0x1_DEAD_BEEF);
+
+        clint.write64(MTIME_OFFSET, 0x2_0000_0003);
+        assert_eq!(clint.read32(MTIME_OFFSET), 3);
+        assert_eq!(clint.read32(MTIME_OFFSET + 4), 2);
+    }
+
+    #[test]
+    fn test_per_hart_registers() {
+        let mut clint = Clint::with_harts(3);
+        clint.write32(MSIP_OFFSET + 4 * 2, 1);
+        assert!(!clint.check_software_interrupt(0));
+        assert!(clint.check_software_interrupt(2));
+
+        clint.write64(MTIMECMP_OFFSET + 8, 10);
+        clint.write32(MTIMECMP_OFFSET + 8 * 2 + 4, 1);
+        assert_eq!(clint.read64(MTIMECMP_OFFSET), 0);
+        assert_eq!(clint.read64(MTIMECMP_OFFSET + 8 * 2), 1 << 32);
+        clint.advance(10);
+        assert!(!clint.check_timer_interrupt(0));
+        assert!(clint.check_timer_interrupt(1));
+        assert_eq!(clint.ticks_until_timer(2), Some((1 << 32) - 10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_msip_beyond_harts() {
+        let clint = Clint::with_harts(2);
+        clint.read32(MSIP_OFFSET + 4 * 2);
     }
 }
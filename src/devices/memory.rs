@@ -1,67 +1,303 @@
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicU64, Ordering};
+
 pub const DRAM_BASE: u64 = 0x80000000;
 pub const DRAM_SIZE: u64 = 0x8000000;
 
+/// 예약 그래뉼 크기 (AMOCAS.Q 블록 하나가 그래뉼 하나)
+pub const GRANULE_SHIFT: usize = 4;
+/// 그래뉼 순번 테이블 크기. 그래뉼 번호를 이 크기로 접으므로 1 MiB 간격의 그래뉼은 순번을 공유
+/// (공유하는 그래뉼에 대한 store는 SC를 실패시킬 수 있음. 명세상 SC의 실패는 허용됨)
+pub const GRANULES: usize = 1 << 16;
+// 그래뉼 순번의 최하위 비트: 병렬 실행에서 쓰는 중 (순번은 2씩 증가)
+const LOCKED: u64 = 1;
+
+fn granule(offset: usize) -> usize {
+    (offset >> GRANULE_SHIFT) & (GRANULES - 1)
+}
+
+/// DRAM. 병렬 실행에서 여러 hart 스레드가 같은 저장소를 공유하도록 atomic 워드 배열로 보관
+/// 모든 접근은 AtomicU64 단위. 폭이 다른 atomic으로 같은 바이트에 동시에 접근하면 Rust 메모리
+/// 모델에서 undefined behaviour이므로, 8바이트 워드 안의 접근은 워드 load 후 shift/mask,
+/// 워드보다 작은 store는 워드에 대한 CAS 루프 (호스트가 little-endian이라고 가정)
+/// 워드 경계를 넘는 접근은 바이트 단위 (명세상 misaligned 접근은 원자성을 보장하지 않음)
+/// clone()은 같은 저장소를 가리킴
+///
+/// DRAM 쓰기는 16바이트 그래뉼마다 store 순번을 올림. LR이 읽은 순번이 SC 때까지 그대로면
+/// 사이에 다른 store가 없었다는 뜻이라 같은 값을 다시 쓴 store(ABA)도 SC를 실패시킴
+/// share()로 만든 병렬 실행용 Memory는 쓰기마다 그래뉼을 잠가서 AMO, SC, AMOCAS.Q가
+/// 같은 그래뉼에 대한 다른 스레드의 모든 쓰기와 원자적
+#[derive(Clone)]
 pub struct Memory {
-    dram: Vec<u8>,
+    dram: Arc<Vec<AtomicU64>>,
+    // 그래뉼별 store 순번 (LOCKED 비트 포함)
+    granules: Arc<Vec<AtomicU64>>,
+    // 다른 스레드와 동시에 쓰는지 (쓰기가 그래뉼 잠금을 잡음)
+    concurrent: bool,
 }
 
 impl Memory {
     pub fn new() -> Self {
+        // vec![0; n]은 0으로 채운 페이지를 지연 할당하므로 AtomicU64로 바꿔서 재사용
+        // (AtomicU64는 u64와 크기/정렬이 같고 0은 유효한 값)
+        let mut words = std::mem::ManuallyDrop::new(vec![0u64; (DRAM_SIZE / 8) as usize]);
+        let dram = unsafe {
+            Vec::from_raw_parts(
+                words.as_mut_ptr().cast::<AtomicU64>(),
+                words.len(),
+                words.capacity(),
+            )
+        };
+        Self {
+            dram: Arc::new(dram),
+            granules: Arc::new((0..GRANULES).map(|_| AtomicU64::new(0)).collect()),
+            concurrent: false,
+        }
+    }
+
+    /// DRAM이 없는 메모리 (접근하면 패닉). 실행 중이 아닌 hart의 빈 버스에 사용
+    pub fn empty() -> Self {
+        Self {
+            dram: Arc::new(Vec::new()),
+            granules: Arc::new(Vec::new()),
+            concurrent: false,
+        }
+    }
+
+    /// 같은 저장소를 다른 스레드와 동시에 쓰는 Memory (병렬 실행의 hart 스레드마다 하나)
+    pub fn share(&self) -> Self {
         Self {
-            dram: vec![0; DRAM_SIZE as usize],
+            concurrent: true,
+            ..self.clone()
         }
     }
 
+    fn len(&self) -> usize {
+        self.dram.len() * 8
+    }
+
+    // offset부터 size바이트가 DRAM 안인지 확인 (벗어나면 패닉)
+    fn check_range(&self, offset: usize, size: usize) {
+        assert!(
+            offset
+                .checked_add(size)
+                .is_some_and(|end| end <= self.len()),
+            "DRAM access out of range: offset={:#x} size={}",
+            offset,
+            size
+        );
+    }
+
+    // size바이트 값의 마스크
+    fn mask(size: usize) -> u64 {
+        u64::MAX >> (64 - 8 * size)
+    }
+
     pub fn read8(&self, addr: u64) -> u8 {
-        let index = (addr - DRAM_BASE) as usize;
-        self.dram[index]
+        self.load((addr - DRAM_BASE) as usize, 1) as u8
     }
 
     pub fn read16(&self, addr: u64) -> u16 {
-        let index = (addr - DRAM_BASE) as usize;
-        u16::from_le_bytes([self.dram[index], self.dram[index + 1]])
+        self.load((addr - DRAM_BASE) as usize, 2) as u16
     }
 
     pub fn read32(&self, addr: u64) -> u32 {
-        let index = (addr - DRAM_BASE) as usize;
-        let slice = &self.dram[index..(index + 4)];
-        u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]])
+        self.load((addr - DRAM_BASE) as usize, 4) as u32
     }
 
     pub fn read64(&self, addr: u64) -> u64 {
-        let index = (addr - DRAM_BASE) as usize;
-        let slice = &self.dram[index..(index + 8)];
-        u64::from_le_bytes([
-            slice[0], slice[1], slice[2], slice[3], slice[4], slice[5], slice[6], slice[7],
-        ])
+        self.load((addr - DRAM_BASE) as usize, 8)
     }
 
     pub fn write8(&mut self, addr: u64, value: u8) {
-        let index = (addr - DRAM_BASE) as usize;
-        self.dram[index] = value;
+        self.store((addr - DRAM_BASE) as usize, 1, value as u64);
     }
 
     pub fn write16(&mut self, addr: u64, value: u16) {
-        let index = (addr - DRAM_BASE) as usize;
-        let bytes = value.to_le_bytes();
-        for i in 0..2 {
-            self.dram[index + i] = bytes[i]
-        }
+        self.store((addr - DRAM_BASE) as usize, 2, value as u64);
     }
 
     pub fn write32(&mut self, addr: u64, value: u32) {
-        let index = (addr - DRAM_BASE) as usize;
-        let bytes = value.to_le_bytes();
-        for i in 0..4 {
-            self.dram[index + i] = bytes[i]
-        }
+        self.store((addr - DRAM_BASE) as usize, 4, value as u64);
     }
 
     pub fn write64(&mut self, addr: u64, value: u64) {
-        let index = (addr - DRAM_BASE) as usize;
-        let bytes = value.to_le_bytes();
-        for i in 0..8 {
-            self.dram[index + i] = bytes[i]
+        self.store((addr - DRAM_BASE) as usize, 8, value);
+    }
+
+    /// DRAM 시작 기준 오프셋으로 size(1/2/4/8)바이트 읽기 (DRAM 직접 접근)
+    pub fn load(&self, offset: usize, size: usize) -> u64 {
+        self.check_range(offset, size);
+        let shift = offset % 8;
+        if shift + size > 8 {
+            return (0..size)
+                .rev()
+                .fold(0, |value, i| (value << 8) | self.load(offset + i, 1));
+        }
+        let word = self.dram[offset / 8].load(Ordering::Relaxed);
+        (word >> (8 * shift)) & Self::mask(size)
+    }
+
+    /// DRAM 시작 기준 오프셋으로 size(1/2/4/8)바이트 쓰기 (DRAM 직접 접근)
+    pub fn store(&mut self, offset: usize, size: usize, value: u64) {
+        self.check_range(offset, size);
+        self.write_granules(offset, size, |_seq| {
+            self.store_raw(offset, size, value);
+            ((), true)
+        });
+    }
+
+    // 그래뉼 순번을 건드리지 않는 쓰기 (범위 검사 포함)
+    fn store_raw(&self, offset: usize, size: usize, value: u64) {
+        self.check_range(offset, size);
+        let shift = offset % 8;
+        if shift + size > 8 {
+            for i in 0..size {
+                self.store_raw(offset + i, 1, value >> (8 * i));
+            }
+            return;
+        }
+        let word = &self.dram[offset / 8];
+        if size == 8 {
+            word.store(value, Ordering::Relaxed);
+            return;
+        }
+        // 워드의 나머지 바이트는 다른 스레드가 동시에 쓸 수 있으므로 CAS로 병합
+        let mask = Self::mask(size) << (8 * shift);
+        let bits = (value << (8 * shift)) & mask;
+        let _ = word.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |old| {
+            Some((old & !mask) | bits)
+        });
+    }
+
+    /// 정렬된 size바이트 위치를 읽고 f가 돌려준 값이 있으면 씀. 항상 이전 값을 반환
+    /// 같은 그래뉼의 다른 쓰기와 원자적이므로 AMO에 사용
+    pub fn update(&self, offset: usize, size: usize, f: impl FnOnce(u64) -> Option<u64>) -> u64 {
+        assert!(offset.is_multiple_of(size), "unaligned atomic access");
+        self.check_range(offset, size);
+        self.write_granules(offset, size, |_seq| {
+            let old = self.load(offset, size);
+            match f(old) {
+                Some(new) => {
+                    self.store_raw(offset, size, new);
+                    (old, true)
+                }
+                None => (old, false),
+            }
+        })
+    }
+
+    /// 16바이트 정렬 위치가 current이면 new로 바꿈 (AMOCAS.Q). 항상 이전 값을 반환
+    pub fn compare_exchange128(&self, offset: usize, current: u128, new: u128) -> u128 {
+        assert!(offset.is_multiple_of(16), "unaligned atomic access");
+        self.check_range(offset, 16);
+        self.write_granules(offset, 16, |_seq| {
+            let old = self.load(offset, 8) as u128 | ((self.load(offset + 8, 8) as u128) << 64);
+            if old != current {
+                return (old, false);
+            }
+            self.store_raw(offset, 8, new as u64);
+            self.store_raw(offset + 8, 8, (new >> 64) as u64);
+            (old, true)
+        })
+    }
+
+    /// LR: 정렬된 size바이트를 읽고 그래뉼의 store 순번을 함께 반환
+    pub fn load_linked(&self, offset: usize, size: usize) -> (u64, u64) {
+        let word = &self.granules[granule(offset)];
+        loop {
+            // seqlock 읽기: 읽기 전후 순번이 같고 쓰는 중이 아니면 그 사이 store가 없음
+            let seq = word.load(Ordering::Acquire);
+            if seq & LOCKED == 0 {
+                let value = self.load(offset, size);
+                atomic::fence(Ordering::Acquire);
+                if word.load(Ordering::Relaxed) == seq {
+                    return (value, seq);
+                }
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// SC: 그래뉼의 store 순번이 LR 때와 같으면 쓰고 true
+    pub fn store_conditional(&self, offset: usize, size: usize, value: u64, seq: u64) -> bool {
+        assert!(offset.is_multiple_of(size), "unaligned atomic access");
+        self.check_range(offset, size);
+        self.write_granules(offset, size, |current| {
+            let stored = current == seq;
+            if stored {
+                self.store_raw(offset, size, value);
+            }
+            (stored, stored)
+        })
+    }
+
+    /// offset이 속한 그래뉼의 현재 store 순번 (LR이 받은 순번과 같으면 예약이 유효)
+    pub fn granule_seq(&self, offset: usize) -> u64 {
+        self.granules[granule(offset)].load(Ordering::Acquire)
+    }
+
+    /// 다른 스레드와 동시에 쓰는 Memory인지 (share())
+    pub fn is_concurrent(&self) -> bool {
+        self.concurrent
+    }
+
+    /// DRAM 시작의 호스트 주소 (JIT 번역 코드가 직접 접근)
+    pub fn host_ptr(&self) -> *mut u8 {
+        self.dram.as_ptr().cast::<u8>().cast_mut()
+    }
+
+    /// 그래뉼 store 순번 테이블의 호스트 주소 (JIT 번역 코드의 store가 순번을 올림)
+    pub fn granules_ptr(&self) -> *mut u64 {
+        self.granules.as_ptr().cast::<u64>().cast_mut()
+    }
+
+    // [offset, offset + size)가 걸친 그래뉼(하나 또는 둘)을 잠그고 write 실행
+    // write는 첫 그래뉼의 순번을 받아 (결과, 썼는지)를 반환하고, 썼으면 순번을 올림
+    // 한 스레드에서 실행할 때는 잠금 없이 순번만 올림
+    fn write_granules<R>(
+        &self,
+        offset: usize,
+        size: usize,
+        write: impl FnOnce(u64) -> (R, bool),
+    ) -> R {
+        let (first, last) = (granule(offset), granule(offset + size - 1));
+        let words = [
+            &self.granules[first.min(last)],
+            &self.granules[first.max(last)],
+        ];
+        let words = if first == last {
+            &words[..1]
+        } else {
+            &words[..]
+        };
+        if self.concurrent {
+            // 잠그는 순서를 그래뉼 번호 순으로 고정해서 교착을 피함
+            for word in words {
+                Self::lock_granule(word);
+            }
+        }
+        let (result, wrote) = write(self.granules[first].load(Ordering::Relaxed) & !LOCKED);
+        if self.concurrent || wrote {
+            for word in words {
+                let seq = (word.load(Ordering::Relaxed) & !LOCKED) + if wrote { 2 } else { 0 };
+                word.store(seq, Ordering::Release);
+            }
+        }
+        result
+    }
+
+    fn lock_granule(word: &AtomicU64) {
+        loop {
+            let seq = word.load(Ordering::Relaxed);
+            if seq & LOCKED == 0
+                && word
+                    .compare_exchange_weak(seq, seq | LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return;
+            }
+            std::hint::spin_loop();
         }
     }
 }
@@ -98,6 +334,15 @@ mod tests {
         assert_eq!(mem.read8(0x80000001), 0xAB); // MSB
     }
 
+    #[test]
+    fn test_memory_load_store_by_offset() {
+        let mut mem = Memory::new();
+        mem.store(0x10, 4, 0x1234_5678_9ABC_DEF0);
+        assert_eq!(mem.read32(DRAM_BASE + 0x10), 0x9ABC_DEF0);
+        assert_eq!(mem.load(0x10, 2), 0xDEF0);
+        assert_eq!(mem.load(0x10, 8), 0x9ABC_DEF0);
+    }
+
     #[test]
     fn test_memory_little_endian() {
         let mut mem = Memory::new();
@@ -107,4 +352,123 @@ mod tests {
         assert_eq!(mem.read8(0x80000002), 0x34);
         assert_eq!(mem.read8(0x80000003), 0x12); // MSB last
     }
+
+    #[test]
+    fn test_memory_unaligned_access() {
+        let mut mem = Memory::new();
+        mem.store(0x3, 8, 0x1122_3344_5566_7788);
+        assert_eq!(mem.load(0x3, 8), 0x1122_3344_5566_7788);
+        assert_eq!(mem.load(0x4, 2), 0x6677);
+        assert_eq!(mem.read8(DRAM_BASE + 0x3), 0x88);
+    }
+
+    #[test]
+    fn test_memory_update() {
+        let mut mem = Memory::new();
+        mem.store(0x8, 4, 5);
+        assert_eq!(mem.update(0x8, 4, |old| Some(old + 2)), 5);
+        assert_eq!(mem.update(0x8, 4, |_| None), 7);
+        assert_eq!(mem.load(0x8, 8), 7);
+    }
+
+    #[test]
+    fn test_store_conditional_sees_aba_store() {
+        let mut mem = Memory::new();
+        mem.store(0x10, 8, 1);
+        let (value, seq) = mem.load_linked(0x10, 8);
+        assert_eq!(value, 1);
+        // 같은 값을 다시 써도 그래뉼 순번이 바뀌므로 SC 실패
+        mem.store(0x10, 8, 1);
+        assert!(!mem.store_conditional(0x10, 8, 2, seq));
+        assert_eq!(mem.load(0x10, 8), 1);
+
+        let (_, seq) = mem.load_linked(0x10, 8);
+        // 다른 그래뉼에 대한 store는 예약에 영향 없음
+        mem.store(0x20, 8, 5);
+        assert!(mem.store_conditional(0x10, 8, 2, seq));
+        assert_eq!(mem.load(0x10, 8), 2);
+        assert_ne!(mem.granule_seq(0x10), seq);
+    }
+
+    #[test]
+    fn test_compare_exchange128() {
+        let mem = Memory::new().share();
+        let old = mem.compare_exchange128(0x20, 0, (2u128 << 64) | 1);
+        assert_eq!(old, 0);
+        assert_eq!(mem.compare_exchange128(0x20, 0, 7), (2u128 << 64) | 1);
+        assert_eq!((mem.load(0x20, 8), mem.load(0x28, 8)), (1, 2));
+    }
+
+    #[test]
+    fn test_concurrent_wide_cas_is_atomic_with_stores() {
+        // 한 스레드는 AMOCAS.Q로 두 워드를 함께 올리고, 다른 스레드는 위쪽 워드만 올림
+        // 쓰기가 모두 그래뉼 잠금을 거치므로 어느 쪽의 갱신도 사라지지 않음
+        let mem = Memory::new();
+        std::thread::scope(|scope| {
+            let wide = mem.share();
+            scope.spawn(move || {
+                for _ in 0..10_000 {
+                    let mut old = wide.compare_exchange128(0x40, 0, 0);
+                    loop {
+                        let new = old + ((1u128 << 64) | 1);
+                        match wide.compare_exchange128(0x40, old, new) {
+                            current if current == old => break,
+                            current => old = current,
+                        }
+                    }
+                }
+            });
+            let narrow = mem.share();
+            scope.spawn(move || {
+                for _ in 0..10_000 {
+                    narrow.update(0x48, 8, |old| Some(old + 1));
+                }
+            });
+        });
+        assert_eq!(mem.load(0x40, 8), 10_000);
+        assert_eq!(mem.load(0x48, 8), 20_000);
+    }
+
+    #[test]
+    fn test_concurrent_narrow_stores_keep_neighbours() {
+        // 같은 워드의 서로 다른 바이트/하프워드를 잠금 없이 동시에 써도 (clone()) 다른 쪽 쓰기가
+        // 사라지지 않고, 워드 load는 항상 각 부분의 완전한 값만 봄
+        let mem = Memory::new();
+        std::thread::scope(|scope| {
+            for (offset, size) in [(0, 1), (1, 1), (2, 2), (4, 4)] {
+                let mut mem = mem.clone();
+                scope.spawn(move || {
+                    for i in 1..=1000u64 {
+                        mem.store(0x80 + offset, size, i);
+                    }
+                });
+            }
+            let mem = mem.clone();
+            scope.spawn(move || {
+                for _ in 0..1000 {
+                    let word = mem.load(0x80, 8);
+                    assert!(word >> 32 <= 1000 && (word >> 16) & 0xFFFF <= 1000);
+                }
+            });
+        });
+        assert_eq!(
+            mem.load(0x80, 8),
+            (1000 << 32) | (1000 << 16) | ((1000 & 0xFF) << 8) | (1000 & 0xFF)
+        );
+    }
+
+    #[test]
+    fn test_clones_share_storage() {
+        let mut mem = Memory::new();
+        let other = mem.clone();
+        mem.write64(DRAM_BASE + 0x100, 42);
+        assert_eq!(other.read64(DRAM_BASE + 0x100), 42);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range_access() {
+        let mem = Memory::new();
+        mem.load(DRAM_SIZE as usize - 4, 8);
+    }
 }
@@ -2,6 +2,7 @@ pub mod clint;
 pub mod memory;
 pub mod stdioterminal;
 pub mod terminal;
+pub mod timebase;
 pub mod uart;
 
 pub use clint::CLINT_BASE;
@@ -12,6 +13,8 @@ pub use memory::DRAM_BASE;
 pub use memory::DRAM_SIZE;
 pub use memory::Memory;
 
+pub use timebase::Timebase;
+
 pub use uart::UART_BASE;
 pub use uart::UART_SIZE;
 pub use uart::Uart;
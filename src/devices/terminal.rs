@@ -1,8 +1,19 @@
-pub trait Terminal {
+pub trait Terminal: Send {
     fn write(&mut self, data: u8);
     fn read(&mut self) -> Option<u8>;
 }
 
+/// 출력을 버리고 입력이 없는 터미널
+pub struct NullTerminal;
+
+impl Terminal for NullTerminal {
+    fn write(&mut self, _data: u8) {}
+
+    fn read(&mut self) -> Option<u8> {
+        None
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
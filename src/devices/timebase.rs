@@ -0,0 +1,119 @@
+//! mtime 시간 기준
+//! 명령어 수 기반(결정적)과 호스트 monotonic clock 기반 중 선택
+//! frequency는 mtime이 1초에 증가하는 양으로, 디바이스 트리 timebase-frequency로 게스트에 전달
+
+use std::time::Instant;
+
+/// QEMU virt 머신과 같은 기본값
+pub const DEFAULT_TIMEBASE_FREQUENCY: u64 = 10_000_000;
+
+// 호스트 시계 기준일 때 mtimecmp 도달 여부를 다시 확인하는 간격 (명령어 수)
+const HOST_POLL_INTERVAL: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeSource {
+    /// 명령어 instructions_per_tick개마다 mtime 1 증가
+    Icount { instructions_per_tick: u64 },
+    /// 시작 시각부터 흐른 호스트 시간
+    Host { start: Instant },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timebase {
+    /// 게스트에 알려주는 mtime 주파수 (Hz)
+    pub frequency: u64,
+    pub source: TimeSource,
+}
+
+impl Default for Timebase {
+    /// 명령어 하나에 mtime 1
+    fn default() -> Self {
+        Self::icount(DEFAULT_TIMEBASE_FREQUENCY, 1)
+    }
+}
+
+impl Timebase {
+    pub fn icount(frequency: u64, instructions_per_tick: u64) -> Self {
+        assert!(
+            instructions_per_tick > 0,
+            "instructions_per_tick must be > 0"
+        );
+        Timebase {
+            frequency,
+            source: TimeSource::Icount {
+                instructions_per_tick,
+            },
+        }
+    }
+
+    /// 지금부터 호스트 시계로 frequency Hz
+    pub fn host(frequency: u64) -> Self {
+        Timebase {
+            frequency,
+            source: TimeSource::Host {
+                start: Instant::now(),
+            },
+        }
+    }
+
+    /// 버스 tick(실행한 명령어 수)이 ticks일 때 시작 이후 흐른 mtime 단위 시간
+    pub fn time(&self, ticks: u64) -> u64 {
+        match self.source {
+            TimeSource::Icount {
+                instructions_per_tick,
+            } => ticks / instructions_per_tick,
+            TimeSource::Host { start } => {
+                let nanos = start.elapsed().as_nanos();
+                (nanos * self.frequency as u128 / 1_000_000_000) as u64
+            }
+        }
+    }
+
+    /// 현재 ticks에서 mtime이 delta만큼 증가하기까지 기다릴 버스 tick 수
+    /// 호스트 시계는 실행 속도와 무관하므로 일정 간격으로 다시 확인
+    pub fn ticks_until(&self, ticks: u64, delta: u64) -> u64 {
+        match self.source {
+            TimeSource::Icount {
+                instructions_per_tick,
+            } => {
+                let target = (ticks / instructions_per_tick).saturating_add(delta);
+                target
+                    .saturating_mul(instructions_per_tick)
+                    .saturating_sub(ticks)
+            }
+            TimeSource::Host { .. } => HOST_POLL_INTERVAL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_one_tick_per_instruction() {
+        let timebase = Timebase::default();
+        assert_eq!(timebase.frequency, DEFAULT_TIMEBASE_FREQUENCY);
+        assert_eq!(timebase.time(42), 42);
+        assert_eq!(timebase.ticks_until(42, 5), 5);
+    }
+
+    #[test]
+    fn test_icount_ratio() {
+        let timebase = Timebase::icount(1_000_000, 100);
+        assert_eq!(timebase.time(99), 0);
+        assert_eq!(timebase.time(250), 2);
+        // 250에서 mtime 2 → 4가 되는 시점은 400
+        assert_eq!(timebase.ticks_until(250, 2), 150);
+    }
+
+    #[test]
+    fn test_host_clock_advances_with_wall_time() {
+        let timebase = Timebase::host(1_000_000_000);
+        let before = timebase.time(0);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        // 1 GHz에서 2ms는 2,000,000 tick 이상. 실행한 명령어 수와 무관
+        assert!(timebase.time(0) - before >= 2_000_000);
+        assert_eq!(timebase.ticks_until(0, 1), HOST_POLL_INTERVAL);
+    }
+}
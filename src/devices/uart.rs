@@ -110,6 +110,11 @@ impl Uart {
         }
     }
 
+    /// 입출력할 터미널 교체
+    pub fn set_terminal(&mut self, terminal: Box<dyn Terminal>) {
+        self.terminal = terminal;
+    }
+
     pub fn push_input(&mut self, data: u8) {
         self.rx_fifo_push(data);
     }
@@ -191,18 +196,17 @@ impl Uart {
 mod tests {
     use super::*;
     use crate::devices::terminal::tests::MockTerminal;
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
 
-    /// 테스트용 SharedMockTerminal - Rc<RefCell>로 MockTerminal을 감싸서 공유 가능하게 함
-    struct SharedMockTerminal(Rc<RefCell<MockTerminal>>);
+    /// 테스트용 SharedMockTerminal - Arc<Mutex>로 MockTerminal을 감싸서 공유 가능하게 함
+    struct SharedMockTerminal(Arc<Mutex<MockTerminal>>);
 
     impl Terminal for SharedMockTerminal {
         fn write(&mut self, data: u8) {
-            self.0.borrow_mut().output.push(data);
+            self.0.lock().unwrap().output.push(data);
         }
         fn read(&mut self) -> Option<u8> {
-            self.0.borrow_mut().input.pop_front()
+            self.0.lock().unwrap().input.pop_front()
         }
     }
 
@@ -210,8 +214,8 @@ mod tests {
         Uart::new(Box::new(MockTerminal::new()))
     }
 
-    fn create_uart_with_mock() -> (Uart, Rc<RefCell<MockTerminal>>) {
-        let mock = Rc::new(RefCell::new(MockTerminal::new()));
+    fn create_uart_with_mock() -> (Uart, Arc<Mutex<MockTerminal>>) {
+        let mock = Arc::new(Mutex::new(MockTerminal::new()));
         let uart = Uart::new(Box::new(SharedMockTerminal(mock.clone())));
         (uart, mock)
     }
@@ -300,7 +304,7 @@ mod tests {
         uart.transmit();
 
         // Terminal에 출력 확인
-        assert_eq!(mock.borrow().output_as_string(), "Hi");
+        assert_eq!(mock.lock().unwrap().output_as_string(), "Hi");
     }
 
     #[test]
@@ -310,7 +314,7 @@ mod tests {
         // 빈 FIFO에서 transmit - 아무것도 출력 안 됨
         uart.transmit();
 
-        assert_eq!(mock.borrow().output.len(), 0);
+        assert_eq!(mock.lock().unwrap().output.len(), 0);
     }
 
     #[test]
@@ -479,7 +483,7 @@ mod tests {
         uart.write8(UART_THR, b'H');
         uart.write8(UART_THR, b'i');
 
-        assert_eq!(mock.borrow().output_as_string(), "Hi");
+        assert_eq!(mock.lock().unwrap().output_as_string(), "Hi");
     }
 
     #[test]
@@ -681,8 +685,8 @@ mod tests {
         let (mut uart, mock) = create_uart_with_mock();
 
         // Terminal에 입력 데이터 설정
-        mock.borrow_mut().input.push_back(b'A');
-        mock.borrow_mut().input.push_back(b'B');
+        mock.lock().unwrap().input.push_back(b'A');
+        mock.lock().unwrap().input.push_back(b'B');
 
         // receive_input 호출
         uart.receive_input();
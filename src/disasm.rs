@@ -0,0 +1,861 @@
+//! RISC-V 디스어셈블러
+//! decoder::decode / compressed::decode 결과를 ABI 레지스터 이름과 의사 명령어(li, mv, ret, j, csrr 등)로 표기
+//! 압축 명령어는 확장된 32비트 명령어로 표기 (objdump 기본 출력과 동일)
+
+use crate::cpu::compressed::{self, CompressedOp};
+use crate::cpu::crypto::CryptoOp;
+use crate::cpu::extensions::Extensions;
+use crate::cpu::{self, Xlen};
+use crate::csr;
+use crate::decoder::{
+    self, AluOp, AmoOp, BranchOp, CboOp, CsrOp, FpFmt, FpOp, FusedOp, Instruction, LoadOp, Width,
+};
+use crate::elf::SymbolTable;
+
+pub const ABI_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+pub const FP_ABI_NAMES: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+// rm 필드 값 7: frm 사용 (표기 생략)
+const RM_DYNAMIC: u32 = 0x7;
+
+fn x(reg: usize) -> &'static str {
+    ABI_NAMES[reg & 0x1F]
+}
+
+fn f(reg: usize) -> &'static str {
+    FP_ABI_NAMES[reg & 0x1F]
+}
+
+/// 알려진 CSR의 이름 (그 외는 16진수로 표기)
+pub fn csr_name(addr: u16) -> Option<&'static str> {
+    let name = match addr {
+        csr::FFLAGS => "fflags",
+        csr::FRM => "frm",
+        csr::FCSR => "fcsr",
+        csr::SEED => "seed",
+        csr::JVT => "jvt",
+        csr::CYCLE => "cycle",
+        csr::TIME => "time",
+        csr::INSTRET => "instret",
+        csr::CYCLEH => "cycleh",
+        csr::TIMEH => "timeh",
+        csr::INSTRETH => "instreth",
+        csr::SSTATUS => "sstatus",
+        csr::STVEC => "stvec",
+        csr::SENVCFG => "senvcfg",
+        csr::SEPC => "sepc",
+        csr::SCAUSE => "scause",
+        csr::STVAL => "stval",
+        csr::MSTATUS => "mstatus",
+        csr::MISA => "misa",
+        csr::MIE => "mie",
+        csr::MTVEC => "mtvec",
+        csr::MENVCFG => "menvcfg",
+        csr::MSTATUSH => "mstatush",
+        csr::MEPC => "mepc",
+        csr::MCAUSE => "mcause",
+        csr::MTVAL => "mtval",
+        csr::MIP => "mip",
+        csr::MSECCFG => "mseccfg",
+        csr::MCYCLE => "mcycle",
+        csr::MINSTRET => "minstret",
+        csr::MCYCLEH => "mcycleh",
+        csr::MINSTRETH => "minstreth",
+        csr::MHARTID => "mhartid",
+        csr::TSELECT => "tselect",
+        csr::TDATA1 => "tdata1",
+        csr::TDATA2 => "tdata2",
+        csr::TDATA3 => "tdata3",
+        csr::TINFO => "tinfo",
+        csr::DCSR => "dcsr",
+        csr::DPC => "dpc",
+        csr::DSCRATCH0 => "dscratch0",
+        csr::DSCRATCH1 => "dscratch1",
+        0x340 => "mscratch",
+        0x140 => "sscratch",
+        0x180 => "satp",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn csr_str(addr: u16) -> String {
+    match csr_name(addr) {
+        Some(name) => name.to_string(),
+        None => format!("{:#x}", addr),
+    }
+}
+
+fn width_suffix(width: Width) -> &'static str {
+    match width {
+        Width::B => "b",
+        Width::H => "h",
+        Width::W => "w",
+        Width::D => "d",
+        Width::Q => "q",
+    }
+}
+
+fn rm_name(rm: u32) -> &'static str {
+    match rm {
+        0 => "rne",
+        1 => "rtz",
+        2 => "rdn",
+        3 => "rup",
+        4 => "rmm",
+        _ => "dyn",
+    }
+}
+
+// fence의 fm, rs1, rd 필드
+const FENCE_RESERVED_BITS: u32 = 0xF00F_8F80;
+
+/// fence의 pred/succ 집합 (i, o, r, w 순)
+fn fence_set(bits: u32) -> String {
+    let set: String = [(8, 'i'), (4, 'o'), (2, 'r'), (1, 'w')]
+        .iter()
+        .filter(|(mask, _)| bits & mask != 0)
+        .map(|(_, c)| *c)
+        .collect();
+    if set.is_empty() { "0".to_string() } else { set }
+}
+
+/// Zcmp rlist 표기: {ra}, {ra, s0}, {ra, s0-s3}
+fn rlist_str(rlist: u32) -> String {
+    let regs = compressed::rlist_regs(rlist);
+    match regs.len() {
+        1 => "{ra}".to_string(),
+        2 => "{ra, s0}".to_string(),
+        n => format!("{{ra, s0-{}}}", x(regs[n - 1])),
+    }
+}
+
+/// 명령어 워드를 어셈블리로 변환
+/// 분기/점프 대상은 pc 기준 절대 주소로 표기하고, 심볼이 있으면 <name+off>를 덧붙임
+pub struct Disassembler<'a> {
+    pub xlen: Xlen,
+    pub extensions: Extensions,
+    symbols: Option<&'a SymbolTable>,
+}
+
+impl Default for Disassembler<'_> {
+    fn default() -> Self {
+        Self::new(Xlen::Rv64)
+    }
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(xlen: Xlen) -> Self {
+        Disassembler {
+            xlen,
+            extensions: Extensions::default(),
+            symbols: None,
+        }
+    }
+
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// 명령어 하나를 어셈블리로 변환. 하위 2비트가 11이 아니면 하위 16비트만 사용
+    /// 디코딩할 수 없는 워드는 .word / .half 로 표기
+    pub fn disassemble(&self, inst: u32, pc: u64) -> String {
+        if inst & 0x3 != 0x3 {
+            return self.disassemble_compressed(inst as u16, pc);
+        }
+        if self.xlen == Xlen::Rv32 && cpu::rv64_only(inst) {
+            return format!(".word {:#010x}", inst);
+        }
+        match decoder::decode(inst) {
+            // fm/rs1/rd가 0이 아닌 fence는 fence.tso 말고는 어셈블리 표기가 없음
+            Ok(Instruction::Fence { pred, succ }) if inst & FENCE_RESERVED_BITS != 0 => {
+                let rw = 0b0011;
+                match decoder::fence_tso(inst) && inst & FENCE_RESERVED_BITS == 0x8000_0000 {
+                    true if (pred, succ) == (rw, rw) => "fence.tso".to_string(),
+                    _ => format!(".word {:#010x}", inst),
+                }
+            }
+            Ok(instruction) => self.format(instruction, pc),
+            Err(_) => format!(".word {:#010x}", inst),
+        }
+    }
+
+    /// objdump 형식의 목록: 심볼 시작 위치에 라벨을 붙이고 한 줄에 명령어 하나
+    pub fn listing(&self, data: &[u8], base: u64) -> String {
+        let mut out = String::new();
+        let mut offset = 0;
+        while offset + 2 <= data.len() {
+            let addr = base + offset as u64;
+            if let Some(sym) = self.symbols.and_then(|symbols| symbols.at(addr)) {
+                out.push_str(&format!("\n{:016x} <{}>:\n", addr, sym.name));
+            }
+            let low = u16::from_le_bytes([data[offset], data[offset + 1]]) as u32;
+            let (inst, len) = if low & 0x3 != 0x3 {
+                (low, 2)
+            } else if offset + 4 <= data.len() {
+                let high = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as u32;
+                ((high << 16) | low, 4)
+            } else {
+                // 끝에 잘린 32비트 명령어
+                (low, 2)
+            };
+            let raw = match len {
+                2 => format!("{:04x}    ", inst),
+                _ => format!("{:08x}", inst),
+            };
+            let asm = match (len, inst & 0x3) {
+                (2, 0x3) => format!(".half {:#06x}", inst),
+                _ => self.disassemble(inst, addr),
+            };
+            out.push_str(&format!("{:8x}:\t{}\t{}\n", addr, raw, asm));
+            offset += len;
+        }
+        out
+    }
+
+    fn disassemble_compressed(&self, inst: u16, pc: u64) -> String {
+        let Some(op) = compressed::decode(inst, self.xlen, &self.extensions) else {
+            return format!(".half {:#06x}", inst);
+        };
+        match op {
+            CompressedOp::Expanded(expanded) => self.disassemble(expanded, pc),
+            CompressedOp::SextB(rd) => format!("sext.b {},{}", x(rd), x(rd)),
+            CompressedOp::SextH(rd) => format!("sext.h {},{}", x(rd), x(rd)),
+            CompressedOp::ZextH(rd) => format!("zext.h {},{}", x(rd), x(rd)),
+            CompressedOp::ZextW(rd) => format!("zext.w {},{}", x(rd), x(rd)),
+            CompressedOp::Push { rlist, stack_adj } => {
+                format!("cm.push {},-{}", rlist_str(rlist), stack_adj)
+            }
+            CompressedOp::Pop { rlist, stack_adj } => {
+                format!("cm.pop {},{}", rlist_str(rlist), stack_adj)
+            }
+            CompressedOp::Popret { rlist, stack_adj } => {
+                format!("cm.popret {},{}", rlist_str(rlist), stack_adj)
+            }
+            CompressedOp::Popretz { rlist, stack_adj } => {
+                format!("cm.popretz {},{}", rlist_str(rlist), stack_adj)
+            }
+            CompressedOp::MvSa01 { r1s, r2s } => format!("cm.mvsa01 {},{}", x(r1s), x(r2s)),
+            CompressedOp::MvA01s { r1s, r2s } => format!("cm.mva01s {},{}", x(r1s), x(r2s)),
+            CompressedOp::TableJump { index } if index < compressed::JALT_MIN_INDEX => {
+                format!("cm.jt {}", index)
+            }
+            CompressedOp::TableJump { index } => format!("cm.jalt {}", index),
+        }
+    }
+
+    /// 분기 대상 주소 (+ 심볼)
+    fn target(&self, pc: u64, offset: i32) -> String {
+        let addr = pc.wrapping_add(offset as i64 as u64);
+        let addr = match self.xlen {
+            Xlen::Rv32 => addr & 0xFFFF_FFFF,
+            Xlen::Rv64 => addr,
+        };
+        match self.symbols.and_then(|symbols| symbols.describe(addr)) {
+            Some(sym) => format!("{:#x} {}", addr, sym),
+            None => format!("{:#x}", addr),
+        }
+    }
+
+    fn format(&self, instruction: Instruction, pc: u64) -> String {
+        match instruction {
+            Instruction::Lui { rd, imm } => format!("lui {},{:#x}", x(rd), (imm as u32) >> 12),
+            Instruction::Auipc { rd, imm } => {
+                format!("auipc {},{:#x}", x(rd), (imm as u32) >> 12)
+            }
+            Instruction::Jal { rd, offset } => match rd {
+                0 => format!("j {}", self.target(pc, offset)),
+                1 => format!("jal {}", self.target(pc, offset)),
+                _ => format!("jal {},{}", x(rd), self.target(pc, offset)),
+            },
+            Instruction::Jalr { rd, rs1, offset } => match (rd, rs1, offset) {
+                (0, 1, 0) => "ret".to_string(),
+                (0, _, 0) => format!("jr {}", x(rs1)),
+                (1, _, 0) => format!("jalr {}", x(rs1)),
+                _ => format!("jalr {},{}({})", x(rd), offset, x(rs1)),
+            },
+            Instruction::Branch {
+                op,
+                rs1,
+                rs2,
+                offset,
+            } => self.format_branch(op, rs1, rs2, pc, offset),
+            Instruction::Load {
+                op,
+                rd,
+                rs1,
+                offset,
+            } => {
+                let name = match op {
+                    LoadOp::Lb => "lb",
+                    LoadOp::Lh => "lh",
+                    LoadOp::Lw => "lw",
+                    LoadOp::Ld => "ld",
+                    LoadOp::Lbu => "lbu",
+                    LoadOp::Lhu => "lhu",
+                    LoadOp::Lwu => "lwu",
+                };
+                format!("{} {},{}({})", name, x(rd), offset, x(rs1))
+            }
+            Instruction::Store {
+                width,
+                rs1,
+                rs2,
+                offset,
+            } => format!("s{} {},{}({})", width_suffix(width), x(rs2), offset, x(rs1)),
+            Instruction::OpImm { op, rd, rs1, imm } => format_op_imm(op, rd, rs1, imm),
+            Instruction::OpImm32 { op, rd, rs1, imm } => match (op, imm) {
+                (AluOp::Add, 0) => format!("sext.w {},{}", x(rd), x(rs1)),
+                _ => format!("{}w {},{},{}", imm_name(op), x(rd), x(rs1), imm),
+            },
+            Instruction::Op { op, rd, rs1, rs2 } => format_op(op, rd, rs1, rs2),
+            Instruction::Op32 { op, rd, rs1, rs2 } => match (op, rs1) {
+                (AluOp::Sub, 0) => format!("negw {},{}", x(rd), x(rs2)),
+                _ => format!("{}w {},{},{}", op_name(op), x(rd), x(rs1), x(rs2)),
+            },
+            Instruction::Fence { pred, succ } => match (pred, succ) {
+                (0xF, 0xF) => "fence".to_string(),
+                _ => format!("fence {},{}", fence_set(pred), fence_set(succ)),
+            },
+            Instruction::FenceI => "fence.i".to_string(),
+            Instruction::Pause => "pause".to_string(),
+            Instruction::Cbo { op, rs1 } => {
+                let name = match op {
+                    CboOp::Inval => "inval",
+                    CboOp::Clean => "clean",
+                    CboOp::Flush => "flush",
+                    CboOp::Zero => "zero",
+                };
+                format!("cbo.{} ({})", name, x(rs1))
+            }
+            Instruction::Ecall => "ecall".to_string(),
+            Instruction::Ebreak => "ebreak".to_string(),
+            Instruction::Mret => "mret".to_string(),
+            Instruction::Sret => "sret".to_string(),
+            Instruction::Dret => "dret".to_string(),
+            Instruction::Wfi => "wfi".to_string(),
+            Instruction::SfenceVma { rs1, rs2 } => format!("sfence.vma {},{}", x(rs1), x(rs2)),
+            Instruction::WrsNto => "wrs.nto".to_string(),
+            Instruction::WrsSto => "wrs.sto".to_string(),
+            Instruction::Csr { op, rd, rs1, csr } => format_csr(op, rd, rs1, csr),
+            Instruction::MopR { n, rd, rs1 } => format!("mop.r.{} {},{}", n, x(rd), x(rs1)),
+            Instruction::MopRr { n, rd, rs1, rs2 } => {
+                format!("mop.rr.{} {},{},{}", n, x(rd), x(rs1), x(rs2))
+            }
+            Instruction::Amo {
+                op,
+                width,
+                aq,
+                rl,
+                rd,
+                rs1,
+                rs2,
+            } => {
+                let ordering = match (aq, rl) {
+                    (true, true) => ".aqrl",
+                    (true, false) => ".aq",
+                    (false, true) => ".rl",
+                    (false, false) => "",
+                };
+                let name = format!("{}.{}{}", amo_name(op), width_suffix(width), ordering);
+                match op {
+                    AmoOp::Lr => format!("{} {},({})", name, x(rd), x(rs1)),
+                    _ => format!("{} {},{},({})", name, x(rd), x(rs2), x(rs1)),
+                }
+            }
+            Instruction::FpLoad {
+                width,
+                rd,
+                rs1,
+                offset,
+            } => format!(
+                "fl{} {},{}({})",
+                fp_load_suffix(width),
+                f(rd),
+                offset,
+                x(rs1)
+            ),
+            Instruction::FpStore {
+                width,
+                rs1,
+                rs2,
+                offset,
+            } => format!(
+                "fs{} {},{}({})",
+                fp_load_suffix(width),
+                f(rs2),
+                offset,
+                x(rs1)
+            ),
+            Instruction::FpOp {
+                op,
+                fmt,
+                rd,
+                rs1,
+                rs2,
+                rm,
+            } => format_fp_op(op, fmt, rd, rs1, rs2, rm),
+            Instruction::FpFused {
+                op,
+                fmt,
+                rd,
+                rs1,
+                rs2,
+                rs3,
+                rm,
+            } => {
+                let name = match op {
+                    FusedOp::Fmadd => "fmadd",
+                    FusedOp::Fmsub => "fmsub",
+                    FusedOp::Fnmsub => "fnmsub",
+                    FusedOp::Fnmadd => "fnmadd",
+                };
+                let asm = format!(
+                    "{}.{} {},{},{},{}",
+                    name,
+                    fp_fmt_suffix(fmt),
+                    f(rd),
+                    f(rs1),
+                    f(rs2),
+                    f(rs3)
+                );
+                with_rm(asm, rm)
+            }
+            Instruction::Crypto { op, rd, rs1, rs2 } => format_crypto(op, rd, rs1, rs2),
+        }
+    }
+
+    fn format_branch(&self, op: BranchOp, rs1: usize, rs2: usize, pc: u64, offset: i32) -> String {
+        let target = self.target(pc, offset);
+        // x0과 비교하는 분기는 단항 의사 명령어로
+        match (op, rs1, rs2) {
+            (BranchOp::Beq, _, 0) => format!("beqz {},{}", x(rs1), target),
+            (BranchOp::Bne, _, 0) => format!("bnez {},{}", x(rs1), target),
+            (BranchOp::Blt, _, 0) => format!("bltz {},{}", x(rs1), target),
+            (BranchOp::Bge, _, 0) => format!("bgez {},{}", x(rs1), target),
+            (BranchOp::Blt, 0, _) => format!("bgtz {},{}", x(rs2), target),
+            (BranchOp::Bge, 0, _) => format!("blez {},{}", x(rs2), target),
+            _ => {
+                let name = match op {
+                    BranchOp::Beq => "beq",
+                    BranchOp::Bne => "bne",
+                    BranchOp::Blt => "blt",
+                    BranchOp::Bge => "bge",
+                    BranchOp::Bltu => "bltu",
+                    BranchOp::Bgeu => "bgeu",
+                };
+                format!("{} {},{},{}", name, x(rs1), x(rs2), target)
+            }
+        }
+    }
+}
+
+/// 기본 설정(RV64, 심볼 없음)으로 명령어 하나를 변환
+pub fn disassemble(inst: u32, pc: u64) -> String {
+    Disassembler::default().disassemble(inst, pc)
+}
+
+fn fp_load_suffix(width: Width) -> &'static str {
+    match width {
+        Width::H => "h",
+        Width::W => "w",
+        Width::D => "d",
+        _ => "q",
+    }
+}
+
+fn imm_name(op: AluOp) -> &'static str {
+    match op {
+        AluOp::Add => "addi",
+        AluOp::Slt => "slti",
+        AluOp::Sltu => "sltiu",
+        AluOp::Xor => "xori",
+        AluOp::Or => "ori",
+        AluOp::And => "andi",
+        AluOp::Sll => "slli",
+        AluOp::Srl => "srli",
+        AluOp::Sra => "srai",
+        // OP-IMM에는 다른 연산이 없음
+        _ => "?",
+    }
+}
+
+fn op_name(op: AluOp) -> &'static str {
+    match op {
+        AluOp::Add => "add",
+        AluOp::Sub => "sub",
+        AluOp::Sll => "sll",
+        AluOp::Slt => "slt",
+        AluOp::Sltu => "sltu",
+        AluOp::Xor => "xor",
+        AluOp::Srl => "srl",
+        AluOp::Sra => "sra",
+        AluOp::Or => "or",
+        AluOp::And => "and",
+        AluOp::Mul => "mul",
+        AluOp::Mulh => "mulh",
+        AluOp::Mulhsu => "mulhsu",
+        AluOp::Mulhu => "mulhu",
+        AluOp::Div => "div",
+        AluOp::Divu => "divu",
+        AluOp::Rem => "rem",
+        AluOp::Remu => "remu",
+        AluOp::CzeroEqz => "czero.eqz",
+        AluOp::CzeroNez => "czero.nez",
+    }
+}
+
+fn format_op_imm(op: AluOp, rd: usize, rs1: usize, imm: i32) -> String {
+    match (op, rd, rs1, imm) {
+        (AluOp::Add, 0, 0, 0) => "nop".to_string(),
+        (AluOp::Add, _, 0, _) => format!("li {},{}", x(rd), imm),
+        (AluOp::Add, _, _, 0) => format!("mv {},{}", x(rd), x(rs1)),
+        (AluOp::Xor, _, _, -1) => format!("not {},{}", x(rd), x(rs1)),
+        (AluOp::Sltu, _, _, 1) => format!("seqz {},{}", x(rd), x(rs1)),
+        _ => format!("{} {},{},{}", imm_name(op), x(rd), x(rs1), imm),
+    }
+}
+
+fn format_op(op: AluOp, rd: usize, rs1: usize, rs2: usize) -> String {
+    match (op, rs1, rs2) {
+        // c.mv은 add rd,x0,rs2로 확장됨
+        (AluOp::Add, 0, _) => format!("mv {},{}", x(rd), x(rs2)),
+        (AluOp::Sub, 0, _) => format!("neg {},{}", x(rd), x(rs2)),
+        (AluOp::Sltu, 0, _) => format!("snez {},{}", x(rd), x(rs2)),
+        (AluOp::Slt, _, 0) => format!("sltz {},{}", x(rd), x(rs1)),
+        (AluOp::Slt, 0, _) => format!("sgtz {},{}", x(rd), x(rs2)),
+        _ => format!("{} {},{},{}", op_name(op), x(rd), x(rs1), x(rs2)),
+    }
+}
+
+fn format_csr(op: CsrOp, rd: usize, rs1: usize, csr: u16) -> String {
+    let name = csr_str(csr);
+    // 즉시값 형식의 rs1은 uimm
+    match (op, rd, rs1) {
+        (CsrOp::Rs, _, 0) => format!("csrr {},{}", x(rd), name),
+        (CsrOp::Rw, 0, _) => format!("csrw {},{}", name, x(rs1)),
+        (CsrOp::Rs, 0, _) => format!("csrs {},{}", name, x(rs1)),
+        (CsrOp::Rc, 0, _) => format!("csrc {},{}", name, x(rs1)),
+        (CsrOp::Rwi, 0, _) => format!("csrwi {},{}", name, rs1),
+        (CsrOp::Rsi, 0, _) => format!("csrsi {},{}", name, rs1),
+        (CsrOp::Rci, 0, _) => format!("csrci {},{}", name, rs1),
+        (CsrOp::Rw, _, _) => format!("csrrw {},{},{}", x(rd), name, x(rs1)),
+        (CsrOp::Rs, _, _) => format!("csrrs {},{},{}", x(rd), name, x(rs1)),
+        (CsrOp::Rc, _, _) => format!("csrrc {},{},{}", x(rd), name, x(rs1)),
+        (CsrOp::Rwi, _, _) => format!("csrrwi {},{},{}", x(rd), name, rs1),
+        (CsrOp::Rsi, _, _) => format!("csrrsi {},{},{}", x(rd), name, rs1),
+        (CsrOp::Rci, _, _) => format!("csrrci {},{},{}", x(rd), name, rs1),
+    }
+}
+
+fn amo_name(op: AmoOp) -> &'static str {
+    match op {
+        AmoOp::Lr => "lr",
+        AmoOp::Sc => "sc",
+        AmoOp::Swap => "amoswap",
+        AmoOp::Add => "amoadd",
+        AmoOp::Xor => "amoxor",
+        AmoOp::And => "amoand",
+        AmoOp::Or => "amoor",
+        AmoOp::Min => "amomin",
+        AmoOp::Max => "amomax",
+        AmoOp::Minu => "amominu",
+        AmoOp::Maxu => "amomaxu",
+        AmoOp::Cas => "amocas",
+    }
+}
+
+fn with_rm(asm: String, rm: u32) -> String {
+    if rm == RM_DYNAMIC {
+        asm
+    } else {
+        format!("{},{}", asm, rm_name(rm))
+    }
+}
+
+fn format_fp_op(op: FpOp, fmt: FpFmt, rd: usize, rs1: usize, rs2: usize, rm: u32) -> String {
+    use FpOp::*;
+    let suffix = fp_fmt_suffix(fmt);
+    let asm = match op {
+        // 같은 레지스터의 부호 주입은 fmv/fneg/fabs
+        Sgnj if rs1 == rs2 => format!("fmv.{} {},{}", suffix, f(rd), f(rs1)),
+        Sgnjn if rs1 == rs2 => format!("fneg.{} {},{}", suffix, f(rd), f(rs1)),
+        Sgnjx if rs1 == rs2 => format!("fabs.{} {},{}", suffix, f(rd), f(rs1)),
+        Add | Sub | Mul | Div | Sgnj | Sgnjn | Sgnjx | Min | Max => {
+            let name = match op {
+                Add => "fadd",
+                Sub => "fsub",
+                Mul => "fmul",
+                Div => "fdiv",
+                Sgnj => "fsgnj",
+                Sgnjn => "fsgnjn",
+                Sgnjx => "fsgnjx",
+                Min => "fmin",
+                _ => "fmax",
+            };
+            format!("{}.{} {},{},{}", name, suffix, f(rd), f(rs1), f(rs2))
+        }
+        Le | Lt | Eq => {
+            let name = match op {
+                Le => "fle",
+                Lt => "flt",
+                _ => "feq",
+            };
+            format!("{}.{} {},{},{}", name, suffix, x(rd), f(rs1), f(rs2))
+        }
+        Sqrt => format!("fsqrt.{} {},{}", suffix, f(rd), f(rs1)),
+        // f → x
+        CvtWF | CvtWuF | CvtLF | CvtLuF | Class => {
+            let name = match op {
+                CvtWF => "fcvt.w",
+                CvtWuF => "fcvt.wu",
+                CvtLF => "fcvt.l",
+                CvtLuF => "fcvt.lu",
+                _ => "fclass",
+            };
+            format!("{}.{} {},{}", name, suffix, x(rd), f(rs1))
+        }
+        // fmv.x.s가 아니라 fmv.x.w
+        MvXF => {
+            let suffix = if fmt == FpFmt::S { "w" } else { suffix };
+            format!("fmv.x.{} {},{}", suffix, x(rd), f(rs1))
+        }
+        // x → f
+        CvtFW | CvtFWu | CvtFL | CvtFLu => {
+            let name = match op {
+                CvtFW => "w",
+                CvtFWu => "wu",
+                CvtFL => "l",
+                _ => "lu",
+            };
+            format!("fcvt.{}.{} {},{}", suffix, name, f(rd), x(rs1))
+        }
+        MvFX => {
+            let suffix = if fmt == FpFmt::S { "w" } else { suffix };
+            format!("fmv.{}.x {},{}", suffix, f(rd), x(rs1))
+        }
+        // f → f 형식 변환
+        CvtFF(from) => format!(
+            "fcvt.{}.{} {},{}",
+            suffix,
+            fp_fmt_suffix(from),
+            f(rd),
+            f(rs1)
+        ),
+        CvtSBf16 => format!("fcvt.s.bf16 {},{}", f(rd), f(rs1)),
+        CvtBf16S => format!("fcvt.bf16.s {},{}", f(rd), f(rs1)),
+    };
+    if op.uses_rm() { with_rm(asm, rm) } else { asm }
+}
+
+fn fp_fmt_suffix(fmt: FpFmt) -> &'static str {
+    match fmt {
+        FpFmt::S => "s",
+        FpFmt::D => "d",
+        FpFmt::H => "h",
+    }
+}
+
+fn format_crypto(op: CryptoOp, rd: usize, rs1: usize, rs2: usize) -> String {
+    use CryptoOp::*;
+    let (name, unary) = match op {
+        Rori(shamt) => return format!("rori {},{},{}", x(rd), x(rs1), shamt),
+        Roriw(shamt) => return format!("roriw {},{},{}", x(rd), x(rs1), shamt),
+        Aes64ks1i(rnum) => return format!("aes64ks1i {},{},{}", x(rd), x(rs1), rnum),
+        Sm4ed(bs) => return format!("sm4ed {},{},{},{}", x(rd), x(rs1), x(rs2), bs),
+        Sm4ks(bs) => return format!("sm4ks {},{},{},{}", x(rd), x(rs1), x(rs2), bs),
+        Andn => ("andn", false),
+        Orn => ("orn", false),
+        Xnor => ("xnor", false),
+        Rol => ("rol", false),
+        Ror => ("ror", false),
+        Rolw => ("rolw", false),
+        Rorw => ("rorw", false),
+        Pack => ("pack", false),
+        Packh => ("packh", false),
+        Packw => ("packw", false),
+        Rev8 => ("rev8", true),
+        Brev8 => ("brev8", true),
+        Clmul => ("clmul", false),
+        Clmulh => ("clmulh", false),
+        Xperm4 => ("xperm4", false),
+        Xperm8 => ("xperm8", false),
+        Aes64es => ("aes64es", false),
+        Aes64esm => ("aes64esm", false),
+        Aes64ds => ("aes64ds", false),
+        Aes64dsm => ("aes64dsm", false),
+        Aes64im => ("aes64im", true),
+        Aes64ks2 => ("aes64ks2", false),
+        Sha256sig0 => ("sha256sig0", true),
+        Sha256sig1 => ("sha256sig1", true),
+        Sha256sum0 => ("sha256sum0", true),
+        Sha256sum1 => ("sha256sum1", true),
+        Sha512sig0 => ("sha512sig0", true),
+        Sha512sig1 => ("sha512sig1", true),
+        Sha512sum0 => ("sha512sum0", true),
+        Sha512sum1 => ("sha512sum1", true),
+        Sm3p0 => ("sm3p0", true),
+        Sm3p1 => ("sm3p1", true),
+    };
+    if unary {
+        format!("{} {},{}", name, x(rd), x(rs1))
+    } else {
+        format!("{} {},{},{}", name, x(rd), x(rs1), x(rs2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf::Symbol;
+
+    fn symbols() -> SymbolTable {
+        SymbolTable::new(vec![
+            Symbol {
+                name: "_start".to_string(),
+                addr: 0x80000000,
+                size: 0,
+            },
+            Symbol {
+                name: "main".to_string(),
+                addr: 0x80000100,
+                size: 0x40,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_base_instructions() {
+        assert_eq!(disassemble(0x003100B3, 0), "add ra,sp,gp");
+        assert_eq!(disassemble(0x40B50533, 0), "sub a0,a0,a1");
+        assert_eq!(disassemble(0x00853583, 0), "ld a1,8(a0)");
+        assert_eq!(disassemble(0xFEB53C23, 0), "sd a1,-8(a0)");
+        assert_eq!(disassemble(0x12345537, 0), "lui a0,0x12345");
+        assert_eq!(disassemble(0x00351513, 0), "slli a0,a0,3");
+        assert_eq!(disassemble(0x02B50533, 0), "mul a0,a0,a1");
+        assert_eq!(disassemble(0x0EB5252F, 0), "amoswap.w.aqrl a0,a1,(a0)");
+        assert_eq!(disassemble(0x1005252F, 0), "lr.w a0,(a0)");
+    }
+
+    #[test]
+    fn test_pseudo_instructions() {
+        assert_eq!(disassemble(0x00000013, 0), "nop");
+        assert_eq!(disassemble(0x02A00513, 0), "li a0,42");
+        assert_eq!(disassemble(0x00058513, 0), "mv a0,a1");
+        assert_eq!(disassemble(0xFFF5C513, 0), "not a0,a1");
+        assert_eq!(disassemble(0x40B00533, 0), "neg a0,a1");
+        assert_eq!(disassemble(0x0005851B, 0), "sext.w a0,a1");
+        assert_eq!(disassemble(0x00008067, 0), "ret");
+        assert_eq!(disassemble(0x00050067, 0), "jr a0");
+        assert_eq!(disassemble(0x300022F3, 0), "csrr t0,mstatus");
+        assert_eq!(disassemble(0x30529073, 0), "csrw mtvec,t0");
+        assert_eq!(disassemble(0x3007A073, 0), "csrs mstatus,a5");
+        assert_eq!(disassemble(0x0FF0000F, 0), "fence");
+        assert_eq!(disassemble(0x0220000F, 0), "fence r,r");
+        assert_eq!(disassemble(0x8330000F, 0), "fence.tso");
+        // fm/rs1/rd가 0이 아니면 표기할 수 없으므로 .word
+        assert_eq!(disassemble(0xB4950F8F, 0), ".word 0xb4950f8f");
+    }
+
+    #[test]
+    fn test_branch_targets() {
+        // j +8, beqz a0,-4
+        assert_eq!(disassemble(0x0080006F, 0x80000000), "j 0x80000008");
+        assert_eq!(disassemble(0xFE050EE3, 0x80000010), "beqz a0,0x8000000c");
+        assert_eq!(disassemble(0x00B50463, 0x80000000), "beq a0,a1,0x80000008");
+        assert_eq!(disassemble(0x008000EF, 0x80000000), "jal 0x80000008");
+    }
+
+    #[test]
+    fn test_branch_targets_use_symbols() {
+        let symbols = symbols();
+        let disasm = Disassembler::new(Xlen::Rv64).with_symbols(&symbols);
+        // jal ra, +0x100 → main
+        assert_eq!(
+            disasm.disassemble(0x100000EF, 0x80000000),
+            "jal 0x80000100 <main>"
+        );
+        // j +0x10 → _start+0x10
+        assert_eq!(
+            disasm.disassemble(0x0100006F, 0x80000000),
+            "j 0x80000010 <_start+0x10>"
+        );
+    }
+
+    #[test]
+    fn test_compressed_expands() {
+        // c.li a0,1 / c.mv a0,a1 / c.jr ra
+        assert_eq!(disassemble(0x4505, 0), "li a0,1");
+        assert_eq!(disassemble(0x852E, 0), "mv a0,a1");
+        assert_eq!(disassemble(0x8082, 0), "ret");
+        assert_eq!(disassemble(0x0000, 0), ".half 0x0000");
+    }
+
+    #[test]
+    fn test_zcmp_push() {
+        let ext = Extensions {
+            zcd: false,
+            zcmp: true,
+            ..Extensions::default()
+        };
+        let disasm = Disassembler::new(Xlen::Rv64).with_extensions(ext);
+        // cm.push {ra, s0-s1}, -32
+        assert_eq!(disasm.disassemble(0xB862, 0), "cm.push {ra, s0-s1},-32");
+    }
+
+    #[test]
+    fn test_rv32_rejects_rv64_only() {
+        let disasm = Disassembler::new(Xlen::Rv32);
+        assert_eq!(disasm.disassemble(0x00853583, 0), ".word 0x00853583");
+        assert_eq!(disasm.disassemble(0x0080006F, 0xFFFFFFFC), "j 0x4");
+    }
+
+    #[test]
+    fn test_illegal_word() {
+        assert_eq!(disassemble(0x0000007F, 0), ".word 0x0000007f");
+    }
+
+    #[test]
+    fn test_fp_and_crypto() {
+        // fadd.h fa0,fa1,fa2 (dyn), fmv.x.w a0,fa0, fld fa0,8(sp)
+        assert_eq!(disassemble(0x04C5F553, 0), "fadd.h fa0,fa1,fa2");
+        assert_eq!(disassemble(0x04C58553, 0), "fadd.h fa0,fa1,fa2,rne");
+        assert_eq!(disassemble(0xE0050553, 0), "fmv.x.w a0,fa0");
+        assert_eq!(disassemble(0x00813507, 0), "fld fa0,8(sp)");
+        // fmul.d fa0,fa1,fa2,rne / fcvt.d.s fa0,fa1 / fneg.s fa0,fa1
+        assert_eq!(disassemble(0x12C58553, 0), "fmul.d fa0,fa1,fa2,rne");
+        assert_eq!(disassemble(0x420585D3, 0), "fcvt.d.s fa1,fa1,rne");
+        assert_eq!(disassemble(0x20B59553, 0), "fneg.s fa0,fa1");
+        // andn a0,a1,a2 / rev8 a0,a1
+        assert_eq!(disassemble(0x40C5F533, 0), "andn a0,a1,a2");
+        assert_eq!(disassemble(0x6B85D513, 0), "rev8 a0,a1");
+    }
+
+    #[test]
+    fn test_listing_labels_symbols() {
+        let symbols = symbols();
+        let disasm = Disassembler::new(Xlen::Rv64).with_symbols(&symbols);
+        // li a0,42 ; c.jr ra
+        let data = [0x13, 0x05, 0xA0, 0x02, 0x82, 0x80];
+        let listing = disasm.listing(&data, 0x80000000);
+        assert_eq!(
+            listing,
+            "\n0000000080000000 <_start>:\n80000000:\t02a00513\tli a0,42\n80000004:\t8082    \tret\n"
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use std::fmt;
 
+use crate::cpu::extensions::{ExtensionError, Extensions};
+
 pub const ELF_MAGIC: [u8; 4] = [0x7F, b'E', b'L', b'F'];
 
 pub const EI_CLASS: usize = 4;
 pub const EI_DATA: usize = 5;
 
+pub const ELF_CLASS32: u8 = 1;
 pub const ELF_CLASS64: u8 = 2;
 pub const ELF_DATA2LSB: u8 = 1;
 
@@ -12,6 +15,18 @@ pub const EM_RISCV: u16 = 0xF3;
 
 pub const PT_LOAD: u32 = 1;
 
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_RISCV_ATTRIBUTES: u32 = 0x7000_0003;
+pub const SHN_UNDEF: u16 = 0;
+
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+
+// .riscv.attributes의 파일 단위 속성 묶음과 arch 문자열 태그
+const TAG_FILE: u8 = 1;
+const TAG_RISCV_ARCH: u64 = 5;
+
 #[derive(Debug)]
 pub enum ElfError {
     InvalidMagic,
@@ -25,7 +40,7 @@ impl fmt::Display for ElfError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ElfError::InvalidMagic => write!(f, "Invalid ELF magic"),
-            ElfError::InvalidClass => write!(f, "Invalid ELF class (not 64-bit)"),
+            ElfError::InvalidClass => write!(f, "Invalid ELF class (not 32/64-bit)"),
             ElfError::InvalidMachine => write!(f, "Invalid machine (not RISC-V)"),
             ElfError::InvalidEndian => write!(f, "Invalid endian (not little-endian)"),
             ElfError::ParseError => write!(f, "Failed to parse ELF"),
@@ -35,9 +50,39 @@ impl fmt::Display for ElfError {
 
 impl std::error::Error for ElfError {}
 
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, ElfError> {
+    bytes
+        .get(offset..offset + 2)
+        .and_then(|b| b.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(ElfError::ParseError)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, ElfError> {
+    bytes
+        .get(offset..offset + 4)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_le_bytes)
+        .ok_or(ElfError::ParseError)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, ElfError> {
+    bytes
+        .get(offset..offset + 8)
+        .and_then(|b| b.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ElfError::ParseError)
+}
+
 pub struct ElfFile {
     pub entry: u64,
     pub segments: Vec<Segment>,
+    // ELF_CLASS32 (RV32) / ELF_CLASS64 (RV64)
+    pub class: u8,
+    // .symtab이 없으면 (strip된 바이너리) 비어 있음
+    pub symbols: SymbolTable,
+    // .riscv.attributes의 Tag_RISCV_arch (예: "rv32i2p1_m2p0_c2p0_zcb1p0_zcmp1p0")
+    pub arch: Option<String>,
 }
 
 impl ElfFile {
@@ -51,7 +96,11 @@ impl ElfFile {
         let mut segments: Vec<Segment> = Vec::new();
         for i in 0..(phnum as usize) {
             let offset = phoff as usize + i * (phentsize as usize);
-            let ph = ProgramHeader::parse(&bytes[offset..])?;
+            let ph_bytes = bytes.get(offset..).ok_or(ElfError::ParseError)?;
+            let ph = match header.class() {
+                ELF_CLASS32 => ProgramHeader::parse32(ph_bytes)?,
+                _ => ProgramHeader::parse(ph_bytes)?,
+            };
 
             if ph.p_type() == PT_LOAD {
                 let data =
@@ -67,8 +116,253 @@ impl ElfFile {
         Ok(ElfFile {
             entry: header.entry(),
             segments: segments,
+            class: header.class(),
+            symbols: SymbolTable::load(bytes, &header)?,
+            arch: load_arch(bytes, &header)?,
         })
     }
+
+    /// 실행할 확장: isa(--isa)가 있으면 그것, 없으면 ELF의 arch 문자열, 둘 다 없으면 기본값
+    pub fn extensions(&self, isa: Option<&str>) -> Result<Extensions, ExtensionError> {
+        match isa.or(self.arch.as_deref()) {
+            Some(isa) => Extensions::from_isa(isa),
+            None => Ok(Extensions::default()),
+        }
+    }
+}
+
+/// 섹션 헤더에서 SHT_RISCV_ATTRIBUTES를 찾아 arch 문자열을 읽음
+fn load_arch(bytes: &[u8], header: &ElfHeader) -> Result<Option<String>, ElfError> {
+    let elf32 = header.class() == ELF_CLASS32;
+    for i in 0..header.shnum() as usize {
+        let offset = header.shoff() as usize + i * header.shentsize() as usize;
+        let sh = SectionHeader::parse(bytes.get(offset..).ok_or(ElfError::ParseError)?, elf32)?;
+        if sh.sh_type != SHT_RISCV_ATTRIBUTES {
+            continue;
+        }
+        let section = bytes
+            .get(sh.sh_offset as usize..(sh.sh_offset + sh.sh_size) as usize)
+            .ok_or(ElfError::ParseError)?;
+        return parse_arch_attribute(section);
+    }
+    Ok(None)
+}
+
+// 형식: 'A' 다음에 (u32 길이, 벤더 이름, (태그, u32 길이, 속성들)*)*
+// 속성 태그가 홀수면 값은 NUL로 끝나는 문자열, 짝수면 ULEB128 정수
+fn parse_arch_attribute(section: &[u8]) -> Result<Option<String>, ElfError> {
+    if section.first() != Some(&b'A') {
+        return Err(ElfError::ParseError);
+    }
+    let mut offset = 1;
+    while offset < section.len() {
+        let len = read_u32(section, offset)? as usize;
+        let subsection = section
+            .get(offset..offset + len)
+            .filter(|_| len > 4)
+            .ok_or(ElfError::ParseError)?;
+        offset += len;
+        let vendor = read_str(subsection, 4)?;
+        if vendor != "riscv" {
+            continue;
+        }
+        let mut pos = 4 + vendor.len() + 1;
+        while pos < subsection.len() {
+            let tag = subsection[pos];
+            let size = read_u32(subsection, pos + 1)? as usize;
+            let attributes = subsection
+                .get(pos + 5..pos + size)
+                .ok_or(ElfError::ParseError)?;
+            pos += size;
+            if tag != TAG_FILE {
+                continue;
+            }
+            let mut at = 0;
+            while at < attributes.len() {
+                let (attribute, n) = read_uleb128(attributes, at)?;
+                at += n;
+                if attribute % 2 == 0 {
+                    at += read_uleb128(attributes, at)?.1;
+                    continue;
+                }
+                let value = read_str(attributes, at)?;
+                at += value.len() + 1;
+                if attribute == TAG_RISCV_ARCH {
+                    return Ok(Some(value.to_string()));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+// (값, 읽은 바이트 수)
+fn read_uleb128(bytes: &[u8], offset: usize) -> Result<(u64, usize), ElfError> {
+    let tail = bytes.get(offset..).ok_or(ElfError::ParseError)?;
+    let mut value = 0;
+    for (i, &byte) in tail.iter().enumerate().take(10) {
+        value |= ((byte & 0x7F) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(ElfError::ParseError)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub addr: u64,
+    pub size: u64,
+}
+
+/// 주소 → 심볼 이름 조회 (디스어셈블러, 트레이서용)
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    // 주소 순으로 정렬
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new(mut symbols: Vec<Symbol>) -> Self {
+        symbols.sort_by_key(|sym| sym.addr);
+        SymbolTable { symbols }
+    }
+
+    /// 섹션 헤더에서 SHT_SYMTAB을 찾아 함수/데이터/라벨 심볼만 읽음
+    fn load(bytes: &[u8], header: &ElfHeader) -> Result<Self, ElfError> {
+        let elf32 = header.class() == ELF_CLASS32;
+        let mut symbols = Vec::new();
+        for i in 0..header.shnum() as usize {
+            let offset = header.shoff() as usize + i * header.shentsize() as usize;
+            let sh = SectionHeader::parse(bytes.get(offset..).ok_or(ElfError::ParseError)?, elf32)?;
+            if sh.sh_type != SHT_SYMTAB {
+                continue;
+            }
+            let strtab_offset =
+                header.shoff() as usize + sh.sh_link as usize * header.shentsize() as usize;
+            let strtab = SectionHeader::parse(
+                bytes.get(strtab_offset..).ok_or(ElfError::ParseError)?,
+                elf32,
+            )?;
+            let strings = bytes
+                .get(strtab.sh_offset as usize..(strtab.sh_offset + strtab.sh_size) as usize)
+                .ok_or(ElfError::ParseError)?;
+
+            let entsize = if elf32 { 16 } else { 24 };
+            for j in 0..(sh.sh_size / entsize) as usize {
+                let entry = bytes
+                    .get(sh.sh_offset as usize + j * entsize as usize..)
+                    .ok_or(ElfError::ParseError)?;
+                // ELF32/ELF64 심볼 엔트리는 필드 순서가 다름
+                let (name, info, shndx, addr, size) = if elf32 {
+                    (
+                        read_u32(entry, 0x00)?,
+                        *entry.get(0x0C).ok_or(ElfError::ParseError)?,
+                        read_u16(entry, 0x0E)?,
+                        read_u32(entry, 0x04)? as u64,
+                        read_u32(entry, 0x08)? as u64,
+                    )
+                } else {
+                    (
+                        read_u32(entry, 0x00)?,
+                        *entry.get(0x04).ok_or(ElfError::ParseError)?,
+                        read_u16(entry, 0x06)?,
+                        read_u64(entry, 0x08)?,
+                        read_u64(entry, 0x10)?,
+                    )
+                };
+                if shndx == SHN_UNDEF || !matches!(info & 0xF, STT_NOTYPE | STT_OBJECT | STT_FUNC) {
+                    continue;
+                }
+                let name = read_str(strings, name as usize)?;
+                // $x/$d 매핑 심볼과 .L 로컬 라벨은 제외
+                if name.is_empty() || name.starts_with('$') || name.starts_with(".L") {
+                    continue;
+                }
+                symbols.push(Symbol {
+                    name: name.to_string(),
+                    addr,
+                    size,
+                });
+            }
+        }
+        Ok(SymbolTable::new(symbols))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    /// 정확히 addr에서 시작하는 심볼
+    pub fn at(&self, addr: u64) -> Option<&Symbol> {
+        let start = self.symbols.partition_point(|sym| sym.addr < addr);
+        self.symbols.get(start).filter(|sym| sym.addr == addr)
+    }
+
+    /// addr을 포함하는 심볼과 심볼 시작으로부터의 오프셋
+    /// 크기가 0인 심볼(어셈블리 라벨)은 다음 심볼 전까지 포함하는 것으로 취급
+    pub fn lookup(&self, addr: u64) -> Option<(&Symbol, u64)> {
+        let end = self.symbols.partition_point(|sym| sym.addr <= addr);
+        let sym = self.symbols[..end].last()?;
+        let offset = addr - sym.addr;
+        if sym.size != 0 && offset >= sym.size {
+            return None;
+        }
+        Some((sym, offset))
+    }
+
+    /// objdump 형식의 "<name+0x10>" 표기
+    pub fn describe(&self, addr: u64) -> Option<String> {
+        self.lookup(addr).map(|(sym, offset)| match offset {
+            0 => format!("<{}>", sym.name),
+            _ => format!("<{}+{:#x}>", sym.name, offset),
+        })
+    }
+}
+
+fn read_str(bytes: &[u8], offset: usize) -> Result<&str, ElfError> {
+    let tail = bytes.get(offset..).ok_or(ElfError::ParseError)?;
+    let len = tail
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(ElfError::ParseError)?;
+    std::str::from_utf8(&tail[..len]).map_err(|_| ElfError::ParseError)
+}
+
+struct SectionHeader {
+    sh_type: u32,
+    sh_offset: u64,
+    sh_size: u64,
+    sh_link: u32,
+}
+
+impl SectionHeader {
+    fn parse(bytes: &[u8], elf32: bool) -> Result<Self, ElfError> {
+        if elf32 {
+            Ok(SectionHeader {
+                sh_type: read_u32(bytes, 0x04)?,
+                sh_offset: read_u32(bytes, 0x10)? as u64,
+                sh_size: read_u32(bytes, 0x14)? as u64,
+                sh_link: read_u32(bytes, 0x18)?,
+            })
+        } else {
+            Ok(SectionHeader {
+                sh_type: read_u32(bytes, 0x04)?,
+                sh_offset: read_u64(bytes, 0x18)?,
+                sh_size: read_u64(bytes, 0x20)?,
+                sh_link: read_u32(bytes, 0x28)?,
+            })
+        }
+    }
 }
 
 pub struct ElfHeader {
@@ -78,37 +372,44 @@ pub struct ElfHeader {
     e_phoff: u64,
     e_phentsize: u16,
     e_phnum: u16,
+    e_shoff: u64,
+    e_shentsize: u16,
+    e_shnum: u16,
 }
 
 impl ElfHeader {
     pub fn parse(bytes: &[u8]) -> Result<Self, ElfError> {
-        let header = ElfHeader {
-            e_ident: bytes[0..16].try_into().map_err(|_| ElfError::ParseError)?,
-            e_machine: u16::from_le_bytes(
-                bytes[0x12..0x14]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            e_entry: u64::from_le_bytes(
-                bytes[0x18..0x20]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            e_phoff: u64::from_le_bytes(
-                bytes[0x20..0x28]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            e_phentsize: u16::from_le_bytes(
-                bytes[0x36..0x38]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            e_phnum: u16::from_le_bytes(
-                bytes[0x38..0x3A]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
+        let e_ident: [u8; 16] = bytes
+            .get(0..16)
+            .and_then(|b| b.try_into().ok())
+            .ok_or(ElfError::ParseError)?;
+        let e_machine = read_u16(bytes, 0x12)?;
+
+        // ELF32는 주소 필드가 4바이트라 이후 오프셋이 다름
+        let header = if e_ident[EI_CLASS] == ELF_CLASS32 {
+            ElfHeader {
+                e_ident,
+                e_machine,
+                e_entry: read_u32(bytes, 0x18)? as u64,
+                e_phoff: read_u32(bytes, 0x1C)? as u64,
+                e_phentsize: read_u16(bytes, 0x2A)?,
+                e_phnum: read_u16(bytes, 0x2C)?,
+                e_shoff: read_u32(bytes, 0x20)? as u64,
+                e_shentsize: read_u16(bytes, 0x2E)?,
+                e_shnum: read_u16(bytes, 0x30)?,
+            }
+        } else {
+            ElfHeader {
+                e_ident,
+                e_machine,
+                e_entry: read_u64(bytes, 0x18)?,
+                e_phoff: read_u64(bytes, 0x20)?,
+                e_phentsize: read_u16(bytes, 0x36)?,
+                e_phnum: read_u16(bytes, 0x38)?,
+                e_shoff: read_u64(bytes, 0x28)?,
+                e_shentsize: read_u16(bytes, 0x3A)?,
+                e_shnum: read_u16(bytes, 0x3C)?,
+            }
         };
 
         header.validate()?;
@@ -120,11 +421,11 @@ impl ElfHeader {
             return Err(ElfError::InvalidMagic);
         }
 
-        if self.e_ident[4] != ELF_CLASS64 {
+        if self.e_ident[EI_CLASS] != ELF_CLASS32 && self.e_ident[EI_CLASS] != ELF_CLASS64 {
             return Err(ElfError::InvalidClass);
         }
 
-        if self.e_ident[5] != ELF_DATA2LSB {
+        if self.e_ident[EI_DATA] != ELF_DATA2LSB {
             return Err(ElfError::InvalidEndian);
         }
 
@@ -135,6 +436,10 @@ impl ElfHeader {
         Ok(())
     }
 
+    pub fn class(&self) -> u8 {
+        self.e_ident[EI_CLASS]
+    }
+
     pub fn entry(&self) -> u64 {
         self.e_entry
     }
@@ -150,6 +455,18 @@ impl ElfHeader {
     pub fn phnum(&self) -> u16 {
         self.e_phnum
     }
+
+    pub fn shoff(&self) -> u64 {
+        self.e_shoff
+    }
+
+    pub fn shentsize(&self) -> u16 {
+        self.e_shentsize
+    }
+
+    pub fn shnum(&self) -> u16 {
+        self.e_shnum
+    }
 }
 
 #[allow(dead_code)]
@@ -167,46 +484,28 @@ pub struct ProgramHeader {
 impl ProgramHeader {
     pub fn parse(bytes: &[u8]) -> Result<Self, ElfError> {
         Ok(ProgramHeader {
-            p_type: u32::from_le_bytes(
-                bytes[0x00..0x04]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_flags: u32::from_le_bytes(
-                bytes[0x04..0x08]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_offset: u64::from_le_bytes(
-                bytes[0x08..0x10]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_vaddr: u64::from_le_bytes(
-                bytes[0x10..0x18]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_paddr: u64::from_le_bytes(
-                bytes[0x18..0x20]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_filesz: u64::from_le_bytes(
-                bytes[0x20..0x28]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_memsz: u64::from_le_bytes(
-                bytes[0x28..0x30]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
-            p_align: u64::from_le_bytes(
-                bytes[0x30..0x38]
-                    .try_into()
-                    .map_err(|_| ElfError::ParseError)?,
-            ),
+            p_type: read_u32(bytes, 0x00)?,
+            p_flags: read_u32(bytes, 0x04)?,
+            p_offset: read_u64(bytes, 0x08)?,
+            p_vaddr: read_u64(bytes, 0x10)?,
+            p_paddr: read_u64(bytes, 0x18)?,
+            p_filesz: read_u64(bytes, 0x20)?,
+            p_memsz: read_u64(bytes, 0x28)?,
+            p_align: read_u64(bytes, 0x30)?,
+        })
+    }
+
+    /// ELF32 프로그램 헤더 (p_flags 위치도 다름)
+    pub fn parse32(bytes: &[u8]) -> Result<Self, ElfError> {
+        Ok(ProgramHeader {
+            p_type: read_u32(bytes, 0x00)?,
+            p_offset: read_u32(bytes, 0x04)? as u64,
+            p_vaddr: read_u32(bytes, 0x08)? as u64,
+            p_paddr: read_u32(bytes, 0x0C)? as u64,
+            p_filesz: read_u32(bytes, 0x10)? as u64,
+            p_memsz: read_u32(bytes, 0x14)? as u64,
+            p_flags: read_u32(bytes, 0x18)?,
+            p_align: read_u32(bytes, 0x1C)? as u64,
         })
     }
 
@@ -294,7 +593,7 @@ mod tests {
     #[test]
     fn test_elf_header_invalid_class() {
         let mut bytes = create_valid_elf_header();
-        bytes[4] = 1; // 32-bit instead of 64-bit
+        bytes[4] = 3; // neither 32-bit nor 64-bit
         assert!(matches!(
             ElfHeader::parse(&bytes),
             Err(ElfError::InvalidClass)
@@ -432,4 +731,182 @@ mod tests {
             Err(ElfError::InvalidMagic)
         ));
     }
+
+    fn create_elf32_file() -> Vec<u8> {
+        let mut elf = vec![0u8; 52];
+        elf[0..4].copy_from_slice(&ELF_MAGIC);
+        elf[4] = 1; // 32-bit
+        elf[5] = 1; // little-endian
+        elf[6] = 1; // version
+        elf[0x10] = 0x02; // EXEC
+        elf[0x12] = 0xF3; // RISC-V
+        elf[0x18..0x1C].copy_from_slice(&0x80000000u32.to_le_bytes()); // e_entry
+        elf[0x1C..0x20].copy_from_slice(&52u32.to_le_bytes()); // e_phoff
+        elf[0x2A..0x2C].copy_from_slice(&32u16.to_le_bytes()); // e_phentsize
+        elf[0x2C..0x2E].copy_from_slice(&1u16.to_le_bytes()); // e_phnum
+
+        // Program Header: PT_LOAD (offset=84, filesz=4, memsz=8)
+        let mut ph = [0u8; 32];
+        ph[0x00..0x04].copy_from_slice(&PT_LOAD.to_le_bytes());
+        ph[0x04..0x08].copy_from_slice(&84u32.to_le_bytes());
+        ph[0x08..0x0C].copy_from_slice(&0x80000000u32.to_le_bytes());
+        ph[0x0C..0x10].copy_from_slice(&0x80000000u32.to_le_bytes());
+        ph[0x10..0x14].copy_from_slice(&4u32.to_le_bytes());
+        ph[0x14..0x18].copy_from_slice(&8u32.to_le_bytes());
+        ph[0x18..0x1C].copy_from_slice(&7u32.to_le_bytes());
+        elf.extend_from_slice(&ph);
+
+        elf.extend_from_slice(&[0x13, 0x00, 0x00, 0x00]); // nop
+        elf
+    }
+
+    #[test]
+    fn test_elf32_file_load() {
+        let elf = ElfFile::load(&create_elf32_file()).unwrap();
+        assert_eq!(elf.class, ELF_CLASS32);
+        assert_eq!(elf.entry, 0x80000000);
+        assert_eq!(elf.segments.len(), 1);
+        assert_eq!(elf.segments[0].vaddr, 0x80000000);
+        assert_eq!(elf.segments[0].data, vec![0x13, 0x00, 0x00, 0x00]);
+        assert_eq!(elf.segments[0].memsz, 8);
+    }
+
+    #[test]
+    fn test_elf_header_truncated() {
+        let bytes = create_valid_elf_header();
+        assert!(matches!(
+            ElfHeader::parse(&bytes[..0x30]),
+            Err(ElfError::ParseError)
+        ));
+    }
+
+    // .symtab + .strtab 섹션을 덧붙인 ELF64
+    fn create_elf_with_symbols() -> Vec<u8> {
+        let mut elf = create_elf_file();
+
+        let strtab = b"\0_start\0main\0$x\0".to_vec();
+        let strtab_offset = elf.len() as u64;
+        elf.extend_from_slice(&strtab);
+
+        // (name, info, shndx, value, size)
+        let entries: [(u32, u8, u16, u64, u64); 5] = [
+            (0, 0, 0, 0, 0),                     // null 심볼
+            (1, STT_NOTYPE, 1, 0x80000000, 0),   // _start
+            (8, STT_FUNC, 1, 0x80000004, 4),     // main
+            (13, STT_NOTYPE, 1, 0x80000000, 0),  // $x (매핑 심볼)
+            (8, STT_FUNC, SHN_UNDEF, 0x1234, 0), // 외부 심볼
+        ];
+        let symtab_offset = elf.len() as u64;
+        for (name, info, shndx, value, size) in entries {
+            let mut sym = [0u8; 24];
+            sym[0x00..0x04].copy_from_slice(&name.to_le_bytes());
+            sym[0x04] = info;
+            sym[0x06..0x08].copy_from_slice(&shndx.to_le_bytes());
+            sym[0x08..0x10].copy_from_slice(&value.to_le_bytes());
+            sym[0x10..0x18].copy_from_slice(&size.to_le_bytes());
+            elf.extend_from_slice(&sym);
+        }
+
+        // 섹션 헤더: [0] null, [1] .symtab (link=2), [2] .strtab
+        let shoff = elf.len() as u64;
+        let mut sections = [[0u8; 64]; 3];
+        sections[1][0x04..0x08].copy_from_slice(&SHT_SYMTAB.to_le_bytes());
+        sections[1][0x18..0x20].copy_from_slice(&symtab_offset.to_le_bytes());
+        sections[1][0x20..0x28].copy_from_slice(&(entries.len() as u64 * 24).to_le_bytes());
+        sections[1][0x28..0x2C].copy_from_slice(&2u32.to_le_bytes());
+        sections[2][0x04..0x08].copy_from_slice(&3u32.to_le_bytes()); // SHT_STRTAB
+        sections[2][0x18..0x20].copy_from_slice(&strtab_offset.to_le_bytes());
+        sections[2][0x20..0x28].copy_from_slice(&(strtab.len() as u64).to_le_bytes());
+        for section in &sections {
+            elf.extend_from_slice(section);
+        }
+
+        elf[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        elf[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+        elf[0x3C..0x3E].copy_from_slice(&3u16.to_le_bytes());
+        elf
+    }
+
+    #[test]
+    fn test_elf_without_symtab_has_no_symbols() {
+        let elf = ElfFile::load(&create_elf_file()).unwrap();
+        assert!(elf.symbols.is_empty());
+    }
+
+    #[test]
+    fn test_elf_symbols_load() {
+        let elf = ElfFile::load(&create_elf_with_symbols()).unwrap();
+        // null, 매핑 심볼, 정의되지 않은 심볼은 제외
+        assert_eq!(elf.symbols.len(), 2);
+        assert_eq!(elf.symbols.at(0x80000000).unwrap().name, "_start");
+        assert_eq!(elf.symbols.at(0x80000004).unwrap().name, "main");
+        assert!(elf.symbols.at(0x80000002).is_none());
+    }
+
+    // .riscv.attributes 섹션 하나를 덧붙인 ELF64 (앞에 다른 벤더 서브섹션과 정수 속성을 둠)
+    fn create_elf_with_attributes(arch: &str) -> Vec<u8> {
+        let mut elf = create_elf_file();
+
+        let mut file = vec![4, 16]; // Tag_RISCV_stack_align = 16
+        file.push(TAG_RISCV_ARCH as u8);
+        file.extend_from_slice(arch.as_bytes());
+        file.push(0);
+        let mut riscv = b"riscv\0".to_vec();
+        riscv.push(TAG_FILE);
+        riscv.extend_from_slice(&(5 + file.len() as u32).to_le_bytes());
+        riscv.extend_from_slice(&file);
+        let mut section = vec![b'A'];
+        let other = b"gnu\0\x01\x05\0\0\0";
+        section.extend_from_slice(&(4 + other.len() as u32).to_le_bytes());
+        section.extend_from_slice(other);
+        section.extend_from_slice(&(4 + riscv.len() as u32).to_le_bytes());
+        section.extend_from_slice(&riscv);
+        let section_offset = elf.len() as u64;
+        elf.extend_from_slice(&section);
+
+        let shoff = elf.len() as u64;
+        let mut sections = [[0u8; 64]; 2];
+        sections[1][0x04..0x08].copy_from_slice(&SHT_RISCV_ATTRIBUTES.to_le_bytes());
+        sections[1][0x18..0x20].copy_from_slice(&section_offset.to_le_bytes());
+        sections[1][0x20..0x28].copy_from_slice(&(section.len() as u64).to_le_bytes());
+        for section in &sections {
+            elf.extend_from_slice(section);
+        }
+
+        elf[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        elf[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+        elf[0x3C..0x3E].copy_from_slice(&2u16.to_le_bytes());
+        elf
+    }
+
+    #[test]
+    fn test_riscv_attributes_arch() {
+        let arch = "rv64i2p1_m2p0_a2p1_c2p0_zcb1p0";
+        let elf = ElfFile::load(&create_elf_with_attributes(arch)).unwrap();
+        assert_eq!(elf.arch.as_deref(), Some(arch));
+        let ext = elf.extensions(None).unwrap();
+        assert!(ext.zca && ext.zcb && !ext.f);
+        // --isa가 ELF보다 우선
+        assert!(elf.extensions(Some("rv64gc")).unwrap().f);
+
+        let elf = ElfFile::load(&create_elf_file()).unwrap();
+        assert_eq!(elf.arch, None);
+        assert_eq!(elf.extensions(None), Ok(Extensions::default()));
+    }
+
+    #[test]
+    fn test_symbol_lookup() {
+        let elf = ElfFile::load(&create_elf_with_symbols()).unwrap();
+        let symbols = &elf.symbols;
+        assert_eq!(symbols.describe(0x80000000).as_deref(), Some("<_start>"));
+        // 크기 0인 라벨은 다음 심볼 전까지
+        assert_eq!(
+            symbols.describe(0x80000002).as_deref(),
+            Some("<_start+0x2>")
+        );
+        assert_eq!(symbols.describe(0x80000006).as_deref(), Some("<main+0x2>"));
+        // main 크기(4) 밖
+        assert_eq!(symbols.describe(0x80000008), None);
+        assert_eq!(symbols.describe(0x7FFFFFFF), None);
+    }
 }
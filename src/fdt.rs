@@ -0,0 +1,311 @@
+//! Flattened Device Tree (DTB) 생성
+//! 게스트에 메모리 맵, hart, timebase-frequency를 알려줌
+//! 부팅 규약대로 a0 = hartid, a1 = DTB 주소로 전달 (Cpu::load_device_tree)
+
+use std::collections::HashMap;
+
+use crate::cpu::Xlen;
+use crate::devices;
+
+pub const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_VERSION: u32 = 17;
+const FDT_LAST_COMP_VERSION: u32 = 16;
+
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_END: u32 = 0x9;
+
+// 헤더 40바이트 + 빈 memory reservation 블록 16바이트
+const HEADER_SIZE: usize = 40;
+const RSVMAP_SIZE: usize = 16;
+
+/// 노드/프로퍼티를 순서대로 쌓아서 DTB 바이트열을 만듦
+#[derive(Default)]
+pub struct FdtBuilder {
+    structure: Vec<u8>,
+    strings: Vec<u8>,
+    string_offsets: HashMap<String, u32>,
+}
+
+impl FdtBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin_node(&mut self, name: &str) {
+        self.push_u32(FDT_BEGIN_NODE);
+        self.structure.extend_from_slice(name.as_bytes());
+        self.structure.push(0);
+        self.align();
+    }
+
+    pub fn end_node(&mut self) {
+        self.push_u32(FDT_END_NODE);
+    }
+
+    pub fn property(&mut self, name: &str, value: &[u8]) {
+        let name_offset = self.string_offset(name);
+        self.push_u32(FDT_PROP);
+        self.push_u32(value.len() as u32);
+        self.push_u32(name_offset);
+        self.structure.extend_from_slice(value);
+        self.align();
+    }
+
+    /// 값 없는 프로퍼티 (예: interrupt-controller)
+    pub fn property_empty(&mut self, name: &str) {
+        self.property(name, &[]);
+    }
+
+    pub fn property_u32(&mut self, name: &str, value: u32) {
+        self.property_cells(name, &[value]);
+    }
+
+    pub fn property_cells(&mut self, name: &str, cells: &[u32]) {
+        let bytes: Vec<u8> = cells.iter().flat_map(|cell| cell.to_be_bytes()).collect();
+        self.property(name, &bytes);
+    }
+
+    pub fn property_string(&mut self, name: &str, value: &str) {
+        self.property_strings(name, &[value]);
+    }
+
+    /// NUL로 구분한 문자열 목록 (예: compatible)
+    pub fn property_strings(&mut self, name: &str, values: &[&str]) {
+        let mut bytes = Vec::new();
+        for value in values {
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.push(0);
+        }
+        self.property(name, &bytes);
+    }
+
+    /// #address-cells = #size-cells = 2 기준 reg
+    pub fn property_reg(&mut self, name: &str, base: u64, size: u64) {
+        self.property_cells(
+            name,
+            &[
+                (base >> 32) as u32,
+                base as u32,
+                (size >> 32) as u32,
+                size as u32,
+            ],
+        );
+    }
+
+    /// 헤더를 붙여서 DTB 완성
+    pub fn finish(mut self, boot_cpuid: u32) -> Vec<u8> {
+        self.push_u32(FDT_END);
+
+        let off_mem_rsvmap = HEADER_SIZE;
+        let off_dt_struct = off_mem_rsvmap + RSVMAP_SIZE;
+        let off_dt_strings = off_dt_struct + self.structure.len();
+        let total_size = off_dt_strings + self.strings.len();
+
+        let mut dtb = Vec::with_capacity(total_size);
+        for field in [
+            FDT_MAGIC,
+            total_size as u32,
+            off_dt_struct as u32,
+            off_dt_strings as u32,
+            off_mem_rsvmap as u32,
+            FDT_VERSION,
+            FDT_LAST_COMP_VERSION,
+            boot_cpuid,
+            self.strings.len() as u32,
+            self.structure.len() as u32,
+        ] {
+            dtb.extend_from_slice(&field.to_be_bytes());
+        }
+        dtb.extend_from_slice(&[0; RSVMAP_SIZE]);
+        dtb.extend_from_slice(&self.structure);
+        dtb.extend_from_slice(&self.strings);
+        dtb
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.structure.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn align(&mut self) {
+        while !self.structure.len().is_multiple_of(4) {
+            self.structure.push(0);
+        }
+    }
+
+    // 같은 이름은 strings 블록에 한 번만 저장
+    fn string_offset(&mut self, name: &str) -> u32 {
+        if let Some(&offset) = self.string_offsets.get(name) {
+            return offset;
+        }
+        let offset = self.strings.len() as u32;
+        self.strings.extend_from_slice(name.as_bytes());
+        self.strings.push(0);
+        self.string_offsets.insert(name.to_string(), offset);
+        offset
+    }
+}
+
+/// 이 에뮬레이터 머신의 디바이스 트리
+/// harts개 hart, DRAM, CLINT, UART와 mtime 주파수(timebase_frequency)를 기술
+pub fn machine(xlen: Xlen, harts: usize, timebase_frequency: u64) -> Vec<u8> {
+    let isa = match xlen {
+        Xlen::Rv32 => "rv32imafdc_zicsr_zifencei",
+        Xlen::Rv64 => "rv64imafdc_zicsr_zifencei",
+    };
+    // hart i의 로컬 인터럽트 컨트롤러 phandle = i + 1
+    let intc_phandle = |hart: usize| hart as u32 + 1;
+
+    let mut fdt = FdtBuilder::new();
+    fdt.begin_node("");
+    fdt.property_u32("#address-cells", 2);
+    fdt.property_u32("#size-cells", 2);
+    fdt.property_string("compatible", "riscv-emulator");
+    fdt.property_string("model", "riscv-emulator");
+
+    fdt.begin_node("chosen");
+    fdt.property_string(
+        "stdout-path",
+        &format!("/soc/serial@{:x}", devices::UART_BASE),
+    );
+    fdt.end_node();
+
+    fdt.begin_node("cpus");
+    fdt.property_u32("#address-cells", 1);
+    fdt.property_u32("#size-cells", 0);
+    fdt.property_u32("timebase-frequency", timebase_frequency as u32);
+    for hart in 0..harts {
+        fdt.begin_node(&format!("cpu@{}", hart));
+        fdt.property_string("device_type", "cpu");
+        fdt.property_u32("reg", hart as u32);
+        fdt.property_string("status", "okay");
+        fdt.property_string("compatible", "riscv");
+        fdt.property_string("riscv,isa", isa);
+        fdt.begin_node("interrupt-controller");
+        fdt.property_u32("#interrupt-cells", 1);
+        fdt.property_empty("interrupt-controller");
+        fdt.property_string("compatible", "riscv,cpu-intc");
+        fdt.property_u32("phandle", intc_phandle(hart));
+        fdt.end_node();
+        fdt.end_node();
+    }
+    fdt.end_node();
+
+    fdt.begin_node(&format!("memory@{:x}", devices::DRAM_BASE));
+    fdt.property_string("device_type", "memory");
+    fdt.property_reg("reg", devices::DRAM_BASE, devices::DRAM_SIZE);
+    fdt.end_node();
+
+    fdt.begin_node("soc");
+    fdt.property_u32("#address-cells", 2);
+    fdt.property_u32("#size-cells", 2);
+    fdt.property_string("compatible", "simple-bus");
+    fdt.property_empty("ranges");
+
+    // hart마다 M-mode 소프트웨어(3)와 타이머(7) 인터럽트
+    fdt.begin_node(&format!("clint@{:x}", devices::CLINT_BASE));
+    fdt.property_strings("compatible", &["sifive,clint0", "riscv,clint0"]);
+    fdt.property_reg("reg", devices::CLINT_BASE, devices::CLINT_SIZE);
+    let interrupts: Vec<u32> = (0..harts)
+        .flat_map(|hart| [intc_phandle(hart), 3, intc_phandle(hart), 7])
+        .collect();
+    fdt.property_cells("interrupts-extended", &interrupts);
+    fdt.end_node();
+
+    fdt.begin_node(&format!("serial@{:x}", devices::UART_BASE));
+    fdt.property_string("compatible", "ns16550a");
+    fdt.property_reg("reg", devices::UART_BASE, devices::UART_SIZE);
+    fdt.property_u32("clock-frequency", 3_686_400);
+    fdt.end_node();
+    fdt.end_node();
+
+    fdt.end_node();
+    fdt.finish(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be32(dtb: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(dtb[offset..offset + 4].try_into().unwrap())
+    }
+
+    // 구조 블록을 따라가며 path 노드의 name 프로퍼티 값을 찾음
+    fn find_property<'a>(dtb: &'a [u8], path: &str, name: &str) -> Option<&'a [u8]> {
+        let struct_offset = be32(dtb, 8) as usize;
+        let strings_offset = be32(dtb, 12) as usize;
+        let mut stack: Vec<String> = Vec::new();
+        let mut pos = struct_offset;
+        loop {
+            let token = be32(dtb, pos);
+            pos += 4;
+            match token {
+                FDT_BEGIN_NODE => {
+                    let len = dtb[pos..].iter().position(|&b| b == 0).unwrap();
+                    stack.push(String::from_utf8_lossy(&dtb[pos..pos + len]).into_owned());
+                    pos = (pos + len + 1).next_multiple_of(4);
+                }
+                FDT_END_NODE => {
+                    stack.pop();
+                }
+                FDT_PROP => {
+                    let len = be32(dtb, pos) as usize;
+                    let name_offset = strings_offset + be32(dtb, pos + 4) as usize;
+                    let value = &dtb[pos + 8..pos + 8 + len];
+                    pos = (pos + 8 + len).next_multiple_of(4);
+                    let name_len = dtb[name_offset..].iter().position(|&b| b == 0).unwrap();
+                    let node_path = if stack.len() <= 1 {
+                        "/".to_string()
+                    } else {
+                        stack.join("/")
+                    };
+                    if node_path == path
+                        && &dtb[name_offset..name_offset + name_len] == name.as_bytes()
+                    {
+                        return Some(value);
+                    }
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let dtb = machine(Xlen::Rv64, 1, 10_000_000);
+        assert_eq!(be32(&dtb, 0), FDT_MAGIC);
+        assert_eq!(be32(&dtb, 4) as usize, dtb.len());
+        assert_eq!(be32(&dtb, 20), FDT_VERSION);
+        // 구조 블록은 FDT_END로 끝남
+        let struct_end = (be32(&dtb, 8) + be32(&dtb, 36)) as usize;
+        assert_eq!(be32(&dtb, struct_end - 4), FDT_END);
+    }
+
+    #[test]
+    fn test_timebase_frequency_exported() {
+        let dtb = machine(Xlen::Rv64, 1, 1_000_000);
+        assert_eq!(
+            find_property(&dtb, "/cpus", "timebase-frequency"),
+            Some(&1_000_000u32.to_be_bytes()[..])
+        );
+        assert_eq!(
+            find_property(&dtb, "/cpus/cpu@0", "riscv,isa"),
+            Some(&b"rv64imafdc_zicsr_zifencei\0"[..])
+        );
+    }
+
+    #[test]
+    fn test_memory_and_clint_per_hart() {
+        let dtb = machine(Xlen::Rv32, 2, 10_000_000);
+        let reg = find_property(&dtb, "/memory@80000000", "reg").unwrap();
+        assert_eq!(be32(reg, 4) as u64, devices::DRAM_BASE);
+        assert_eq!(be32(reg, 12) as u64, devices::DRAM_SIZE);
+        let interrupts = find_property(&dtb, "/soc/clint@2000000", "interrupts-extended").unwrap();
+        // hart 2개 × (phandle, 3, phandle, 7)
+        assert_eq!(interrupts.len(), 2 * 4 * 4);
+        assert_eq!(be32(interrupts, 16), 2);
+        assert!(find_property(&dtb, "/cpus/cpu@1", "reg").is_some());
+    }
+}
@@ -1,13 +1,21 @@
+pub mod asm;
 pub mod bus;
 pub mod cpu;
 pub mod csr;
 pub mod decoder;
 pub mod devices;
+pub mod disasm;
 pub mod elf;
+pub mod fdt;
+pub mod litmus;
+pub mod machine;
+pub mod scheduler;
+pub mod write_buffer;
 
 pub use bus::Bus;
 pub use cpu::Cpu;
 pub use csr::Csr;
+pub use machine::Machine;
 
 #[macro_export]
 macro_rules! debug_log {
@@ -0,0 +1,208 @@
+//! herd 형식 RISC-V litmus 테스트 실행기
+//! 스레드마다 hart 하나에 올려 무작위 명령어 순서와 write buffer 반영 순서(MemoryModel::Relaxed)로
+//! 여러 번 실행하고 관찰한 최종 상태의 분포를 보고함
+//! RVWMO 공리 모델(model.rs)로 허용되는 최종 상태를 모두 구해서, 모델이 금지하는 상태를 관찰하면 표시
+//!
+//! ```text
+//! RISCV SB
+//! {
+//! 0:x5=1; 0:x6=x; 0:x8=y;
+//! 1:x5=1; 1:x6=y; 1:x8=x;
+//! }
+//!  P0          | P1          ;
+//!  sw x5,0(x6) | sw x5,0(x6) ;
+//!  lw x7,0(x8) | lw x7,0(x8) ;
+//! exists (0:x7=0 /\ 1:x7=0)
+//! ```
+
+mod model;
+mod parse;
+mod runner;
+
+use std::fmt;
+
+use crate::asm;
+use crate::devices;
+
+pub use runner::{Report, run};
+
+/// 스레드 i의 코드 시작 주소 = CODE_BASE + i * CODE_STRIDE
+pub const CODE_BASE: u64 = devices::DRAM_BASE;
+pub const CODE_STRIDE: u64 = 0x1000;
+/// 위치 i의 주소 = DATA_BASE + i * DATA_STRIDE
+pub const DATA_BASE: u64 = devices::DRAM_BASE + 0x10_0000;
+pub const DATA_STRIDE: u64 = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LitmusError {
+    /// 1부터 시작하는 소스 줄 번호 (테스트 전체에 대한 오류면 None)
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl LitmusError {
+    fn new(message: impl Into<String>) -> Self {
+        LitmusError {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn at(line: usize, message: impl Into<String>) -> Self {
+        LitmusError {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LitmusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for LitmusError {}
+
+/// 최종 상태에서 관찰하는 값
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+    /// 스레드의 레지스터
+    Register { thread: usize, reg: usize },
+    /// 메모리 위치 (Litmus::locations 인덱스)
+    Memory(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prop {
+    True,
+    False,
+    /// Litmus::targets[index] == value
+    Eq(usize, u64),
+    Not(Box<Prop>),
+    And(Box<Prop>, Box<Prop>),
+    Or(Box<Prop>, Box<Prop>),
+}
+
+impl Prop {
+    pub fn eval(&self, state: &State) -> bool {
+        match self {
+            Prop::True => true,
+            Prop::False => false,
+            Prop::Eq(index, value) => state.0[*index] == *value,
+            Prop::Not(prop) => !prop.eval(state),
+            Prop::And(left, right) => left.eval(state) && right.eval(state),
+            Prop::Or(left, right) => left.eval(state) || right.eval(state),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantifier {
+    /// 조건을 만족하는 실행이 있는지 확인
+    Exists,
+    /// 조건을 만족하는 실행이 없어야 함
+    NotExists,
+    /// 모든 실행이 조건을 만족해야 함
+    Forall,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub quantifier: Quantifier,
+    pub prop: Prop,
+    /// 원문 (보고서에 그대로 출력)
+    pub text: String,
+}
+
+/// 메모리 위치
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub init: u64,
+    /// 선언된 폭 (바이트). 최종 값은 이 폭으로 비교
+    pub width: u8,
+}
+
+/// targets 순서대로의 최종 값
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct State(pub Vec<u64>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Litmus {
+    pub name: String,
+    pub locations: Vec<Location>,
+    /// 스레드별 레지스터 초기값 (레지스터 번호, 값). 위치 이름은 주소로 바뀜
+    pub registers: Vec<Vec<(usize, u64)>>,
+    /// 스레드별 어셈블리 소스
+    pub threads: Vec<String>,
+    /// 최종 상태에서 관찰하는 값 (조건과 locations 절에 나온 순서)
+    pub targets: Vec<Target>,
+    pub condition: Condition,
+}
+
+impl Litmus {
+    pub fn parse(text: &str) -> Result<Litmus, LitmusError> {
+        parse::parse(text)
+    }
+
+    pub fn location_address(index: usize) -> u64 {
+        DATA_BASE + index as u64 * DATA_STRIDE
+    }
+
+    pub fn code_address(thread: usize) -> u64 {
+        CODE_BASE + thread as u64 * CODE_STRIDE
+    }
+
+    /// 주소에 있는 위치 인덱스
+    pub fn location_at(&self, addr: u64) -> Option<usize> {
+        let offset = addr.checked_sub(DATA_BASE)?;
+        let index = (offset / DATA_STRIDE) as usize;
+        (offset.is_multiple_of(DATA_STRIDE) && index < self.locations.len()).then_some(index)
+    }
+
+    /// 스레드별 기계어 (herd의 .aq.rl 표기는 .aqrl로 바꿔서 어셈블)
+    pub fn assemble(&self) -> Result<Vec<Vec<u32>>, LitmusError> {
+        self.threads
+            .iter()
+            .enumerate()
+            .map(|(thread, source)| {
+                asm::assemble(&source.replace(".aq.rl", ".aqrl"))
+                    .map_err(|error| LitmusError::new(format!("P{}: {}", thread, error)))
+            })
+            .collect()
+    }
+
+    /// "0:x5" 또는 "x"
+    pub fn target_name(&self, target: Target) -> String {
+        match target {
+            Target::Register { thread, reg } => format!("{}:x{}", thread, reg),
+            Target::Memory(index) => self.locations[index].name.clone(),
+        }
+    }
+
+    /// herd 형식 상태 문자열 ("0:x7=0; 1:x7=1;")
+    pub fn format_state(&self, state: &State) -> String {
+        let mut text = String::new();
+        for (target, value) in self.targets.iter().zip(&state.0) {
+            text += &format!("{}={}; ", self.target_name(*target), *value as i64);
+        }
+        text.trim_end().to_string()
+    }
+
+    // 메모리 값은 위치 폭에서 부호 확장해서 비교 (레지스터는 그대로)
+    fn normalize(&self, target: Target, value: u64) -> u64 {
+        match target {
+            Target::Memory(index) => sign_extend(value, self.locations[index].width),
+            Target::Register { .. } => value,
+        }
+    }
+}
+
+fn sign_extend(value: u64, size: u8) -> u64 {
+    let shift = 64 - 8 * size as u32;
+    (((value << shift) as i64) >> shift) as u64
+}
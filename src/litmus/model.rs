@@ -0,0 +1,1019 @@
+//! RVWMO 공리 모델 (herd riscv.cat)
+//! 스레드마다 load가 읽을 수 있는 값을 모두 골라 가며 실행해서 이벤트 열(trace)을 만들고,
+//! trace 조합마다 rf(load가 읽는 store)와 co(위치별 store 순서)를 모두 골라 세 공리를 확인
+//! - Coherence: acyclic(po-loc | rf | co | fr)
+//! - Atomicity: empty(rmw & (fre; coe))
+//! - Model: acyclic(ppo | rfe | co | fr), ppo는 명세의 규칙 1~13
+//!
+//! 지원 범위: 위치 시작 주소에 선언 폭 그대로 접근하는 정수 명령어, FENCE/FENCE.TSO, LR/SC, AMO
+//! 루프는 경로 하나가 MAX_PATH_STEPS개 명령어를 넘지 않는 만큼만 펼침
+
+use std::collections::BTreeSet;
+
+use super::{Litmus, State, Target};
+use crate::cpu::amo_alu;
+use crate::decoder::{self, AluOp, AmoOp, BranchOp, Instruction, LoadOp, Width};
+
+// 경로 하나에서 실행하는 최대 명령어 수
+const MAX_PATH_STEPS: usize = 64;
+// 스레드 하나의 최대 이벤트 수 (의존성을 u64 비트마스크로 표현)
+const MAX_THREAD_EVENTS: usize = 64;
+// 전체 메모리 이벤트 수 (초기 store 포함, 관계를 u128 비트셋으로 표현)
+const MAX_EVENTS: usize = 128;
+// 스레드 하나의 최대 trace 수
+const MAX_TRACES: usize = 4096;
+// 확인하는 후보 실행 (trace 조합 x rf x co) 수 상한
+const MAX_CANDIDATES: usize = 1 << 20;
+
+// FENCE pred/succ 비트
+const FENCE_R: u32 = 0b0010;
+const FENCE_W: u32 = 0b0001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Read,
+    Write,
+    /// pred/succ는 R/W 비트만 봄
+    Fence {
+        pred: u32,
+        succ: u32,
+        tso: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Event {
+    kind: Kind,
+    loc: usize,
+    /// 위치 폭으로 자른 값
+    value: u64,
+    aq: bool,
+    rl: bool,
+    /// AMO 또는 LR/SC
+    atomic: bool,
+    /// 주소/데이터/제어가 의존하는 같은 스레드 이벤트 (비트마스크)
+    addr: u64,
+    data: u64,
+    ctrl: u64,
+    /// AMO와 성공한 SC의 write: 짝이 되는 read
+    rmw: Option<usize>,
+}
+
+impl Event {
+    fn memory(kind: Kind, loc: usize, value: u64) -> Self {
+        Event {
+            kind,
+            loc,
+            value,
+            aq: false,
+            rl: false,
+            atomic: false,
+            addr: 0,
+            data: 0,
+            ctrl: 0,
+            rmw: None,
+        }
+    }
+
+    fn is_memory(&self) -> bool {
+        matches!(self.kind, Kind::Read | Kind::Write)
+    }
+
+    // FENCE pred/succ 비트 중 이 이벤트에 해당하는 것
+    fn fence_bit(&self) -> u32 {
+        match self.kind {
+            Kind::Read => FENCE_R,
+            Kind::Write => FENCE_W,
+            Kind::Fence { .. } => 0,
+        }
+    }
+}
+
+/// 스레드 하나의 실행 경로
+#[derive(Debug, Clone)]
+struct Trace {
+    events: Vec<Event>,
+    regs: [u64; 32],
+}
+
+// 탐색 중인 경로
+#[derive(Clone)]
+struct Path {
+    index: usize,
+    regs: [u64; 32],
+    // 레지스터 값이 의존하는 이벤트
+    deps: [u64; 32],
+    // 지나온 분기 조건이 의존하는 이벤트
+    ctrl: u64,
+    events: Vec<Event>,
+    // LR 이벤트와 위치
+    reservation: Option<(usize, usize)>,
+    steps: usize,
+}
+
+impl Path {
+    fn write_reg(&mut self, rd: usize, value: u64, deps: u64) {
+        if rd != 0 {
+            self.regs[rd] = value;
+            self.deps[rd] = deps;
+        }
+    }
+
+    fn push(&mut self, mut event: Event) -> Result<usize, String> {
+        if self.events.len() >= MAX_THREAD_EVENTS {
+            return Err(format!(
+                "more than {} events in a thread",
+                MAX_THREAD_EVENTS
+            ));
+        }
+        event.ctrl = self.ctrl;
+        self.events.push(event);
+        Ok(self.events.len() - 1)
+    }
+}
+
+/// RVWMO가 허용하는 최종 상태 (targets 순서). 모델이 다룰 수 없는 테스트면 Err(이유)
+pub(super) fn allowed(litmus: &Litmus, programs: &[Vec<u32>]) -> Result<BTreeSet<State>, String> {
+    let traces = explore_all(litmus, programs)?;
+    let mut checker = Checker {
+        litmus,
+        candidates: 0,
+        states: BTreeSet::new(),
+    };
+    let mut chosen = Vec::new();
+    checker.combine(&traces, &mut chosen)?;
+    Ok(checker.states)
+}
+
+// load가 읽을 수 있는 값 집합을 넓혀 가며 모든 스레드를 탐색
+// 실행 하나의 store 값은 out-of-thin-air 없이 앞선 store에서만 유도되므로, 반복 횟수가
+// 실행 하나의 store 수 이상이면 일관된 실행이 쓰는 값은 모두 나옴 (집합이 더 안 늘면 바로 끝)
+fn explore_all(litmus: &Litmus, programs: &[Vec<u32>]) -> Result<Vec<Vec<Trace>>, String> {
+    let mut values: Vec<BTreeSet<u64>> = litmus
+        .locations
+        .iter()
+        .map(|location| BTreeSet::from([truncate(location.init, location.width)]))
+        .collect();
+    let mut round = 0;
+    loop {
+        let traces = programs
+            .iter()
+            .enumerate()
+            .map(|(thread, program)| {
+                explore(litmus, thread, program, &values).map_err(|e| format!("P{}: {}", thread, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let writes = |trace: &Trace| {
+            trace
+                .events
+                .iter()
+                .filter(|event| event.kind == Kind::Write)
+                .count()
+        };
+        let most_writes: usize = traces
+            .iter()
+            .map(|paths| paths.iter().map(writes).max().unwrap_or(0))
+            .sum();
+        let mut grown = false;
+        for event in traces.iter().flatten().flat_map(|trace| &trace.events) {
+            if event.kind == Kind::Write {
+                grown |= values[event.loc].insert(event.value);
+            }
+        }
+        if !grown || round >= most_writes {
+            return Ok(traces);
+        }
+        round += 1;
+    }
+}
+
+// 스레드의 모든 경로. load는 values에 있는 값을 하나씩 골라 봄
+fn explore(
+    litmus: &Litmus,
+    thread: usize,
+    program: &[u32],
+    values: &[BTreeSet<u64>],
+) -> Result<Vec<Trace>, String> {
+    let mut regs = [0; 32];
+    for &(reg, value) in &litmus.registers[thread] {
+        regs[reg] = value;
+    }
+    regs[0] = 0;
+    let mut pending = vec![Path {
+        index: 0,
+        regs,
+        deps: [0; 32],
+        ctrl: 0,
+        events: Vec::new(),
+        reservation: None,
+        steps: 0,
+    }];
+    let mut traces = Vec::new();
+    while let Some(path) = pending.pop() {
+        if path.index == program.len() {
+            if traces.len() >= MAX_TRACES {
+                return Err(format!("more than {} paths", MAX_TRACES));
+            }
+            traces.push(Trace {
+                events: path.events,
+                regs: path.regs,
+            });
+            continue;
+        }
+        // 끝나지 않는 경로는 최종 상태가 없음
+        if path.steps >= MAX_PATH_STEPS {
+            continue;
+        }
+        pending.extend(step(litmus, thread, program, values, path)?);
+    }
+    Ok(traces)
+}
+
+// 명령어 하나를 실행한 다음 경로들 (load 값과 SC 성공 여부마다 하나씩)
+fn step(
+    litmus: &Litmus,
+    thread: usize,
+    program: &[u32],
+    values: &[BTreeSet<u64>],
+    mut path: Path,
+) -> Result<Vec<Path>, String> {
+    let word = program[path.index];
+    let pc = Litmus::code_address(thread) + 4 * path.index as u64;
+    let inst = decoder::decode(word).map_err(|error| error.to_string())?;
+    let unsupported = || format!("unsupported instruction {:#010x} at {:#x}", word, pc);
+    let location = |addr: u64, size: u64| -> Result<usize, String> {
+        let index = litmus
+            .location_at(addr)
+            .ok_or_else(|| format!("access to {:#x} is not a location", addr))?;
+        if litmus.locations[index].width as u64 != size {
+            return Err(format!(
+                "mixed-size access to {}",
+                litmus.locations[index].name
+            ));
+        }
+        Ok(index)
+    };
+    path.steps += 1;
+    let mut next = path.index as i64 + 1;
+    let (regs, deps) = (path.regs, path.deps);
+    let mut forks = Vec::new();
+    match inst {
+        Instruction::Lui { rd, imm } => path.write_reg(rd, imm as i64 as u64, 0),
+        Instruction::Auipc { rd, imm } => path.write_reg(rd, pc.wrapping_add(imm as i64 as u64), 0),
+        Instruction::Jal { rd, offset } => {
+            path.write_reg(rd, pc + 4, 0);
+            next = jump_target(path.index, offset, program.len())?;
+        }
+        Instruction::Branch {
+            op,
+            rs1,
+            rs2,
+            offset,
+        } => {
+            path.ctrl |= deps[rs1] | deps[rs2];
+            let (a, b) = (regs[rs1], regs[rs2]);
+            let taken = match op {
+                BranchOp::Beq => a == b,
+                BranchOp::Bne => a != b,
+                BranchOp::Blt => (a as i64) < (b as i64),
+                BranchOp::Bge => (a as i64) >= (b as i64),
+                BranchOp::Bltu => a < b,
+                BranchOp::Bgeu => a >= b,
+            };
+            if taken {
+                next = jump_target(path.index, offset, program.len())?;
+            }
+        }
+        Instruction::OpImm { op, rd, rs1, imm } => {
+            let value = alu(op, regs[rs1], imm as i64 as u64).ok_or_else(unsupported)?;
+            path.write_reg(rd, value, deps[rs1]);
+        }
+        Instruction::OpImm32 { op, rd, rs1, imm } => {
+            let value = alu32(op, regs[rs1], imm as i64 as u64).ok_or_else(unsupported)?;
+            path.write_reg(rd, value, deps[rs1]);
+        }
+        Instruction::Op { op, rd, rs1, rs2 } => {
+            let value = alu(op, regs[rs1], regs[rs2]).ok_or_else(unsupported)?;
+            path.write_reg(rd, value, deps[rs1] | deps[rs2]);
+        }
+        Instruction::Op32 { op, rd, rs1, rs2 } => {
+            let value = alu32(op, regs[rs1], regs[rs2]).ok_or_else(unsupported)?;
+            path.write_reg(rd, value, deps[rs1] | deps[rs2]);
+        }
+        Instruction::Load {
+            op,
+            rd,
+            rs1,
+            offset,
+        } => {
+            let (size, signed) = match op {
+                LoadOp::Lb => (1, true),
+                LoadOp::Lh => (2, true),
+                LoadOp::Lw => (4, true),
+                LoadOp::Ld => (8, true),
+                LoadOp::Lbu => (1, false),
+                LoadOp::Lhu => (2, false),
+                LoadOp::Lwu => (4, false),
+            };
+            let loc = location(regs[rs1].wrapping_add(offset as i64 as u64), size)?;
+            for &value in &values[loc] {
+                let mut fork = path.clone();
+                let mut event = Event::memory(Kind::Read, loc, value);
+                event.addr = deps[rs1];
+                let index = fork.push(event)?;
+                let value = if signed {
+                    sign_extend(value, size)
+                } else {
+                    value
+                };
+                fork.write_reg(rd, value, 1 << index);
+                forks.push(fork);
+            }
+        }
+        Instruction::Store {
+            width,
+            rs1,
+            rs2,
+            offset,
+        } => {
+            let size = width.bytes();
+            let loc = location(regs[rs1].wrapping_add(offset as i64 as u64), size)?;
+            let mut event = Event::memory(Kind::Write, loc, truncate(regs[rs2], size as u8));
+            event.addr = deps[rs1];
+            event.data = deps[rs2];
+            path.push(event)?;
+        }
+        Instruction::Fence { pred, succ } => {
+            path.push(Event::memory(
+                Kind::Fence {
+                    pred: pred & (FENCE_R | FENCE_W),
+                    succ: succ & (FENCE_R | FENCE_W),
+                    tso: decoder::fence_tso(word),
+                },
+                0,
+                0,
+            ))?;
+        }
+        // Zihintpause는 메모리 순서에 영향 없음
+        Instruction::Pause => {}
+        Instruction::Amo {
+            op,
+            width,
+            aq,
+            rl,
+            rd,
+            rs1,
+            rs2,
+        } if width != Width::Q => {
+            let size = width.bytes();
+            let loc = location(regs[rs1], size)?;
+            match op {
+                AmoOp::Lr => {
+                    for &value in &values[loc] {
+                        let mut fork = path.clone();
+                        let mut event = Event::memory(Kind::Read, loc, value);
+                        (event.aq, event.rl, event.atomic) = (aq, rl, true);
+                        event.addr = deps[rs1];
+                        let index = fork.push(event)?;
+                        fork.write_reg(rd, sign_extend(value, size), 1 << index);
+                        fork.reservation = Some((index, loc));
+                        forks.push(fork);
+                    }
+                }
+                AmoOp::Sc => {
+                    // 실패는 항상 가능하고, 성공은 같은 위치의 LR 뒤에서만 가능
+                    let reservation = path.reservation.take();
+                    if let Some((read, _)) = reservation.filter(|&(_, reserved)| reserved == loc) {
+                        let mut fork = path.clone();
+                        let value = truncate(regs[rs2], size as u8);
+                        let mut event = Event::memory(Kind::Write, loc, value);
+                        (event.aq, event.rl, event.atomic) = (aq, rl, true);
+                        event.addr = deps[rs1];
+                        event.data = deps[rs2];
+                        event.rmw = Some(read);
+                        fork.push(event)?;
+                        fork.write_reg(rd, 0, 0);
+                        forks.push(fork);
+                    }
+                    path.write_reg(rd, 1, 0);
+                    forks.push(path.clone());
+                }
+                _ => {
+                    for &old in &values[loc] {
+                        let mut fork = path.clone();
+                        let mut read = Event::memory(Kind::Read, loc, old);
+                        // .aqrl이면 두 이벤트 모두 RCsc, 아니면 aq는 read에만, rl은 write에만
+                        (read.aq, read.rl, read.atomic) = (aq, aq && rl, true);
+                        read.addr = deps[rs1];
+                        let read_index = fork.push(read)?;
+                        let new = match op {
+                            AmoOp::Cas if old != truncate(regs[rd], size as u8) => None,
+                            AmoOp::Cas => Some(regs[rs2]),
+                            op => Some(amo_alu(op, old, regs[rs2], size)),
+                        };
+                        if let Some(new) = new {
+                            let mut write =
+                                Event::memory(Kind::Write, loc, truncate(new, size as u8));
+                            (write.aq, write.rl, write.atomic) = (aq && rl, rl, true);
+                            write.addr = deps[rs1];
+                            write.data = deps[rs2];
+                            if op == AmoOp::Cas {
+                                write.data |= deps[rd];
+                            }
+                            write.rmw = Some(read_index);
+                            fork.push(write)?;
+                        }
+                        fork.write_reg(rd, sign_extend(old, size), 1 << read_index);
+                        forks.push(fork);
+                    }
+                }
+            }
+        }
+        _ => return Err(unsupported()),
+    }
+    if forks.is_empty() {
+        forks.push(path);
+    }
+    for fork in forks.iter_mut() {
+        fork.index = next as usize;
+    }
+    Ok(forks)
+}
+
+// 분기 대상 명령어 번호 (프로그램 끝은 종료)
+fn jump_target(index: usize, offset: i32, len: usize) -> Result<i64, String> {
+    if offset % 4 != 0 {
+        return Err(format!("branch offset {} is not a multiple of 4", offset));
+    }
+    let target = index as i64 + (offset / 4) as i64;
+    if target < 0 || target > len as i64 {
+        return Err("branch target outside the thread".to_string());
+    }
+    Ok(target)
+}
+
+fn alu(op: AluOp, a: u64, b: u64) -> Option<u64> {
+    let shamt = (b & 0x3F) as u32;
+    Some(match op {
+        AluOp::Add => a.wrapping_add(b),
+        AluOp::Sub => a.wrapping_sub(b),
+        AluOp::Sll => a << shamt,
+        AluOp::Slt => ((a as i64) < (b as i64)) as u64,
+        AluOp::Sltu => (a < b) as u64,
+        AluOp::Xor => a ^ b,
+        AluOp::Srl => a >> shamt,
+        AluOp::Sra => ((a as i64) >> shamt) as u64,
+        AluOp::Or => a | b,
+        AluOp::And => a & b,
+        AluOp::Mul => a.wrapping_mul(b),
+        AluOp::CzeroEqz => {
+            if b == 0 {
+                0
+            } else {
+                a
+            }
+        }
+        AluOp::CzeroNez => {
+            if b != 0 {
+                0
+            } else {
+                a
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn alu32(op: AluOp, a: u64, b: u64) -> Option<u64> {
+    let (a, b) = (a as u32, b as u32);
+    let shamt = b & 0x1F;
+    let value = match op {
+        AluOp::Add => a.wrapping_add(b),
+        AluOp::Sub => a.wrapping_sub(b),
+        AluOp::Sll => a << shamt,
+        AluOp::Srl => a >> shamt,
+        AluOp::Sra => ((a as i32) >> shamt) as u32,
+        AluOp::Mul => a.wrapping_mul(b),
+        _ => return None,
+    };
+    Some(value as i32 as i64 as u64)
+}
+
+fn truncate(value: u64, size: u8) -> u64 {
+    match size {
+        8 => value,
+        _ => value & ((1 << (8 * size)) - 1),
+    }
+}
+
+fn sign_extend(value: u64, size: u64) -> u64 {
+    super::sign_extend(value, size as u8)
+}
+
+// 비트셋 관계가 사이클이 없는지 (들어오는 간선이 없는 노드를 반복해서 제거)
+fn acyclic(edges: &[u128]) -> bool {
+    let mut remaining: u128 = match edges.len() {
+        128 => u128::MAX,
+        n => (1 << n) - 1,
+    };
+    while remaining != 0 {
+        let targets = (0..edges.len())
+            .filter(|&node| remaining & (1 << node) != 0)
+            .fold(0, |targets, node| targets | edges[node]);
+        let sources = remaining & !targets;
+        if sources == 0 {
+            return false;
+        }
+        remaining &= !sources;
+    }
+    true
+}
+
+// 후보 실행의 전역 메모리 이벤트
+struct Node<'a> {
+    event: &'a Event,
+    // 초기 store는 None
+    thread: Option<usize>,
+    // 스레드 안에서의 이벤트 번호
+    local: usize,
+}
+
+struct Checker<'a> {
+    litmus: &'a Litmus,
+    candidates: usize,
+    states: BTreeSet<State>,
+}
+
+impl<'a> Checker<'a> {
+    // 스레드마다 trace 하나씩 골라 조합
+    fn combine(
+        &mut self,
+        traces: &'a [Vec<Trace>],
+        chosen: &mut Vec<&'a Trace>,
+    ) -> Result<(), String> {
+        let Some(options) = traces.get(chosen.len()) else {
+            return self.check(chosen);
+        };
+        for trace in options {
+            chosen.push(trace);
+            self.combine(traces, chosen)?;
+            chosen.pop();
+        }
+        Ok(())
+    }
+
+    fn check(&mut self, chosen: &[&'a Trace]) -> Result<(), String> {
+        let inits: Vec<Event> = self
+            .litmus
+            .locations
+            .iter()
+            .enumerate()
+            .map(|(loc, location)| {
+                Event::memory(Kind::Write, loc, truncate(location.init, location.width))
+            })
+            .collect();
+        let mut nodes: Vec<Node> = inits
+            .iter()
+            .map(|event| Node {
+                event,
+                thread: None,
+                local: 0,
+            })
+            .collect();
+        // (스레드, 이벤트 번호) -> 노드 번호
+        let mut ids: Vec<Vec<Option<usize>>> = Vec::new();
+        for (thread, trace) in chosen.iter().enumerate() {
+            let mut thread_ids = Vec::new();
+            for (local, event) in trace.events.iter().enumerate() {
+                if event.is_memory() {
+                    thread_ids.push(Some(nodes.len()));
+                    nodes.push(Node {
+                        event,
+                        thread: Some(thread),
+                        local,
+                    });
+                } else {
+                    thread_ids.push(None);
+                }
+            }
+            ids.push(thread_ids);
+        }
+        if nodes.len() > MAX_EVENTS {
+            return Err(format!("more than {} memory events", MAX_EVENTS));
+        }
+
+        let reads: Vec<usize> = (0..nodes.len())
+            .filter(|&id| nodes[id].event.kind == Kind::Read)
+            .collect();
+        // 같은 위치에 같은 값을 쓴 store만 rf 후보
+        let sources: Vec<Vec<usize>> = reads
+            .iter()
+            .map(|&read| {
+                (0..nodes.len())
+                    .filter(|&write| {
+                        let (r, w) = (nodes[read].event, nodes[write].event);
+                        w.kind == Kind::Write && w.loc == r.loc && w.value == r.value
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut search = Execution {
+            litmus: self.litmus,
+            chosen,
+            nodes: &nodes,
+            ids: &ids,
+            reads: &reads,
+            rf: vec![0; nodes.len()],
+        };
+        search.choose_rf(self, &sources, 0)
+    }
+}
+
+// trace 조합 하나에 대한 rf/co 탐색
+struct Execution<'a> {
+    litmus: &'a Litmus,
+    chosen: &'a [&'a Trace],
+    nodes: &'a [Node<'a>],
+    ids: &'a [Vec<Option<usize>>],
+    reads: &'a [usize],
+    // read 노드 -> 읽은 store 노드
+    rf: Vec<usize>,
+}
+
+impl Execution<'_> {
+    fn choose_rf(
+        &mut self,
+        checker: &mut Checker,
+        sources: &[Vec<usize>],
+        index: usize,
+    ) -> Result<(), String> {
+        if index == self.reads.len() {
+            return self.check_rf(checker);
+        }
+        for &write in &sources[index] {
+            self.rf[self.reads[index]] = write;
+            self.choose_rf(checker, sources, index + 1)?;
+        }
+        Ok(())
+    }
+
+    // rf가 정해지면 ppo가 정해지므로 co 순열만 남음
+    fn check_rf(&self, checker: &mut Checker) -> Result<(), String> {
+        let ppo = self.ppo();
+        let locations = self.litmus.locations.len();
+        // 초기 store(노드 번호 = 위치)는 co의 맨 앞
+        let writes: Vec<Vec<usize>> = (0..locations)
+            .map(|loc| {
+                (locations..self.nodes.len())
+                    .filter(|&id| {
+                        let event = self.nodes[id].event;
+                        event.kind == Kind::Write && event.loc == loc
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut orders: Vec<Vec<usize>> = Vec::new();
+        self.choose_co(checker, &ppo, &writes, &mut orders)
+    }
+
+    fn choose_co(
+        &self,
+        checker: &mut Checker,
+        ppo: &[u128],
+        writes: &[Vec<usize>],
+        orders: &mut Vec<Vec<usize>>,
+    ) -> Result<(), String> {
+        let loc = orders.len();
+        if loc == writes.len() {
+            checker.candidates += 1;
+            if checker.candidates > MAX_CANDIDATES {
+                return Err(format!("more than {} candidate executions", MAX_CANDIDATES));
+            }
+            if self.consistent(ppo, orders) {
+                checker.states.insert(self.final_state(orders));
+            }
+            return Ok(());
+        }
+        let mut order = vec![loc];
+        let mut rest = writes[loc].clone();
+        self.permute(checker, ppo, writes, orders, &mut order, &mut rest)
+    }
+
+    fn permute(
+        &self,
+        checker: &mut Checker,
+        ppo: &[u128],
+        writes: &[Vec<usize>],
+        orders: &mut Vec<Vec<usize>>,
+        order: &mut Vec<usize>,
+        rest: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        if rest.is_empty() {
+            orders.push(order.clone());
+            let result = self.choose_co(checker, ppo, writes, orders);
+            orders.pop();
+            return result;
+        }
+        for index in 0..rest.len() {
+            let write = rest.remove(index);
+            order.push(write);
+            self.permute(checker, ppo, writes, orders, order, rest)?;
+            order.pop();
+            rest.insert(index, write);
+        }
+        Ok(())
+    }
+
+    fn same_thread(&self, a: usize, b: usize) -> bool {
+        self.nodes[a].thread.is_some() && self.nodes[a].thread == self.nodes[b].thread
+    }
+
+    // a가 b보다 프로그램 순서상 앞
+    fn po(&self, a: usize, b: usize) -> bool {
+        self.same_thread(a, b) && self.nodes[a].local < self.nodes[b].local
+    }
+
+    // 스레드 안의 이벤트 번호 -> 노드 번호 (메모리 이벤트만)
+    fn node(&self, thread: usize, local: usize) -> Option<usize> {
+        self.ids[thread][local]
+    }
+
+    fn ppo(&self) -> Vec<u128> {
+        let mut ppo = vec![0u128; self.nodes.len()];
+        for (thread, trace) in self.chosen.iter().enumerate() {
+            let events = &trace.events;
+            for j in 0..events.len() {
+                let Some(b) = self.node(thread, j) else {
+                    continue;
+                };
+                for i in 0..j {
+                    let Some(a) = self.node(thread, i) else {
+                        continue;
+                    };
+                    if self.preserved(thread, i, j, a, b) {
+                        ppo[a] |= 1 << b;
+                    }
+                }
+            }
+        }
+        ppo
+    }
+
+    // 스레드의 이벤트 i < j (노드 a, b) 사이의 preserved program order
+    fn preserved(&self, thread: usize, i: usize, j: usize, a: usize, b: usize) -> bool {
+        let events = &self.chosen[thread].events;
+        let (ea, eb) = (&events[i], &events[j]);
+        let bit = |index: usize| 1u64 << index;
+        let same_loc = ea.loc == eb.loc;
+        let between = || events[i + 1..j].iter();
+        let rfi =
+            |write: usize, read: usize| self.rf[read] == write && self.same_thread(write, read);
+
+        // 1. 같은 주소 뒤의 store
+        if same_loc && eb.kind == Kind::Write {
+            return true;
+        }
+        // 2. 같은 주소 load 둘 사이에 store가 없고 서로 다른 store를 읽음
+        if same_loc
+            && ea.kind == Kind::Read
+            && eb.kind == Kind::Read
+            && !between().any(|e| e.kind == Kind::Write && e.loc == ea.loc)
+            && self.rf[a] != self.rf[b]
+        {
+            return true;
+        }
+        // 3. AMO/SC의 store를 같은 스레드 load가 읽음
+        if ea.atomic && ea.kind == Kind::Write && rfi(a, b) {
+            return true;
+        }
+        // 4. FENCE
+        if between().any(|fence| match fence.kind {
+            Kind::Fence { tso: true, .. } => ea.kind == Kind::Read || eb.kind == Kind::Write,
+            Kind::Fence { pred, succ, .. } => {
+                pred & ea.fence_bit() != 0 && succ & eb.fence_bit() != 0
+            }
+            _ => false,
+        }) {
+            return true;
+        }
+        // 5~7. acquire 뒤, release 앞, RCsc 사이
+        if ea.aq || eb.rl || (ea.rl && eb.aq) {
+            return true;
+        }
+        // 8. AMO/SC의 read와 write
+        if eb.rmw == Some(i) {
+            return true;
+        }
+        // 9~11. 주소 의존, store로의 데이터/제어 의존
+        if eb.addr & bit(i) != 0 {
+            return true;
+        }
+        if eb.kind == Kind::Write && (eb.data | eb.ctrl) & bit(i) != 0 {
+            return true;
+        }
+        if ea.kind != Kind::Read {
+            return false;
+        }
+        // 12. load -(주소/데이터 의존)-> store -(rfi)-> load
+        // 13. load -(주소 의존)-> 접근 -(po)-> store
+        (i + 1..j).any(|k| {
+            let ek = &events[k];
+            let Some(c) = self.node(thread, k) else {
+                return false;
+            };
+            let depends = (ek.addr | ek.data) & bit(i) != 0;
+            (depends && ek.kind == Kind::Write && eb.kind == Kind::Read && rfi(c, b))
+                || (ek.addr & bit(i) != 0 && eb.kind == Kind::Write)
+        })
+    }
+
+    fn consistent(&self, ppo: &[u128], orders: &[Vec<usize>]) -> bool {
+        let count = self.nodes.len();
+        let mut co = vec![0u128; count];
+        let mut position = vec![0; count];
+        for order in orders {
+            for (index, &write) in order.iter().enumerate() {
+                position[write] = index;
+                for &later in &order[index + 1..] {
+                    co[write] |= 1 << later;
+                }
+            }
+        }
+        let mut rf = vec![0u128; count];
+        let mut rfe = vec![0u128; count];
+        let mut fr = vec![0u128; count];
+        for &read in self.reads {
+            let write = self.rf[read];
+            rf[write] |= 1 << read;
+            if !self.same_thread(write, read) {
+                rfe[write] |= 1 << read;
+            }
+            fr[read] = co[write];
+        }
+
+        // Atomicity: rmw 쌍 사이에 다른 스레드의 store가 co에 끼지 않음
+        for (id, node) in self.nodes.iter().enumerate() {
+            let Some(read_local) = node.event.rmw else {
+                continue;
+            };
+            let thread = node.thread.unwrap();
+            let read = self.node(thread, read_local).unwrap();
+            let order = &orders[node.event.loc];
+            let (from, to) = (position[self.rf[read]], position[id]);
+            if from >= to
+                || order[from + 1..to]
+                    .iter()
+                    .any(|&other| !self.same_thread(other, id))
+            {
+                return false;
+            }
+        }
+
+        // Coherence: acyclic(po-loc | rf | co | fr)
+        let coherence: Vec<u128> = (0..count)
+            .map(|a| {
+                let po_loc = (0..count)
+                    .filter(|&b| {
+                        self.po(a, b) && self.nodes[a].event.loc == self.nodes[b].event.loc
+                    })
+                    .fold(0, |set, b| set | (1 << b));
+                po_loc | rf[a] | co[a] | fr[a]
+            })
+            .collect();
+        if !acyclic(&coherence) {
+            return false;
+        }
+
+        // Model: acyclic(ppo | rfe | co | fr)
+        let model: Vec<u128> = (0..count)
+            .map(|a| ppo[a] | rfe[a] | co[a] | fr[a])
+            .collect();
+        acyclic(&model)
+    }
+
+    fn final_state(&self, orders: &[Vec<usize>]) -> State {
+        State(
+            self.litmus
+                .targets
+                .iter()
+                .map(|&target| {
+                    let value = match target {
+                        Target::Register { thread, reg } => self.chosen[thread].regs[reg],
+                        Target::Memory(loc) => {
+                            let last = *orders[loc].last().unwrap();
+                            self.nodes[last].event.value
+                        }
+                    };
+                    self.litmus.normalize(target, value)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed_states(source: &str) -> Vec<String> {
+        let litmus = Litmus::parse(source).unwrap();
+        let programs = litmus.assemble().unwrap();
+        allowed(&litmus, &programs)
+            .unwrap()
+            .iter()
+            .map(|state| litmus.format_state(state))
+            .collect()
+    }
+
+    fn sb(fence: &str) -> String {
+        format!(
+            "RISCV SB\n{{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x5=1; 1:x6=y; 1:x8=x; }}\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | sw x5,0(x6) ;\n\
+             {fence} | {fence} ;\n\
+             lw x7,0(x8) | lw x7,0(x8) ;\n\
+             exists (0:x7=0 /\\ 1:x7=0)\n"
+        )
+    }
+
+    #[test]
+    fn test_store_buffering() {
+        assert_eq!(allowed_states(&sb("nop")).len(), 4);
+        let fenced = allowed_states(&sb("fence rw,rw"));
+        assert_eq!(fenced.len(), 3);
+        assert!(!fenced.contains(&"0:x7=0; 1:x7=0;".to_string()));
+        // fence.tso는 W→R을 정렬하지 않음
+        assert_eq!(allowed_states(&sb("fence.tso")).len(), 4);
+    }
+
+    fn mp(writer: &str, reader: &str) -> Vec<String> {
+        allowed_states(&format!(
+            "RISCV MP\n{{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x6=y; 1:x8=x; }}\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | lw x5,0(x6) ;\n\
+             {writer} | {reader} ;\n\
+             sw x5,0(x8) | lw x7,0(x8) ;\n\
+             exists (1:x5=1 /\\ 1:x7=0)\n"
+        ))
+    }
+
+    #[test]
+    fn test_message_passing() {
+        let stale = "1:x5=1; 1:x7=0;".to_string();
+        assert!(mp("nop", "nop").contains(&stale));
+        assert!(mp("fence w,w", "nop").contains(&stale));
+        assert!(!mp("fence w,w", "fence r,r").contains(&stale));
+        // 주소 의존: 읽은 값에 따라 주소가 바뀌므로 load 순서가 유지됨
+        let states = allowed_states(
+            "RISCV MP+fence+addr\n{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x6=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | lw x5,0(x6) ;\n\
+             fence w,w | xor x9,x5,x5 ;\n\
+             sw x5,0(x8) | add x8,x8,x9 ;\n\
+             | lw x7,0(x8) ;\n\
+             exists (1:x5=1 /\\ 1:x7=0)\n",
+        );
+        assert!(!states.contains(&stale));
+    }
+
+    #[test]
+    fn test_coherence_and_atomics() {
+        // 같은 주소 store 두 번은 순서대로 보임 (CoWW)
+        let states = allowed_states(
+            "RISCV CoWW\n{ 0:x5=1; 0:x6=2; 0:x7=x; }\nP0 ;\nsw x5,0(x7) ;\nsw x6,0(x7) ;\n\
+             exists (x=1)\n",
+        );
+        assert_eq!(states, ["x=2;"]);
+        // 두 스레드의 amoadd는 서로를 건너뛰지 않음
+        let states = allowed_states(
+            "RISCV AMO\n{ 0:x5=1; 0:x6=x; 1:x5=1; 1:x6=x; }\nP0 | P1 ;\n\
+             amoadd.w x7,x5,(x6) | amoadd.w x7,x5,(x6) ;\nexists (x=1)\n",
+        );
+        assert_eq!(states, ["x=2;"]);
+        // LR/SC 증가는 SC가 실패할 수 있지만 값을 잃지는 않음
+        let states = allowed_states(
+            "RISCV LRSC\n{ 0:x6=x; 1:x6=x; }\nP0 | P1 ;\n\
+             lr.w x5,(x6) | lr.w x5,(x6) ;\naddi x5,x5,1 | addi x5,x5,1 ;\n\
+             sc.w x7,x5,(x6) | sc.w x7,x5,(x6) ;\nexists (x=1 /\\ 0:x7=0 /\\ 1:x7=0)\n",
+        );
+        assert!(
+            !states
+                .iter()
+                .any(|state| state.starts_with("x=1; 0:x7=0; 1:x7=0;"))
+        );
+        assert!(states.iter().any(|state| state.starts_with("x=2;")));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let litmus = Litmus::parse(
+            "RISCV T\n{ uint64_t x; 0:x6=x; }\nP0 ;\nlw x5,0(x6) ;\nexists (0:x5=0)\n",
+        )
+        .unwrap();
+        let programs = litmus.assemble().unwrap();
+        assert_eq!(
+            allowed(&litmus, &programs).unwrap_err(),
+            "P0: mixed-size access to x"
+        );
+    }
+}
@@ -0,0 +1,489 @@
+//! herd litmus 파일 파서
+//! 머리줄(RISCV 이름), 초기 상태 {...}, 스레드 열(P0 | P1 ...), locations 절, 조건 순서
+
+use super::{Condition, Litmus, LitmusError, Location, Prop, Quantifier, Target};
+use crate::asm;
+
+// 선언 없는 위치는 herd 기본 타입(int)처럼 4바이트
+const DEFAULT_WIDTH: u8 = 4;
+
+pub(super) fn parse(text: &str) -> Result<Litmus, LitmusError> {
+    let text = strip_comments(text);
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let (line_no, header) = lines
+        .next()
+        .ok_or_else(|| LitmusError::new("empty litmus test"))?;
+    let mut words = header.split_whitespace();
+    if !words
+        .next()
+        .is_some_and(|arch| arch.eq_ignore_ascii_case("RISCV"))
+    {
+        return Err(LitmusError::at(line_no, "expected 'RISCV <name>'"));
+    }
+    let name = words.next().unwrap_or_default().to_string();
+
+    let mut litmus = Litmus {
+        name,
+        locations: Vec::new(),
+        registers: Vec::new(),
+        threads: Vec::new(),
+        targets: Vec::new(),
+        condition: Condition {
+            quantifier: Quantifier::Exists,
+            prop: Prop::True,
+            text: String::new(),
+        },
+    };
+
+    // 설명 문자열과 key=value 줄은 건너뛰고 { 부터 } 까지 초기 상태
+    let mut init = String::new();
+    let mut init_line = 0;
+    let mut in_init = false;
+    for (line_no, line) in lines.by_ref() {
+        let line = match line.find('{') {
+            Some(start) if !in_init => {
+                in_init = true;
+                init_line = line_no;
+                &line[start + 1..]
+            }
+            _ if !in_init => continue,
+            _ => line,
+        };
+        match line.find('}') {
+            Some(end) => {
+                init += &line[..end];
+                break;
+            }
+            None => {
+                init += line;
+                init += "\n";
+            }
+        }
+    }
+    if !in_init {
+        return Err(LitmusError::new("missing initial state { ... }"));
+    }
+    for entry in init.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        parse_init(&mut litmus, entry).map_err(|message| LitmusError::at(init_line, message))?;
+    }
+
+    // 스레드 열: 첫 줄은 P0 | P1 ..., 이후 줄마다 칸 하나가 스레드 하나의 명령어
+    let mut rest = Vec::new();
+    for (line_no, line) in lines.by_ref() {
+        if is_condition_start(line) {
+            rest.push((line_no, line));
+            break;
+        }
+        let cells: Vec<&str> = line
+            .trim_end_matches(';')
+            .split('|')
+            .map(str::trim)
+            .collect();
+        if litmus.threads.is_empty() {
+            for (thread, cell) in cells.iter().enumerate() {
+                if *cell != format!("P{}", thread) {
+                    return Err(LitmusError::at(line_no, format!("expected P{}", thread)));
+                }
+            }
+            litmus.threads = vec![String::new(); cells.len()];
+            continue;
+        }
+        if cells.len() > litmus.threads.len() {
+            return Err(LitmusError::at(line_no, "more columns than threads"));
+        }
+        for (source, cell) in litmus.threads.iter_mut().zip(cells) {
+            *source += cell;
+            *source += "\n";
+        }
+    }
+    if litmus.threads.is_empty() {
+        return Err(LitmusError::new("missing thread header P0 | P1 ..."));
+    }
+    litmus
+        .registers
+        .resize(litmus.threads.len().max(litmus.registers.len()), Vec::new());
+    if litmus.registers.len() > litmus.threads.len() {
+        return Err(LitmusError::new("initial state names a missing thread"));
+    }
+
+    // locations [...] 와 조건은 여러 줄에 걸칠 수 있음
+    rest.extend(lines);
+    let condition_line = rest.first().map_or(0, |(line_no, _)| *line_no);
+    let mut tail: String = rest
+        .iter()
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(list) = tail.strip_prefix("locations") {
+        let list = list.trim_start();
+        let end = list
+            .find(']')
+            .filter(|_| list.starts_with('['))
+            .ok_or_else(|| LitmusError::at(condition_line, "expected locations [...]"))?;
+        for name in list[1..end]
+            .split(';')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+        {
+            let target = parse_target(&mut litmus, name)
+                .map_err(|message| LitmusError::at(condition_line, message))?;
+            target_index(&mut litmus, target);
+        }
+        tail = list[end + 1..].trim().to_string();
+    }
+    parse_condition(&mut litmus, &tail)
+        .map_err(|message| LitmusError::at(condition_line, message))?;
+    Ok(litmus)
+}
+
+// (* ... *) 주석 제거 (줄 수는 유지)
+fn strip_comments(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("(*") {
+        result += &rest[..start];
+        let end = rest[start..]
+            .find("*)")
+            .map_or(rest.len(), |end| start + end + 2);
+        result.extend(rest[start..end].chars().filter(|&c| c == '\n'));
+        rest = &rest[end..];
+    }
+    result + rest
+}
+
+fn is_condition_start(line: &str) -> bool {
+    ["exists", "~", "forall", "locations", "filter"]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+fn type_width(name: &str) -> Option<u8> {
+    match name {
+        "char" | "int8_t" | "uint8_t" => Some(1),
+        "short" | "int16_t" | "uint16_t" => Some(2),
+        "int" | "int32_t" | "uint32_t" => Some(4),
+        "long" | "int64_t" | "uint64_t" | "intptr_t" | "uintptr_t" => Some(8),
+        _ => None,
+    }
+}
+
+// "0:x5=1", "0:x6=x", "x=1", "uint64_t x=1", "int y"
+fn parse_init(litmus: &mut Litmus, entry: &str) -> Result<(), String> {
+    let (lhs, rhs) = match entry.split_once('=') {
+        Some((lhs, rhs)) => (lhs.trim(), Some(rhs.trim())),
+        None => (entry, None),
+    };
+    let mut words: Vec<&str> = lhs.split_whitespace().collect();
+    let name = words.pop().ok_or("empty initial state entry")?;
+    let width = match words.as_slice() {
+        [] => None,
+        [ty] => Some(type_width(ty).ok_or(format!("unknown type '{}'", ty))?),
+        _ => return Err(format!("invalid initial state entry '{}'", entry)),
+    };
+    if let Some((thread, reg)) = name.split_once(':') {
+        let thread: usize = thread
+            .parse()
+            .map_err(|_| format!("invalid thread in '{}'", name))?;
+        let reg = asm::parse_reg(reg)?;
+        let value = match rhs {
+            Some(rhs) => parse_value(litmus, rhs)?,
+            None => 0,
+        };
+        if litmus.registers.len() <= thread {
+            litmus.registers.resize(thread + 1, Vec::new());
+        }
+        litmus.registers[thread].push((reg, value));
+        return Ok(());
+    }
+    let index = location(litmus, name.trim_start_matches('[').trim_end_matches(']'))?;
+    if let Some(width) = width {
+        litmus.locations[index].width = width;
+    }
+    if let Some(rhs) = rhs {
+        litmus.locations[index].init = parse_value(litmus, rhs)?;
+    }
+    Ok(())
+}
+
+// 정수 또는 위치 이름 (위치의 주소)
+fn parse_value(litmus: &mut Litmus, text: &str) -> Result<u64, String> {
+    let text = text.trim().trim_start_matches('&');
+    if let Some(value) = parse_int(text) {
+        return Ok(value);
+    }
+    Ok(Litmus::location_address(location(litmus, text)?))
+}
+
+fn parse_int(text: &str) -> Option<u64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+// 위치 인덱스 (처음 보면 추가)
+fn location(litmus: &mut Litmus, name: &str) -> Result<usize, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("invalid location '{}'", name));
+    }
+    if let Some(index) = litmus.locations.iter().position(|l| l.name == name) {
+        return Ok(index);
+    }
+    litmus.locations.push(Location {
+        name: name.to_string(),
+        init: 0,
+        width: DEFAULT_WIDTH,
+    });
+    Ok(litmus.locations.len() - 1)
+}
+
+fn parse_target(litmus: &mut Litmus, text: &str) -> Result<Target, String> {
+    let text = text.trim();
+    match text.split_once(':') {
+        Some((thread, reg)) => {
+            let thread: usize = thread
+                .parse()
+                .map_err(|_| format!("invalid thread in '{}'", text))?;
+            if thread >= litmus.threads.len() {
+                return Err(format!("no thread P{}", thread));
+            }
+            Ok(Target::Register {
+                thread,
+                reg: asm::parse_reg(reg)?,
+            })
+        }
+        None => {
+            let name = text.trim_start_matches('[').trim_end_matches(']');
+            Ok(Target::Memory(location(litmus, name)?))
+        }
+    }
+}
+
+fn target_index(litmus: &mut Litmus, target: Target) -> usize {
+    match litmus.targets.iter().position(|&t| t == target) {
+        Some(index) => index,
+        None => {
+            litmus.targets.push(target);
+            litmus.targets.len() - 1
+        }
+    }
+}
+
+fn parse_condition(litmus: &mut Litmus, text: &str) -> Result<(), String> {
+    let text = text.trim();
+    let (quantifier, body) = if let Some(body) = text.strip_prefix("exists") {
+        (Quantifier::Exists, body)
+    } else if let Some(body) = text.strip_prefix('~') {
+        let body = body
+            .trim_start()
+            .strip_prefix("exists")
+            .ok_or("expected ~exists")?;
+        (Quantifier::NotExists, body)
+    } else if let Some(body) = text.strip_prefix("forall") {
+        (Quantifier::Forall, body)
+    } else if text.starts_with("filter") {
+        return Err("filter clauses are not supported".to_string());
+    } else {
+        return Err("expected exists, ~exists or forall".to_string());
+    };
+    let tokens = tokenize(body)?;
+    let mut parser = PropParser {
+        litmus,
+        tokens: &tokens,
+        pos: 0,
+    };
+    let prop = parser.or()?;
+    if parser.pos != tokens.len() {
+        return Err(format!("unexpected '{}' in condition", tokens[parser.pos]));
+    }
+    litmus.condition = Condition {
+        quantifier,
+        prop,
+        text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+    };
+    Ok(())
+}
+
+// 조건 토큰: ( ) ~ /\ \/ = 와 나머지 단어
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '~' | '=' => tokens.push(c.to_string()),
+            '/' | '\\' => {
+                let next = chars.next();
+                match (c, next) {
+                    ('/', Some('\\')) => tokens.push("/\\".to_string()),
+                    ('\\', Some('/')) => tokens.push("\\/".to_string()),
+                    _ => return Err(format!("unexpected '{}' in condition", c)),
+                }
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "()~=/\\".contains(next) {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct PropParser<'a> {
+    litmus: &'a mut Litmus,
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl PropParser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<&str, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or("unexpected end of condition")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<Prop, String> {
+        let mut prop = self.and()?;
+        while self.peek() == Some("\\/") {
+            self.pos += 1;
+            prop = Prop::Or(Box::new(prop), Box::new(self.and()?));
+        }
+        Ok(prop)
+    }
+
+    fn and(&mut self) -> Result<Prop, String> {
+        let mut prop = self.unary()?;
+        while self.peek() == Some("/\\") {
+            self.pos += 1;
+            prop = Prop::And(Box::new(prop), Box::new(self.unary()?));
+        }
+        Ok(prop)
+    }
+
+    fn unary(&mut self) -> Result<Prop, String> {
+        match self.next()? {
+            "~" => Ok(Prop::Not(Box::new(self.unary()?))),
+            "(" => {
+                let prop = self.or()?;
+                match self.next()? {
+                    ")" => Ok(prop),
+                    token => Err(format!("expected ')' but found '{}'", token)),
+                }
+            }
+            "true" => Ok(Prop::True),
+            "false" => Ok(Prop::False),
+            target => {
+                let target = target.to_string();
+                if self.next()? != "=" {
+                    return Err(format!("expected '=' after '{}'", target));
+                }
+                let value = self.next()?.to_string();
+                let target = parse_target(self.litmus, &target)?;
+                let value = parse_value(self.litmus, &value)?;
+                let value = self.litmus.normalize(target, value);
+                Ok(Prop::Eq(target_index(self.litmus, target), value))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SB: &str = r#"RISCV SB
+"Fre PodWR Fre PodWR"
+Cycle=Fre PodWR Fre PodWR
+{
+0:x5=1; 0:x6=x; 0:x8=y;
+1:x5=1; 1:x6=y; 1:x8=x;
+}
+ P0          | P1          ;
+ sw x5,0(x6) | sw x5,0(x6) ;
+ lw x7,0(x8) | lw x7,0(x8) ;
+exists
+(0:x7=0 /\ 1:x7=0)
+"#;
+
+    #[test]
+    fn test_parse_store_buffering() {
+        let litmus = Litmus::parse(SB).unwrap();
+        assert_eq!(litmus.name, "SB");
+        assert_eq!(litmus.threads.len(), 2);
+        assert_eq!(litmus.threads[1], "sw x5,0(x6)\nlw x7,0(x8)\n");
+        let names: Vec<&str> = litmus.locations.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["x", "y"]);
+        assert_eq!(
+            litmus.registers[0],
+            [
+                (5, 1),
+                (6, Litmus::location_address(0)),
+                (8, Litmus::location_address(1))
+            ]
+        );
+        assert_eq!(litmus.condition.quantifier, Quantifier::Exists);
+        assert_eq!(litmus.condition.text, "exists (0:x7=0 /\\ 1:x7=0)");
+        assert_eq!(
+            litmus.targets,
+            [
+                Target::Register { thread: 0, reg: 7 },
+                Target::Register { thread: 1, reg: 7 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_condition_forms() {
+        let litmus = Litmus::parse(
+            "RISCV T\n{ int64_t x=-1; 0:x6=x; }\nP0 ;\nlw x5,0(x6) ;\n\
+             locations [x;]\n~exists (0:x5=1 \\/ ~(x=-1) /\\ true) (* note *)\n",
+        )
+        .unwrap();
+        assert_eq!(litmus.locations[0].width, 8);
+        assert_eq!(litmus.locations[0].init, u64::MAX);
+        assert_eq!(litmus.condition.quantifier, Quantifier::NotExists);
+        assert_eq!(litmus.targets[0], Target::Memory(0));
+        let prop = &litmus.condition.prop;
+        assert!(prop.eval(&crate::litmus::State(vec![1, 0])));
+        assert!(prop.eval(&crate::litmus::State(vec![0, 0])));
+        assert!(!prop.eval(&crate::litmus::State(vec![u64::MAX, 0])));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Litmus::parse("ARM SB\n{}\nP0;\nexists (true)").is_err());
+        assert!(Litmus::parse("RISCV SB\nP0;\nexists (true)").is_err());
+        let error = Litmus::parse("RISCV SB\n{}\nP0 | P2 ;\nexists (true)").unwrap_err();
+        assert_eq!(error.line, Some(3));
+        assert!(Litmus::parse("RISCV SB\n{}\nP0 ;\nnop ;\nexists (0:x5=)").is_err());
+        assert!(Litmus::parse("RISCV SB\n{}\nP0 ;\nnop ;\nexists (1:x5=0)").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! litmus 테스트를 Machine에서 여러 번 실행하고 최종 상태 분포를 모음
+//! 실행마다 write buffer seed를 새로 뽑고, 매 명령어마다 실행할 hart를 무작위로 고름
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::{CODE_STRIDE, Litmus, LitmusError, Quantifier, State, Target, model};
+use crate::asm;
+use crate::cpu::entropy::EntropySource;
+use crate::cpu::{Cpu, Xlen};
+use crate::csr;
+use crate::machine::Machine;
+use crate::write_buffer::MemoryModel;
+
+/// 실행 하나에서 모든 hart가 합쳐 실행하는 최대 명령어 수. 넘으면 끝나지 않은 실행으로 셈
+pub const MAX_RUN_STEPS: usize = 100_000;
+
+pub struct Report {
+    pub litmus: Litmus,
+    pub runs: usize,
+    /// 최종 상태별 관찰 횟수
+    pub histogram: BTreeMap<State, usize>,
+    /// MAX_RUN_STEPS 안에 모든 스레드가 끝나지 않은 실행 수
+    pub timeouts: usize,
+    /// RVWMO가 허용하는 최종 상태. 모델이 다룰 수 없는 테스트면 Err(이유)
+    pub allowed: Result<BTreeSet<State>, String>,
+}
+
+impl Report {
+    /// 관찰했지만 RVWMO가 금지하는 최종 상태
+    pub fn forbidden(&self) -> Vec<&State> {
+        match &self.allowed {
+            Ok(allowed) => self
+                .histogram
+                .keys()
+                .filter(|state| !allowed.contains(state))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// 조건식을 만족한 실행 수
+    pub fn positive(&self) -> usize {
+        self.histogram
+            .iter()
+            .filter(|(state, _)| self.litmus.condition.prop.eval(state))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// 끝난 실행 중 조건식을 만족하지 않은 실행 수
+    pub fn negative(&self) -> usize {
+        self.histogram.values().sum::<usize>() - self.positive()
+    }
+
+    /// 관찰 결과가 조건을 만족하는지 (herd의 Ok/No)
+    pub fn holds(&self) -> bool {
+        match self.litmus.condition.quantifier {
+            Quantifier::Exists => self.positive() > 0,
+            Quantifier::NotExists => self.positive() == 0,
+            Quantifier::Forall => self.negative() == 0,
+        }
+    }
+}
+
+/// herd 출력 형식에 모델 판정을 덧붙임. 금지된 상태는 줄 끝에 표시
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let litmus = &self.litmus;
+        let forbidden = self.forbidden();
+        writeln!(f, "Test {}", litmus.name)?;
+        writeln!(f, "States {}", self.histogram.len())?;
+        for (state, count) in &self.histogram {
+            write!(f, "{:>8}:> {}", count, litmus.format_state(state))?;
+            if forbidden.contains(&state) {
+                write!(f, " (forbidden by RVWMO)")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", if self.holds() { "Ok" } else { "No" })?;
+        writeln!(f, "Witnesses")?;
+        writeln!(
+            f,
+            "Positive: {} Negative: {}",
+            self.positive(),
+            self.negative()
+        )?;
+        if self.timeouts > 0 {
+            writeln!(f, "Timeouts: {}", self.timeouts)?;
+        }
+        writeln!(f, "Condition {}", litmus.condition.text)?;
+        let observation = match (self.positive(), self.negative()) {
+            (0, _) => "Never",
+            (_, 0) => "Always",
+            _ => "Sometimes",
+        };
+        writeln!(
+            f,
+            "Observation {} {} {} {}",
+            litmus.name,
+            observation,
+            self.positive(),
+            self.negative()
+        )?;
+        match &self.allowed {
+            Ok(allowed) => {
+                let unobserved = allowed
+                    .iter()
+                    .filter(|state| !self.histogram.contains_key(state))
+                    .count();
+                writeln!(
+                    f,
+                    "Model RVWMO allows {} states, {} unobserved, {} forbidden observed",
+                    allowed.len(),
+                    unobserved,
+                    forbidden.len()
+                )
+            }
+            Err(reason) => writeln!(f, "Model RVWMO unavailable: {}", reason),
+        }
+    }
+}
+
+/// litmus 테스트를 runs번 실행. 같은 seed면 같은 결과
+pub fn run(litmus: &Litmus, runs: usize, seed: u64) -> Result<Report, LitmusError> {
+    let mut programs = litmus.assemble()?;
+    for (thread, program) in programs.iter().enumerate() {
+        if 4 * (program.len() as u64 + 1) > CODE_STRIDE {
+            return Err(LitmusError::new(format!("P{} is too long", thread)));
+        }
+    }
+    let allowed = model::allowed(litmus, &programs);
+    // 마지막 ebreak에서 debug mode로 들어가 멈춤
+    for program in programs.iter_mut() {
+        program.push(asm::ebreak());
+    }
+    let mut rng = EntropySource::new(seed);
+    let mut histogram = BTreeMap::new();
+    let mut timeouts = 0;
+    for _ in 0..runs {
+        match run_once(litmus, &programs, &mut rng) {
+            Some(state) => *histogram.entry(state).or_insert(0) += 1,
+            None => timeouts += 1,
+        }
+    }
+    Ok(Report {
+        litmus: litmus.clone(),
+        runs,
+        histogram,
+        timeouts,
+        allowed,
+    })
+}
+
+// 한 번 실행한 최종 상태. 명령어 한도 안에 끝나지 않으면 None
+fn run_once(litmus: &Litmus, programs: &[Vec<u32>], rng: &mut EntropySource) -> Option<State> {
+    let mut machine = Machine::new(programs.len(), Xlen::Rv64);
+    machine.bus.set_memory_model(MemoryModel::Relaxed {
+        seed: rng.next_u64(),
+    });
+    for (index, location) in litmus.locations.iter().enumerate() {
+        let addr = Litmus::location_address(index);
+        machine.bus.write_sized(addr, location.width, location.init);
+    }
+    for (thread, program) in programs.iter().enumerate() {
+        let base = Litmus::code_address(thread);
+        for (index, &inst) in program.iter().enumerate() {
+            machine.bus.write32(base + 4 * index as u64, inst);
+        }
+        let hart = &mut machine.harts[thread];
+        hart.pc = base;
+        for &(reg, value) in &litmus.registers[thread] {
+            hart.write_reg(reg, value);
+        }
+        let dcsr = hart.csr.read(csr::DCSR);
+        hart.csr.write(csr::DCSR, dcsr | csr::DCSR_EBREAKM);
+    }
+
+    let running = |machine: &Machine| -> Vec<usize> {
+        (0..machine.harts.len())
+            .filter(|&index| !machine.harts[index].debug_mode && !machine.harts[index].halted)
+            .collect()
+    };
+    for _ in 0..MAX_RUN_STEPS {
+        let running = running(&machine);
+        if running.is_empty() {
+            break;
+        }
+        let index = running[rng.next_u64() as usize % running.len()];
+        machine.with_hart(index, Cpu::step);
+    }
+    if !running(&machine).is_empty() {
+        return None;
+    }
+    machine.bus.flush_write_buffers();
+
+    let state = litmus
+        .targets
+        .iter()
+        .map(|&target| {
+            let value = match target {
+                Target::Register { thread, reg } => machine.harts[thread].read_reg(reg),
+                Target::Memory(index) => machine.bus.read_sized(
+                    Litmus::location_address(index),
+                    litmus.locations[index].width,
+                ),
+            };
+            litmus.normalize(target, value)
+        })
+        .collect();
+    Some(State(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNS: usize = 300;
+
+    fn run_test(source: &str) -> Report {
+        let report = run(&Litmus::parse(source).unwrap(), RUNS, 1).unwrap();
+        assert!(report.allowed.is_ok(), "{}", report);
+        assert_eq!(report.timeouts, 0);
+        assert!(report.forbidden().is_empty(), "{}", report);
+        report
+    }
+
+    #[test]
+    fn test_store_buffering_observed() {
+        let report = run_test(
+            "RISCV SB\n{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x5=1; 1:x6=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | sw x5,0(x6) ;\n\
+             lw x7,0(x8) | lw x7,0(x8) ;\n\
+             exists (0:x7=0 /\\ 1:x7=0)\n",
+        );
+        assert!(report.holds(), "{}", report);
+        assert_eq!(report.histogram.values().sum::<usize>(), RUNS);
+        assert!(report.to_string().contains("Observation SB Sometimes"));
+    }
+
+    #[test]
+    fn test_fenced_tests_stay_within_rvwmo() {
+        // fence로 정렬한 MP와 SB, aq/rl AMO로 정렬한 MP는 금지된 상태가 나오지 않아야 함
+        for source in [
+            "RISCV MP+fences\n{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x6=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | lw x5,0(x6) ;\n\
+             fence w,w | fence r,r ;\n\
+             sw x5,0(x8) | lw x7,0(x8) ;\n\
+             ~exists (1:x5=1 /\\ 1:x7=0)\n",
+            "RISCV SB+fences\n{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x5=1; 1:x6=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | sw x5,0(x6) ;\n\
+             fence rw,rw | fence rw,rw ;\n\
+             lw x7,0(x8) | lw x7,0(x8) ;\n\
+             ~exists (0:x7=0 /\\ 1:x7=0)\n",
+            "RISCV MP+rl+aq\n{ 0:x5=1; 0:x6=x; 0:x8=y; 1:x6=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x5,0(x6) | amoor.w.aq x5,x0,(x6) ;\n\
+             amoswap.w.rl x0,x5,(x8) | lw x7,0(x8) ;\n\
+             ~exists (1:x5=1 /\\ 1:x7=0)\n",
+        ] {
+            assert!(run_test(source).holds());
+        }
+    }
+
+    #[test]
+    fn test_lr_sc_increments_are_atomic() {
+        // 두 SC가 모두 성공하면 둘 중 하나는 다른 쪽의 값을 읽었어야 함
+        let report = run_test(
+            "RISCV LRSC\n{ 0:x6=x; 1:x6=x; }\n\
+             P0 | P1 ;\n\
+             lr.w x5,(x6) | lr.w x5,(x6) ;\n\
+             addi x5,x5,1 | addi x5,x5,1 ;\n\
+             sc.w x7,x5,(x6) | sc.w x7,x5,(x6) ;\n\
+             ~exists (x=1 /\\ 0:x7=0 /\\ 1:x7=0)\n",
+        );
+        assert!(report.holds(), "{}", report);
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        let litmus = Litmus::parse(
+            "RISCV 2+2W\n{ 0:x5=1; 0:x6=2; 0:x7=x; 0:x8=y; 1:x5=1; 1:x6=2; 1:x7=y; 1:x8=x; }\n\
+             P0 | P1 ;\n\
+             sw x6,0(x7) | sw x6,0(x7) ;\n\
+             sw x5,0(x8) | sw x5,0(x8) ;\n\
+             exists (x=2 /\\ y=2)\n",
+        )
+        .unwrap();
+        let first = run(&litmus, 100, 7).unwrap();
+        let second = run(&litmus, 100, 7).unwrap();
+        assert_eq!(first.histogram, second.histogram);
+        assert!(first.forbidden().is_empty());
+    }
+}
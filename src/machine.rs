@@ -0,0 +1,816 @@
+//! 여러 hart가 버스 하나를 공유하는 SMP 머신
+//! Cpu는 자기 Bus를 소유하므로, 실행할 차례인 hart에 공유 버스를 옮겨 주고 quantum이 끝나면 돌려받음
+//! run()은 한 스레드에서 정해진 순서로 실행하므로 결과가 결정적 (디버깅용)
+//! run_parallel()은 hart마다 호스트 스레드에서 share()로 만든 Bus로 실행
+//! - 메모리는 atomic 저장소이고 DRAM 쓰기는 16바이트 그래뉼 잠금을 거치므로,
+//!   AMO, LR/SC, AMOCAS.Q가 실제 동시 실행에서도 같은 위치의 다른 쓰기와 원자적
+//! - FENCE와 AMO의 aq/rl은 호스트 메모리 펜스로 실행 (W→R 순서는 SeqCst)
+//! - 인터럽트 선은 atomic, CLINT/UART는 잠금 뒤에 있어 다른 hart의 IPI가 안전하게 전달됨
+//! - MemoryModel::Relaxed면 Bus마다 따로 write buffer를 가지고, 스레드가 끝날 때 반영
+//! - 코드 쓰기는 hart별 무효화 큐로 각 hart의 디코딩/블록/JIT 캐시에 전달됨
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::bus::Bus;
+use crate::cpu::extensions::{ExtensionError, Extensions};
+use crate::cpu::{Cpu, Xlen};
+use crate::devices::timebase::TimeSource;
+use crate::elf;
+
+// 패닉한 hart 번호와 패닉 값
+type HartPanic = (usize, Box<dyn Any + Send>);
+
+/// hart 하나가 차례마다 실행하는 기본 명령어 수
+pub const DEFAULT_QUANTUM: u64 = 1000;
+
+pub struct Machine {
+    /// 모든 hart가 공유하는 버스 (메모리, CLINT, UART)
+    pub bus: Bus,
+    /// 실행 중이 아닌 hart의 bus 필드는 Bus::detached()
+    pub harts: Vec<Cpu>,
+    /// hart 차례마다 실행할 명령어 수. 1이면 명령어 단위 round-robin
+    pub quantum: u64,
+    // 마지막으로 실행한 (또는 실행 중인) hart
+    current: usize,
+    // 다음 차례를 찾기 시작할 hart
+    next: usize,
+    // 이번 라운드에서 가장 오래 실행한 hart의 tick 수
+    round_ticks: u64,
+}
+
+impl Machine {
+    pub fn new(harts: usize, xlen: Xlen) -> Self {
+        assert!(harts > 0, "machine needs at least one hart");
+        Machine {
+            bus: Bus::with_harts(harts),
+            harts: (0..harts)
+                .map(|hart_id| Cpu::with_bus(hart_id as u64, xlen, Bus::detached()))
+                .collect(),
+            quantum: DEFAULT_QUANTUM,
+            current: 0,
+            next: 0,
+            round_ticks: 0,
+        }
+    }
+
+    /// 마지막으로 실행한 hart 번호
+    /// 실행 중 패닉이 나면 공유 버스는 이 hart에 남아 있음
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// 공유 버스를 hart에 연결한 상태로 f 실행
+    pub fn with_hart<R>(&mut self, index: usize, f: impl FnOnce(&mut Cpu) -> R) -> R {
+        self.current = index;
+        let hart = &mut self.harts[index];
+        std::mem::swap(&mut self.bus, &mut hart.bus);
+        let result = f(hart);
+        std::mem::swap(&mut self.bus, &mut hart.bus);
+        result
+    }
+
+    /// 세그먼트를 메모리에 올리고 모든 hart가 entry에서 시작
+    pub fn load_segments(&mut self, segments: &[elf::Segment], entry: u64) {
+        self.with_hart(0, |hart| hart.load_segments(segments, entry));
+        for hart in self.harts.iter_mut() {
+            hart.pc = entry;
+        }
+    }
+
+    /// ELF를 올리고 모든 hart에 확장과 심볼을 설정 (main이 쓰는 경로)
+    pub fn load_elf(
+        &mut self,
+        elf_file: &elf::ElfFile,
+        extensions: Extensions,
+    ) -> Result<(), ExtensionError> {
+        for hart in self.harts.iter_mut() {
+            hart.set_extensions(extensions)?;
+            hart.symbols = elf_file.symbols.clone();
+        }
+        self.load_segments(&elf_file.segments, elf_file.entry);
+        Ok(())
+    }
+
+    /// DTB를 올리고 hart마다 a0 = hartid, a1 = DTB 주소 설정
+    pub fn load_device_tree(&mut self, dtb: &[u8]) -> u64 {
+        let mut addr = 0;
+        for index in 0..self.harts.len() {
+            addr = self.with_hart(index, |hart| hart.load_device_tree(dtb));
+        }
+        addr
+    }
+
+    fn runnable(hart: &Cpu) -> bool {
+        !hart.halted && !hart.debug_mode
+    }
+
+    // 실행 가능하고 WFI로 자고 있지 않은 hart
+    fn schedulable(&self, index: usize) -> bool {
+        let hart = &self.harts[index];
+        Self::runnable(hart) && !hart.wfi_waiting(self.bus.irq_lines(index as u64))
+    }
+
+    /// 다음 차례의 hart를 quantum만큼 실행. 실행할 hart가 없으면 false
+    /// 라운드(깨어 있는 hart가 한 차례씩 실행) 동안 공유 시계는 멈춰 있고,
+    /// 라운드가 끝나면 가장 오래 실행한 hart의 tick 수만큼 한 번 진행
+    /// 모든 hart가 WFI로 자고 있으면 다음 장치 이벤트까지 시계를 건너뜀
+    pub fn step(&mut self) -> bool {
+        if !self.harts.iter().any(Self::runnable) {
+            return false;
+        }
+        let count = self.harts.len();
+        let index = match (self.next..count).find(|&index| self.schedulable(index)) {
+            Some(index) => index,
+            None => {
+                self.end_round();
+                match (0..count).find(|&index| self.schedulable(index)) {
+                    Some(index) => index,
+                    None => return self.skip_idle(),
+                }
+            }
+        };
+        let quantum = self.quantum;
+        let ticks = self.with_hart(index, |hart| {
+            hart.bus.hold_clock();
+            if quantum == 1 {
+                hart.step();
+            } else {
+                // 블록 단위로 실행하므로 quantum을 블록 하나 길이만큼 넘길 수 있음
+                // 차례 중에는 시계가 멈춰 있으므로 WFI로 잠들면 바로 차례를 넘김
+                while hart.bus.held_ticks() < quantum
+                    && Self::runnable(hart)
+                    && !hart.wfi_waiting(hart.bus.irq_lines(hart.hart_id))
+                {
+                    hart.step_block();
+                }
+            }
+            hart.bus.release_clock()
+        });
+        self.round_ticks = self.round_ticks.max(ticks);
+        self.next = index + 1;
+        if !(self.next..count).any(|index| self.schedulable(index)) {
+            self.end_round();
+        }
+        true
+    }
+
+    // 라운드 동안 멈춰 있던 시계를 진행하고 다음 라운드를 hart 0부터 시작
+    fn end_round(&mut self) {
+        self.bus.advance(self.round_ticks);
+        self.round_ticks = 0;
+        self.next = 0;
+    }
+
+    // 깨어 있는 hart가 없으면 인터럽트를 일으킬 수 있는 다음 장치 이벤트로 건너뜀
+    fn skip_idle(&mut self) -> bool {
+        let Some(ticks) = self.bus.ticks_until_next_event() else {
+            return false;
+        };
+        self.bus.advance(ticks.max(1));
+        true
+    }
+
+    /// 모든 hart가 멈출 때까지 실행. 끝나면 write buffer에 남은 store를 반영
+    pub fn run(&mut self) {
+        self.end_round();
+        if self.harts.len() == 1 {
+            self.with_hart(0, Cpu::run);
+        } else {
+            while self.step() {}
+        }
+        self.bus.flush_write_buffers();
+    }
+
+    /// hart마다 호스트 스레드 하나로 모든 hart가 멈출 때까지 실행
+    /// 실행 순서가 호스트 스케줄링에 달려 있으므로 결과는 실행마다 다를 수 있음
+    /// 명령어 수 기반 mtime은 hart마다 어긋나므로 timebase가 호스트 시계여야 함
+    /// hart 하나가 패닉하면 나머지를 멈추고 그 패닉을 다시 일으킴 (current()가 그 hart)
+    pub fn run_parallel(&mut self) {
+        assert!(
+            matches!(self.bus.timebase().source, TimeSource::Host { .. }),
+            "parallel execution needs a host-clock timebase"
+        );
+        self.end_round();
+        for hart in self.harts.iter_mut() {
+            hart.bus = self.bus.share();
+        }
+        let stop = AtomicBool::new(false);
+        let failure: Mutex<Option<HartPanic>> = Mutex::new(None);
+        thread::scope(|scope| {
+            for (index, hart) in self.harts.iter_mut().enumerate() {
+                let (stop, failure) = (&stop, &failure);
+                scope.spawn(move || {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        while Self::runnable(hart) && !stop.load(Ordering::Relaxed) {
+                            hart.step_block();
+                        }
+                        hart.bus.flush_write_buffers();
+                    }));
+                    if let Err(payload) = result {
+                        stop.store(true, Ordering::Relaxed);
+                        failure
+                            .lock()
+                            .unwrap_or_else(|error| error.into_inner())
+                            .get_or_insert((index, payload));
+                    }
+                });
+            }
+        });
+        if let Some((index, payload)) = failure.into_inner().unwrap_or_else(|e| e.into_inner()) {
+            // 패닉한 hart는 자기 Bus를 그대로 가지고 있어 위치를 보고할 수 있음
+            self.current = index;
+            panic::resume_unwind(payload);
+        }
+        for hart in self.harts.iter_mut() {
+            hart.bus = Bus::detached();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr;
+    use crate::devices;
+
+    fn load(machine: &mut Machine, source: &str) {
+        let program = crate::asm::assemble(source).unwrap();
+        machine.with_hart(0, |hart| hart.load_program(&program));
+    }
+
+    fn run_for(machine: &mut Machine, turns: usize) {
+        for _ in 0..turns {
+            machine.step();
+        }
+    }
+
+    #[test]
+    fn test_harts_share_memory() {
+        let mut machine = Machine::new(3, Xlen::Rv64);
+        // 0x80001000 + 8 * hartid 에 hartid + 1 저장
+        load(
+            &mut machine,
+            "
+                auipc t0, 1
+                csrr a0, mhartid
+                slli t1, a0, 3
+                add t0, t0, t1
+                addi a1, a0, 1
+                sd a1, 0(t0)
+            done:
+                j done
+            ",
+        );
+        run_for(&mut machine, 3);
+        for hart in 0..3 {
+            assert_eq!(machine.bus.read64(0x80001000 + 8 * hart), hart + 1);
+            assert_eq!(machine.harts[hart as usize].read_reg(10), hart);
+        }
+    }
+
+    #[test]
+    fn test_atomic_counter_across_harts() {
+        let mut machine = Machine::new(3, Xlen::Rv64);
+        machine.quantum = 1;
+        // 명령어 단위로 번갈아 실행해도 amoadd와 lr/sc 카운터는 정확
+        load(
+            &mut machine,
+            "
+                auipc t0, 1
+                li t1, 50
+            amo_loop:
+                li t2, 1
+                amoadd.d zero, t2, (t0)
+                addi t1, t1, -1
+                bnez t1, amo_loop
+                li t1, 50
+            lrsc_loop:
+                lr.d t2, (t0)
+                addi t2, t2, 1
+                sc.d t3, t2, (t0)
+                bnez t3, lrsc_loop
+                addi t1, t1, -1
+                bnez t1, lrsc_loop
+            done:
+                j done
+            ",
+        );
+        run_for(&mut machine, 3 * 2000);
+        assert_eq!(machine.bus.read64(0x80001000), 3 * 100);
+    }
+
+    #[test]
+    fn test_software_interrupt_between_harts() {
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.quantum = 1;
+        // hart 0이 hart 1의 msip를 써서 IPI를 보내고, hart 1은 핸들러에서 msip를 지움
+        load(
+            &mut machine,
+            "
+                csrr a0, mhartid
+                bnez a0, secondary
+                li t0, 0x2000004
+                li t1, 1
+                sw t1, 0(t0)
+            primary_done:
+                j primary_done
+            secondary:
+                auipc t0, 0
+                addi t0, t0, 24
+                csrw mtvec, t0
+                csrsi mie, 8
+                csrsi mstatus, 8
+            wait:
+                j wait
+            handler:
+                li t0, 0x2000004
+                sw zero, 0(t0)
+                li a1, 1
+            handled:
+                j handled
+            ",
+        );
+        run_for(&mut machine, 40);
+        let secondary = &machine.harts[1];
+        assert_eq!(secondary.read_reg(11), 1);
+        assert_eq!(
+            secondary.csr.read(csr::MCAUSE),
+            csr::INTERRUPT_BIT | csr::INTERRUPT_FROM_SOFTWARE
+        );
+        assert!(!machine.bus.check_software_interrupt(1));
+        // hart 0에는 인터럽트가 가지 않음
+        assert_eq!(machine.harts[0].csr.read(csr::MCAUSE), 0);
+    }
+
+    // ebreak로 debug mode에 들어가 멈추도록 설정
+    fn stop_on_ebreak(machine: &mut Machine) {
+        for hart in machine.harts.iter_mut() {
+            let dcsr = hart.csr.read(csr::DCSR);
+            hart.csr.write(csr::DCSR, dcsr | csr::DCSR_EBREAKM);
+        }
+    }
+
+    #[test]
+    fn test_parallel_atomic_counter() {
+        let mut machine = Machine::new(4, Xlen::Rv64);
+        machine.bus.set_timebase(devices::Timebase::host(1_000_000));
+        // 스레드가 실제로 동시에 실행해도 amoadd와 lr/sc 카운터는 정확
+        load(
+            &mut machine,
+            "
+                auipc t0, 1
+                li t1, 2000
+            amo_loop:
+                li t2, 1
+                amoadd.w zero, t2, (t0)
+                addi t1, t1, -1
+                bnez t1, amo_loop
+                li t1, 2000
+            lrsc_loop:
+                lr.w t2, (t0)
+                addi t2, t2, 1
+                sc.w t3, t2, (t0)
+                bnez t3, lrsc_loop
+                addi t1, t1, -1
+                bnez t1, lrsc_loop
+                ebreak
+            ",
+        );
+        stop_on_ebreak(&mut machine);
+        machine.run_parallel();
+        assert_eq!(machine.bus.read32(0x80001000), 4 * 4000);
+        assert!(machine.harts.iter().all(|hart| hart.debug_mode));
+        // 실행이 끝나면 hart는 다시 빈 버스를 가짐
+        assert!(machine.harts.iter().all(|hart| hart.bus.harts() == 0));
+    }
+
+    #[test]
+    fn test_parallel_fence_forbids_store_buffering() {
+        const ROUNDS: u64 = 300;
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.bus.set_timebase(devices::Timebase::host(1_000_000));
+        // 매 라운드 두 hart가 amoadd 카운터로 만난 뒤 새 변수 쌍으로 SB를 실행하고 읽은 값을 기록
+        // 호스트 스레드가 실제로 동시에 실행해도 fence rw,rw 사이의 (0, 0)은 나오면 안 됨
+        load(
+            &mut machine,
+            &format!(
+                "
+                    auipc t0, 1
+                    csrr a0, mhartid
+                    li s0, 0
+                    li s1, {ROUNDS}
+                    li t2, 1
+                    lui s2, 1
+                    add s2, s2, t0
+                    lui s3, 0x80
+                    add s3, s3, t0
+                round:
+                    amoadd.w zero, t2, (t0)
+                    addi t3, s0, 1
+                    slli t3, t3, 1
+                wait:
+                    lw t4, 0(t0)
+                    blt t4, t3, wait
+                    slli t5, s0, 7
+                    add t5, t5, s2
+                    bnez a0, second
+                    sw t2, 0(t5)
+                    fence rw, rw
+                    lw a1, 64(t5)
+                    j record
+                second:
+                    sw t2, 64(t5)
+                    fence rw, rw
+                    lw a1, 0(t5)
+                record:
+                    slli t6, s0, 4
+                    add t6, t6, s3
+                    slli a2, a0, 3
+                    add t6, t6, a2
+                    sd a1, 0(t6)
+                    addi s0, s0, 1
+                    blt s0, s1, round
+                    ebreak
+                "
+            ),
+        );
+        stop_on_ebreak(&mut machine);
+        machine.run_parallel();
+        assert!(machine.harts.iter().all(|hart| hart.debug_mode));
+        for round in 0..ROUNDS {
+            let results = 0x80081000 + 16 * round;
+            let observed = (machine.bus.read64(results), machine.bus.read64(results + 8));
+            assert_ne!(observed, (0, 0), "round {round}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_panic_reports_hart() {
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.bus.set_timebase(devices::Timebase::host(1_000_000));
+        // hart 1만 DRAM 밖으로 점프해서 패닉, hart 0은 멈출 때까지 대기
+        load(
+            &mut machine,
+            "
+                csrr a0, mhartid
+                bnez a0, secondary
+            spin:
+                j spin
+            secondary:
+                li t0, 0x1000
+                jr t0
+            ",
+        );
+        let result = panic::catch_unwind(AssertUnwindSafe(|| machine.run_parallel()));
+        assert!(result.is_err());
+        assert_eq!(machine.current(), 1);
+        assert_eq!(machine.harts[1].pc, 0x1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "host-clock timebase")]
+    fn test_parallel_needs_host_timebase() {
+        Machine::new(2, Xlen::Rv64).run_parallel();
+    }
+
+    // SB (store buffering): 각 hart가 자기 변수에 쓰고 상대 변수를 읽음
+    // 두 hart 모두 0을 읽는 결과는 fence 없이는 RVWMO에서 허용, fence rw,rw가 있으면 금지
+    fn store_buffering(seed: u64, fence: &str) -> (u64, u64) {
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.quantum = 1;
+        machine
+            .bus
+            .set_memory_model(crate::write_buffer::MemoryModel::Relaxed { seed });
+        load(
+            &mut machine,
+            &format!(
+                "
+                    auipc t0, 1
+                    addi t1, t0, 64
+                    csrr a0, mhartid
+                    li t2, 1
+                    bnez a0, second
+                    sw t2, 0(t0)
+                    {fence}
+                    lw a1, 0(t1)
+                    ebreak
+                second:
+                    sw t2, 0(t1)
+                    {fence}
+                    lw a1, 0(t0)
+                    ebreak
+                "
+            ),
+        );
+        stop_on_ebreak(&mut machine);
+        machine.run();
+        // 실행이 끝나면 밀린 store가 모두 반영됨
+        assert_eq!(machine.bus.read32(0x80001000), 1);
+        assert_eq!(machine.bus.read32(0x80001040), 1);
+        (machine.harts[0].read_reg(11), machine.harts[1].read_reg(11))
+    }
+
+    #[test]
+    fn test_relaxed_store_buffering_needs_fence() {
+        let relaxed = (0..32).filter(|&seed| store_buffering(seed, "nop") == (0, 0));
+        assert!(relaxed.count() > 0);
+        for seed in 0..32 {
+            assert_ne!(store_buffering(seed, "fence rw, rw"), (0, 0));
+            assert_ne!(store_buffering(seed, "fence w, r"), (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_relaxed_amo_aqrl_orders_stores() {
+        // aqrl AMO는 앞뒤 접근을 모두 정렬하므로 fence rw,rw와 같은 효과
+        for seed in 0..32 {
+            assert_ne!(
+                store_buffering(seed, "addi t3, t0, 128\n amoswap.w.aqrl zero, zero, (t3)"),
+                (0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_relaxed_atomic_counter() {
+        let mut machine = Machine::new(3, Xlen::Rv64);
+        machine.quantum = 1;
+        machine
+            .bus
+            .set_memory_model(crate::write_buffer::MemoryModel::Relaxed { seed: 5 });
+        // 같은 주소의 일반 store와 섞여도 amoadd와 lr/sc는 원자적
+        load(
+            &mut machine,
+            "
+                auipc t0, 1
+                csrr a0, mhartid
+                slli a0, a0, 3
+                add a0, a0, t0
+                li t1, 50
+            loop:
+                sd t1, 64(a0)
+                li t2, 1
+                amoadd.d zero, t2, (t0)
+            retry:
+                lr.d t2, (t0)
+                addi t2, t2, 1
+                sc.d t3, t2, (t0)
+                bnez t3, retry
+                addi t1, t1, -1
+                bnez t1, loop
+                ebreak
+            ",
+        );
+        stop_on_ebreak(&mut machine);
+        machine.run();
+        assert_eq!(machine.bus.read64(0x80001000), 3 * 100);
+        assert_eq!(machine.bus.read64(0x80001040), 1);
+    }
+
+    #[test]
+    fn test_idle_harts_hold_detached_bus() {
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.bus.write32(devices::DRAM_BASE, 0x0000006F); // j 0
+        run_for(&mut machine, 2);
+        assert_eq!(machine.current(), 1);
+        assert_eq!(machine.bus.harts(), 2);
+        assert_eq!(machine.harts[0].bus.harts(), 0);
+        assert_eq!(machine.harts[1].bus.harts(), 0);
+        // 공유 시계는 라운드마다 한 번 진행
+        assert_eq!(machine.bus.now(), DEFAULT_QUANTUM);
+    }
+
+    #[test]
+    fn test_clock_advances_once_per_round() {
+        let mut machine = Machine::new(3, Xlen::Rv64);
+        machine.quantum = 1;
+        machine.bus.write32(devices::DRAM_BASE, 0x0000006F); // j 0
+        run_for(&mut machine, 3 * 10);
+        // hart 수와 무관하게 mtime은 라운드당 1 tick
+        assert_eq!(machine.bus.now(), 10);
+        assert_eq!(machine.bus.mtime(), 10);
+        assert!(
+            machine
+                .harts
+                .iter()
+                .all(|hart| hart.csr.read(csr::MCYCLE) == 10)
+        );
+    }
+
+    #[test]
+    fn test_wfi_harts_sleep_until_ipi() {
+        let mut machine = Machine::new(3, Xlen::Rv64);
+        machine.quantum = 1;
+        // hart 1, 2는 WFI로 자고, hart 0이 잠시 뒤 두 hart에 IPI를 보냄
+        load(
+            &mut machine,
+            "
+                csrr a0, mhartid
+                bnez a0, secondary
+                li t1, 50
+            delay:
+                addi t1, t1, -1
+                bnez t1, delay
+                li t0, 0x2000004
+                li t1, 1
+                sw t1, 0(t0)
+                sw t1, 4(t0)
+            primary_done:
+                j primary_done
+            secondary:
+                csrsi mie, 8
+                wfi
+                li a1, 1
+            done:
+                j done
+            ",
+        );
+        run_for(&mut machine, 3 * 5);
+        assert!(machine.harts[1].wfi && machine.harts[2].wfi);
+        // 자는 hart는 차례를 받지 않으므로 라운드마다 hart 0만 실행
+        let now = machine.bus.now();
+        run_for(&mut machine, 10);
+        assert_eq!(machine.bus.now(), now + 10);
+        assert_eq!(machine.current(), 0);
+
+        run_for(&mut machine, 200);
+        for hart in 1..3 {
+            assert!(!machine.harts[hart].wfi);
+            assert_eq!(machine.harts[hart].read_reg(11), 1);
+            // mstatus.MIE=0이므로 트랩 없이 깨어남
+            assert_eq!(machine.harts[hart].csr.read(csr::MCAUSE), 0);
+        }
+    }
+
+    #[test]
+    fn test_idle_machine_skips_to_next_event() {
+        let mut machine = Machine::new(2, Xlen::Rv64);
+        machine.bus.write64(0x2004000, 5000); // hart 0 mtimecmp
+        machine.bus.write64(0x2004000 + 8, u64::MAX); // hart 1 mtimecmp
+        load(
+            &mut machine,
+            "
+                li t0, 0x80
+                csrw mie, t0
+                wfi
+                li a1, 1
+            done:
+                j done
+            ",
+        );
+        run_for(&mut machine, 2);
+        assert!(machine.harts.iter().all(|hart| hart.wfi));
+        // 모든 hart가 자면 명령어를 실행하지 않고 장치 이벤트 단위로 시계를 건너뜀
+        let mut steps = 0;
+        while machine.harts[0].read_reg(11) == 0 {
+            assert!(machine.step());
+            steps += 1;
+        }
+        assert!(steps < 10);
+        assert!(machine.bus.mtime() >= 5000);
+        assert!(machine.harts[1].wfi);
+    }
+
+    // UART 출력을 모으는 터미널
+    struct CaptureTerminal(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl devices::terminal::Terminal for CaptureTerminal {
+        fn write(&mut self, data: u8) {
+            self.0.lock().unwrap().push(data);
+        }
+
+        fn read(&mut self) -> Option<u8> {
+            None
+        }
+    }
+
+    // xv6 기본 설정(CPUS=3) 부팅 확인. 커널 빌드가 필요하므로 기본으로는 건너뜀
+    // XV6_KERNEL=xv6-riscv/kernel/kernel cargo test --release -- --ignored xv6
+    #[test]
+    #[ignore = "needs an xv6 kernel in XV6_KERNEL"]
+    fn test_xv6_boots_with_three_harts() {
+        const HARTS: usize = 3;
+        const MAX_TURNS: usize = 1_000_000;
+        let path = std::env::var("XV6_KERNEL").expect("XV6_KERNEL is not set");
+        let bytes = std::fs::read(path).unwrap();
+        let elf_file = elf::ElfFile::load(&bytes).unwrap();
+        let mut machine = Machine::new(HARTS, Xlen::Rv64);
+        let output = std::sync::Arc::new(Mutex::new(Vec::new()));
+        machine
+            .bus
+            .set_terminal(Box::new(CaptureTerminal(output.clone())));
+        machine
+            .load_elf(&elf_file, elf_file.extensions(None).unwrap())
+            .unwrap();
+        let frequency = machine.bus.timebase().frequency;
+        machine.load_device_tree(&crate::fdt::machine(Xlen::Rv64, HARTS, frequency));
+
+        let console = || String::from_utf8_lossy(&output.lock().unwrap()).into_owned();
+        for _ in 0..MAX_TURNS {
+            if console().contains("init: starting sh") || !machine.step() {
+                break;
+            }
+        }
+        let console = console();
+        for hart in 1..HARTS {
+            assert!(
+                console.contains(&format!("hart {hart} starting")),
+                "{console}"
+            );
+        }
+        assert!(console.contains("init: starting sh"), "{console}");
+    }
+
+    // PT_LOAD 하나와 .riscv.attributes(Tag_RISCV_arch)만 있는 RV32 ELF
+    fn rv32_elf_with_arch(code: &[u8], arch: &str) -> Vec<u8> {
+        let mut attributes = b"A\0\0\0\0riscv\0\x01\0\0\0\0\x05".to_vec();
+        attributes.extend_from_slice(arch.as_bytes());
+        attributes.push(0);
+        let len = attributes.len() as u32;
+        attributes[1..5].copy_from_slice(&(len - 1).to_le_bytes());
+        attributes[12..16].copy_from_slice(&(len - 11).to_le_bytes());
+
+        let mut elf = vec![0u8; 52];
+        elf[0..4].copy_from_slice(&elf::ELF_MAGIC);
+        elf[4] = elf::ELF_CLASS32;
+        elf[5] = elf::ELF_DATA2LSB;
+        elf[6] = 1;
+        elf[0x10] = 2; // EXEC
+        elf[0x12..0x14].copy_from_slice(&elf::EM_RISCV.to_le_bytes());
+        elf[0x18..0x1C].copy_from_slice(&0x80000000u32.to_le_bytes());
+        elf[0x1C..0x20].copy_from_slice(&52u32.to_le_bytes()); // e_phoff
+        elf[0x2A..0x2C].copy_from_slice(&32u16.to_le_bytes());
+        elf[0x2C..0x2E].copy_from_slice(&1u16.to_le_bytes());
+        let code_offset = 52 + 32;
+        let attributes_offset = code_offset + code.len();
+        let shoff = attributes_offset + attributes.len();
+        elf[0x20..0x24].copy_from_slice(&(shoff as u32).to_le_bytes());
+        elf[0x2E..0x30].copy_from_slice(&40u16.to_le_bytes());
+        elf[0x30..0x32].copy_from_slice(&2u16.to_le_bytes());
+
+        let mut ph = [0u8; 32];
+        ph[0x00..0x04].copy_from_slice(&elf::PT_LOAD.to_le_bytes());
+        ph[0x04..0x08].copy_from_slice(&(code_offset as u32).to_le_bytes());
+        ph[0x08..0x0C].copy_from_slice(&0x80000000u32.to_le_bytes());
+        ph[0x0C..0x10].copy_from_slice(&0x80000000u32.to_le_bytes());
+        ph[0x10..0x14].copy_from_slice(&(code.len() as u32).to_le_bytes());
+        ph[0x14..0x18].copy_from_slice(&(code.len() as u32).to_le_bytes());
+        elf.extend_from_slice(&ph);
+        elf.extend_from_slice(code);
+        elf.extend_from_slice(&attributes);
+
+        let mut sections = [[0u8; 40]; 2];
+        sections[1][0x04..0x08].copy_from_slice(&elf::SHT_RISCV_ATTRIBUTES.to_le_bytes());
+        sections[1][0x10..0x14].copy_from_slice(&(attributes_offset as u32).to_le_bytes());
+        sections[1][0x14..0x18].copy_from_slice(&(attributes.len() as u32).to_le_bytes());
+        for section in &sections {
+            elf.extend_from_slice(section);
+        }
+        elf
+    }
+
+    #[test]
+    fn test_zcmp_elf_uses_arch_attribute() {
+        // -march=rv32imac_zcb_zcmp로 빌드한 것처럼 cm.push/cm.popret을 쓰는 함수 호출
+        let mut code = Vec::new();
+        for inst in [
+            crate::asm::lui(2, 0x80002),  // sp = 0x80002000
+            crate::asm::addi(8, 0, 0x22), // s0 = 0x22
+            crate::asm::jal(1, 8),        // call func
+            crate::asm::jal(0, 0),        // done: j done
+        ] {
+            code.extend_from_slice(&inst.to_le_bytes());
+        }
+        for inst in [
+            0xB866u16, // func: cm.push {ra, s0-s1}, -32
+            0x4401,    // c.li s0, 0
+            0xBE66,    // cm.popret {ra, s0-s1}, 32
+        ] {
+            code.extend_from_slice(&inst.to_le_bytes());
+        }
+        let elf_file = elf::ElfFile::load(&rv32_elf_with_arch(
+            &code,
+            "rv32i2p1_m2p0_a2p1_c2p0_zcb1p0_zcmp1p0",
+        ))
+        .unwrap();
+        let extensions = elf_file.extensions(None).unwrap();
+        assert!(extensions.zcmp && !extensions.zcd);
+
+        let disassembler = crate::disasm::Disassembler::new(Xlen::Rv32).with_extensions(extensions);
+        let listing = disassembler.listing(&elf_file.segments[0].data, 0x80000000);
+        assert!(listing.contains("cm.push {ra, s0-s1},-32"));
+        assert!(listing.contains("cm.popret {ra, s0-s1},32"));
+
+        let mut machine = Machine::new(1, Xlen::Rv32);
+        machine.load_elf(&elf_file, extensions).unwrap();
+        machine.step();
+        let hart = &machine.harts[0];
+        assert_eq!(hart.pc, 0x8000000C);
+        assert_eq!(hart.read_reg(8), 0x22);
+        assert_eq!(hart.read_reg(2), 0xFFFF_FFFF_8000_2000);
+    }
+}
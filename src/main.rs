@@ -1,22 +1,202 @@
-use std::{env, fs};
+use std::panic::{self, AssertUnwindSafe};
+use std::{env, fs, process};
 
-use riscv_emulator::{Cpu, elf::ElfFile};
+use riscv_emulator::Machine;
+use riscv_emulator::cpu::Xlen;
+use riscv_emulator::cpu::timing::{InOrderPipeline, Timing};
+use riscv_emulator::devices;
+use riscv_emulator::disasm::Disassembler;
+use riscv_emulator::elf::{self, ElfFile};
+use riscv_emulator::fdt;
+use riscv_emulator::litmus::{self, Litmus};
+use riscv_emulator::machine;
+use riscv_emulator::write_buffer::MemoryModel;
+
+const USAGE: &str = "[--trace] [--disasm] [--timing] [--isa rv64gc_zcb|...] [--harts N] [--quantum N] [--parallel] [--timebase icount[:N]|realtime] [--timebase-frequency HZ] [--memory-model sequential|relaxed[:SEED]] [--litmus [--runs N] [--seed N]] <elf-file|litmus-file>";
+
+// --litmus 기본 실행 횟수
+const DEFAULT_LITMUS_RUNS: usize = 1000;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() < 2 {
-        println!("Usage: {} <elf-file>", args[0]);
+    let mut trace = false;
+    let mut disasm = false;
+    let mut timing = false;
+    let mut isa = None;
+    let mut harts = 1;
+    let mut quantum = machine::DEFAULT_QUANTUM;
+    let mut parallel = false;
+    let mut timebase_source = None;
+    let mut timebase_frequency = devices::timebase::DEFAULT_TIMEBASE_FREQUENCY;
+    let mut memory_model = "sequential";
+    let mut litmus = false;
+    let mut runs = DEFAULT_LITMUS_RUNS;
+    let mut seed = 0;
+    let mut elf_path = None;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--trace" => trace = true,
+            "--disasm" => disasm = true,
+            "--timing" => timing = true,
+            "--isa" => isa = Some(rest.next().ok_or("--isa needs a value")?.as_str()),
+            "--harts" => harts = rest.next().ok_or("--harts needs a value")?.parse()?,
+            "--quantum" => quantum = rest.next().ok_or("--quantum needs a value")?.parse()?,
+            "--parallel" => parallel = true,
+            "--timebase" => {
+                timebase_source = Some(rest.next().ok_or("--timebase needs a value")?.as_str())
+            }
+            "--timebase-frequency" => {
+                let value = rest.next().ok_or("--timebase-frequency needs a value")?;
+                timebase_frequency = value.parse()?;
+            }
+            "--memory-model" => {
+                memory_model = rest.next().ok_or("--memory-model needs a value")?;
+            }
+            "--litmus" => litmus = true,
+            "--runs" => runs = rest.next().ok_or("--runs needs a value")?.parse()?,
+            "--seed" => seed = rest.next().ok_or("--seed needs a value")?.parse()?,
+            path => elf_path = Some(path),
+        }
+    }
+    let Some(elf_path) = elf_path else {
+        println!("Usage: {} {}", args[0], USAGE);
+        return Ok(());
+    };
+    // --litmus: herd litmus 테스트를 relaxed 메모리 모델로 여러 번 실행해서 최종 상태 분포를 출력
+    // RVWMO가 금지하는 상태를 관찰하면 종료 코드 1
+    if litmus {
+        let source = fs::read_to_string(elf_path)?;
+        let report = litmus::run(&Litmus::parse(&source)?, runs, seed)?;
+        print!("{}", report);
+        if !report.forbidden().is_empty() {
+            process::exit(1);
+        }
         return Ok(());
     }
-    let elf_path = &args[1];
+    // icount[:N]: 명령어 N개마다 mtime 1 (결정적), realtime: 호스트 시계
+    // --parallel은 hart마다 명령어 수가 다르므로 호스트 시계만 가능
+    let timebase_source = timebase_source.unwrap_or(if parallel { "realtime" } else { "icount" });
+    if parallel && timebase_source != "realtime" {
+        return Err("--parallel needs --timebase realtime".into());
+    }
+    let timebase = match timebase_source {
+        "realtime" => devices::Timebase::host(timebase_frequency),
+        "icount" => devices::Timebase::icount(timebase_frequency, 1),
+        source => {
+            let ratio = source
+                .strip_prefix("icount:")
+                .and_then(|ratio| ratio.parse().ok())
+                .filter(|&ratio| ratio > 0)
+                .ok_or_else(|| format!("unknown timebase: {}", source))?;
+            devices::Timebase::icount(timebase_frequency, ratio)
+        }
+    };
+    // relaxed[:SEED]: store를 hart별 write buffer에 두고 seed로 정한 순서로 반영 (RVWMO)
+    let memory_model = match memory_model {
+        "sequential" => MemoryModel::Sequential,
+        "relaxed" => MemoryModel::Relaxed { seed: 0 },
+        model => {
+            let seed = model
+                .strip_prefix("relaxed:")
+                .and_then(|seed| seed.parse().ok())
+                .ok_or_else(|| format!("unknown memory model: {}", model))?;
+            MemoryModel::Relaxed { seed }
+        }
+    };
     let bytes = fs::read(elf_path)?;
     let elf_file = ElfFile::load(&bytes)?;
 
-    let mut cpu = Cpu::new(0);
+    // 32비트 ELF는 RV32 hart로 실행
+    let xlen = match elf_file.class {
+        elf::ELF_CLASS32 => Xlen::Rv32,
+        _ => Xlen::Rv64,
+    };
+
+    // --isa가 없으면 ELF .riscv.attributes의 arch 문자열 (예: -march=rv32imac_zcb_zcmp)
+    let extensions = elf_file.extensions(isa)?;
+
+    // --disasm: 실행하지 않고 로드 세그먼트를 디스어셈블해서 출력
+    if disasm {
+        let disassembler = Disassembler::new(xlen)
+            .with_symbols(&elf_file.symbols)
+            .with_extensions(extensions);
+        for segment in &elf_file.segments {
+            print!("{}", disassembler.listing(&segment.data, segment.vaddr));
+        }
+        return Ok(());
+    }
+
+    if harts == 0 || quantum == 0 {
+        return Err("--harts and --quantum must be at least 1".into());
+    }
+    let mut machine = Machine::new(harts, xlen);
+    machine.quantum = quantum;
+    for hart in machine.harts.iter_mut() {
+        hart.trace = trace;
+        if timing {
+            hart.timing = Some(Timing::new(Box::new(InOrderPipeline::default())));
+        }
+    }
+
+    machine.bus.set_timebase(timebase);
+    machine.bus.set_memory_model(memory_model);
+
+    // 모든 hart가 entry에서 시작 (mhartid로 역할을 나눔)
+    machine.load_elf(&elf_file, extensions)?;
+    // timebase-frequency를 담은 디바이스 트리 (a0 = hartid, a1 = DTB 주소)
+    machine.load_device_tree(&fdt::machine(xlen, harts, timebase_frequency));
+    let run = |machine: &mut Machine| {
+        if parallel {
+            machine.run_parallel()
+        } else {
+            machine.run()
+        }
+    };
+    if panic::catch_unwind(AssertUnwindSafe(|| run(&mut machine))).is_err() {
+        // 패닉 메시지 뒤에 죽은 hart와 위치의 명령어를 덧붙임
+        // 패닉한 hart는 공유 버스(또는 --parallel이면 자기 버스 포트)를 가진 채로 남아 있음
+        let current = machine.current();
+        let cpu = &mut machine.harts[current];
+        let pc = cpu.pc;
+        let location = cpu.symbols.describe(pc).unwrap_or_default();
+        if (devices::DRAM_BASE..devices::DRAM_BASE + devices::DRAM_SIZE - 4).contains(&pc) {
+            let inst = cpu.fetch();
+            eprintln!(
+                "hart {} at {:#x} {}: {}",
+                cpu.hart_id,
+                pc,
+                location,
+                cpu.disassemble(inst, pc)
+            );
+        } else {
+            eprintln!("hart {} at {:#x} {}", cpu.hart_id, pc, location);
+        }
+        process::exit(1);
+    }
 
-    cpu.load_segments(&elf_file.segments, elf_file.entry);
-    cpu.run();
+    // --timing: hart별 사이클 합계와 함수별 사이클 (많은 순서)
+    for hart in &machine.harts {
+        let Some(timing) = &hart.timing else {
+            continue;
+        };
+        let total = timing.profile.total();
+        eprintln!(
+            "hart {}: {} cycles, {} instructions, CPI {:.2}",
+            hart.hart_id,
+            total.cycles,
+            total.instructions,
+            total.cpi()
+        );
+        eprintln!(
+            "{:>12} {:>12} {:>6}  function",
+            "cycles", "instructions", "CPI"
+        );
+        for region in timing.profile.by_symbol(&hart.symbols) {
+            eprintln!("{}", region);
+        }
+    }
 
     Ok(())
 }
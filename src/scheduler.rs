@@ -0,0 +1,122 @@
+//! 장치 이벤트 스케줄러
+//! 버스 시간(tick 수) 기준으로 장치가 미래 이벤트를 예약하고, tick마다 가장 가까운 마감 시각과만 비교
+//! 이벤트 종류마다 슬롯이 하나라서 다시 예약하면 이전 예약을 대체함
+
+/// 예약 가능한 장치 이벤트
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// CLINT mtime이 mtimecmp에 도달
+    Timer,
+    /// UART 호스트 입력 확인
+    UartPoll,
+}
+
+const EVENT_KINDS: usize = 2;
+const EVENTS: [Event; EVENT_KINDS] = [Event::Timer, Event::UartPoll];
+const NEVER: u64 = u64::MAX;
+
+pub struct Scheduler {
+    now: u64,
+    deadlines: [u64; EVENT_KINDS],
+    // deadlines 중 최솟값
+    next: u64,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler {
+            now: 0,
+            deadlines: [NEVER; EVENT_KINDS],
+            next: NEVER,
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// delay tick 뒤에 event 발생 (같은 종류의 기존 예약은 취소)
+    pub fn schedule(&mut self, event: Event, delay: u64) {
+        self.deadlines[event as usize] = self.now.saturating_add(delay);
+        self.update_next();
+    }
+
+    pub fn cancel(&mut self, event: Event) {
+        self.deadlines[event as usize] = NEVER;
+        self.update_next();
+    }
+
+    /// 시간을 진행하고, 마감된 이벤트가 있으면 true
+    pub fn advance(&mut self, ticks: u64) -> bool {
+        self.now += ticks;
+        self.now >= self.next
+    }
+
+    /// 마감된 이벤트 하나를 꺼냄 (마감 시각 순)
+    pub fn pop_due(&mut self) -> Option<Event> {
+        if self.now < self.next {
+            return None;
+        }
+        let event = EVENTS
+            .into_iter()
+            .min_by_key(|&event| self.deadlines[event as usize])?;
+        self.deadlines[event as usize] = NEVER;
+        self.update_next();
+        Some(event)
+    }
+
+    /// 다음 이벤트까지 남은 tick 수 (예약이 없으면 None)
+    pub fn ticks_until_next(&self) -> Option<u64> {
+        (self.next != NEVER).then(|| self.next.saturating_sub(self.now))
+    }
+
+    fn update_next(&mut self) {
+        self.next = self.deadlines.iter().copied().min().unwrap_or(NEVER);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_fires_at_deadline() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::Timer, 3);
+        assert!(!scheduler.advance(2));
+        assert_eq!(scheduler.pop_due(), None);
+        assert_eq!(scheduler.ticks_until_next(), Some(1));
+        assert!(scheduler.advance(1));
+        assert_eq!(scheduler.pop_due(), Some(Event::Timer));
+        assert_eq!(scheduler.pop_due(), None);
+        assert_eq!(scheduler.ticks_until_next(), None);
+    }
+
+    #[test]
+    fn test_due_events_pop_in_deadline_order() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::UartPoll, 1);
+        scheduler.schedule(Event::Timer, 2);
+        scheduler.advance(5);
+        assert_eq!(scheduler.pop_due(), Some(Event::UartPoll));
+        assert_eq!(scheduler.pop_due(), Some(Event::Timer));
+    }
+
+    #[test]
+    fn test_reschedule_and_cancel() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::Timer, 2);
+        scheduler.schedule(Event::Timer, 10);
+        assert!(!scheduler.advance(5));
+        scheduler.cancel(Event::Timer);
+        assert!(!scheduler.advance(100));
+        scheduler.schedule(Event::Timer, 1000);
+        assert_eq!(scheduler.ticks_until_next(), Some(1000));
+    }
+}
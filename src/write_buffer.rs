@@ -0,0 +1,352 @@
+//! RVWMO 약한 메모리 모델 시뮬레이션용 hart별 write buffer
+//! 켜면 DRAM store는 바로 메모리에 쓰지 않고 hart의 buffer에 쌓였다가, seed로 정한 무작위
+//! 시점과 순서로 메모리에 반영됨. 같은 hart의 load는 buffer의 값을 전달받음
+//! 모델링하는 완화는 store 쪽(다른 주소에 대한 W→W, W→R 재배치)뿐이고 load는 실행 시점에 수행
+//! - 같은 주소(겹치는 바이트)의 store는 프로그램 순서대로 반영
+//! - FENCE w,r (w,rw 등)은 buffer를 비우고, FENCE w,w는 이전 store가 모두 반영되기 전까지
+//!   이후 store를 잡아둠 (epoch)
+//! - AMO/LR/SC는 같은 주소의 이전 store를 먼저 반영하고, rl이면 buffer 전체를 비움
+//!   (aq는 AMO가 실행 시점에 메모리에서 수행되므로 따로 할 일이 없음)
+//! - MMIO 접근은 buffer를 비운 뒤 수행 (장치 접근은 보수적으로 프로그램 순서 유지)
+
+use std::collections::BTreeMap;
+
+use crate::cpu::entropy::EntropySource;
+
+/// hart마다 buffer에 담을 수 있는 store 수. 가득 차면 하나를 반영한 뒤 넣음
+pub const WRITE_BUFFER_CAPACITY: usize = 8;
+
+/// tick마다 store 하나를 반영할 확률의 역수
+pub const DRAIN_ONE_IN: u64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryModel {
+    /// store가 실행 즉시 모든 hart에 보임 (기본값)
+    #[default]
+    Sequential,
+    /// RVWMO: store를 hart별 write buffer에 두고 seed로 정한 순서로 반영
+    Relaxed { seed: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBufferEntry {
+    pub addr: u64,
+    pub value: u64,
+    pub size: u8,
+    /// store 당시 hart의 FENCE w,w 구간 번호. 앞 구간이 다 반영돼야 반영 가능
+    pub epoch: u64,
+}
+
+impl WriteBufferEntry {
+    fn overlaps(&self, addr: u64, size: u64) -> bool {
+        self.addr < addr + size && addr < self.addr + self.size as u64
+    }
+
+    // [addr, addr + size)가 이 store 안에 있으면 해당 바이트 값
+    fn forward(&self, addr: u64, size: u8) -> Option<u64> {
+        if addr < self.addr || addr + size as u64 > self.addr + self.size as u64 {
+            return None;
+        }
+        let value = self.value >> (8 * (addr - self.addr));
+        Some(match size {
+            8 => value,
+            _ => value & ((1 << (8 * size)) - 1),
+        })
+    }
+}
+
+/// load가 자기 hart의 buffer에서 본 결과
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forward {
+    /// 가장 최근의 겹치는 store가 읽을 바이트를 모두 담고 있음
+    Value(u64),
+    /// 겹치는 store가 없어 메모리에서 읽음
+    Memory,
+    /// 일부만 겹침. 겹치는 store까지 반영한 뒤 메모리에서 읽어야 함
+    Partial,
+}
+
+#[derive(Default)]
+struct HartBuffer {
+    // 오래된 것부터
+    entries: Vec<WriteBufferEntry>,
+    epoch: u64,
+}
+
+impl HartBuffer {
+    // 지금 메모리에 반영해도 되는 store: 가장 오래된 구간에 속하고 더 오래된 겹치는 store가 없음
+    fn drainable(&self) -> Vec<usize> {
+        let Some(oldest) = self.entries.first().map(|entry| entry.epoch) else {
+            return Vec::new();
+        };
+        (0..self.entries.len())
+            .take_while(|&index| self.entries[index].epoch == oldest)
+            .filter(|&index| {
+                let entry = &self.entries[index];
+                !self.entries[..index]
+                    .iter()
+                    .any(|older| older.overlaps(entry.addr, entry.size as u64))
+            })
+            .collect()
+    }
+}
+
+/// 모든 hart의 write buffer와 반영 순서를 정하는 RNG
+pub struct WriteBuffers {
+    harts: BTreeMap<u64, HartBuffer>,
+    // 모든 buffer의 store 수 (tick마다 빠르게 확인)
+    len: usize,
+    rng: EntropySource,
+}
+
+impl WriteBuffers {
+    pub fn new(seed: u64) -> Self {
+        WriteBuffers {
+            harts: BTreeMap::new(),
+            len: 0,
+            rng: EntropySource::new(seed),
+        }
+    }
+
+    /// 같은 설정의 빈 buffer. seed는 이 buffer의 RNG에서 뽑음 (병렬 실행의 hart별 Bus)
+    pub fn fork(&mut self) -> Self {
+        Self::new(self.rng.next_u64())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// store를 buffer에 넣음. buffer가 가득 차 있으면 먼저 반영할 store 하나를 반환
+    pub fn push(
+        &mut self,
+        hart_id: u64,
+        addr: u64,
+        size: u8,
+        value: u64,
+    ) -> Option<WriteBufferEntry> {
+        let evicted = if self.buffer_len(hart_id) >= WRITE_BUFFER_CAPACITY {
+            self.take_random(hart_id)
+        } else {
+            None
+        };
+        let buffer = self.harts.entry(hart_id).or_default();
+        let epoch = buffer.epoch;
+        buffer.entries.push(WriteBufferEntry {
+            addr,
+            value,
+            size,
+            epoch,
+        });
+        self.len += 1;
+        evicted
+    }
+
+    /// hart의 load가 buffer에서 값을 전달받을 수 있는지
+    pub fn forward(&self, hart_id: u64, addr: u64, size: u8) -> Forward {
+        let Some(buffer) = self.harts.get(&hart_id) else {
+            return Forward::Memory;
+        };
+        match buffer
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.overlaps(addr, size as u64))
+        {
+            None => Forward::Memory,
+            Some(entry) => entry
+                .forward(addr, size)
+                .map_or(Forward::Partial, Forward::Value),
+        }
+    }
+
+    /// FENCE w,w: 지금까지의 store가 모두 반영되기 전에는 이후 store를 반영하지 않음
+    pub fn barrier(&mut self, hart_id: u64) {
+        let buffer = self.harts.get_mut(&hart_id);
+        if let Some(buffer) = buffer.filter(|buffer| !buffer.entries.is_empty()) {
+            buffer.epoch += 1;
+        }
+    }
+
+    /// [addr, addr + size)와 겹치는 가장 최근 store까지 오래된 순서로 꺼냄
+    pub fn take_through(&mut self, hart_id: u64, addr: u64, size: u64) -> Vec<WriteBufferEntry> {
+        let Some(buffer) = self.harts.get_mut(&hart_id) else {
+            return Vec::new();
+        };
+        let Some(last) = buffer
+            .entries
+            .iter()
+            .rposition(|entry| entry.overlaps(addr, size))
+        else {
+            return Vec::new();
+        };
+        let taken: Vec<_> = buffer.entries.drain(..=last).collect();
+        self.len -= taken.len();
+        taken
+    }
+
+    /// hart의 store를 모두 오래된 순서로 꺼냄
+    pub fn take_all(&mut self, hart_id: u64) -> Vec<WriteBufferEntry> {
+        let taken = self
+            .harts
+            .get_mut(&hart_id)
+            .map(|buffer| std::mem::take(&mut buffer.entries))
+            .unwrap_or_default();
+        self.len -= taken.len();
+        taken
+    }
+
+    /// 모든 hart의 store를 hart별로 오래된 순서로 꺼냄
+    pub fn take_everything(&mut self) -> Vec<WriteBufferEntry> {
+        let hart_ids: Vec<u64> = self.harts.keys().copied().collect();
+        hart_ids
+            .into_iter()
+            .flat_map(|hart_id| self.take_all(hart_id))
+            .collect()
+    }
+
+    /// tick마다 호출. 1/DRAIN_ONE_IN 확률로 무작위 hart의 반영 가능한 store 하나를 꺼냄
+    pub fn pick_drain(&mut self) -> Option<WriteBufferEntry> {
+        if self.len == 0 || !self.rng.next_u64().is_multiple_of(DRAIN_ONE_IN) {
+            return None;
+        }
+        let pending: Vec<u64> = self
+            .harts
+            .iter()
+            .filter(|(_, buffer)| !buffer.entries.is_empty())
+            .map(|(&hart_id, _)| hart_id)
+            .collect();
+        let hart_id = pending[self.rng.next_u64() as usize % pending.len()];
+        self.take_random(hart_id)
+    }
+
+    fn buffer_len(&self, hart_id: u64) -> usize {
+        self.harts
+            .get(&hart_id)
+            .map_or(0, |buffer| buffer.entries.len())
+    }
+
+    // hart의 반영 가능한 store 중 하나를 무작위로 꺼냄
+    fn take_random(&mut self, hart_id: u64) -> Option<WriteBufferEntry> {
+        let buffer = self.harts.get_mut(&hart_id)?;
+        let drainable = buffer.drainable();
+        if drainable.is_empty() {
+            return None;
+        }
+        let index = drainable[self.rng.next_u64() as usize % drainable.len()];
+        self.len -= 1;
+        Some(buffer.entries.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain_all_randomly(buffers: &mut WriteBuffers) -> Vec<WriteBufferEntry> {
+        let mut drained = Vec::new();
+        while !buffers.is_empty() {
+            drained.extend(buffers.pick_drain());
+        }
+        drained
+    }
+
+    #[test]
+    fn test_forwarding() {
+        let mut buffers = WriteBuffers::new(1);
+        buffers.push(0, 0x1000, 8, 0x1122_3344_5566_7788);
+        buffers.push(0, 0x1002, 2, 0xAABB);
+        // 가장 최근 store가 우선
+        assert_eq!(buffers.forward(0, 0x1002, 2), Forward::Value(0xAABB));
+        assert_eq!(buffers.forward(0, 0x1004, 4), Forward::Value(0x1122_3344));
+        assert_eq!(buffers.forward(0, 0x1000, 4), Forward::Partial);
+        assert_eq!(buffers.forward(0, 0x1008, 4), Forward::Memory);
+        // 다른 hart의 buffer는 보이지 않음
+        assert_eq!(buffers.forward(1, 0x1000, 8), Forward::Memory);
+    }
+
+    #[test]
+    fn test_same_address_stays_in_order() {
+        for seed in 0..50 {
+            let mut buffers = WriteBuffers::new(seed);
+            for value in 0..4 {
+                buffers.push(0, 0x1000, 4, value);
+                buffers.push(0, 0x2000 + 8 * value, 8, value);
+            }
+            let order: Vec<u64> = drain_all_randomly(&mut buffers)
+                .iter()
+                .filter(|entry| entry.addr == 0x1000)
+                .map(|entry| entry.value)
+                .collect();
+            assert_eq!(order, [0, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn test_different_addresses_reorder() {
+        // 어떤 seed에서는 두 번째 store가 먼저 반영됨
+        let reordered = (0..50).any(|seed| {
+            let mut buffers = WriteBuffers::new(seed);
+            buffers.push(0, 0x1000, 4, 1);
+            buffers.push(0, 0x2000, 4, 2);
+            drain_all_randomly(&mut buffers)[0].addr == 0x2000
+        });
+        assert!(reordered);
+    }
+
+    #[test]
+    fn test_barrier_orders_epochs() {
+        for seed in 0..50 {
+            let mut buffers = WriteBuffers::new(seed);
+            buffers.push(0, 0x1000, 4, 1);
+            buffers.push(0, 0x1008, 4, 2);
+            buffers.barrier(0);
+            buffers.push(0, 0x2000, 4, 3);
+            let drained = drain_all_randomly(&mut buffers);
+            assert_eq!(drained[2].addr, 0x2000);
+        }
+    }
+
+    #[test]
+    fn test_take_through_drains_prefix() {
+        let mut buffers = WriteBuffers::new(1);
+        buffers.push(0, 0x1000, 4, 1);
+        buffers.push(0, 0x2000, 4, 2);
+        buffers.push(0, 0x3000, 4, 3);
+        let taken = buffers.take_through(0, 0x2002, 1);
+        assert_eq!(
+            taken.iter().map(|entry| entry.value).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(buffers.len(), 1);
+        assert!(buffers.take_through(0, 0x5000, 8).is_empty());
+    }
+
+    #[test]
+    fn test_full_buffer_evicts() {
+        let mut buffers = WriteBuffers::new(1);
+        for index in 0..WRITE_BUFFER_CAPACITY as u64 {
+            assert_eq!(buffers.push(0, 0x1000 + 8 * index, 8, index), None);
+        }
+        assert!(buffers.push(0, 0x2000, 8, 99).is_some());
+        assert_eq!(buffers.len(), WRITE_BUFFER_CAPACITY);
+    }
+
+    #[test]
+    fn test_same_seed_same_drain_order() {
+        let order = |seed| {
+            let mut buffers = WriteBuffers::new(seed);
+            for hart in 0..2 {
+                for index in 0..4 {
+                    buffers.push(hart, 0x1000 * (hart + 1) + 8 * index, 8, index);
+                }
+            }
+            drain_all_randomly(&mut buffers)
+        };
+        assert_eq!(order(7), order(7));
+    }
+}